@@ -0,0 +1,85 @@
+//! 整形処理のベンチマーク
+//! cargo bench --bench format で、同じ入力を serde_json の pretty printer と比較する
+
+#![allow(clippy::needless_return)]
+
+use rust_json_formatter::json::{FormatOptions, JsonValue};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 20;
+
+/**
+ * オブジェクト・配列・文字列・数値を一通り含む、それなりの大きさの JSON を生成する
+ */
+fn sample_input() -> String {
+    let mut input = String::from("[");
+    for i in 0..20_000 {
+        if i > 0 {
+            input.push(',');
+        }
+        input.push_str(&format!(
+            r#"{{"id": {}, "name": "user-{}", "bio": "line\nbreak \"quoted\" 日本語", "score": {}, "active": {}, "tags": ["a", "b", "c"], "location": {{"lat": 35.{}, "lng": 139.{}}}}}"#,
+            i,
+            i,
+            i as f64 / 7.0,
+            i % 2 == 0,
+            i,
+            i
+        ));
+    }
+    input.push(']');
+    return input;
+}
+
+/**
+ * f を ITERATIONS 回実行し、1回あたりの平均時間を返す
+ */
+fn measure<F: FnMut() -> usize>(mut f: F) -> Duration {
+    black_box(f());
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    return start.elapsed() / ITERATIONS;
+}
+
+fn report(name: &str, bytes: usize, elapsed: Duration) {
+    let throughput = bytes as f64 / elapsed.as_secs_f64() / 1024.0 / 1024.0;
+    println!(
+        "{:<32} {:>10.3} ms {:>10.1} MiB/s",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        throughput
+    );
+}
+
+fn main() {
+    let input = sample_input();
+    let json: JsonValue = input.parse().unwrap();
+    let serde_value: serde_json::Value = serde_json::from_str(&input).unwrap();
+    let options = FormatOptions::default();
+    println!("input: {} bytes", input.len());
+
+    let elapsed = measure(|| json.format_with_options(&options).len());
+    report("format_with_options", input.len(), elapsed);
+
+    let elapsed = measure(|| {
+        let mut formatted = String::with_capacity(input.len() + input.len() / 2);
+        json.format_into(&options, &mut formatted);
+        formatted.len()
+    });
+    report("format_into (presized)", input.len(), elapsed);
+
+    let elapsed = measure(|| serde_json::to_string_pretty(&serde_value).unwrap().len());
+    report("serde_json::to_string_pretty", input.len(), elapsed);
+
+    let elapsed = measure(|| input.parse::<JsonValue>().unwrap().format_with_options(&options).len());
+    report("parse + format", input.len(), elapsed);
+
+    let elapsed = measure(|| {
+        let value: serde_json::Value = serde_json::from_str(&input).unwrap();
+        serde_json::to_string_pretty(&value).unwrap().len()
+    });
+    report("serde_json parse + pretty", input.len(), elapsed);
+}
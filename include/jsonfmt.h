@@ -0,0 +1,43 @@
+/*
+ * rust_json_formatter の C API
+ * cargo build --release --features ffi で生成される共有ライブラリとリンクして使う
+ */
+#ifndef JSONFMT_H
+#define JSONFMT_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct JsonfmtOptions {
+    const char *indent;    /* 1段分の字下げ (--indent と同じ形式。NULL の場合は空白2つ) */
+    bool collapse;         /* 1行に収まるオブジェクトや配列を1行にまとめるかどうか */
+    size_t max_width;      /* 1行にまとめる際の1行の最大幅 */
+    bool trailing_newline; /* 出力の末尾に改行を付けるかどうか */
+    bool crlf;             /* 改行コードを CRLF にするかどうか */
+    bool compact;          /* 改行や空白を含めずに1行で出力するかどうか */
+} JsonfmtOptions;
+
+/* CLI のデフォルトと同じオプションを返す */
+JsonfmtOptions jsonfmt_default_options(void);
+
+/*
+ * NUL 終端の UTF-8 文字列 input を整形した文字列を返す (options が NULL の場合はデフォルト)
+ * 戻り値は jsonfmt_free で解放する。失敗した場合は NULL を返す
+ */
+char *jsonfmt_format(const char *input, const JsonfmtOptions *options);
+
+/* 同じスレッドで直前に失敗した呼び出しの英語のエラーメッセージ (なければ NULL。パースエラーは CLI の英語のメッセージと同じ) */
+const char *jsonfmt_last_error(void);
+
+/* jsonfmt_format が返した文字列を解放する */
+void jsonfmt_free(char *str);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JSONFMT_H */
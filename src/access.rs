@@ -0,0 +1,500 @@
+use indexmap::IndexMap;
+use std::ops::{Index, IndexMut};
+
+use crate::json::{JsonArray, JsonObject, JsonValue};
+
+/**
+ * JsonValue::get や value["key"] / value[0] に渡せるインデックス
+ * 文字列はオブジェクトのキー、usize は配列の添字として扱う
+ */
+pub trait JsonIndex {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue>;
+
+    /**
+     * 書き込み用に子要素を取得する
+     * 取得できない場合の振る舞いは IndexMut の説明を参照
+     */
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue;
+}
+
+impl JsonIndex for str {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        return value.as_object()?.get(self);
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        if let JsonValue::Null = value {
+            *value = JsonValue::Object(IndexMap::new());
+        }
+        return match value {
+            JsonValue::Object(obj) => obj.entry(self.to_string()).or_insert(JsonValue::Null),
+            _ => panic!("オブジェクト以外の値をキー {:?} で書き換えることはできません", self),
+        };
+    }
+}
+
+impl JsonIndex for String {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        return self.as_str().index_into(value);
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        return self.as_str().index_or_insert(value);
+    }
+}
+
+impl JsonIndex for usize {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        return value.as_array()?.get(*self);
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        return match value {
+            JsonValue::Array(array) => {
+                let len = array.len();
+                array
+                    .get_mut(*self)
+                    .unwrap_or_else(|| panic!("添字 {} は配列の長さ {} を超えています", self, len))
+            }
+            _ => panic!("配列以外の値を添字 {} で書き換えることはできません", self),
+        };
+    }
+}
+
+impl<T: JsonIndex + ?Sized> JsonIndex for &T {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        return (**self).index_into(value);
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        return (**self).index_or_insert(value);
+    }
+}
+
+static NULL: JsonValue = JsonValue::Null;
+
+/**
+ * value["users"][0]["name"] のように子要素を参照する
+ * 型が合わない場合や存在しない場合はパニックせずに null を返す
+ */
+impl<I: JsonIndex> Index<I> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: I) -> &JsonValue {
+        return index.index_into(self).unwrap_or(&NULL);
+    }
+}
+
+/**
+ * value["key"] = ... や value[0] = ... のように子要素を書き換える
+ * キーの場合、null はオブジェクトとして扱い、存在しないキーは null で追加してから返す
+ * 添字の場合、配列の範囲外を指定するとパニックする
+ * オブジェクトや配列以外の値に対して書き換えようとした場合もパニックする
+ */
+impl<I: JsonIndex> IndexMut<I> for JsonValue {
+    fn index_mut(&mut self, index: I) -> &mut JsonValue {
+        return index.index_or_insert(self);
+    }
+}
+
+impl JsonValue {
+    /**
+     * 文字列であればその中身を返す
+     */
+    pub fn as_str(&self) -> Option<&str> {
+        return match self {
+            JsonValue::String(str) => Some(str),
+            _ => None,
+        };
+    }
+
+    /**
+     * 数値であればその値を返す
+     */
+    pub fn as_f64(&self) -> Option<f64> {
+        return match self {
+            JsonValue::Number(num) => Some(num.as_f64()),
+            _ => None,
+        };
+    }
+
+    /**
+     * i64 で表せる整数であればその値を返す
+     */
+    pub fn as_i64(&self) -> Option<i64> {
+        return match self {
+            JsonValue::Number(num) => num.as_i64(),
+            _ => None,
+        };
+    }
+
+    /**
+     * u64 で表せる整数であればその値を返す
+     */
+    pub fn as_u64(&self) -> Option<u64> {
+        return match self {
+            JsonValue::Number(num) => num.as_u64(),
+            _ => None,
+        };
+    }
+
+    /**
+     * true / false であれば bool として返す
+     */
+    pub fn as_bool(&self) -> Option<bool> {
+        return match self {
+            JsonValue::Bool(bool) => Some(*bool),
+            _ => None,
+        };
+    }
+
+    /**
+     * オブジェクトであれば中身のマップを返す
+     */
+    pub fn as_object(&self) -> Option<&JsonObject> {
+        return match self {
+            JsonValue::Object(obj) => Some(obj),
+            _ => None,
+        };
+    }
+
+    /**
+     * 配列であれば中身の Vec を返す
+     */
+    pub fn as_array(&self) -> Option<&JsonArray> {
+        return match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        };
+    }
+
+    /**
+     * オブジェクトならキー、配列なら添字で子要素を取得する
+     * 型が合わない場合や存在しない場合は None を返す
+     */
+    pub fn get<I: JsonIndex>(&self, index: I) -> Option<&JsonValue> {
+        return index.index_into(self);
+    }
+
+    /**
+     * 配列の指定した添字の要素を取得する
+     */
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        return self.get(index);
+    }
+
+    pub fn is_object(&self) -> bool {
+        return matches!(self, JsonValue::Object(_));
+    }
+
+    pub fn is_array(&self) -> bool {
+        return matches!(self, JsonValue::Array(_));
+    }
+
+    pub fn is_string(&self) -> bool {
+        return matches!(self, JsonValue::String(_));
+    }
+
+    pub fn is_number(&self) -> bool {
+        return matches!(self, JsonValue::Number(_));
+    }
+
+    pub fn is_bool(&self) -> bool {
+        return matches!(self, JsonValue::Bool(_));
+    }
+
+    pub fn is_null(&self) -> bool {
+        return matches!(self, JsonValue::Null);
+    }
+
+    /**
+     * オブジェクトであれば中身のマップを変更可能な参照で返す
+     */
+    pub fn as_object_mut(&mut self) -> Option<&mut JsonObject> {
+        return match self {
+            JsonValue::Object(obj) => Some(obj),
+            _ => None,
+        };
+    }
+
+    /**
+     * 配列であれば中身の Vec を変更可能な参照で返す
+     */
+    pub fn as_array_mut(&mut self) -> Option<&mut JsonArray> {
+        return match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        };
+    }
+
+    /**
+     * オブジェクトにキーと値を追加し、既に存在していた値を返す
+     * 既存のキーの場合は元の位置のまま値を置き換える
+     * null は空のオブジェクトとして扱い、それ以外のオブジェクトでない値の場合はパニックする
+     */
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Option<JsonValue> {
+        if let JsonValue::Null = self {
+            *self = JsonValue::Object(IndexMap::new());
+        }
+        return match self {
+            JsonValue::Object(obj) => obj.insert(key.into(), value.into()),
+            _ => panic!("オブジェクト以外の値にキーを追加することはできません"),
+        };
+    }
+
+    /**
+     * オブジェクトからキーを削除し、削除した値を返す
+     * 残りのキーの順序は保たれる
+     */
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        return self.as_object_mut()?.shift_remove(key);
+    }
+
+    /**
+     * 配列の末尾に値を追加する
+     * null は空の配列として扱い、それ以外の配列でない値の場合はパニックする
+     */
+    pub fn push(&mut self, value: impl Into<JsonValue>) {
+        if let JsonValue::Null = self {
+            *self = JsonValue::Array(Vec::new());
+        }
+        match self {
+            JsonValue::Array(array) => array.push(value.into()),
+            _ => panic!("配列以外の値に要素を追加することはできません"),
+        }
+    }
+
+    /**
+     * 配列の指定した位置に値を挿入する
+     * 配列でない場合や、添字が配列の長さを超える場合はパニックする
+     */
+    pub fn insert_at(&mut self, index: usize, value: impl Into<JsonValue>) {
+        match self {
+            JsonValue::Array(array) => array.insert(index, value.into()),
+            _ => panic!("配列以外の値に要素を挿入することはできません"),
+        }
+    }
+
+    /**
+     * 配列の指定した位置の要素を削除して返す
+     * 配列でない場合や範囲外の場合は None を返す
+     */
+    pub fn remove_at(&mut self, index: usize) -> Option<JsonValue> {
+        let array = self.as_array_mut()?;
+        if index >= array.len() {
+            return None;
+        }
+        return Some(array.remove(index));
+    }
+
+    /**
+     * JSON Pointer (RFC 6901) で指定した値を取得する
+     * 例: "/users/0/name"、空文字列はルートを指す
+     */
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        let mut current = self;
+        for token in parse_pointer(pointer)? {
+            current = match current {
+                JsonValue::Object(obj) => obj.get(&token)?,
+                JsonValue::Array(array) => array.get(parse_array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        return Some(current);
+    }
+
+    /**
+     * JSON Pointer (RFC 6901) で指定した値を変更可能な参照で取得する
+     */
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        let mut current = self;
+        for token in parse_pointer(pointer)? {
+            current = match current {
+                JsonValue::Object(obj) => obj.get_mut(&token)?,
+                JsonValue::Array(array) => array.get_mut(parse_array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        return Some(current);
+    }
+}
+
+/**
+ * JSON Pointer をトークンに分解し、~1 を /、~0 を ~ に戻す
+ * / で始まらない (空文字列以外の) ポインタは不正として None を返す
+ */
+pub fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    return Some(
+        rest.split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    );
+}
+
+/**
+ * JSON Pointer のトークンを配列の添字として解釈する
+ * 先頭に余分な 0 が付いたものは RFC 6901 に従い不正とする
+ */
+pub(crate) fn parse_array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    return token.parse().ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_accessors() {
+        let string = JsonValue::String("hello".to_string());
+        assert_eq!(string.as_str(), Some("hello"));
+        assert_eq!(string.as_f64(), None);
+
+        assert_eq!(JsonValue::from(1.5).as_f64(), Some(1.5));
+        assert_eq!(JsonValue::from(1.5).as_i64(), None);
+        let id: JsonValue = "12345678901234567890".parse().unwrap();
+        assert_eq!(id.as_u64(), Some(12345678901234567890));
+        assert_eq!(id.as_i64(), None);
+        assert_eq!(JsonValue::from(-1_i64).as_u64(), None);
+        assert_eq!(JsonValue::Bool(true).as_bool(), Some(true));
+        assert_eq!(JsonValue::Bool(false).as_bool(), Some(false));
+        assert_eq!(JsonValue::Null.as_bool(), None);
+    }
+
+    #[test]
+    fn test_get() {
+        let value: JsonValue = r#"{"users": [{"name": "alice"}, {"name": "bob"}]}"#.parse().unwrap();
+
+        let users = value.get("users").unwrap();
+        assert!(users.is_array());
+        assert_eq!(
+            users.get(1).and_then(|user| user.get("name")),
+            Some(&JsonValue::String("bob".to_string()))
+        );
+        assert_eq!(
+            users
+                .get_index(0)
+                .and_then(|user| user.get("name".to_string()))
+                .and_then(JsonValue::as_str),
+            Some("alice")
+        );
+
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value.get(0), None);
+        assert_eq!(users.get(2), None);
+        assert_eq!(users.get("name"), None);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let value: JsonValue = r#"[{}, [], "s", 1, true, null]"#.parse().unwrap();
+        let array = value.as_array().unwrap();
+
+        assert!(array[0].is_object() && array[0].as_object().unwrap().is_empty());
+        assert!(array[1].is_array());
+        assert!(array[2].is_string());
+        assert!(array[3].is_number());
+        assert!(array[4].is_bool());
+        assert!(array[5].is_null());
+        assert!(!array[5].is_object());
+    }
+
+    #[test]
+    fn test_index() {
+        let value: JsonValue = r#"{"users": [{"name": "alice"}]}"#.parse().unwrap();
+
+        assert_eq!(value["users"][0]["name"], JsonValue::String("alice".to_string()));
+        assert_eq!(value["users"][1]["name"], JsonValue::Null);
+        assert_eq!(value["users"]["name"], JsonValue::Null);
+        assert_eq!(value[0], JsonValue::Null);
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut value = JsonValue::Null;
+        value["users"] = JsonValue::Array(vec![JsonValue::Null]);
+        value["users"][0]["name"] = JsonValue::String("bob".to_string());
+        value["users"][0]["admin"] = JsonValue::Bool(true);
+
+        assert_eq!(value.to_string(), r#"{"users":[{"name":"bob","admin":true}]}"#);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_mut_out_of_bounds() {
+        let mut value = JsonValue::Array(vec![]);
+        value[0] = JsonValue::Null;
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_mut_type_mismatch() {
+        let mut value = JsonValue::from(1.0);
+        value["key"] = JsonValue::Null;
+    }
+
+    #[test]
+    fn test_object_mutation() {
+        let mut value: JsonValue = r#"{"a": 1, "b": 2, "c": 3}"#.parse().unwrap();
+
+        assert_eq!(value.insert("b", "two"), Some(JsonValue::from(2.0)));
+        assert_eq!(value.insert("d", true), None);
+        assert_eq!(value.remove("a"), Some(JsonValue::from(1.0)));
+        assert_eq!(value.remove("missing"), None);
+        assert_eq!(value.to_string(), r#"{"b":"two","c":3,"d":true}"#);
+
+        let mut null = JsonValue::Null;
+        null.insert("key", 1.0);
+        assert_eq!(null.to_string(), r#"{"key":1}"#);
+    }
+
+    #[test]
+    fn test_array_mutation() {
+        let mut value = JsonValue::Null;
+        value.push(1.0);
+        value.push(3.0);
+        value.insert_at(1, 2.0);
+        assert_eq!(value.to_string(), "[1,2,3]");
+
+        assert_eq!(value.remove_at(0), Some(JsonValue::from(1.0)));
+        assert_eq!(value.remove_at(5), None);
+        assert_eq!(value.to_string(), "[2,3]");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_to_object() {
+        let mut value = JsonValue::Object(IndexMap::new());
+        value.push(1.0);
+    }
+
+    #[test]
+    fn test_pointer() {
+        let value: JsonValue = r#"{"a": {"b": [10, 20]}, "c/d": 1, "e~f": 2, "": 3}"#.parse().unwrap();
+
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(value.pointer("/a/b/1"), Some(&JsonValue::from(20.0)));
+        assert_eq!(value.pointer("/c~1d"), Some(&JsonValue::from(1.0)));
+        assert_eq!(value.pointer("/e~0f"), Some(&JsonValue::from(2.0)));
+        assert_eq!(value.pointer("/"), Some(&JsonValue::from(3.0)));
+        assert_eq!(value.pointer("/a/b/2"), None);
+        assert_eq!(value.pointer("/a/b/01"), None);
+        assert_eq!(value.pointer("a"), None);
+    }
+
+    #[test]
+    fn test_pointer_mut() {
+        let mut value: JsonValue = r#"{"a": {"b": [10, 20]}}"#.parse().unwrap();
+
+        *value.pointer_mut("/a/b/0").unwrap() = JsonValue::Null;
+        value.pointer_mut("/a").unwrap().insert("c", "new");
+        assert_eq!(value.to_string(), r#"{"a":{"b":[null,20],"c":"new"}}"#);
+        assert_eq!(value.pointer_mut("/x"), None);
+    }
+}
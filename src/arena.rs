@@ -0,0 +1,302 @@
+use bumpalo::collections::Vec as BumpVec;
+use bumpalo::Bump;
+use std::borrow::Cow;
+use std::fmt;
+use std::{iter, slice};
+
+use crate::json::{FormatOptions, FormatTree, JsonValue, ValueRef};
+use crate::lexer::Lexer;
+use crate::number::Number;
+use crate::parser::{ParseError, Parser, ValueBuilder};
+
+/**
+ * ノード・文字列・配列をすべて1つのアリーナ (bumpalo::Bump) に確保する JSON 値
+ * 個々のノードは Drop を持たず、アリーナを破棄した時点でまとめて解放される
+ * (そのため Big の数値は Number ではなく、アリーナに確保したリテラルとして保持する)
+ * オブジェクトは (キー, 値) の並びとして保持し、重複したキーもそのまま残す
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaValue<'a> {
+    Object(&'a [(&'a str, ArenaValue<'a>)]), // {"key": "value"}
+    Array(&'a [ArenaValue<'a>]),             // [1, 2, 3]
+    String(&'a str),                         // "hello, world"
+    Number(Number),                          // 123, 123.456 (Big 以外)
+    BigNumber(&'a str), // f64 で表せない数値のリテラル (1e400 や、bignum 機能が有効な場合の 3.14159265358979323846 など)
+    Bool(bool),         // true, false
+    Null,               // null
+}
+
+/**
+ * アリーナにノードを確保しながら JSON をパースする
+ * エスケープを含まない文字列は入力を借用し、それ以外の文字列はアリーナに確保する
+ */
+pub fn parse_in<'a>(input: &'a str, bump: &'a Bump) -> Result<ArenaValue<'a>, ParseError> {
+    let mut parser = Parser::new(Lexer::new(input));
+    return parser.parse_in(bump);
+}
+
+impl<'a> Parser<'a> {
+    /**
+     * 入力全体を1つの ArenaValue としてパースする
+     * 値の後に余分な入力が残っている場合はエラーになる (回復や上限の確認は parse と同じ)
+     * 重複したキーはすべて残すため、duplicate_keys には現れない
+     */
+    pub fn parse_in(&mut self, bump: &'a Bump) -> Result<ArenaValue<'a>, ParseError> {
+        return self.parse_with(&ArenaBuilder { bump });
+    }
+}
+
+/**
+ * アリーナにノードを確保しながら ArenaValue を組み立てる
+ */
+struct ArenaBuilder<'a> {
+    bump: &'a Bump,
+}
+
+impl<'a> ValueBuilder<'a> for ArenaBuilder<'a> {
+    type Value = ArenaValue<'a>;
+    type Object = BumpVec<'a, (&'a str, ArenaValue<'a>)>;
+    type Array = BumpVec<'a, ArenaValue<'a>>;
+
+    fn string(&self, string: &Cow<'a, str>) -> ArenaValue<'a> {
+        return ArenaValue::String(alloc_str(self.bump, string));
+    }
+
+    fn number(&self, number: &Number) -> ArenaValue<'a> {
+        return match number {
+            Number::Big(literal) => ArenaValue::BigNumber(self.bump.alloc_str(literal)),
+            _ => ArenaValue::Number(number.clone()),
+        };
+    }
+
+    fn bool(&self, bool: bool) -> ArenaValue<'a> {
+        return ArenaValue::Bool(bool);
+    }
+
+    fn null(&self) -> ArenaValue<'a> {
+        return ArenaValue::Null;
+    }
+
+    fn object(&self) -> Self::Object {
+        return BumpVec::new_in(self.bump);
+    }
+
+    fn insert(
+        &self,
+        object: &mut Self::Object,
+        key: Cow<'a, str>,
+        _key_position: usize,
+        value: ArenaValue<'a>,
+    ) -> Option<String> {
+        object.push((alloc_str(self.bump, &key), value));
+        return None;
+    }
+
+    fn finish_object(&self, object: Self::Object) -> ArenaValue<'a> {
+        return ArenaValue::Object(object.into_bump_slice());
+    }
+
+    fn array(&self) -> Self::Array {
+        return BumpVec::new_in(self.bump);
+    }
+
+    fn push(&self, array: &mut Self::Array, value: ArenaValue<'a>) {
+        array.push(value);
+    }
+
+    fn finish_array(&self, array: Self::Array) -> ArenaValue<'a> {
+        return ArenaValue::Array(array.into_bump_slice());
+    }
+}
+
+/**
+ * 入力を借用している文字列はそのまま、エスケープを展開した文字列はアリーナにコピーして返す
+ */
+fn alloc_str<'a>(bump: &'a Bump, string: &Cow<'a, str>) -> &'a str {
+    return match string {
+        Cow::Borrowed(str) => str,
+        Cow::Owned(str) => bump.alloc_str(str),
+    };
+}
+
+impl<'a> ArenaValue<'a> {
+    /**
+     * オブジェクトのキーに対応する値を返す
+     * キーが重複している場合は JsonValue と同じく最後の値を返す
+     */
+    pub fn get(&self, key: &str) -> Option<&ArenaValue<'a>> {
+        let ArenaValue::Object(members) = self else {
+            return None;
+        };
+        return members.iter().rev().find(|(k, _)| *k == key).map(|(_, value)| value);
+    }
+
+    /**
+     * アリーナから切り離し、所有する JsonValue に変換する
+     */
+    pub fn to_owned_value(&self) -> JsonValue {
+        return match self {
+            ArenaValue::Object(members) => JsonValue::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_owned_value()))
+                    .collect(),
+            ),
+            ArenaValue::Array(array) => JsonValue::Array(array.iter().map(|v| v.to_owned_value()).collect()),
+            ArenaValue::String(str) => JsonValue::String(str.to_string()),
+            ArenaValue::Number(num) => JsonValue::Number(num.clone()),
+            ArenaValue::BigNumber(literal) => JsonValue::Number(Number::Big((*literal).into())),
+            ArenaValue::Bool(bool) => JsonValue::Bool(*bool),
+            ArenaValue::Null => JsonValue::Null,
+        };
+    }
+
+    /**
+     * オプションを指定して値全体を整形した文字列を返す (JsonValue::format_with_options と同じ出力になる)
+     */
+    pub fn format_with_options(&self, options: &FormatOptions) -> String {
+        let mut formatted = String::new();
+        self.format_tree(options, &mut formatted);
+        return formatted;
+    }
+}
+
+impl<'a> FormatTree for ArenaValue<'a> {
+    type Members<'v>
+        = iter::Map<
+        slice::Iter<'v, (&'a str, ArenaValue<'a>)>,
+        fn(&'v (&'a str, ArenaValue<'a>)) -> (&'v str, &'v ArenaValue<'a>),
+    >
+    where
+        Self: 'v;
+
+    fn value_ref(&self) -> ValueRef<'_, Self> {
+        return match self {
+            ArenaValue::Object(members) => ValueRef::Object(members.iter().map(member_ref as fn(_) -> _)),
+            ArenaValue::Array(array) => ValueRef::Array(array),
+            ArenaValue::String(str) => ValueRef::String(str),
+            ArenaValue::Number(num) => ValueRef::Number(Cow::Borrowed(num)),
+            ArenaValue::BigNumber(literal) => ValueRef::Number(Cow::Owned(Number::Big((*literal).into()))),
+            ArenaValue::Bool(bool) => ValueRef::Bool(*bool),
+            ArenaValue::Null => ValueRef::Null,
+        };
+    }
+}
+
+/**
+ * オブジェクトのメンバーを、キーと値の参照の組にする
+ */
+fn member_ref<'v, 'a>((key, value): &'v (&'a str, ArenaValue<'a>)) -> (&'v str, &'v ArenaValue<'a>) {
+    return (key, value);
+}
+
+/**
+ * JsonValue と同じく {} で1行に、{:#} で字下げして出力する
+ */
+impl fmt::Display for ArenaValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = if f.alternate() {
+            FormatOptions::default()
+        } else {
+            FormatOptions::compact()
+        };
+        return f.write_str(&self.format_with_options(&options));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Limits, ParseErrorKind};
+
+    #[test]
+    fn test_parse_in() {
+        let bump = Bump::new();
+        let value = parse_in(r#"{"a": [1, true, null], "b": "c\nd"}"#, &bump).unwrap();
+
+        assert_eq!(value.get("b"), Some(&ArenaValue::String("c\nd")));
+        let Some(ArenaValue::Array(array)) = value.get("a") else {
+            panic!("array expected");
+        };
+        assert_eq!(
+            array,
+            &[
+                ArenaValue::Number(Number::Int(1)),
+                ArenaValue::Bool(true),
+                ArenaValue::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_in_errors() {
+        let bump = Bump::new();
+        assert!(parse_in(r#"{"a" 1}"#, &bump).is_err());
+        assert!(parse_in("[1, 2] 3", &bump).is_err());
+    }
+
+    #[test]
+    fn test_arena_duplicate_keys() {
+        let bump = Bump::new();
+        let value = parse_in(r#"{"a": 1, "a": 2}"#, &bump).unwrap();
+
+        assert_eq!(value.get("a"), Some(&ArenaValue::Number(Number::Int(2))));
+        assert_eq!(value.to_owned_value(), r#"{"a": 2}"#.parse::<JsonValue>().unwrap());
+    }
+
+    #[test]
+    fn test_arena_display_matches_json_value() {
+        let input = r#"{"a": [1, {"b": "c"}, []], "d": {}, "e": -1.5}"#;
+        let bump = Bump::new();
+        let value = parse_in(input, &bump).unwrap();
+        let owned = input.parse::<JsonValue>().unwrap();
+
+        assert_eq!(format!("{}", value), format!("{}", owned));
+        assert_eq!(format!("{:#}", value), format!("{:#}", owned));
+
+        let options = FormatOptions {
+            collapse: true,
+            max_width: 24,
+            indent_unit: "\t".to_string(),
+            ..FormatOptions::default()
+        };
+        assert_eq!(value.format_with_options(&options), owned.format_with_options(&options));
+    }
+
+    #[test]
+    fn test_parse_in_limits() {
+        let bump = Bump::new();
+        let limits = Limits {
+            max_elements: Some(2),
+            ..Limits::default()
+        };
+        let mut parser = Parser::new(Lexer::new("[1, 2, 3]")).with_limits(limits);
+        assert_eq!(
+            parser.parse_in(&bump).unwrap_err().kind,
+            ParseErrorKind::TooManyElements
+        );
+
+        // 入れ子の深さは既定でも制限する
+        let error = parse_in(&"[".repeat(200_000), &bump).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::TooDeep);
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_parse_in_big_number() {
+        // f64 に丸めず、元のリテラルを保持する
+        let bump = Bump::new();
+        let input = "[3.14159265358979323846264338327950288, 1e400]";
+        let value = parse_in(input, &bump).unwrap();
+
+        assert_eq!(
+            value,
+            ArenaValue::Array(&[
+                ArenaValue::BigNumber("3.14159265358979323846264338327950288"),
+                ArenaValue::BigNumber("1e400")
+            ])
+        );
+        assert_eq!(value.to_string(), "[3.14159265358979323846264338327950288,1e400]");
+        assert_eq!(value.to_owned_value(), input.parse::<JsonValue>().unwrap());
+    }
+}
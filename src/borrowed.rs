@@ -0,0 +1,284 @@
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::fmt;
+use std::iter;
+
+use crate::json::{FormatOptions, FormatTree, JsonValue, ValueRef};
+use crate::lexer::Lexer;
+use crate::number::Number;
+use crate::parser::{ParseError, Parser, ValueBuilder};
+
+/**
+ * 文字列やキーを入力から借用する JSON 値
+ * エスケープシーケンスを含まない文字列は入力をそのまま指すため、一度整形するだけの大きな入力でもコピーが発生しない
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Object(BorrowedObject<'a>), // {"key": "value"}
+    Array(BorrowedArray<'a>),   // [1, 2, 3]
+    String(Cow<'a, str>),       // "hello, world"
+    Number(Number),             // 123, 123.456
+    Bool(bool),                 // true, false
+    Null,                       // null
+}
+
+pub type BorrowedObject<'a> = IndexMap<Cow<'a, str>, BorrowedValue<'a>>;
+pub type BorrowedArray<'a> = Vec<BorrowedValue<'a>>;
+
+/**
+ * 入力を借用したまま JSON をパースする
+ */
+pub fn parse_borrowed(input: &str) -> Result<BorrowedValue<'_>, ParseError> {
+    let mut parser = Parser::new(Lexer::new(input));
+    return parser.parse_borrowed();
+}
+
+impl<'a> Parser<'a> {
+    /**
+     * 入力全体を、文字列を入力から借用する BorrowedValue としてパースする
+     * 値の後に余分な入力が残っている場合はエラーになる (回復や上限の確認、キーの重複の検出は parse と同じ)
+     */
+    pub fn parse_borrowed(&mut self) -> Result<BorrowedValue<'a>, ParseError> {
+        return self.parse_with(&BorrowedBuilder);
+    }
+}
+
+/**
+ * 文字列やキーを入力から借用する BorrowedValue を組み立てる
+ */
+struct BorrowedBuilder;
+
+impl<'a> ValueBuilder<'a> for BorrowedBuilder {
+    type Value = BorrowedValue<'a>;
+    type Object = BorrowedObject<'a>;
+    type Array = BorrowedArray<'a>;
+
+    fn string(&self, string: &Cow<'a, str>) -> BorrowedValue<'a> {
+        return BorrowedValue::String(string.clone());
+    }
+
+    fn number(&self, number: &Number) -> BorrowedValue<'a> {
+        return BorrowedValue::Number(number.clone());
+    }
+
+    fn bool(&self, bool: bool) -> BorrowedValue<'a> {
+        return BorrowedValue::Bool(bool);
+    }
+
+    fn null(&self) -> BorrowedValue<'a> {
+        return BorrowedValue::Null;
+    }
+
+    fn object(&self) -> BorrowedObject<'a> {
+        return IndexMap::new();
+    }
+
+    fn insert(
+        &self,
+        object: &mut BorrowedObject<'a>,
+        key: Cow<'a, str>,
+        _key_position: usize,
+        value: BorrowedValue<'a>,
+    ) -> Option<String> {
+        let (index, replaced) = object.insert_full(key, value);
+        return replaced.map(|_| object.get_index(index).unwrap().0.to_string());
+    }
+
+    fn finish_object(&self, object: BorrowedObject<'a>) -> BorrowedValue<'a> {
+        return BorrowedValue::Object(object);
+    }
+
+    fn array(&self) -> BorrowedArray<'a> {
+        return Vec::new();
+    }
+
+    fn push(&self, array: &mut BorrowedArray<'a>, value: BorrowedValue<'a>) {
+        array.push(value);
+    }
+
+    fn finish_array(&self, array: BorrowedArray<'a>) -> BorrowedValue<'a> {
+        return BorrowedValue::Array(array);
+    }
+}
+
+impl BorrowedValue<'_> {
+    /**
+     * 入力への借用をなくし、文字列をすべて所有する JsonValue に変換する
+     */
+    pub fn into_owned(self) -> JsonValue {
+        return match self {
+            BorrowedValue::Object(obj) => JsonValue::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+            BorrowedValue::Array(array) => JsonValue::Array(array.into_iter().map(|v| v.into_owned()).collect()),
+            BorrowedValue::String(str) => JsonValue::String(str.into_owned()),
+            BorrowedValue::Number(num) => JsonValue::Number(num),
+            BorrowedValue::Bool(bool) => JsonValue::Bool(bool),
+            BorrowedValue::Null => JsonValue::Null,
+        };
+    }
+
+    /**
+     * オプションを指定して値全体を整形した文字列を返す (JsonValue::format_with_options と同じ出力になる)
+     */
+    pub fn format_with_options(&self, options: &FormatOptions) -> String {
+        let mut formatted = String::new();
+        self.format_into(options, &mut formatted);
+        return formatted;
+    }
+
+    /**
+     * オプションを指定して値全体を整形し、formatted の末尾に追加する
+     */
+    pub fn format_into(&self, options: &FormatOptions, formatted: &mut String) {
+        self.format_tree(options, formatted);
+    }
+}
+
+impl<'a> FormatTree for BorrowedValue<'a> {
+    type Members<'v>
+        = iter::Map<
+        indexmap::map::Iter<'v, Cow<'a, str>, BorrowedValue<'a>>,
+        fn((&'v Cow<'a, str>, &'v BorrowedValue<'a>)) -> (&'v str, &'v BorrowedValue<'a>),
+    >
+    where
+        Self: 'v;
+
+    fn value_ref(&self) -> ValueRef<'_, Self> {
+        return match self {
+            BorrowedValue::Object(obj) => ValueRef::Object(obj.iter().map(member_ref as fn(_) -> _)),
+            BorrowedValue::Array(array) => ValueRef::Array(array),
+            BorrowedValue::String(str) => ValueRef::String(str),
+            BorrowedValue::Number(num) => ValueRef::Number(Cow::Borrowed(num)),
+            BorrowedValue::Bool(bool) => ValueRef::Bool(*bool),
+            BorrowedValue::Null => ValueRef::Null,
+        };
+    }
+}
+
+/**
+ * オブジェクトのメンバーのキーを &str にする
+ */
+fn member_ref<'v, 'a>((key, value): (&'v Cow<'a, str>, &'v BorrowedValue<'a>)) -> (&'v str, &'v BorrowedValue<'a>) {
+    return (key, value);
+}
+
+/**
+ * JsonValue と同じく {} で1行に、{:#} で字下げして出力する
+ */
+impl fmt::Display for BorrowedValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = if f.alternate() {
+            FormatOptions::default()
+        } else {
+            FormatOptions::compact()
+        };
+        return f.write_str(&self.format_with_options(&options));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::NumericArrayLayout;
+    use crate::parser::{Limits, ParseErrorKind};
+
+    #[test]
+    fn test_parse_borrowed_borrows_unescaped_strings() {
+        let input = r#"{"name": "alice", "quote": "say \"hi\""}"#;
+        let BorrowedValue::Object(obj) = parse_borrowed(input).unwrap() else {
+            panic!("object expected");
+        };
+
+        assert!(obj.keys().all(|key| matches!(key, Cow::Borrowed(_))));
+        assert!(matches!(obj["name"], BorrowedValue::String(Cow::Borrowed("alice"))));
+        assert_eq!(
+            obj["quote"],
+            BorrowedValue::String(Cow::Owned("say \"hi\"".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_borrowed_errors() {
+        assert!(parse_borrowed(r#"{"a" 1}"#).is_err());
+        assert!(parse_borrowed("[1, 2] 3").is_err());
+    }
+
+    #[test]
+    fn test_parse_borrowed_limits_and_duplicates() {
+        // 上限やキーの重複は parse と同じく確かめる
+        let limits = Limits {
+            max_string_length: Some(3),
+            ..Limits::default()
+        };
+        let mut parser = Parser::new(Lexer::new(r#"["abc", "abcd"]"#)).with_limits(limits);
+        let error = parser.parse_borrowed().unwrap_err();
+        assert_eq!((error.kind, error.position), (ParseErrorKind::StringTooLong, 8));
+
+        // 入れ子の深さは既定でも制限する
+        let error = parse_borrowed(&"[".repeat(200_000)).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::TooDeep);
+
+        let mut parser = Parser::new(Lexer::new(r#"{"a": 1, "b": 2, "a": 3}"#));
+        let BorrowedValue::Object(obj) = parser.parse_borrowed().unwrap() else {
+            panic!("object expected");
+        };
+        assert_eq!(obj["a"], BorrowedValue::Number(Number::Int(3)));
+        assert_eq!(parser.duplicate_keys().len(), 1);
+        assert_eq!(
+            (parser.duplicate_keys()[0].first, parser.duplicate_keys()[0].second),
+            (1, 17)
+        );
+    }
+
+    #[test]
+    fn test_borrowed_into_owned() {
+        let input = r#"{"a": [1, true, null], "b": "c\nd", "e": {}}"#;
+        let borrowed = parse_borrowed(input).unwrap();
+
+        assert_eq!(borrowed.into_owned(), input.parse::<JsonValue>().unwrap());
+    }
+
+    #[test]
+    fn test_borrowed_display_matches_json_value() {
+        let input = r#"{"a": [1, {"b": "c"}, []], "d": {}, "e": -1.5}"#;
+        let borrowed = parse_borrowed(input).unwrap();
+        let owned = input.parse::<JsonValue>().unwrap();
+
+        assert_eq!(format!("{}", borrowed), format!("{}", owned));
+        assert_eq!(format!("{:#}", borrowed), format!("{:#}", owned));
+    }
+
+    #[test]
+    fn test_borrowed_format_with_options() {
+        let input = r#"{"a": [1, 2, 3, 4], "b": {"c": "d\/e"}, "f": [[1], {"g": null}], "h": 1.25}"#;
+        let borrowed = parse_borrowed(input).unwrap();
+        let owned = input.parse::<JsonValue>().unwrap();
+
+        let options = [
+            FormatOptions {
+                indent_unit: "\t".to_string(),
+                trailing_newline: true,
+                collapse: true,
+                max_width: 20,
+                ..FormatOptions::default()
+            },
+            FormatOptions {
+                numeric_arrays: NumericArrayLayout::Wrapped(2),
+                max_array_items: Some(1),
+                max_depth: Some(2),
+                precision: Some(1),
+                ..FormatOptions::default()
+            },
+            FormatOptions::compact(),
+        ];
+        for options in options {
+            assert_eq!(
+                borrowed.format_with_options(&options),
+                owned.format_with_options(&options)
+            );
+        }
+    }
+}
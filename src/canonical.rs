@@ -0,0 +1,222 @@
+use sha2::{Digest, Sha256, Sha512};
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::json::{push_escaped_string, JsonValue};
+use crate::number::{format_shortest, ExponentNotation};
+use crate::walk::JsonPath;
+
+/**
+ * 正規化形式のハッシュ値を求めるアルゴリズム
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DigestAlgorithm {
+    Sha256, // SHA-256
+    Sha512, // SHA-512
+}
+
+/**
+ * 正規化形式にできない、有限でない数値があることを表すエラー
+ * RFC 8785 は数値を倍精度浮動小数点数として出力するため、NaN や Infinity、倍精度で表せない 1e400 などは正規化できない
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonFiniteError {
+    pub path: JsonPath, // 最初に現れた有限でない数値の経路
+}
+
+impl fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "有限でない数値は正規化形式にできません (経路 \"{}\")", self.path);
+    }
+}
+
+impl Error for NonFiniteError {}
+
+impl JsonValue {
+    /**
+     * RFC 8785 (JSON Canonicalization Scheme) に準拠した文字列を返す
+     * キーは UTF-16 のコード単位順に並べ、空白は一切含めない
+     * 有限でない数値がある場合は、RFC 8785 と同じくエラーにする
+     */
+    pub fn format_canonical(&self) -> Result<String, NonFiniteError> {
+        let non_finite = self
+            .walk()
+            .find(|(_, value)| matches!(value, JsonValue::Number(num) if !num.as_f64().is_finite()));
+        if let Some((path, _)) = non_finite {
+            return Err(NonFiniteError { path });
+        }
+        let mut formatted = String::new();
+        self.format_canonical_value(&mut formatted);
+        return Ok(formatted);
+    }
+
+    /**
+     * RFC 8785 の正規化形式 (UTF-8) のハッシュ値を、16 進数の小文字の文字列で返す
+     * 空白やキーの順序、数値の書き方が異なっていても、意味が同じ値は同じハッシュ値になる
+     */
+    pub fn canonical_digest(&self, algorithm: DigestAlgorithm) -> Result<String, NonFiniteError> {
+        let canonical = self.format_canonical()?;
+        let hash = match algorithm {
+            DigestAlgorithm::Sha256 => Sha256::digest(canonical.as_bytes()).to_vec(),
+            DigestAlgorithm::Sha512 => Sha512::digest(canonical.as_bytes()).to_vec(),
+        };
+        let mut hex = String::with_capacity(hash.len() * 2);
+        for byte in hash {
+            write!(hex, "{:02x}", byte).unwrap();
+        }
+        return Ok(hex);
+    }
+
+    fn format_canonical_value(&self, formatted: &mut String) {
+        match self {
+            JsonValue::Object(obj) => {
+                // キーは UTF-16 のコード単位で比較してソートする
+                let mut entries: Vec<(&String, &JsonValue)> = obj.iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+                formatted.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        formatted.push(',');
+                    }
+                    push_escaped_string(formatted, key);
+                    formatted.push(':');
+                    value.format_canonical_value(formatted);
+                }
+                formatted.push('}');
+            }
+            JsonValue::Array(array) => {
+                formatted.push('[');
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        formatted.push(',');
+                    }
+                    value.format_canonical_value(formatted);
+                }
+                formatted.push(']');
+            }
+            JsonValue::String(str) => push_escaped_string(formatted, str),
+            JsonValue::Number(num) => formatted.push_str(&format_es6_number(num.as_f64())),
+            JsonValue::Bool(true) => formatted.push_str("true"),
+            JsonValue::Bool(false) => formatted.push_str("false"),
+            JsonValue::Null => formatted.push_str("null"),
+        }
+    }
+}
+
+/**
+ * 数値を ECMAScript の Number.prototype.toString と同じ形式で文字列化する
+ * 有効数字は往復変換可能な最短桁数を用いる
+ */
+pub fn format_es6_number(num: f64) -> String {
+    return format_shortest(num, ExponentNotation::Auto);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_format_canonical_sorts_keys() {
+        let mut object = IndexMap::new();
+        object.insert("b".to_string(), JsonValue::from(2.0));
+        object.insert(
+            "a".to_string(),
+            JsonValue::Array(vec![JsonValue::Bool(true), JsonValue::Null]),
+        );
+        object.insert("c".to_string(), JsonValue::String("x".to_string()));
+        let value = JsonValue::Object(object);
+
+        assert_eq!(value.format_canonical().unwrap(), r#"{"a":[true,null],"b":2,"c":"x"}"#);
+    }
+
+    #[test]
+    fn test_format_canonical_sorts_keys_by_utf16() {
+        // U+1F600 (サロゲートペア) は U+FB33 よりも UTF-16 では前に並ぶ
+        let mut object = IndexMap::new();
+        object.insert("\u{FB33}".to_string(), JsonValue::from(1.0));
+        object.insert("\u{1F600}".to_string(), JsonValue::from(2.0));
+        let value = JsonValue::Object(object);
+
+        assert_eq!(value.format_canonical().unwrap(), "{\"\u{1F600}\":2,\"\u{FB33}\":1}");
+    }
+
+    #[test]
+    fn test_format_canonical_string_escape() {
+        let value = JsonValue::String("\"\\/\u{8}\u{c}\n\r\t\u{1f}é".to_string());
+        assert_eq!(value.format_canonical().unwrap(), r#""\"\\/\b\f\n\r\t\u001fé""#);
+    }
+
+    #[test]
+    fn test_canonical_digest() {
+        let value: JsonValue = "{ \"b\": 1,\n  \"a\": [1.0] }".parse().unwrap();
+        assert_eq!(
+            value.canonical_digest(DigestAlgorithm::Sha256).unwrap(),
+            "964ac5a0bb65d615144e0fca569cac7f8f8c7c6647f35a79c8f399878e5b9af6"
+        );
+        assert_eq!(
+            value.canonical_digest(DigestAlgorithm::Sha512).unwrap(),
+            "a3f61492f994199869edde25e07d7814637a45d1903026764921014e5f526a0af7136d9a578ee08a4b6f5f9044acf0ab50dcbab1bc4d3f4509a2dd58eeaf5ce0"
+        );
+    }
+
+    #[test]
+    fn test_canonical_digest_escaped_string() {
+        // エスケープした書き方と、そのままの書き方の文字列は同じハッシュ値になる
+        let digest = |input: &str| {
+            let value: JsonValue = input.parse().unwrap();
+            return value.canonical_digest(DigestAlgorithm::Sha256).unwrap();
+        };
+        assert_eq!(
+            digest(r#"{"\u006b\u0065y": "\u00e9\ud83d\ude00\/"}"#),
+            digest(r#"{"key": "é😀/"}"#)
+        );
+        assert_eq!(digest(r#"["A\u0042C"]"#), digest(r#"["ABC"]"#));
+
+        // 異なる文字列は異なるハッシュ値になる
+        assert_ne!(digest(r#"["\u0041BC"]"#), digest(r#"["BC"]"#));
+        assert_ne!(digest(r#"{"key": "é"}"#), digest(r#"{"key": "e"}"#));
+        assert_ne!(digest(r#"{"key": 1}"#), digest(r#"{"kex": 1}"#));
+    }
+
+    #[test]
+    fn test_format_canonical_non_finite() {
+        // 有限でない数値は null にせずエラーにする
+        let value = JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(f64::NAN)]);
+        let error = value.format_canonical().unwrap_err();
+        assert_eq!(error.path.to_string(), "/1");
+        assert!(value.canonical_digest(DigestAlgorithm::Sha256).is_err());
+        assert!(JsonValue::from(f64::INFINITY)
+            .format_canonical()
+            .unwrap_err()
+            .path
+            .is_root());
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_format_canonical_big_number() {
+        // 倍精度で表せない数値もエラーにし、表せる数値は倍精度に丸めて出力する
+        let value: JsonValue = r#"{"a": [1e400]}"#.parse().unwrap();
+        assert_eq!(value.format_canonical().unwrap_err().path.to_string(), "/a/0");
+        let value: JsonValue = "[3.14159265358979323846264338327950288]".parse().unwrap();
+        assert_eq!(value.format_canonical().unwrap(), "[3.141592653589793]");
+    }
+
+    #[test]
+    fn test_format_es6_number() {
+        assert_eq!(format_es6_number(0.0), "0");
+        assert_eq!(format_es6_number(-0.0), "0");
+        assert_eq!(format_es6_number(1.0), "1");
+        assert_eq!(format_es6_number(-123.456), "-123.456");
+        assert_eq!(format_es6_number(1e21), "1e+21");
+        assert_eq!(format_es6_number(1e20), "100000000000000000000");
+        assert_eq!(format_es6_number(0.000001), "0.000001");
+        assert_eq!(format_es6_number(1e-7), "1e-7");
+        assert_eq!(format_es6_number(4.5e-7), "4.5e-7");
+        assert_eq!(format_es6_number(333333333.3333333), "333333333.3333333");
+        assert_eq!(format_es6_number(1.7976931348623157e308), "1.7976931348623157e+308");
+        assert_eq!(format_es6_number(5e-324), "5e-324");
+    }
+}
@@ -0,0 +1,819 @@
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use rust_json_formatter::access::parse_pointer;
+use rust_json_formatter::json::{
+    EscapeOptions, FormatOptions, JsonValue, LineEnding, NumericArrayLayout, OmissionMarker, DEFAULT_MAX_WIDTH,
+};
+use rust_json_formatter::normalize::NormalizationForm;
+use rust_json_formatter::number::{ExponentNotation, NonFiniteOutput};
+use rust_json_formatter::parser::{Limits, DEFAULT_MAX_DEPTH};
+use rust_json_formatter::pick::PickLayout;
+use rust_json_formatter::sort::KeyOrder;
+use rust_json_formatter::walk::PathPattern;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::compression::Compression;
+use crate::error::CliError;
+use crate::messages::{self, t, Lang};
+
+/**
+ * --max-depth に指定できる深さの上限
+ * 整形や変換も入れ子の深さだけ再帰するため、main の STACK_SIZE で足りる深さまでに制限する
+ */
+pub const MAX_DEPTH_CEILING: usize = 10_000;
+
+/**
+ * コマンドライン引数の定義
+ */
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "標準入力から読み込んだ JSON を整形して出力する",
+    long_about = None,
+    args_override_self = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 整形するファイル、ディレクトリ、glob パターンまたは URL (指定しない場合は標準入力から読み込む)
+    #[arg(value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// 整形結果を標準出力ではなく入力ファイルに上書きする
+    #[arg(short, long, requires = "files")]
+    pub write: bool,
+
+    /// 整形結果を出力せず、入力が整形済みかどうかだけを確かめる (整形されていない入力があれば終了コード 3 で終了する)
+    #[arg(long, conflicts_with_all = ["write", "watch", "stream", "clipboard"])]
+    pub check: bool,
+
+    /// 整形結果やメッセージを一切出力しない (終了コードのみで結果を返す)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// 処理の経過を標準エラー出力に表示する (-v で処理時間や件数、-vv でファイルの探索や誤りからの回復などの判断も表示する)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// 入力ファイルやディレクトリを監視し、変更されるたびに整形する
+    #[arg(long, requires = "files")]
+    pub watch: bool,
+
+    /// ディレクトリを指定した場合に対象とするファイルの拡張子 (カンマ区切りで複数指定可)
+    #[arg(
+        long = "ext",
+        value_name = "EXT",
+        value_delimiter = ',',
+        default_value = "json",
+        global = true
+    )]
+    pub extensions: Vec<String>,
+
+    /// ディレクトリを辿る際に除外するパスの glob パターン (node_modules や **/fixtures/** など。複数指定可)
+    #[arg(long, value_name = "PATTERN", global = true)]
+    pub ignore: Vec<String>,
+
+    /// 設定ファイルのパス (指定しない場合は入力のパスから親ディレクトリを辿って .jsonfmt.toml を探す)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// 設定ファイルを読み込まない
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
+
+    /// クリップボードの JSON を整形する (write はクリップボードに書き戻し、print は標準出力に出力する)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "write",
+        conflicts_with_all = ["files", "stream"]
+    )]
+    pub clipboard: Option<ClipboardMode>,
+
+    /// 入力を gzip として展開する (指定しなくても gzip の先頭のバイト列や .gz の拡張子から判定する)
+    #[arg(long, global = true)]
+    pub gzip: bool,
+
+    /// UTF-8 として不正なバイト列を含む入力の扱い (replace は U+FFFD に置き換えて警告を表示し、error はエラーにする)
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = InvalidUtf8::Error, global = true)]
+    pub invalid_utf8: InvalidUtf8,
+
+    /// 出力の圧縮形式 (--write の場合、指定しなければ入力と同じ形式で書き込む)
+    #[arg(long, value_enum)]
+    pub output_compression: Option<Compression>,
+
+    /// よくある誤り (`,` の抜けや余分な `,`、クォートのないキー、閉じ括弧の不足) を直して整形し、直した箇所を警告として表示する
+    #[arg(long, visible_alias = "best-effort", conflicts_with = "stream")]
+    pub fix: bool,
+
+    /// NaN、Infinity、-Infinity を数値として受け付ける (Python の json モジュールなどが出力する JSON 向け)
+    #[arg(long, conflicts_with = "stream")]
+    pub allow_non_finite: bool,
+
+    /// 入力 (展開した後の内容) のバイト数の上限。超えた場合は読み込みを打ち切ってエラーにする
+    #[arg(long, value_name = "BYTES", global = true)]
+    pub max_input_size: Option<usize>,
+
+    /// 文字列 (キーを含む) のエスケープを解いた後のバイト数の上限
+    #[arg(long, value_name = "BYTES")]
+    pub max_string_length: Option<usize>,
+
+    /// 1つの配列の要素数や、1つのオブジェクトのメンバー数の上限
+    #[arg(long, value_name = "COUNT")]
+    pub max_elements: Option<usize>,
+
+    /// 入力全体に含まれる値 (オブジェクトや配列自体も含む) の数の上限
+    #[arg(long, value_name = "COUNT")]
+    pub max_nodes: Option<usize>,
+
+    /// オブジェクトや配列の入れ子の深さの上限 (深すぎる入力でスタックを使い果たさないよう、指定しない場合も 512 に制限する。10000 まで指定できる)
+    #[arg(long, value_name = "DEPTH", global = true, value_parser = parse_max_depth)]
+    pub max_depth: Option<usize>,
+
+    /// --check や validate の結果の報告形式 (sarif の場合は SARIF 2.1.0 のログを標準出力に出力する)
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = ReportFormat::Text, global = true)]
+    pub report_format: ReportFormat,
+
+    /// 入力形式
+    #[arg(long, value_enum, default_value_t = InputFormat::Json, global = true)]
+    pub from: InputFormat,
+
+    /// 出力形式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub to: OutputFormat,
+
+    /// 値が文字列の場合、クォートやエスケープをせずに中身をそのまま出力する
+    #[arg(short, long)]
+    pub raw_output: bool,
+
+    /// 文字列として埋め込まれた JSON ("{\"a\":1}" のようなオブジェクトや配列) をパースし、入れ子の値として整形する
+    #[arg(long)]
+    pub parse_nested: bool,
+
+    /// --parse-nested でパースした値を、1行の JSON の文字列に戻して出力する (埋め込まれた JSON の検証と空白の正規化に使う)
+    #[arg(long, requires = "parse_nested")]
+    pub restringify_nested: bool,
+
+    /// 経路がパターンに一致する値 (オブジェクトのメンバーや配列の要素) を、整形する前に削除する (複数指定可)
+    /// 配列の添字は削除する前の位置で指定する
+    #[arg(long, value_name = "PATTERN", value_parser = parse_path_pattern)]
+    pub delete: Vec<PathPattern>,
+
+    /// 文字列の値に含まれる ${NAME} を環境変数の値に置き換える ($${ は置き換えずに ${ にする)
+    #[arg(long)]
+    pub substitute_env: bool,
+
+    /// --substitute-env で環境変数が設定されていない場合の扱い (keep の場合は ${NAME} のまま残して警告を表示する)
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = MissingEnv::Error, requires = "substitute_env")]
+    pub missing_env: MissingEnv,
+
+    /// 経路がパターン (JSON Pointer に任意の1要素の * と任意の深さの ** を加えたもの) に一致する文字列を Base64 として復号する (複数指定可)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_path_pattern)]
+    pub decode_base64: Vec<PathPattern>,
+
+    /// JSON のリテラルとして読める文字列 ("42"、"true"、"null" など) を対応する型の値に変換する
+    /// --coerce=PATTERN のように経路のパターンを指定すると、一致する値だけを変換する (複数指定可)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_path_pattern, num_args = 0..=1, require_equals = true, default_missing_value = "/**")]
+    pub coerce: Vec<PathPattern>,
+
+    /// 配列から重複した要素 (キーの順序を無視し、数値は値で比較して同じもの) を取り除き、最初に現れた要素を残す
+    /// --dedupe=PATTERN のように経路のパターンを指定すると、一致する配列だけを対象にする (複数指定可)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_path_pattern, num_args = 0..=1, require_equals = true, default_missing_value = "/**")]
+    pub dedupe: Vec<PathPattern>,
+
+    /// --dedupe で、要素の中のこの経路 (.id や .user.id、または JSON Pointer の /id) の値が同じ要素を重複とみなす
+    #[arg(long, value_name = "KEY", value_parser = parse_dedupe_key, requires = "dedupe")]
+    pub dedupe_by: Option<String>,
+
+    /// オブジェクトのキーを指定した Unicode の正規化形式に揃える (正規化によって重複したキーは後の値で上書きし、警告を表示する)
+    #[arg(long, value_enum, value_name = "FORM")]
+    pub normalize_keys: Option<KeyNormalization>,
+
+    /// オブジェクトのキーの名前を OLD から NEW に変える (どの深さのキーも対象で、メンバーの位置は変えない。複数指定可)
+    #[arg(long, value_name = "OLD=NEW", value_parser = parse_rename)]
+    pub rename: Vec<(String, String)>,
+
+    /// --rename の対応表を {"OLD": "NEW", ...} の形式の JSON ファイルから読み込む (--rename の指定を優先する)
+    #[arg(long, value_name = "FILE", value_parser = load_rename_file)]
+    pub rename_file: Vec<HashMap<String, String>>,
+
+    /// JSON Pointer で指定した値だけを、元の入れ子の構造を保って取り出す (複数指定可。配列は選んだ要素だけを詰めて並べる)
+    #[arg(long, value_name = "POINTER", value_parser = parse_pick)]
+    pub pick: Vec<String>,
+
+    /// --pick で取り出した値を、JSON Pointer をキーとする1段のオブジェクトにする (指定した順に並べる)
+    #[arg(long, requires = "pick")]
+    pub pick_flat: bool,
+
+    /// オブジェクトのメンバーをキーの順に並べ替える
+    /// --sort-keys=natural を指定すると、数字の並びを数値として比較する ("item2" を "item10" より前にする)
+    #[arg(long, value_enum, value_name = "ORDER", num_args = 0..=1, require_equals = true, default_missing_value = "lexical")]
+    pub sort_keys: Option<KeySort>,
+
+    /// 指定したキー (カンマ区切り) のメンバーを、その順にすべてのオブジェクトの先頭へ移す
+    /// 残りのメンバーは元の順序 (--sort-keys を指定した場合は並べ替えた順) で続ける
+    #[arg(long, value_name = "KEYS", value_delimiter = ',')]
+    pub key_order: Vec<String>,
+
+    /// オブジェクトのメンバーを、JSON Schema ファイルの properties に書かれたキーの順に並べ替える
+    /// スキーマにないキーのメンバーは元の順序で後ろに続ける (--key-order を指定した場合はその後に先頭へ移す)
+    #[arg(long, value_name = "FILE", value_parser = load_json_file, conflicts_with = "key_order_example")]
+    pub key_order_schema: Option<JsonValue>,
+
+    /// オブジェクトのメンバーを、見本の JSON ファイルの同じ位置にあるオブジェクトのキーの順に並べ替える
+    /// 配列の要素は、見本の同じ位置の要素 (なければ先頭の要素) に合わせる
+    #[arg(long, value_name = "FILE", value_parser = load_json_file)]
+    pub key_order_example: Option<JsonValue>,
+
+    /// UNIX 時間の数値を指定した形式の文字列に変換する (単位は秒、ミリ秒、マイクロ秒、ナノ秒から値の大きさで推定する)
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub timestamps: Option<TimestampFormat>,
+
+    /// --timestamps で変換する値のキーの glob パターン (複数指定可)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_key_pattern, default_values = ["*_at", "*_time"], requires = "timestamps")]
+    pub timestamp_key: Vec<glob::Pattern>,
+
+    /// --timestamps で変換する値の経路のパターン (キーの名前によらず変換する。複数指定可)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_path_pattern, requires = "timestamps")]
+    pub timestamp_path: Vec<PathPattern>,
+
+    /// RFC 8785 (JCS) に準拠した正規化形式で出力する
+    #[arg(long)]
+    pub canonical: bool,
+
+    /// 1行の幅に収まるオブジェクトや配列を1行にまとめる
+    #[arg(long)]
+    pub collapse: bool,
+
+    /// --collapse で1行にまとめる際の1行の最大幅
+    #[arg(long, value_name = "WIDTH", default_value_t = DEFAULT_MAX_WIDTH, requires = "collapse")]
+    pub max_width: usize,
+
+    /// 指定した深さ以上にあるオブジェクトや配列を {…} や [… 34 items] に置き換えて表示する (巨大な文書の構造の確認用で、出力は JSON ではない)
+    #[arg(long, value_name = "DEPTH", conflicts_with_all = ["write", "check", "stream", "clipboard"])]
+    pub max_display_depth: Option<usize>,
+
+    /// 配列の先頭から指定した数の要素だけを表示し、残りは /* 995 more */ のような印で示す
+    #[arg(long, value_name = "ITEMS", conflicts_with_all = ["write", "check", "stream", "clipboard"])]
+    pub max_array_items: Option<usize>,
+
+    /// --max-array-items で省略した要素の数を示す印の形式 (string は "… 995 more" という要素で、出力が JSON として正しくなる)
+    #[arg(long, value_enum, value_name = "STYLE", default_value_t = ArrayMarker::Comment, requires = "max_array_items")]
+    pub array_marker: ArrayMarker,
+
+    /// 入力のうち START:END の範囲 (バイト単位、END の位置は含まない) を囲む値だけを整形し、それ以外の部分はそのまま出力する
+    #[arg(
+        long,
+        value_name = "START:END",
+        value_parser = parse_range,
+        conflicts_with_all = ["line_range", "stream", "from", "to", "fix", "allow_non_finite", "max_string_length", "max_elements", "max_nodes", "max_depth", "raw_output", "canonical", "max_display_depth", "max_array_items", "delete", "substitute_env", "parse_nested", "decode_base64", "normalize_keys", "rename", "rename_file", "pick", "coerce", "dedupe", "timestamps", "sort_keys", "key_order", "key_order_schema", "key_order_example"]
+    )]
+    pub range: Option<(usize, usize)>,
+
+    /// --range と同じだが、範囲を行番号 (1 始まりで、END の行を含む) で指定する
+    #[arg(
+        long,
+        value_name = "START:END",
+        value_parser = parse_range,
+        conflicts_with_all = ["stream", "from", "to", "fix", "allow_non_finite", "max_string_length", "max_elements", "max_nodes", "max_depth", "raw_output", "canonical", "max_display_depth", "max_array_items", "delete", "substitute_env", "parse_nested", "decode_base64", "normalize_keys", "rename", "rename_file", "pick", "coerce", "dedupe", "timestamps", "sort_keys", "key_order", "key_order_schema", "key_order_example"]
+    )]
+    pub line_range: Option<(usize, usize)>,
+
+    /// 数値・真偽値のみからなる配列を1行にまとめる (値を指定するとその要素数ごとに折り返す)
+    #[arg(long, value_name = "ITEMS_PER_LINE", num_args = 0..=1, require_equals = true)]
+    pub compact_numeric_arrays: Option<Option<usize>>,
+
+    /// 1段分の字下げ (数値なら空白の数、"tab" ならタブ文字、それ以外は指定した文字列そのもの)
+    #[arg(long, default_value = "2", allow_hyphen_values = true)]
+    pub indent: String,
+
+    /// 出力の末尾に改行を付ける (--canonical 以外ではデフォルト)
+    #[arg(long, overrides_with = "no_newline")]
+    pub newline: bool,
+
+    /// 出力の末尾に改行を付けない
+    #[arg(long, overrides_with = "newline")]
+    pub no_newline: bool,
+
+    /// 入力の先頭に BOM がある場合、出力の先頭にも BOM を付ける (指定しない場合は取り除く)
+    #[arg(long)]
+    pub keep_bom: bool,
+
+    /// 文字列中の / を \/ とエスケープする
+    #[arg(long)]
+    pub escape_slash: bool,
+
+    /// 制御文字のエスケープの形式 (short は \n や \t などの短縮形があればそれを使い、unicode は常に \u00XX 形式にする)
+    #[arg(long, value_enum, value_name = "STYLE", default_value_t = ControlEscapes::Short)]
+    pub control_escapes: ControlEscapes,
+
+    /// \u00XX 形式のエスケープに使う16進数の大文字・小文字
+    #[arg(long, value_enum, value_name = "CASE", default_value_t = HexCase::Lower)]
+    pub hex_case: HexCase,
+
+    /// 数値を指数表記にする条件 (auto は JSON.stringify と同じ、never は常に位取りの表記、数値 N は指数の絶対値が N 以上の場合)
+    #[arg(long, value_name = "MODE", value_parser = parse_exponent, default_value = "auto")]
+    pub exponent: ExponentNotation,
+
+    /// すべての数値を小数点以下の指定した桁数に丸めるか 0 で補って出力する (3.1 は 3.100。ちょうど中間の値は偶数側に丸める。--exponent より優先する)
+    #[arg(long, value_name = "DIGITS", conflicts_with = "canonical")]
+    pub number_precision: Option<usize>,
+
+    /// 有限でない数値 (NaN、Infinity、-Infinity) の出力方法 (error はエラーにし、null は null に、string は "NaN" のような文字列に置き換える)
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = NonFinitePolicy::Error)]
+    pub non_finite: NonFinitePolicy,
+
+    /// 出力の改行コード
+    #[arg(long, value_enum, default_value_t = Eol::Lf)]
+    pub eol: Eol,
+
+    /// 標準出力が端末の場合も、ページャー ($PAGER、なければ less) を使わずに出力する
+    #[arg(long)]
+    pub no_pager: bool,
+
+    /// 出力に色を付けるかどうか (auto は標準出力が端末の場合のみ)
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// 入力全体を読み込まずに1トークンずつ整形する (巨大な入力向け。連結した複数の値も1つずつ整形する。レイアウトを変えるオプションとは併用できない)
+    #[arg(
+        long,
+        conflicts_with_all = ["files", "from", "to", "raw_output", "canonical", "collapse", "compact_numeric_arrays", "delete", "substitute_env", "parse_nested", "decode_base64", "normalize_keys", "rename", "rename_file", "pick", "coerce", "dedupe", "timestamps", "sort_keys", "key_order", "key_order_schema", "key_order_example"]
+    )]
+    pub stream: bool,
+
+    /// 色付けに使うテーマ (組み込みテーマ名またはテーマ定義 JSON ファイルのパス)
+    #[arg(long, default_value = "default")]
+    pub theme: String,
+
+    /// メッセージの言語 (指定しない場合は LC_ALL、LC_MESSAGES、LANG が ja で始まれば日本語、それ以外は英語)
+    #[arg(long, value_enum, global = true)]
+    pub lang: Option<Lang>,
+}
+
+impl Cli {
+    /**
+     * --compact-numeric-arrays の指定から数値配列のレイアウトを決定する
+     */
+    pub fn numeric_array_layout(&self) -> NumericArrayLayout {
+        return match self.compact_numeric_arrays {
+            None => NumericArrayLayout::Expanded,
+            Some(None) => NumericArrayLayout::SingleLine,
+            Some(Some(per_line)) => NumericArrayLayout::Wrapped(per_line),
+        };
+    }
+
+    /**
+     * 出力の末尾に改行を付けるかどうかを決定する
+     * --canonical の場合はバイト列を厳密に保つため、--newline を指定した場合のみ付ける
+     */
+    pub fn trailing_newline(&self) -> bool {
+        if self.canonical {
+            return self.newline;
+        }
+        return !self.no_newline;
+    }
+
+    /**
+     * --eol の指定から改行コードを決定する
+     */
+    pub fn line_ending(&self) -> LineEnding {
+        return match self.eol {
+            Eol::Lf => LineEnding::Lf,
+            Eol::Crlf => LineEnding::Crlf,
+        };
+    }
+
+    /**
+     * --escape-slash、--control-escapes、--hex-case の指定から文字列のエスケープの方法を決定する
+     */
+    pub fn escape_options(&self) -> EscapeOptions {
+        return EscapeOptions {
+            escape_slash: self.escape_slash,
+            unicode_control: self.control_escapes == ControlEscapes::Unicode,
+            uppercase_hex: self.hex_case == HexCase::Upper,
+        };
+    }
+
+    /**
+     * --array-marker の指定から省略した要素の印の形式を決定する
+     */
+    pub fn omission_marker(&self) -> OmissionMarker {
+        return match self.array_marker {
+            ArrayMarker::Comment => OmissionMarker::Comment,
+            ArrayMarker::String => OmissionMarker::String,
+        };
+    }
+
+    /**
+     * --normalize-keys の指定からキーの正規化形式を決定する
+     */
+    pub fn normalization_form(&self) -> Option<NormalizationForm> {
+        return self.normalize_keys.map(|form| match form {
+            KeyNormalization::Nfc => NormalizationForm::Nfc,
+            KeyNormalization::Nfd => NormalizationForm::Nfd,
+            KeyNormalization::Nfkc => NormalizationForm::Nfkc,
+            KeyNormalization::Nfkd => NormalizationForm::Nfkd,
+        });
+    }
+
+    /**
+     * --max-input-size などの指定から、パースする入力の大きさの上限を決定する
+     */
+    pub fn limits(&self) -> Limits {
+        return Limits {
+            max_input_size: self.max_input_size,
+            max_string_length: self.max_string_length,
+            max_elements: self.max_elements,
+            max_nodes: self.max_nodes,
+            max_depth: self.max_depth.or(Some(DEFAULT_MAX_DEPTH)),
+        };
+    }
+
+    /**
+     * --sort-keys の指定からキーの並べ方を決定する
+     */
+    pub fn key_order(&self) -> Option<KeyOrder> {
+        return self.sort_keys.map(|order| match order {
+            KeySort::Lexical => KeyOrder::Lexical,
+            KeySort::Natural => KeyOrder::Natural,
+        });
+    }
+
+    /**
+     * --non-finite の指定から有限でない数値の出力形式を決定する
+     * error の場合は出力前にエラーにするため、リテラルのまま出力する形式にしておく
+     */
+    pub fn non_finite_output(&self) -> NonFiniteOutput {
+        return match self.non_finite {
+            NonFinitePolicy::Error => NonFiniteOutput::Literal,
+            NonFinitePolicy::Null => NonFiniteOutput::Null,
+            NonFinitePolicy::String => NonFiniteOutput::String,
+        };
+    }
+
+    /**
+     * --range または --line-range の指定を、入力中のバイト単位の範囲に変換する (どちらも指定しない場合は None)
+     * --range が入力の長さを超える場合は使い方の誤りとする
+     */
+    pub fn input_range(&self, input: &str) -> Result<Option<(usize, usize)>, CliError> {
+        if let Some((start, end)) = self.range {
+            if end > input.len() {
+                return Err(CliError::usage(t!(RangeOutOfBounds, start, end, input.len())));
+            }
+            return Ok(Some((start, end)));
+        }
+        let Some((first, last)) = self.line_range else {
+            return Ok(None);
+        };
+        let mut line_starts = std::iter::once(0).chain(input.match_indices('\n').map(|(i, _)| i + 1));
+        let start = line_starts.nth(first.saturating_sub(1)).unwrap_or(input.len());
+        // END の行の末尾 (改行の直前) までを範囲とする
+        let end = line_starts
+            .nth(last.saturating_sub(first.max(1)))
+            .map_or(input.len(), |next| next - 1);
+        return Ok(Some((start, end.max(start))));
+    }
+
+    /**
+     * --rename-file と --rename の指定から、キーの名前の対応表を作る (指定がない場合は None)
+     */
+    pub fn rename_mapping(&self) -> Option<HashMap<String, String>> {
+        if self.rename.is_empty() && self.rename_file.is_empty() {
+            return None;
+        }
+        let mut mapping = HashMap::new();
+        for file in &self.rename_file {
+            mapping.extend(file.clone());
+        }
+        mapping.extend(self.rename.iter().cloned());
+        return Some(mapping);
+    }
+
+    /**
+     * --pick-flat の指定から、取り出した値を並べる形を決定する
+     */
+    pub fn pick_layout(&self) -> PickLayout {
+        return if self.pick_flat {
+            PickLayout::Flat
+        } else {
+            PickLayout::Nested
+        };
+    }
+
+    /**
+     * --indent の指定から1段分の字下げ文字列を決定する
+     */
+    pub fn indent_unit(&self) -> String {
+        return FormatOptions::parse_indent_unit(&self.indent);
+    }
+}
+
+/**
+ * --max-depth の深さを解析する (スタックを使い果たさないよう MAX_DEPTH_CEILING を超える深さは受け付けない)
+ */
+fn parse_max_depth(depth: &str) -> Result<usize, String> {
+    return match depth.parse::<usize>() {
+        Ok(depth) if depth <= MAX_DEPTH_CEILING => Ok(depth),
+        _ => Err(t!(InvalidMaxDepth, MAX_DEPTH_CEILING, depth)),
+    };
+}
+
+/**
+ * --exponent の指定 (auto、never、または指数の閾値) を解析する
+ */
+fn parse_exponent(spec: &str) -> Result<ExponentNotation, String> {
+    return ExponentNotation::parse(spec).map_err(|_| t!(InvalidExponent, spec));
+}
+
+/**
+ * --timestamp-key のキーの glob パターンを解析する
+ */
+fn parse_key_pattern(pattern: &str) -> Result<glob::Pattern, String> {
+    return glob::Pattern::new(pattern).map_err(|error| t!(InvalidKeyPattern, pattern, error.pos + 1));
+}
+
+/**
+ * --delete などの経路のパターンを解析する
+ */
+fn parse_path_pattern(pattern: &str) -> Result<PathPattern, String> {
+    return PathPattern::parse(pattern).map_err(|_| t!(InvalidPathPattern, pattern));
+}
+
+/**
+ * --pick の JSON Pointer が正しい形式か確かめる
+ */
+fn parse_pick(pointer: &str) -> Result<String, String> {
+    return match parse_pointer(pointer) {
+        Some(_) => Ok(pointer.to_string()),
+        None => Err(t!(InvalidPick, pointer)),
+    };
+}
+
+/**
+ * --rename の OLD=NEW 形式の指定を解析する
+ */
+fn parse_rename(spec: &str) -> Result<(String, String), String> {
+    return match spec.split_once('=') {
+        Some((old, new)) => Ok((old.to_string(), new.to_string())),
+        None => Err(t!(InvalidRename, spec)),
+    };
+}
+
+/**
+ * --rename-file の JSON ファイルを読み込み、キーの名前の対応表にする
+ */
+fn load_rename_file(path: &str) -> Result<HashMap<String, String>, String> {
+    let value = load_json_file(path)?;
+    let error = || t!(InvalidRenameFile);
+    let mut mapping = HashMap::new();
+    for (old, new) in value.as_object().ok_or_else(error)? {
+        mapping.insert(old.clone(), new.as_str().ok_or_else(error)?.to_string());
+    }
+    return Ok(mapping);
+}
+
+/**
+ * --key-order-schema、--key-order-example の JSON ファイルを読み込む
+ */
+fn load_json_file(path: &str) -> Result<JsonValue, String> {
+    let text = fs::read_to_string(path).map_err(|e| t!(ReadFileFailed, e))?;
+    return text
+        .parse()
+        .map_err(|e| t!(ParseFailed, "JSON", messages::parse_error(&e)));
+}
+
+/**
+ * --dedupe-by の経路 (.id のような . 区切り、または JSON Pointer) を JSON Pointer に変換する
+ */
+fn parse_dedupe_key(key: &str) -> Result<String, String> {
+    if key.is_empty() || key.starts_with('/') {
+        return Ok(key.to_string());
+    }
+    let Some(rest) = key.strip_prefix('.') else {
+        return Err(t!(InvalidDedupeKey, key));
+    };
+    return Ok(rest
+        .split('.')
+        .map(|name| format!("/{}", name.replace('~', "~0").replace('/', "~1")))
+        .collect());
+}
+
+/**
+ * START:END 形式の範囲の指定を解析する
+ */
+fn parse_range(spec: &str) -> Result<(usize, usize), String> {
+    let error = || t!(InvalidRange, spec);
+    let (start, end) = spec.split_once(':').ok_or_else(error)?;
+    let start: usize = start.parse().map_err(|_| error())?;
+    let end: usize = end.parse().map_err(|_| error())?;
+    if end < start {
+        return Err(error());
+    }
+    return Ok((start, end));
+}
+
+/**
+ * サブコマンド (指定しない場合は標準入力の JSON を整形する)
+ */
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Language Server Protocol のサーバーとして標準入出力で通信し、エディタに整形機能を提供する
+    Lsp,
+
+    /// シェルの補完スクリプトを出力する
+    Completions {
+        /// 補完スクリプトを出力するシェル
+        shell: Shell,
+    },
+
+    /// 入力を折りたたみ可能なツリーとして端末に表示し、展開や検索をしながら閲覧する
+    View {
+        /// 表示するファイルまたは URL (指定しない場合は標準入力から読み込む)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// 標準入力の JWT のヘッダーとペイロードを復号して整形する (署名は検証しない)
+    Jwt,
+
+    /// 値の中身ではなく構造を要約して出力する (配列の要素は同じ形のオブジェクトごとに数をまとめる)
+    Summarize {
+        /// 要約するファイルまたは URL (指定しない場合は標準入力から読み込む)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// 経路がパターンに一致する値ごとに、整形した出力の何バイトを占めるかを大きい順に表示する
+    Weigh {
+        /// 調べるファイルまたは URL (指定しない場合は標準入力から読み込む)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// 大きさを測る値の経路のパターン (デフォルトはルートの直下のメンバーや要素)
+        #[arg(long, value_name = "PATTERN", value_parser = parse_path_pattern, default_value = "/*")]
+        path: PathPattern,
+    },
+
+    /// RFC 8785 の正規化形式のハッシュ値を出力する (空白やキーの順序が異なっていても、意味が同じ文書は同じ値になる)
+    Digest {
+        /// ハッシュ値を求めるファイルまたは URL (指定しない場合は標準入力から読み込む)
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+
+        /// ハッシュ関数
+        #[arg(long, value_enum, default_value_t = DigestAlgo::Sha256)]
+        algo: DigestAlgo,
+    },
+
+    /// 2つの入力の最上位の配列の要素を集合とみなして演算し、結果の配列を出力する (キーの順序を無視し、数値は値で比較する)
+    Setop {
+        /// 演算の種類 (union は和集合、intersect は積集合、diff は A にあって B にない要素)
+        #[arg(value_enum)]
+        operation: SetOp,
+
+        /// 1つ目の配列のファイルまたは URL
+        #[arg(value_name = "A")]
+        a: PathBuf,
+
+        /// 2つ目の配列のファイルまたは URL
+        #[arg(value_name = "B")]
+        b: PathBuf,
+    },
+
+    /// JSON Pointer で指定した値が入力中のどこにあるか (行と列、バイト単位の位置の範囲) を出力する
+    Locate {
+        /// 位置を調べる値の JSON Pointer (例: /users/0/name、空文字列はルート)
+        pointer: String,
+
+        /// 調べる JSON ファイルまたは URL (指定しない場合は標準入力から読み込む)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// 入力が JSON (または --from で指定した形式) として正しいかどうかだけを確かめる (整形結果は出力しない)
+    Validate {
+        /// 確かめるファイル、ディレクトリ、glob パターンまたは URL (指定しない場合は標準入力から読み込む)
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Eol {
+    Lf,
+    Crlf,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ControlEscapes {
+    Short,   // \n や \t などの短縮形があればそれを使う
+    Unicode, // 常に \u00XX 形式にする
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum HexCase {
+    Lower, // \u001f
+    Upper, // \u001F
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ArrayMarker {
+    Comment, // /* 995 more */
+    String,  // "… 995 more"
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum MissingEnv {
+    Error, // エラーにする
+    Keep,  // ${NAME} のまま残す
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum InvalidUtf8 {
+    Error,   // エラーにする
+    Replace, // U+FFFD に置き換える
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum NonFinitePolicy {
+    Error,  // エラーにする
+    Null,   // null に置き換える
+    String, // "NaN"、"Infinity"、"-Infinity" の文字列に置き換える
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum KeyNormalization {
+    Nfc,  // 正準等価な合成
+    Nfd,  // 正準等価な分解
+    Nfkc, // 互換等価な合成
+    Nfkd, // 互換等価な分解
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum DigestAlgo {
+    Sha256, // SHA-256
+    Sha512, // SHA-512
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SetOp {
+    Union,     // 和集合
+    Intersect, // 積集合
+    Diff,      // 差集合
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum KeySort {
+    Lexical, // 文字コード順
+    Natural, // 数字の並びを数値として比較する順
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TimestampFormat {
+    Iso8601, // 2024-01-02T03:04:05Z (UTC)
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    Json,   // JSON
+    Jsonc,  // コメント付きの JSON (コメントを残したまま整形し、閉じ括弧の直前の余分な , も受け付ける)
+    Gron,   // gron 形式の代入文
+    Ndjson, // 1行に1つの JSON (JSON Lines)
+    Xml,    // XML (属性は "@" を付けたキー、同じ名前の子要素は配列にする)
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Json,      // 整形した JSON
+    Gron,      // gron 形式の代入文
+    Xml,       // XML (--from xml と逆の対応で変換する)
+    Plist,     // Apple の XML 形式のプロパティリスト (null は表せないためエラーにする)
+    Markdown,  // 最上位の配列の要素のオブジェクトを行とする Markdown の表
+    HtmlTable, // 最上位の配列の要素のオブジェクトを行とする HTML の <table>
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Text,  // 標準エラー出力へのメッセージのみ
+    Sarif, // SARIF 2.1.0 (GitHub の code scanning などで読み込める)
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ClipboardMode {
+    Write, // 整形結果をクリップボードに書き戻す
+    Print, // 整形結果を標準出力に出力する
+}
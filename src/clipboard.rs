@@ -0,0 +1,32 @@
+use arboard::Clipboard;
+use rust_json_formatter::json::FormatOptions;
+
+use crate::cli::{Cli, ClipboardMode};
+use crate::error::CliError;
+use crate::messages::t;
+use crate::{format_input, open_stdout, print_warnings, write_output};
+
+/**
+ * クリップボードの内容を整形し、モードに応じてクリップボードに書き戻すか標準出力に出力する
+ */
+pub fn run(cli: &Cli, mode: ClipboardMode, options: &FormatOptions) -> Result<(), CliError> {
+    let mut clipboard = Clipboard::new().map_err(|e| CliError::io(t!(ClipboardOpenFailed, e)))?;
+    let input = clipboard
+        .get_text()
+        .map_err(|e| CliError::io(t!(ClipboardReadFailed, e)))?;
+    let mut warnings = Vec::new();
+    let output = format_input(cli, cli.from, options, &input, &mut warnings)?;
+    print_warnings(cli, None, &warnings);
+    match mode {
+        ClipboardMode::Write => {
+            clipboard
+                .set_text(output)
+                .map_err(|e| CliError::io(t!(ClipboardWriteFailed, e)))?;
+            if !cli.quiet {
+                eprintln!("{}", t!(ClipboardFormatted));
+            }
+        }
+        ClipboardMode::Print => write_output(open_stdout(cli), output.as_bytes())?,
+    }
+    return Ok(());
+}
@@ -0,0 +1,156 @@
+use crate::json::JsonValue;
+use crate::number::Number;
+use crate::walk::{JsonPath, PathPattern, PathSegment};
+
+impl JsonValue {
+    /**
+     * 経路がいずれかのパターンに一致する文字列の値のうち、JSON のリテラルとして読めるもの ("42"、"true"、"null" など) を
+     * 対応する型の値に置き換え、置き換えた値の経路を返す (パターンが空の場合はすべての文字列を対象にする)
+     */
+    pub fn coerce_scalars(&mut self, patterns: &[PathPattern]) -> Vec<JsonPath> {
+        let mut coerced = Vec::new();
+        self.coerce_scalars_at(patterns, &mut JsonPath::root(), &mut coerced);
+        return coerced;
+    }
+
+    fn coerce_scalars_at(&mut self, patterns: &[PathPattern], path: &mut JsonPath, coerced: &mut Vec<JsonPath>) {
+        match self {
+            JsonValue::String(str) => {
+                if !patterns.is_empty() && !patterns.iter().any(|pattern| pattern.matches(path)) {
+                    return;
+                }
+                if let Some(value) = coerce_scalar(str) {
+                    *self = value;
+                    coerced.push(path.clone());
+                }
+            }
+            JsonValue::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.coerce_scalars_at(patterns, path, coerced);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.coerce_scalars_at(patterns, path, coerced);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * 文字列が JSON のリテラル (true, false, null) か数値であれば、その値を返す
+ * 前後の空白や先頭の 0 ("007")、+ の符号を含むものは意図した文字列である場合が多いため、置き換えない
+ * 64 ビットの整数で表せない整数も、f64 にすると値が変わってしまうため置き換えない (bignum 機能が有効な場合は置き換える)
+ * f64 の範囲を超える数値 (1e400) はリテラルのまま保持できるため置き換える
+ */
+fn coerce_scalar(str: &str) -> Option<JsonValue> {
+    return match str {
+        "true" => Some(JsonValue::Bool(true)),
+        "false" => Some(JsonValue::Bool(false)),
+        "null" => Some(JsonValue::Null),
+        _ if is_json_number(str) => {
+            let num: Number = str.parse().ok()?;
+            if let Number::Float(float) = num {
+                let integer = !str.contains(['.', 'e', 'E']);
+                if !float.is_finite() || (integer && float != 0.0) {
+                    return None;
+                }
+            }
+            Some(JsonValue::Number(num))
+        }
+        _ => None,
+    };
+}
+
+/**
+ * 文字列が JSON の数値の文法 (-? int frac? exp?) に従っているかどうか
+ */
+fn is_json_number(str: &str) -> bool {
+    let bytes = str.strip_prefix('-').unwrap_or(str).as_bytes();
+    let digits = |start: usize| bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let mut pos = match bytes.first() {
+        Some(b'0') => 1,
+        Some(b'1'..=b'9') => digits(0),
+        _ => return false,
+    };
+    if bytes.get(pos) == Some(&b'.') {
+        let len = digits(pos + 1);
+        if len == 0 {
+            return false;
+        }
+        pos += 1 + len;
+    }
+    if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+        pos += 1;
+        if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+            pos += 1;
+        }
+        let len = digits(pos);
+        if len == 0 {
+            return false;
+        }
+        pos += len;
+    }
+    return pos == bytes.len();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coerce_scalar() {
+        assert_eq!(coerce_scalar("42"), Some(JsonValue::from(42.0)));
+        assert_eq!(coerce_scalar("-1.5e3"), Some(JsonValue::from(-1500.0)));
+        assert_eq!(
+            coerce_scalar("12345678901234567890"),
+            Some(JsonValue::Number(Number::UInt(12345678901234567890)))
+        );
+        assert_eq!(coerce_scalar("true"), Some(JsonValue::Bool(true)));
+        assert_eq!(coerce_scalar("null"), Some(JsonValue::Null));
+        // JSON の文法に従わないものや、値が変わってしまうものは置き換えない
+        for str in ["007", "+1", " 42", "1.", ".5", "1e", "0x1A", "NaN", "True", ""] {
+            assert_eq!(coerce_scalar(str), None, "{}", str);
+        }
+        // bignum 機能が無効な場合、64 ビットの整数で表せない整数は値が変わってしまうため置き換えない
+        let expected = match cfg!(feature = "bignum") {
+            true => Some(JsonValue::Number("123456789012345678901234".parse().unwrap())),
+            false => None,
+        };
+        assert_eq!(coerce_scalar("123456789012345678901234"), expected);
+        // f64 の範囲を超える数値はリテラルのまま保持する
+        assert_eq!(
+            coerce_scalar("1e400"),
+            Some(JsonValue::Number(Number::Big("1e400".into())))
+        );
+    }
+
+    #[test]
+    fn test_coerce_scalars() {
+        let mut json: JsonValue = r#"{"id": "42", "active": "true", "name": "Alice", "tags": ["1", "x"]}"#
+            .parse()
+            .unwrap();
+        let coerced = json.coerce_scalars(&[PathPattern::parse("/tags/*").unwrap()]);
+        assert_eq!(
+            coerced.iter().map(|path| path.to_string()).collect::<Vec<_>>(),
+            vec!["/tags/0"]
+        );
+        assert_eq!(json["id"], JsonValue::from("42"));
+
+        let coerced = json.coerce_scalars(&[]);
+        assert_eq!(
+            coerced.iter().map(|path| path.to_string()).collect::<Vec<_>>(),
+            vec!["/id", "/active"]
+        );
+        assert_eq!(json["id"], JsonValue::from(42.0));
+        assert_eq!(json["active"], JsonValue::Bool(true));
+        assert_eq!(json["name"], JsonValue::from("Alice"));
+    }
+}
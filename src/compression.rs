@@ -0,0 +1,177 @@
+use clap::ValueEnum;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use rust_json_formatter::utf16::{self, ByteOrder, Utf16Decoder};
+use rust_json_formatter::utf8::LossyUtf8Reader;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use crate::cli::{Cli, InvalidUtf8};
+use crate::messages::t;
+
+/**
+ * gzip の先頭のバイト列 (マジックナンバー)
+ */
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/**
+ * zstd の先頭のバイト列 (マジックナンバー)
+ */
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/**
+ * 入力や出力の圧縮形式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Compression {
+    None, // 圧縮しない
+    Gzip, // gzip
+    Zstd, // zstd
+}
+
+impl Compression {
+    /**
+     * ファイルの拡張子 (.gz, .zst) から圧縮形式を判定する
+     */
+    pub fn from_extension(path: &Path) -> Option<Compression> {
+        return match path.extension()?.to_str()? {
+            "gz" => Some(Compression::Gzip),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        };
+    }
+
+    /**
+     * --gzip の指定、先頭のバイト列、ファイルの拡張子から入力の圧縮形式を判定する
+     */
+    fn detect(cli: &Cli, head: &[u8], path: Option<&Path>) -> Compression {
+        if cli.gzip {
+            return Compression::Gzip;
+        }
+        // 拡張子と中身が食い違う場合は中身を優先する
+        if head.starts_with(GZIP_MAGIC) {
+            return Compression::Gzip;
+        }
+        if head.starts_with(ZSTD_MAGIC) {
+            return Compression::Zstd;
+        }
+        return path.and_then(Compression::from_extension).unwrap_or(Compression::None);
+    }
+}
+
+/**
+ * 入力の圧縮形式を判定し、必要であれば展開しながら読み込むリーダーを返す
+ * 展開した内容が UTF-16 の BOM で始まる場合は、UTF-8 に変換しながら読み込む
+ * --invalid-utf8 replace の場合は、UTF-8 として不正なバイト列を U+FFFD に置き換えながら読み込む
+ * --max-input-size の場合は、読み込んだ内容がそのバイト数を超えた時点でエラーにする (展開後の大きさで数える)
+ * path はファイルから読み込む場合のパスで、拡張子による判定に使う
+ */
+pub fn decompress<'a, R: BufRead + 'a>(
+    cli: &Cli,
+    mut reader: R,
+    path: Option<&Path>,
+) -> io::Result<(Box<dyn BufRead + 'a>, Compression)> {
+    let compression = Compression::detect(cli, reader.fill_buf()?, path);
+    let source = path.map_or("standard input".into(), |path| path.display().to_string());
+    if compression != Compression::None {
+        log::debug!("{}: decompressing as {:?}", source, compression);
+    }
+    let mut reader: Box<dyn BufRead + 'a> = match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+    };
+    // 圧縮形式として不正な入力はここでエラーにする (展開に失敗した後の読み込みは入力の終わりとして扱われるため)
+    if let Some(order) = utf16::detect_bom(reader.fill_buf()?) {
+        let encoding = match order {
+            ByteOrder::LittleEndian => "UTF-16LE",
+            ByteOrder::BigEndian => "UTF-16BE",
+        };
+        log::info!("{}: decoding {} input", source, encoding);
+        reader = Box::new(BufReader::new(Utf16Decoder::new(reader, order)));
+    } else if cli.invalid_utf8 == InvalidUtf8::Replace {
+        let quiet = cli.quiet;
+        let on_invalid = move |offset| {
+            if !quiet {
+                eprintln!("{}: {}", source, t!(Warning, t!(InvalidUtf8Replaced, offset)));
+            }
+        };
+        reader = Box::new(BufReader::new(LossyUtf8Reader::new(reader, on_invalid)));
+    }
+    if let Some(limit) = cli.max_input_size {
+        reader = Box::new(BufReader::new(SizeLimited {
+            inner: reader,
+            limit,
+            read: 0,
+        }));
+    }
+    return Ok((reader, compression));
+}
+
+/**
+ * 読み込んだバイト数が上限を超えた時点でエラーにするリーダー
+ */
+struct SizeLimited<R: Read> {
+    inner: R,
+    limit: usize, // 上限のバイト数
+    read: usize,  // これまでに読み込んだバイト数
+}
+
+impl<R: Read> Read for SizeLimited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.read += len;
+        if self.read > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                t!(InputTooLarge, self.limit),
+            ));
+        }
+        return Ok(len);
+    }
+}
+
+/**
+ * ファイルを必要に応じて展開しながら読み込み、内容と圧縮形式を返す
+ */
+pub fn read_file(cli: &Cli, path: &Path) -> io::Result<(String, Compression)> {
+    let file = BufReader::new(File::open(path)?);
+    let (mut reader, compression) = decompress(cli, file, Some(path))?;
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    return Ok((content, compression));
+}
+
+/**
+ * 指定した形式で圧縮しながら書き込むライターを返す
+ * 圧縮の終端はライターを破棄する際に書き込まれる
+ */
+pub fn compress<'a, W: Write + 'a>(writer: W, compression: Compression) -> io::Result<Box<dyn Write + 'a>> {
+    return Ok(match compression {
+        Compression::None => Box::new(writer),
+        Compression::Gzip => Box::new(GzEncoder::new(writer, flate2::Compression::default())),
+        Compression::Zstd => Box::new(zstd::Encoder::new(writer, zstd::DEFAULT_COMPRESSION_LEVEL)?.auto_finish()),
+    });
+}
+
+/**
+ * 指定した形式で圧縮してファイルに書き込む
+ */
+pub fn write_file(path: &Path, content: &str, compression: Compression) -> io::Result<()> {
+    return match compression {
+        Compression::None => fs::write(path, content),
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(File::create(path)?, flate2::Compression::default());
+            encoder.write_all(content.as_bytes())?;
+            encoder.finish()?;
+            Ok(())
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(File::create(path)?, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            encoder.write_all(content.as_bytes())?;
+            encoder.finish()?;
+            Ok(())
+        }
+    };
+}
@@ -0,0 +1,114 @@
+use clap::CommandFactory;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Cli;
+use crate::messages::t;
+
+/**
+ * 設定ファイルの名前
+ */
+pub const CONFIG_FILE_NAME: &str = ".jsonfmt.toml";
+
+/**
+ * 入力のパスから親ディレクトリを順に辿り、最初に見つかった設定ファイルのパスを返す
+ * 入力がファイルでない場合 (標準入力や URL) はカレントディレクトリから辿る
+ */
+pub fn find_config(cli: &Cli) -> Option<PathBuf> {
+    let start = match cli.files.first().and_then(|path| fs::canonicalize(path).ok()) {
+        Some(path) if path.is_dir() => path,
+        Some(path) => path.parent()?.to_path_buf(),
+        None => env::current_dir().ok()?,
+    };
+    return start
+        .ancestors()
+        .map(|directory| directory.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file());
+}
+
+/**
+ * 設定ファイルを読み込み、同じ意味のコマンドライン引数の並びに変換する
+ * 各項目の名前はオプションの長い名前と同じで、値は次のように変換する
+ *   indent = 4                  → --indent=4
+ *   collapse = true             → --collapse
+ *   newline = false             → --no-newline (対になるオプションがない場合は何も指定しない)
+ *   ext = ["json", "jsonc"]     → --ext=json --ext=jsonc
+ */
+pub fn load_config(path: &Path) -> Result<Vec<OsString>, String> {
+    let content = fs::read_to_string(path).map_err(|e| t!(ReadFileFailed, e))?;
+    let table = content.parse::<toml::Table>().map_err(|e| t!(InvalidToml, e))?;
+    let command = Cli::command();
+    let has_flag = |name: &str| command.get_arguments().any(|arg| arg.get_long() == Some(name));
+
+    let mut args = Vec::new();
+    for (key, value) in &table {
+        if !has_flag(key) {
+            return Err(t!(UnknownConfigKey, key));
+        }
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let arg = match value {
+                toml::Value::Boolean(true) => format!("--{}", key),
+                toml::Value::Boolean(false) if has_flag(&format!("no-{}", key)) => format!("--no-{}", key),
+                toml::Value::Boolean(false) => continue,
+                toml::Value::String(string) => format!("--{}={}", key, string),
+                toml::Value::Integer(integer) => format!("--{}={}", key, integer),
+                toml::Value::Float(float) => format!("--{}={}", key, float),
+                _ => return Err(t!(InvalidConfigValue, key)),
+            };
+            args.push(OsString::from(arg));
+        }
+    }
+    return Ok(args);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * 一時ファイルに書き込んだ設定ファイルを読み込む
+     */
+    fn load(name: &str, content: &str) -> Result<Vec<OsString>, String> {
+        let path = env::temp_dir().join(format!("{}-{}.toml", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        let result = load_config(&path);
+        fs::remove_file(&path).unwrap();
+        return result;
+    }
+
+    #[test]
+    fn test_load_config() {
+        let args = load(
+            "config",
+            "indent = 4\ncollapse = false\nnewline = false\next = [\"json\", \"jsonc\"]\nsort-keys = \"natural\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "--ext=json",
+                "--ext=jsonc",
+                "--indent=4",
+                "--no-newline",
+                "--sort-keys=natural"
+            ]
+            .map(OsString::from)
+            .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_load_config_invalid() {
+        assert!(load("config-unknown", "foo = 1\n").unwrap_err().contains("foo"));
+        assert!(load("config-value", "indent = { a = 1 }\n")
+            .unwrap_err()
+            .contains("indent"));
+        assert!(load("config-toml", "indent =\n").is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::json::{JsonArray, JsonObject, JsonValue};
+#[cfg(feature = "serde_json")]
+use crate::number::is_negative_zero;
+use crate::number::Number;
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        return JsonValue::String(value.to_string());
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        return JsonValue::String(value);
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        return JsonValue::Number(Number::Float(value));
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        return JsonValue::Number(Number::Int(value));
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        return JsonValue::Bool(value);
+    }
+}
+
+impl From<JsonArray> for JsonValue {
+    fn from(value: JsonArray) -> Self {
+        return JsonValue::Array(value);
+    }
+}
+
+impl From<JsonObject> for JsonValue {
+    fn from(value: JsonObject) -> Self {
+        return JsonValue::Object(value);
+    }
+}
+
+/**
+ * serde_json::Value から変換する
+ * 整数は i64 または u64 として、それ以外の数値は f64 として保持する
+ * キーの順序は serde_json の Map の順序 (preserve_order が無効な場合はソート順) になる
+ */
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Value> for JsonValue {
+    fn from(value: serde_json::Value) -> Self {
+        return match value {
+            serde_json::Value::Object(map) => {
+                JsonValue::Object(map.into_iter().map(|(key, value)| (key, value.into())).collect())
+            }
+            serde_json::Value::Array(array) => JsonValue::Array(array.into_iter().map(JsonValue::from).collect()),
+            serde_json::Value::String(str) => JsonValue::String(str),
+            serde_json::Value::Number(num) => match (num.as_i64(), num.as_u64()) {
+                (Some(int), _) => JsonValue::from(int),
+                (None, Some(uint)) => JsonValue::Number(Number::from(uint)),
+                (None, None) => JsonValue::from(num.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::Bool(bool) => JsonValue::from(bool),
+            serde_json::Value::Null => JsonValue::Null,
+        };
+    }
+}
+
+/**
+ * serde_json::Value へ変換する
+ * 整数として正確に表せる数値は整数として、それ以外は浮動小数点数として変換する (-0 は符号を保つため浮動小数点数にし、Big の数値は丸める)
+ * NaN や無限大は JSON で表せないため null になる
+ */
+#[cfg(feature = "serde_json")]
+impl From<JsonValue> for serde_json::Value {
+    fn from(value: JsonValue) -> Self {
+        return match value {
+            JsonValue::Object(obj) => {
+                serde_json::Value::Object(obj.into_iter().map(|(key, value)| (key, value.into())).collect())
+            }
+            JsonValue::Array(array) => {
+                serde_json::Value::Array(array.into_iter().map(serde_json::Value::from).collect())
+            }
+            JsonValue::String(str) => serde_json::Value::String(str),
+            JsonValue::Number(Number::Int(int)) => serde_json::Value::Number(int.into()),
+            JsonValue::Number(Number::UInt(uint)) => serde_json::Value::Number(uint.into()),
+            JsonValue::Number(Number::Float(num))
+                if num.fract() == 0.0 && num.abs() <= MAX_SAFE_INTEGER && !is_negative_zero(num) =>
+            {
+                serde_json::Value::Number((num as i64).into())
+            }
+            JsonValue::Number(num) => serde_json::Number::from_f64(num.as_f64())
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            JsonValue::Bool(bool) => serde_json::Value::Bool(bool),
+            JsonValue::Null => serde_json::Value::Null,
+        };
+    }
+}
+
+/**
+ * f64 で正確に表せる整数の最大値 (2^53 - 1)
+ */
+#[cfg(feature = "serde_json")]
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_from_primitives() {
+        assert_eq!(JsonValue::from("a"), JsonValue::String("a".to_string()));
+        assert_eq!(JsonValue::from("b".to_string()), JsonValue::String("b".to_string()));
+        assert_eq!(JsonValue::from(1.5), JsonValue::from(1.5));
+        assert_eq!(JsonValue::from(-3_i64), JsonValue::from(-3.0));
+        assert_eq!(JsonValue::from(true), JsonValue::Bool(true));
+        assert_eq!(JsonValue::from(false), JsonValue::Bool(false));
+    }
+
+    #[test]
+    fn test_from_collections() {
+        let array: JsonValue = vec![1.0.into(), "two".into(), false.into()].into();
+        assert_eq!(array.to_string(), r#"[1,"two",false]"#);
+
+        let object: JsonValue = IndexMap::from([("key".to_string(), array)]).into();
+        assert_eq!(object.to_string(), r#"{"key":[1,"two",false]}"#);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_from_serde_json() {
+        let source = serde_json::json!({"a": [1, -2.5, "s", true, null], "b": {"c": false}});
+        let value = JsonValue::from(source);
+
+        assert_eq!(value.to_string(), r#"{"a":[1,-2.5,"s",true,null],"b":{"c":false}}"#);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_into_serde_json() {
+        let value: JsonValue = r#"{"a": [1, -2.5, "s", true, null], "b": {"c": 1e300}}"#.parse().unwrap();
+        let converted = serde_json::Value::from(value);
+
+        assert_eq!(
+            converted,
+            serde_json::json!({"a": [1, -2.5, "s", true, null], "b": {"c": 1e300}})
+        );
+        assert!(converted["a"][0].is_i64());
+        let negative_zero = serde_json::Value::from("-0".parse::<JsonValue>().unwrap());
+        assert!(negative_zero
+            .as_f64()
+            .is_some_and(|num| num == 0.0 && num.is_sign_negative()));
+        assert_eq!(JsonValue::from(negative_zero).to_string(), "-0");
+        assert_eq!(
+            serde_json::Value::from(JsonValue::from(f64::NAN)),
+            serde_json::Value::Null
+        );
+    }
+}
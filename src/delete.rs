@@ -0,0 +1,78 @@
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathPattern, PathSegment};
+
+impl JsonValue {
+    /**
+     * 経路がいずれかのパターンに一致するオブジェクトのメンバーや配列の要素を削除し、削除した値の経路を返す
+     * 配列の添字は削除する前の位置で判定するため、/items/3 と /items/5 を指定すると元の 3 番目と 5 番目の要素を削除する
+     * ルートの値は削除できないため、ルートに一致するパターンは無視する
+     */
+    pub fn delete_paths(&mut self, patterns: &[PathPattern]) -> Vec<JsonPath> {
+        let mut deleted = Vec::new();
+        self.delete_paths_at(patterns, &mut JsonPath::root(), &mut deleted);
+        return deleted;
+    }
+
+    fn delete_paths_at(&mut self, patterns: &[PathPattern], path: &mut JsonPath, deleted: &mut Vec<JsonPath>) {
+        let mut matches = |path: &mut JsonPath, segment: PathSegment| {
+            path.push(segment);
+            let matched = patterns.iter().any(|pattern| pattern.matches(path));
+            if matched {
+                deleted.push(path.clone());
+            }
+            path.pop();
+            return matched;
+        };
+        match self {
+            JsonValue::Object(obj) => {
+                obj.retain(|key, _| !matches(path, PathSegment::Key(key.clone())));
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.delete_paths_at(patterns, path, deleted);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                let keep: Vec<bool> = (0..array.len())
+                    .map(|index| !matches(path, PathSegment::Index(index)))
+                    .collect();
+                // 子の経路は削除する前の添字で表す
+                let mut kept: Vec<(usize, JsonValue)> = array
+                    .drain(..)
+                    .enumerate()
+                    .zip(keep)
+                    .filter_map(|(element, keep)| keep.then_some(element))
+                    .collect();
+                for (index, value) in kept.iter_mut() {
+                    path.push(PathSegment::Index(*index));
+                    value.delete_paths_at(patterns, path, deleted);
+                    path.pop();
+                }
+                array.extend(kept.into_iter().map(|(_, value)| value));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete_paths() {
+        let mut value: JsonValue = r#"{"a": {"b": 1, "c": 2}, "items": [0, 1, 2, 3, {"secret": 4}, {"secret": 5}]}"#
+            .parse()
+            .unwrap();
+        let patterns: Vec<PathPattern> = ["/a/b", "/items/3", "/items/1", "/items/*/secret", "/", ""]
+            .iter()
+            .map(|pattern| PathPattern::parse(pattern).unwrap())
+            .collect();
+        let deleted = value.delete_paths(&patterns);
+        assert_eq!(value.to_string(), r#"{"a":{"c":2},"items":[0,2,{},{}]}"#);
+        assert_eq!(
+            deleted.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["/a/b", "/items/1", "/items/3", "/items/4/secret", "/items/5/secret"]
+        );
+    }
+}
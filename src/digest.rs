@@ -0,0 +1,38 @@
+use rust_json_formatter::canonical::DigestAlgorithm;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::cli::{Cli, DigestAlgo};
+use crate::error::CliError;
+use crate::messages::{self, t};
+use crate::{check_finite, document, fail, open_stdout, write_output};
+
+/**
+ * 入力ごとに、RFC 8785 の正規化形式のハッシュ値を出力する
+ * ファイルを指定した場合は sha256sum と同じく「ハッシュ値  パス」の形式で、標準入力の場合はハッシュ値だけを出力する
+ */
+pub fn run(cli: &Cli, files: &[PathBuf], algo: DigestAlgo) {
+    let algorithm = match algo {
+        DigestAlgo::Sha256 => DigestAlgorithm::Sha256,
+        DigestAlgo::Sha512 => DigestAlgorithm::Sha512,
+    };
+    let digest = |file: Option<&PathBuf>| {
+        let value = document::load(cli, file.map(PathBuf::as_path)).unwrap_or_else(|error| fail(cli, error));
+        check_finite(cli, &value).unwrap_or_else(|error| fail(cli, error));
+        return value.canonical_digest(algorithm).unwrap_or_else(|error| {
+            fail(
+                cli,
+                CliError::parse(t!(CanonicalNonFinite, messages::path_label(&error.path))),
+            )
+        });
+    };
+
+    let mut report = String::new();
+    if files.is_empty() {
+        writeln!(report, "{}", digest(None)).unwrap();
+    }
+    for file in files {
+        writeln!(report, "{}  {}", digest(Some(file)), file.display()).unwrap();
+    }
+    write_output(open_stdout(cli), report.as_bytes()).unwrap_or_else(|error| fail(cli, error));
+}
@@ -0,0 +1,61 @@
+use rust_json_formatter::json::JsonValue;
+use rust_json_formatter::jsonc::parse_jsonc;
+use std::io::Read;
+use std::path::Path;
+
+use crate::cli::{Cli, InputFormat};
+use crate::error::CliError;
+use crate::files::read_source;
+use crate::messages::t;
+use crate::{
+    open_stdin, parse_error, parse_gron_input, parse_json, parse_ndjson_line, parse_xml_input, print_warnings,
+    transform,
+};
+
+/**
+ * サブコマンドの入力 (ファイルまたは URL、指定しない場合は標準入力) を読み込んで1つの値にパースし、出力前の変換を施す
+ * 入力全体をまとめて扱うサブコマンド (view、summarize など) で使う
+ */
+pub fn load(cli: &Cli, file: Option<&Path>) -> Result<JsonValue, CliError> {
+    let input = read(cli, file)?;
+    let mut warnings = Vec::new();
+    let mut value = parse_document(cli, &input, &mut warnings)?;
+    transform(cli, &mut value, &mut warnings)?;
+    print_warnings(cli, None, &warnings);
+    return Ok(value);
+}
+
+/**
+ * サブコマンドの入力 (ファイルまたは URL、指定しない場合は標準入力) をパースせずに読み込む
+ */
+pub fn read(cli: &Cli, file: Option<&Path>) -> Result<String, CliError> {
+    return match file {
+        Some(path) => Ok(read_source(cli, path).map_err(|error| error.context(path.display()))?.0),
+        None => {
+            let mut input = String::new();
+            open_stdin(cli)
+                .read_to_string(&mut input)
+                .map_err(|e| CliError::io(t!(ReadFailed, e)))?;
+            Ok(input)
+        }
+    };
+}
+
+/**
+ * 入力を --from の形式でパースする (JSON Lines の場合は各行の値の配列にする)
+ */
+fn parse_document(cli: &Cli, input: &str, warnings: &mut Vec<String>) -> Result<JsonValue, CliError> {
+    return match cli.from {
+        InputFormat::Json => parse_json(cli, input, warnings).map_err(|e| parse_error(cli, "JSON", &e, input)),
+        InputFormat::Jsonc => parse_jsonc(input).map_err(|e| parse_error(cli, "JSONC", &[e], input)),
+        InputFormat::Gron => parse_gron_input(input),
+        InputFormat::Xml => parse_xml_input(input),
+        InputFormat::Ndjson => {
+            let mut values = Vec::new();
+            for (i, line) in input.lines().enumerate() {
+                values.extend(parse_ndjson_line(cli, i + 1, line, warnings)?);
+            }
+            Ok(JsonValue::Array(values))
+        }
+    };
+}
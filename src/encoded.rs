@@ -0,0 +1,104 @@
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathPattern, PathSegment};
+
+/**
+ * パディング (末尾の =) の有無を問わない設定
+ */
+const CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+
+/**
+ * 標準の Base64 (+ と /) と、URL で使う Base64 (- と _) の復号器
+ */
+const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, CONFIG);
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, CONFIG);
+
+/**
+ * Base64 の文字列を復号する (標準と URL 用のどちらの文字の組でもよく、パディングは省略してもよい)
+ * Base64 として不正な場合は None を返す
+ */
+pub fn decode_base64(str: &str) -> Option<Vec<u8>> {
+    return STANDARD.decode(str).or_else(|_| URL_SAFE.decode(str)).ok();
+}
+
+impl JsonValue {
+    /**
+     * 経路がいずれかのパターンに一致する文字列の値を Base64 として復号し、置き換えた値の経路を返す
+     * 復号した内容が JSON のオブジェクトや配列であればパースした値に、それ以外の UTF-8 の文字列であればその文字列に置き換える
+     * Base64 として不正な値や、復号した内容が UTF-8 でない値 (画像などのバイナリ) はそのまま残す
+     */
+    pub fn decode_base64_at(&mut self, patterns: &[PathPattern]) -> Vec<JsonPath> {
+        let mut decoded = Vec::new();
+        self.decode_base64_in(patterns, &mut JsonPath::root(), &mut decoded);
+        return decoded;
+    }
+
+    fn decode_base64_in(&mut self, patterns: &[PathPattern], path: &mut JsonPath, decoded: &mut Vec<JsonPath>) {
+        match self {
+            JsonValue::String(str) => {
+                if !patterns.iter().any(|pattern| pattern.matches(path)) {
+                    return;
+                }
+                let Some(text) = decode_base64(str).and_then(|bytes| String::from_utf8(bytes).ok()) else {
+                    return;
+                };
+                *self = match text.trim_start().starts_with(['{', '[']) {
+                    true => text.parse().unwrap_or(JsonValue::String(text)),
+                    false => JsonValue::String(text),
+                };
+                decoded.push(path.clone());
+            }
+            JsonValue::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.decode_base64_in(patterns, path, decoded);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.decode_base64_in(patterns, path, decoded);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("aGVsbG8="), Some(b"hello".to_vec()));
+        assert_eq!(decode_base64("aGVsbG8"), Some(b"hello".to_vec()));
+        // URL 用の文字の組 (- と _)
+        assert_eq!(decode_base64("-_8"), Some(vec![0xfb, 0xff]));
+        assert_eq!(decode_base64("not base64!"), None);
+    }
+
+    #[test]
+    fn test_decode_base64_at() {
+        // {"user":"admin"} と "s3cret" と、UTF-8 でないバイト列
+        let mut json: JsonValue = r#"{"kind": "Secret", "data": {"config": "eyJ1c2VyIjoiYWRtaW4ifQ==", "password": "czNjcmV0", "image": "//79"}}"#
+            .parse()
+            .unwrap();
+        let decoded = json.decode_base64_at(&[PathPattern::parse("/data/*").unwrap()]);
+        assert_eq!(
+            decoded.iter().map(|path| path.to_string()).collect::<Vec<_>>(),
+            vec!["/data/config", "/data/password"]
+        );
+        assert_eq!(json["data"]["config"]["user"], JsonValue::from("admin"));
+        assert_eq!(json["data"]["password"], JsonValue::from("s3cret"));
+        assert_eq!(json["data"]["image"], JsonValue::from("//79"));
+        // パターンに一致しない値は復号しない
+        assert_eq!(json["kind"], JsonValue::from("Secret"));
+    }
+}
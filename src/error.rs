@@ -0,0 +1,104 @@
+use std::fmt;
+use std::io;
+use std::process;
+
+use crate::messages::t;
+
+/**
+ * 終了コード
+ * シェルスクリプトから結果で分岐できるよう、既存の値は変更しない
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitCode {
+    Success = 0,     // 成功
+    ParseError = 1,  // 入力が JSON (または --from で指定した形式) として不正
+    UsageError = 2,  // コマンドライン引数や設定ファイルの誤り (clap の引数エラーと同じ値)
+    CheckFailed = 3, // --check で整形されていない入力があった
+    IoError = 4,     // 入出力の失敗 (ファイルが読めない、書き込めない、URL を取得できないなど)
+    NotFound = 5,    // locate で指定した値が入力にない
+}
+
+impl ExitCode {
+    /**
+     * この終了コードでプロセスを終了する
+     */
+    pub fn exit(self) -> ! {
+        process::exit(self as i32);
+    }
+}
+
+/**
+ * CLI の処理の失敗 (表示するメッセージと終了コード)
+ */
+#[derive(Debug)]
+pub struct CliError {
+    pub code: ExitCode,               // 終了コード
+    pub message: String,              // 標準エラー出力に表示するメッセージ
+    pub diagnostics: Vec<Diagnostic>, // 失敗の原因となった入力中の箇所 (位置がわかる誤りの場合のみ)
+}
+
+/**
+ * 入力中の誤りの箇所 (SARIF などの機械が読む形式で報告する際に使う)
+ */
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub message: String, // 誤りの説明
+    pub line: usize,     // 1 始まりの行番号
+    pub column: usize,   // 1 始まりの列番号 (文字単位)
+}
+
+impl CliError {
+    pub fn new(code: ExitCode, message: impl Into<String>) -> Self {
+        return CliError {
+            code,
+            message: message.into(),
+            diagnostics: Vec::new(),
+        };
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        return CliError::new(ExitCode::ParseError, message);
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        return CliError::new(ExitCode::UsageError, message);
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        return CliError::new(ExitCode::IoError, message);
+    }
+
+    /**
+     * 出力の書き込みの失敗から生成する
+     * 書き込み先のパイプが閉じられた (head などの読み手が先に終了した) 場合は、メッセージを表示せずに成功として終了する
+     */
+    pub fn write(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::BrokenPipe {
+            return CliError::new(ExitCode::Success, "");
+        }
+        return CliError::io(t!(WriteFailedWith, error));
+    }
+
+    /**
+     * 失敗の原因となった入力中の箇所を添える
+     */
+    pub fn with_diagnostics(self, diagnostics: Vec<Diagnostic>) -> Self {
+        return CliError { diagnostics, ..self };
+    }
+
+    /**
+     * メッセージの先頭に、どの入力で失敗したか (ファイルのパスなど) を付ける
+     */
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        return CliError {
+            message: format!("{}: {}", context, self.message),
+            ..self
+        };
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.message);
+    }
+}
@@ -0,0 +1,167 @@
+//! C から呼び出すための API
+//! 宣言は include/jsonfmt.h を参照
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use crate::json::{FormatOptions, JsonValue, LineEnding};
+
+thread_local! {
+    // 直前に失敗した呼び出しのエラーメッセージ (スレッドごと)
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/**
+ * C から指定する整形時のオプション
+ */
+#[repr(C)]
+pub struct JsonfmtOptions {
+    pub indent: *const c_char,  // 1段分の字下げ (--indent と同じ形式。NULL の場合は空白2つ)
+    pub collapse: bool,         // 1行に収まるオブジェクトや配列を1行にまとめるかどうか
+    pub max_width: usize,       // 1行にまとめる際の1行の最大幅
+    pub trailing_newline: bool, // 出力の末尾に改行を付けるかどうか
+    pub crlf: bool,             // 改行コードを CRLF にするかどうか
+    pub compact: bool,          // 改行や空白を含めずに1行で出力するかどうか
+}
+
+/**
+ * CLI のデフォルトと同じオプションを返す
+ */
+#[no_mangle]
+pub extern "C" fn jsonfmt_default_options() -> JsonfmtOptions {
+    let defaults = FormatOptions::default();
+    return JsonfmtOptions {
+        indent: ptr::null(),
+        collapse: defaults.collapse,
+        max_width: defaults.max_width,
+        trailing_newline: true,
+        crlf: false,
+        compact: defaults.compact,
+    };
+}
+
+/**
+ * NUL 終端の UTF-8 文字列 input を JSON としてパースし、整形した文字列を返す
+ * options が NULL の場合はデフォルトのオプションを使う
+ * 戻り値は jsonfmt_free で解放する必要がある。失敗した場合は NULL を返し、jsonfmt_last_error で原因を取得できる
+ * パースエラーのメッセージは、CLI が英語で表示するエラーメッセージの1行目と同じになる
+ *
+ * # Safety
+ * input は有効な NUL 終端文字列、options は NULL か有効な JsonfmtOptions を指している必要がある
+ */
+#[no_mangle]
+pub unsafe extern "C" fn jsonfmt_format(input: *const c_char, options: *const JsonfmtOptions) -> *mut c_char {
+    let result = format(input, options);
+    return match result {
+        Ok(formatted) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            formatted.into_raw()
+        }
+        Err(message) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+            ptr::null_mut()
+        }
+    };
+}
+
+unsafe fn format(input: *const c_char, options: *const JsonfmtOptions) -> Result<CString, String> {
+    if input.is_null() {
+        return Err("input is NULL".to_string());
+    }
+    let input = CStr::from_ptr(input)
+        .to_str()
+        .map_err(|_| "input is not valid UTF-8".to_string())?;
+    let options = match options.as_ref() {
+        Some(options) => to_format_options(options)?,
+        None => to_format_options(&jsonfmt_default_options())?,
+    };
+    let json = input.parse::<JsonValue>().map_err(|e| e.english_message(input))?;
+    // JSON の文字列中の NUL は \u0000 にエスケープされるため、出力には NUL が含まれない
+    return Ok(CString::new(json.format_with_options(&options)).unwrap());
+}
+
+unsafe fn to_format_options(options: &JsonfmtOptions) -> Result<FormatOptions, String> {
+    let indent_unit = if options.indent.is_null() {
+        FormatOptions::default().indent_unit
+    } else {
+        let indent = CStr::from_ptr(options.indent)
+            .to_str()
+            .map_err(|_| "indent is not valid UTF-8".to_string())?;
+        FormatOptions::parse_indent_unit(indent)
+    };
+    return Ok(FormatOptions {
+        collapse: options.collapse,
+        max_width: options.max_width,
+        indent_unit,
+        trailing_newline: options.trailing_newline,
+        line_ending: if options.crlf { LineEnding::Crlf } else { LineEnding::Lf },
+        compact: options.compact,
+        ..Default::default()
+    });
+}
+
+/**
+ * 同じスレッドで直前に失敗した呼び出しのエラーメッセージを返す (なければ NULL)
+ * 戻り値は次にこのライブラリの関数を呼び出すまで有効で、解放する必要はない
+ */
+#[no_mangle]
+pub extern "C" fn jsonfmt_last_error() -> *const c_char {
+    return LAST_ERROR.with(|last| match &*last.borrow() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    });
+}
+
+/**
+ * jsonfmt_format が返した文字列を解放する (NULL の場合は何もしない)
+ *
+ * # Safety
+ * str は jsonfmt_format が返したポインタで、まだ解放されていない必要がある
+ */
+#[no_mangle]
+pub unsafe extern "C" fn jsonfmt_free(str: *mut c_char) {
+    if !str.is_null() {
+        drop(CString::from_raw(str));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jsonfmt_format() {
+        let input = CString::new(r#"{"a": [1, 2]}"#).unwrap();
+        let indent = CString::new("tab").unwrap();
+        let options = JsonfmtOptions {
+            indent: indent.as_ptr(),
+            ..jsonfmt_default_options()
+        };
+        unsafe {
+            let formatted = jsonfmt_format(input.as_ptr(), &options);
+            assert_eq!(
+                CStr::from_ptr(formatted).to_str(),
+                Ok("{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t]\n}\n")
+            );
+            assert!(jsonfmt_last_error().is_null());
+            jsonfmt_free(formatted);
+        }
+    }
+
+    #[test]
+    fn test_jsonfmt_format_error() {
+        let input = CString::new("[1,").unwrap();
+        unsafe {
+            let formatted = jsonfmt_format(input.as_ptr(), ptr::null());
+            assert!(formatted.is_null());
+            assert_eq!(
+                CStr::from_ptr(jsonfmt_last_error()).to_str(),
+                Ok("failed to parse JSON: unexpected end of input (line 1, column 4)")
+            );
+
+            assert!(jsonfmt_format(ptr::null(), ptr::null()).is_null());
+            assert_eq!(CStr::from_ptr(jsonfmt_last_error()).to_str(), Ok("input is NULL"));
+        }
+    }
+}
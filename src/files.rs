@@ -0,0 +1,404 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use rayon::prelude::*;
+use rust_json_formatter::json::FormatOptions;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Instant;
+
+use crate::cli::{Cli, InputFormat, ReportFormat};
+use crate::compression::{read_file, write_file, Compression};
+use crate::error::{CliError, ExitCode};
+use crate::messages::t;
+use crate::sarif::SarifReport;
+use crate::{fail, format_input, open_stdout, print_warnings};
+
+/**
+ * ファイルを整形した結果
+ */
+pub struct FormattedFile {
+    pub formatted: String,     // 整形後の内容
+    pub changed: bool,         // 整形によって内容が変わったかどうか
+    pub warnings: Vec<String>, // パース時の警告 (--fix で直した誤りや重複したキー)
+}
+
+/**
+ * 入力ファイルの指定に含まれる glob パターン (configs/**/*.json など) を展開する
+ * シェルが展開しない環境 (Windows など) でもパターンを使えるよう、自前で展開する
+ * パターンを含まない指定や実在するパス、URL はそのまま返し、一致するファイルが1つもないパターンはエラーにする
+ */
+pub fn expand_patterns(paths: &[PathBuf]) -> Result<Vec<PathBuf>, CliError> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if is_url(path) || path.exists() || !pattern.contains(['*', '?', '[']) {
+            expanded.push(path.clone());
+            continue;
+        }
+        let matches = glob::glob(&pattern).map_err(|e| CliError::usage(t!(InvalidPattern, pattern, e)))?;
+        let before = expanded.len();
+        for entry in matches {
+            let entry = entry.map_err(|e| CliError::io(format!("{}: {}", pattern, e)))?;
+            if entry.is_file() {
+                expanded.push(entry);
+            }
+        }
+        if expanded.len() == before {
+            return Err(CliError::io(t!(NoMatchingFiles, pattern)));
+        }
+        log::debug!("{}: pattern matched {} files", pattern, expanded.len() - before);
+    }
+    return Ok(expanded);
+}
+
+/**
+ * 入力の指定のうちディレクトリを配下のファイルに展開する
+ * 拡張子が --ext に一致するファイルを再帰的に集め、--ignore に一致するパスは除外する
+ */
+pub fn expand_directories(cli: &Cli) -> Result<Vec<PathBuf>, CliError> {
+    let ignore = ignore_patterns(cli)?;
+    let mut expanded = Vec::new();
+    for path in &cli.files {
+        if path.is_dir() {
+            let before = expanded.len();
+            collect_files(cli, &ignore, path, &mut expanded)
+                .map_err(|e| CliError::io(t!(ReadPathFailed, path.display(), e)))?;
+            log::debug!(
+                "{}: found {} files in directory",
+                path.display(),
+                expanded.len() - before
+            );
+        } else {
+            expanded.push(path.clone());
+        }
+    }
+    log::info!("{} input files", expanded.len());
+    return Ok(expanded);
+}
+
+/**
+ * --ignore のパターンをコンパイルする
+ */
+fn ignore_patterns(cli: &Cli) -> Result<Vec<glob::Pattern>, CliError> {
+    return cli
+        .ignore
+        .iter()
+        .map(|pattern| {
+            return glob::Pattern::new(pattern).map_err(|e| CliError::usage(t!(InvalidPattern, pattern, e)));
+        })
+        .collect();
+}
+
+/**
+ * パスが --ignore のいずれかのパターンに一致するかどうか
+ * パス全体に加えてファイル名・ディレクトリ名単体でも照合する (node_modules のように名前だけでも指定できるようにする)
+ */
+fn is_ignored(ignore: &[glob::Pattern], path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    return ignore
+        .iter()
+        .any(|pattern| pattern.matches_path(path) || pattern.matches(&name));
+}
+
+/**
+ * 圧縮されたファイルの場合は .gz や .zst を除いたパスを返す
+ */
+fn uncompressed_path(path: &Path) -> &Path {
+    if Compression::from_extension(path).is_some() {
+        return Path::new(path.file_stem().unwrap_or_default());
+    }
+    return path;
+}
+
+/**
+ * 拡張子が --ext に一致するかどうか (圧縮されたファイルは .gz や .zst を除いた拡張子で判定する)
+ */
+fn has_target_extension(cli: &Cli, path: &Path) -> bool {
+    return uncompressed_path(path).extension().is_some_and(|ext| {
+        cli.extensions
+            .iter()
+            .any(|target| ext == target.trim_start_matches('.'))
+    });
+}
+
+/**
+ * ディレクトリ配下の対象ファイルを名前順に集める
+ */
+fn collect_files(cli: &Cli, ignore: &[glob::Pattern], directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if is_ignored(ignore, &path) {
+            log::trace!("{}: ignored by --ignore", path.display());
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(cli, ignore, &path, files)?;
+        } else if has_target_extension(cli, &path) {
+            files.push(path);
+        } else {
+            log::trace!("{}: skipped, extension not in --ext", path.display());
+        }
+    }
+    return Ok(());
+}
+
+/**
+ * ファイルの入力形式を返す
+ * --from を指定しない (json の) 場合、拡張子が .jsonc のファイルはコメントと閉じ括弧の直前の余分な , を受け付ける JSONC として扱う
+ */
+pub fn input_format(cli: &Cli, path: &Path) -> InputFormat {
+    if cli.from == InputFormat::Json && uncompressed_path(path).extension().is_some_and(|ext| ext == "jsonc") {
+        return InputFormat::Jsonc;
+    }
+    return cli.from;
+}
+
+/**
+ * 入力の指定が http:// か https:// で始まる URL かどうか
+ */
+fn is_url(path: &Path) -> bool {
+    return path
+        .to_str()
+        .is_some_and(|path| path.starts_with("http://") || path.starts_with("https://"));
+}
+
+/**
+ * ファイルまたは URL から入力を読み込み、内容と圧縮形式を返す
+ */
+pub fn read_source(cli: &Cli, path: &Path) -> Result<(String, Compression), CliError> {
+    if is_url(path) {
+        if cli.write {
+            return Err(CliError::usage(t!(CannotWriteUrl)));
+        }
+        return fetch_url(cli, &path.to_string_lossy());
+    }
+    return read_file(cli, path).map_err(|e| CliError::io(t!(ReadFileFailed, e)));
+}
+
+#[cfg(feature = "http")]
+fn fetch_url(cli: &Cli, url: &str) -> Result<(String, Compression), CliError> {
+    return crate::http::fetch(cli, url);
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch_url(_cli: &Cli, _url: &str) -> Result<(String, Compression), CliError> {
+    return Err(CliError::usage(t!(UrlRequiresHttp)));
+}
+
+/**
+ * ファイルの内容を整形し、--write の場合は上書きする
+ * 書き込む際は --output-compression の形式で圧縮する (指定がなければ読み込んだときと同じ形式)
+ */
+pub fn format_file(
+    cli: &Cli,
+    options: &FormatOptions,
+    path: &Path,
+    content: &str,
+    compression: Compression,
+) -> Result<FormattedFile, CliError> {
+    let mut warnings = Vec::new();
+    let start = Instant::now();
+    let formatted = format_input(cli, input_format(cli, path), options, content, &mut warnings)?;
+    let output_compression = cli.output_compression.unwrap_or(compression);
+    let changed = formatted != content || output_compression != compression;
+    log::debug!(
+        "{}: processed {} bytes in {:.2?} ({})",
+        path.display(),
+        content.len(),
+        start.elapsed(),
+        if changed { "changed" } else { "unchanged" }
+    );
+    if cli.write && changed {
+        write_file(path, &formatted, output_compression).map_err(|e| CliError::io(t!(WriteFileFailed, e)))?;
+    }
+    return Ok(FormattedFile {
+        formatted,
+        changed,
+        warnings,
+    });
+}
+
+/**
+ * 整形結果を報告する
+ * --write の場合は1行のログを標準エラー出力に表示し、--check の場合は整形されていないファイルのみを表示する
+ * それ以外の場合は整形結果を標準出力に出力する (--quiet の場合は何も表示しない)
+ */
+fn report(cli: &Cli, stdout: &mut dyn Write, path: &Path, result: &Result<FormattedFile, CliError>) {
+    let log = |message: String| {
+        if !cli.quiet {
+            eprintln!("{}", message);
+        }
+    };
+    if let Ok(file) = result {
+        print_warnings(cli, Some(&path.display()), &file.warnings);
+    }
+    match result {
+        Ok(file) if cli.check => {
+            if file.changed {
+                log(t!(Unformatted, path.display()));
+            }
+        }
+        Ok(file) if cli.write => {
+            if file.changed {
+                log(t!(Formatted, path.display()));
+            } else {
+                log(t!(Unchanged, path.display()));
+            }
+        }
+        Ok(file) => {
+            stdout
+                .write_all(file.formatted.as_bytes())
+                .and_then(|_| stdout.flush())
+                .unwrap_or_else(|error| fail(cli, CliError::write(error)));
+        }
+        Err(error) => log(format!("{}: {}", path.display(), error)),
+    }
+}
+
+/**
+ * 指定されたファイルを並列に整形する
+ * 失敗したファイルがあっても残りのファイルの処理を続け、最後に最初に失敗したファイルの終了コードで終了する
+ * --check の場合は、失敗がなく整形されていないファイルがあれば ExitCode::CheckFailed で終了する
+ */
+pub fn format_files(cli: &Cli, options: &FormatOptions) {
+    let paths = expand_directories(cli).unwrap_or_else(|error| fail(cli, error));
+    let start = Instant::now();
+    // 整形は並列に行い、結果の報告は指定された順に行う
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|path| {
+            return read_source(cli, path)
+                .and_then(|(content, compression)| format_file(cli, options, path, &content, compression));
+        })
+        .collect();
+    log::info!("processed {} files in {:.2?}", paths.len(), start.elapsed());
+    let mut stdout = open_stdout(cli);
+    let mut sarif = SarifReport::new();
+    let mut changed = 0;
+    let mut failed = 0;
+    let mut exit_code = ExitCode::Success;
+    for (path, result) in paths.iter().zip(results) {
+        report(cli, &mut stdout, path, &result);
+        match result {
+            Ok(file) if file.changed => {
+                sarif.add_unformatted(Some(path));
+                changed += 1;
+            }
+            Ok(_) => {}
+            Err(error) => {
+                sarif.add_error(Some(path), &error);
+                if failed == 0 {
+                    exit_code = error.code;
+                }
+                failed += 1;
+            }
+        }
+    }
+    drop(stdout);
+    if cli.report_format == ReportFormat::Sarif {
+        sarif.print().unwrap_or_else(|error| fail(cli, error));
+    }
+
+    if !cli.quiet && cli.write {
+        eprintln!("{}", t!(FormatSummary, paths.len(), changed, failed));
+    }
+    if !cli.quiet && cli.check {
+        eprintln!("{}", t!(CheckSummary, paths.len(), changed, failed));
+    }
+    if exit_code == ExitCode::Success && cli.check && changed > 0 {
+        exit_code = ExitCode::CheckFailed;
+    }
+    if exit_code != ExitCode::Success {
+        exit_code.exit();
+    }
+}
+
+/**
+ * 指定されたファイルやディレクトリを監視し、変更されるたびに整形する
+ * ディレクトリの場合は配下の拡張子が --ext に一致するファイルを対象にする
+ */
+pub fn watch(cli: &Cli, options: &FormatOptions) {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).unwrap_or_else(|e| {
+        fail(cli, CliError::io(t!(WatchStartFailed, e)));
+    });
+
+    let ignore = ignore_patterns(cli).unwrap_or_else(|error| fail(cli, error));
+    let mut files = Vec::new();
+    let mut directories = Vec::new();
+    for path in &cli.files {
+        let Ok(canonical) = fs::canonicalize(path) else {
+            fail(cli, CliError::io(t!(NotFound, path.display())));
+        };
+        // 保存時にファイルを置き換えるエディタもあるため、ファイルは親ディレクトリごと監視する
+        let (target, mode) = if canonical.is_dir() {
+            directories.push(canonical.clone());
+            (canonical, RecursiveMode::Recursive)
+        } else {
+            files.push(canonical.clone());
+            (canonical.parent().unwrap().to_path_buf(), RecursiveMode::NonRecursive)
+        };
+        if let Err(e) = watcher.watch(&target, mode) {
+            fail(cli, CliError::io(t!(WatchPathFailed, path.display(), e)));
+        }
+    }
+
+    // 最後に確認した内容 (自身の書き込みやエディタの複数回の通知で同じ内容を何度も処理しないようにする)
+    let mut last_seen: HashMap<PathBuf, String> = HashMap::new();
+    let mut stdout = open_stdout(cli);
+    let mut process_path = |path: &Path| {
+        let Ok((content, compression)) = read_file(cli, path) else {
+            return;
+        };
+        // 空の内容は保存の途中 (切り詰めた直後) とみなして次の通知を待つ
+        if content.is_empty() || last_seen.get(path) == Some(&content) {
+            return;
+        }
+        let result = format_file(cli, options, path, &content, compression);
+        report(cli, &mut stdout, path, &result);
+        let content = match result {
+            Ok(file) if cli.write => file.formatted,
+            _ => content,
+        };
+        last_seen.insert(path.to_path_buf(), content);
+    };
+
+    for path in &files {
+        process_path(path);
+    }
+    if !cli.quiet {
+        eprintln!("{}", t!(Watching, cli.files.len()));
+    }
+
+    for result in receiver {
+        let event = match result {
+            Ok(event) => event,
+            Err(e) => {
+                if !cli.quiet {
+                    eprintln!("{}", t!(WatchError, e));
+                }
+                continue;
+            }
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            continue;
+        }
+        for path in event.paths {
+            let Ok(path) = fs::canonicalize(&path) else {
+                continue;
+            };
+            let in_directory = has_target_extension(cli, &path)
+                && !is_ignored(&ignore, &path)
+                && directories.iter().any(|directory| path.starts_with(directory));
+            if files.contains(&path) || in_directory {
+                log::debug!("{}: change detected", path.display());
+                process_path(&path);
+            }
+        }
+    }
+}
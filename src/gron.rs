@@ -0,0 +1,267 @@
+use indexmap::IndexMap;
+
+use crate::json::{push_escaped_string, JsonValue};
+use crate::lexer::{Lexer, Token};
+use crate::walk::{JsonPath, PathSegment};
+
+/**
+ * gron 形式の代入文で、ルートを表す変数名
+ */
+pub const GRON_ROOT: &str = "json";
+
+impl JsonValue {
+    /**
+     * gron 形式 (1行に1つの代入文) に変換した文字列を返す
+     * 例: json.users[0].name = "alice";
+     */
+    pub fn to_gron(&self) -> String {
+        let mut formatted = String::new();
+        for (path, value) in self.walk() {
+            formatted.push_str(&gron_path(&path));
+            formatted.push_str(" = ");
+            match value {
+                JsonValue::Object(_) => formatted.push_str("{}"),
+                JsonValue::Array(_) => formatted.push_str("[]"),
+                _ => formatted.push_str(&value.format(0)),
+            }
+            formatted.push_str(";\n");
+        }
+        return formatted;
+    }
+}
+
+/**
+ * 経路を gron 形式のパスに変換する
+ * 識別子として使えるキーは .key 形式、それ以外は ["key"] 形式にする
+ */
+fn gron_path(path: &JsonPath) -> String {
+    let mut gron = GRON_ROOT.to_string();
+    for segment in path.segments() {
+        match segment {
+            PathSegment::Key(key) if is_identifier(key) => {
+                gron.push('.');
+                gron.push_str(key);
+            }
+            PathSegment::Key(key) => {
+                gron.push('[');
+                push_escaped_string(&mut gron, key);
+                gron.push(']');
+            }
+            PathSegment::Index(index) => gron.push_str(&format!("[{}]", index)),
+        }
+    }
+    return gron;
+}
+
+/**
+ * gron 形式の代入文を読み込み、元の JSON を復元する
+ * 空行は無視し、不正な行があれば None を返す
+ */
+pub fn parse_gron(input: &str) -> Option<JsonValue> {
+    let mut root = JsonValue::Null;
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (path, value) = line.split_once(" = ")?;
+        let segments = parse_gron_path(path)?;
+
+        // 値は末尾の ; を取り除いて JSON としてパースする
+        let value = value.trim_end().strip_suffix(';')?;
+        let value = value.parse::<JsonValue>().ok()?;
+
+        assign(&mut root, &segments, value)?;
+    }
+    return Some(root);
+}
+
+/**
+ * json.users[0]["is-admin"] のようなパスを要素に分解する
+ */
+fn parse_gron_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut rest = path.trim().strip_prefix(GRON_ROOT)?;
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            // .key 形式
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let key = &after_dot[..end];
+            if !is_identifier(key) {
+                return None;
+            }
+            segments.push(PathSegment::Key(key.to_string()));
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix("[\"") {
+            // ["key"] 形式 (エスケープされた " を読み飛ばしながら閉じる " を探す)
+            let mut escaped = false;
+            let end = after_bracket.char_indices().find_map(|(i, c)| {
+                let is_end = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                if is_end {
+                    Some(i)
+                } else {
+                    None
+                }
+            })?;
+            let quoted = &rest[1..end + 3];
+            let Some(Token::String(key)) = Lexer::new(quoted).next_token() else {
+                return None;
+            };
+            segments.push(PathSegment::Key(key.into_owned()));
+            rest = after_bracket[end + 1..].strip_prefix(']')?;
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            // [0] 形式
+            let (index, after_index) = after_bracket.split_once(']')?;
+            segments.push(PathSegment::Index(index.parse().ok()?));
+            rest = after_index;
+        } else {
+            return None;
+        }
+    }
+    return Some(segments);
+}
+
+/**
+ * パスが指す位置に値を代入する
+ * 途中のオブジェクトや配列が存在しなければ作成し、配列の不足分は null で埋める
+ */
+fn assign(root: &mut JsonValue, segments: &[PathSegment], value: JsonValue) -> Option<()> {
+    let Some((segment, rest)) = segments.split_first() else {
+        // 既に子要素を持つコンテナへの {} や [] の代入では中身を消さない
+        match (&*root, &value) {
+            (JsonValue::Object(_), JsonValue::Object(obj)) if obj.is_empty() => {}
+            (JsonValue::Array(_), JsonValue::Array(array)) if array.is_empty() => {}
+            _ => *root = value,
+        }
+        return Some(());
+    };
+    match segment {
+        PathSegment::Key(key) => {
+            if let JsonValue::Null = root {
+                *root = JsonValue::Object(IndexMap::new());
+            }
+            let JsonValue::Object(obj) = root else {
+                return None;
+            };
+            let child = obj.entry(key.clone()).or_insert(JsonValue::Null);
+            return assign(child, rest, value);
+        }
+        PathSegment::Index(index) => {
+            if let JsonValue::Null = root {
+                *root = JsonValue::Array(Vec::new());
+            }
+            let JsonValue::Array(array) = root else {
+                return None;
+            };
+            while array.len() <= *index {
+                array.push(JsonValue::Null);
+            }
+            return assign(&mut array[*index], rest, value);
+        }
+    }
+}
+
+/**
+ * JavaScript の識別子として使える文字列かどうか
+ */
+pub fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    return match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_to_gron_scalar() {
+        assert_eq!(JsonValue::from(1.5).to_gron(), "json = 1.5;\n");
+        assert_eq!(JsonValue::String("a\"b".to_string()).to_gron(), "json = \"a\\\"b\";\n");
+    }
+
+    #[test]
+    fn test_to_gron_nested() {
+        let mut user = IndexMap::new();
+        user.insert("name".to_string(), JsonValue::String("alice".to_string()));
+        user.insert("is-admin".to_string(), JsonValue::Bool(true));
+
+        let mut object = IndexMap::new();
+        object.insert("users".to_string(), JsonValue::Array(vec![JsonValue::Object(user)]));
+        object.insert("empty".to_string(), JsonValue::Object(IndexMap::new()));
+        let value = JsonValue::Object(object);
+
+        let expected = r#"json = {};
+json.users = [];
+json.users[0] = {};
+json.users[0].name = "alice";
+json.users[0]["is-admin"] = true;
+json.empty = {};
+"#;
+        assert_eq!(value.to_gron(), expected);
+    }
+
+    #[test]
+    fn test_parse_gron() {
+        let input = r#"json = {};
+json.users = [];
+json.users[0] = {};
+json.users[0].name = "alice; bob";
+json.users[0]["is-admin"] = true;
+json.users[0]["say \"hi\""] = [];
+
+json.empty = {};
+"#;
+        let mut user = IndexMap::new();
+        user.insert("name".to_string(), JsonValue::String("alice; bob".to_string()));
+        user.insert("is-admin".to_string(), JsonValue::Bool(true));
+        user.insert("say \"hi\"".to_string(), JsonValue::Array(vec![]));
+
+        let mut object = IndexMap::new();
+        object.insert("users".to_string(), JsonValue::Array(vec![JsonValue::Object(user)]));
+        object.insert("empty".to_string(), JsonValue::Object(IndexMap::new()));
+
+        assert_eq!(parse_gron(input), Some(JsonValue::Object(object)));
+    }
+
+    #[test]
+    fn test_parse_gron_fills_missing_parents() {
+        let value = parse_gron("json.a[2] = 1;");
+
+        let mut object = IndexMap::new();
+        object.insert(
+            "a".to_string(),
+            JsonValue::Array(vec![JsonValue::Null, JsonValue::Null, JsonValue::from(1.0)]),
+        );
+        assert_eq!(value, Some(JsonValue::Object(object)));
+    }
+
+    #[test]
+    fn test_parse_gron_round_trip() {
+        let value: JsonValue = r#"{"a": [1, {"b c": null}], "d": "e"}"#.parse().unwrap();
+        assert_eq!(parse_gron(&value.to_gron()), Some(value));
+    }
+
+    #[test]
+    fn test_parse_gron_invalid() {
+        assert_eq!(parse_gron("json.a = 1"), None);
+        assert_eq!(parse_gron("data.a = 1;"), None);
+        assert_eq!(parse_gron("json.a = 1;\njson.a.b = 2;"), None);
+        assert_eq!(parse_gron("json[x] = 1;"), None);
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("name"));
+        assert!(is_identifier("_private$1"));
+        assert!(!is_identifier("1st"));
+        assert!(!is_identifier("foo-bar"));
+        assert!(!is_identifier(""));
+    }
+}
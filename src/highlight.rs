@@ -0,0 +1,164 @@
+use crate::lexer::{Lexer, Span, Token};
+use crate::theme::TokenClass;
+
+/**
+ * 色分けに使うトークンの種類
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HighlightClass {
+    Key,         // オブジェクトのキー
+    String,      // 文字列の値
+    Number,      // 数値
+    Literal,     // true, false, null
+    Punctuation, // { } [ ] : ,
+    Comment,     // コメント
+    Invalid,     // 字句解析できない文字やリテラル
+}
+
+impl HighlightClass {
+    /**
+     * テーマの色分けの種類に変換する (テーマで色を決めていないコメントと不正なトークンは None)
+     */
+    pub fn token_class(self) -> Option<TokenClass> {
+        return match self {
+            HighlightClass::Key => Some(TokenClass::Key),
+            HighlightClass::String => Some(TokenClass::String),
+            HighlightClass::Number => Some(TokenClass::Number),
+            HighlightClass::Literal => Some(TokenClass::Literal),
+            HighlightClass::Punctuation => Some(TokenClass::Punctuation),
+            HighlightClass::Comment | HighlightClass::Invalid => None,
+        };
+    }
+}
+
+/**
+ * 種類と入力中の範囲を付けたトークン
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlight {
+    pub class: HighlightClass, // トークンの種類
+    pub span: Span,            // 入力中の範囲
+}
+
+/**
+ * 入力の先頭から順に、色分けの種類を付けたトークンを返すイテレータ (エディタなどのシンタックスハイライト向け)
+ * 括弧の対応だけを追ってキーと文字列の値を見分けるので、構文の誤りがある入力でも最後までトークンを返す
+ * コメントは Comment、字句解析できない部分は Invalid として返す
+ */
+pub struct Highlighter<'a> {
+    lexer: Lexer<'a>,
+    containers: Vec<bool>, // 現在の位置を囲むオブジェクトや配列 (オブジェクトなら true、外側から順に並ぶ)
+    expects_key: bool,     // 次の文字列がキーの位置にあるかどうか
+}
+
+impl<'a> Highlighter<'a> {
+    pub fn new(input: &'a str) -> Self {
+        return Highlighter {
+            lexer: Lexer::new(input).with_comments(),
+            containers: Vec::new(),
+            expects_key: false,
+        };
+    }
+}
+
+impl Iterator for Highlighter<'_> {
+    type Item = Highlight;
+
+    fn next(&mut self) -> Option<Highlight> {
+        let token = match self.lexer.next()? {
+            Ok(token) => token,
+            Err(error) => {
+                return Some(Highlight {
+                    class: HighlightClass::Invalid,
+                    span: error.span,
+                });
+            }
+        };
+        let in_object = self.containers.last() == Some(&true);
+        let class = match token.value {
+            Token::Comment(_) => HighlightClass::Comment,
+            Token::String(_) if self.expects_key => HighlightClass::Key,
+            Token::String(_) => HighlightClass::String,
+            Token::Number(_) => HighlightClass::Number,
+            Token::True | Token::False | Token::Null => HighlightClass::Literal,
+            Token::LeftBrace | Token::LeftBracket => {
+                self.containers.push(token.value == Token::LeftBrace);
+                HighlightClass::Punctuation
+            }
+            Token::RightBrace | Token::RightBracket => {
+                self.containers.pop();
+                HighlightClass::Punctuation
+            }
+            Token::Colon | Token::Comma => HighlightClass::Punctuation,
+        };
+        // コメントはキーの位置を変えない
+        if class != HighlightClass::Comment {
+            self.expects_key = match token.value {
+                Token::LeftBrace => true,
+                Token::Comma => in_object,
+                _ => false,
+            };
+        }
+        return Some(Highlight {
+            class,
+            span: token.span,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight(input: &str) -> Vec<(HighlightClass, &str)> {
+        return Highlighter::new(input)
+            .map(|highlight| (highlight.class, &input[highlight.span.start..highlight.span.end]))
+            .collect();
+    }
+
+    #[test]
+    fn test_highlight() {
+        use HighlightClass::*;
+        assert_eq!(
+            highlight(r#"{"a": ["b", {"c": 1}], /* x */ "d": true}"#),
+            vec![
+                (Punctuation, "{"),
+                (Key, r#""a""#),
+                (Punctuation, ":"),
+                (Punctuation, "["),
+                (String, r#""b""#),
+                (Punctuation, ","),
+                (Punctuation, "{"),
+                (Key, r#""c""#),
+                (Punctuation, ":"),
+                (Number, "1"),
+                (Punctuation, "}"),
+                (Punctuation, "]"),
+                (Punctuation, ","),
+                (Comment, "/* x */"),
+                (Key, r#""d""#),
+                (Punctuation, ":"),
+                (Literal, "true"),
+                (Punctuation, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_invalid() {
+        use HighlightClass::*;
+        // 構文の誤りがあっても、最後までトークンを返す
+        assert_eq!(
+            highlight(r#"{"a" "b", x: 1"#),
+            vec![
+                (Punctuation, "{"),
+                (Key, r#""a""#),
+                (String, r#""b""#),
+                (Punctuation, ","),
+                (Invalid, "x"),
+                (Punctuation, ":"),
+                (Number, "1"),
+            ]
+        );
+    }
+}
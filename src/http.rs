@@ -0,0 +1,46 @@
+use std::io::{BufReader, Read};
+
+use crate::cli::Cli;
+use crate::compression::{decompress, Compression};
+use crate::error::CliError;
+use crate::messages::t;
+
+/**
+ * エラーメッセージに含めるレスポンス本文の最大文字数
+ */
+const ERROR_BODY_LIMIT: usize = 200;
+
+/**
+ * URL から本文を取得し、必要に応じて展開した内容と圧縮形式を返す
+ * 200 番台以外のステータスが返された場合は、ステータスと本文の先頭をエラーメッセージにする
+ */
+pub fn fetch(cli: &Cli, url: &str) -> Result<(String, Compression), CliError> {
+    let response = ureq::get(url)
+        .header("Accept", "application/json")
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .call()
+        .map_err(|e| CliError::io(t!(FetchFailed, e)))?;
+    let status = response.status();
+    log::debug!("{}: HTTP {}", url, status);
+    let body = BufReader::new(response.into_body().into_reader());
+    let (mut reader, compression) = decompress(cli, body, None).map_err(|e| CliError::io(t!(FetchFailed, e)))?;
+    let mut content = String::new();
+    reader
+        .read_to_string(&mut content)
+        .map_err(|e| CliError::io(t!(FetchFailed, e)))?;
+
+    if !status.is_success() {
+        // 複数行の本文 (HTML のエラーページなど) も1行に収める
+        let body: String = content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(ERROR_BODY_LIMIT)
+            .collect();
+        return Err(CliError::io(t!(HttpStatus, status, body)));
+    }
+    return Ok((content, compression));
+}
@@ -0,0 +1,486 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::access::{parse_array_index, parse_pointer};
+use crate::json::{JsonObject, JsonValue};
+use crate::lexer::Lexer;
+use crate::number::Number;
+use crate::parser::{Limits, ParseError, Parser, ValueBuilder};
+
+/**
+ * 入力中の範囲を付けた値 (構文木のノード)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub start: usize,   // 開始位置 (バイト単位)
+    pub end: usize,     // 終了位置 (バイト単位、この位置の文字は含まない)
+    pub kind: NodeKind, // 値の種類と中身
+}
+
+/**
+ * ノードの種類と中身
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Object(Vec<Member>), // オブジェクト (重複したキーのメンバーも入力の順にすべて保持する)
+    Array(Vec<Node>),    // 配列
+    String(String),      // 文字列
+    Number(Number),      // 数値
+    Bool(bool),          // 真偽値
+    Null,                // null
+}
+
+/**
+ * オブジェクトのメンバー
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub key: String,      // キー
+    pub key_start: usize, // キーの開始位置 (バイト単位)
+    pub value: Node,      // 値
+}
+
+impl Node {
+    /**
+     * ノードを JsonValue に変換する (キーが重複している場合は後のメンバーの値で上書きする)
+     */
+    pub fn to_value(&self) -> JsonValue {
+        return match &self.kind {
+            NodeKind::Object(members) => {
+                let mut object = JsonObject::new();
+                for member in members {
+                    object.insert(member.key.clone(), member.value.to_value());
+                }
+                JsonValue::Object(object)
+            }
+            NodeKind::Array(elements) => JsonValue::Array(elements.iter().map(Node::to_value).collect()),
+            NodeKind::String(string) => JsonValue::String(string.clone()),
+            NodeKind::Number(number) => JsonValue::Number(number.clone()),
+            NodeKind::Bool(bool) => JsonValue::Bool(*bool),
+            NodeKind::Null => JsonValue::Null,
+        };
+    }
+
+    /**
+     * JSON Pointer (RFC 6901) で指定した値のノードを取得する (キーが重複している場合は後のメンバーの値を返す)
+     * 例: "/users/0/name"、空文字列はルートを指す
+     */
+    pub fn pointer(&self, pointer: &str) -> Option<&Node> {
+        let mut current = self;
+        for token in parse_pointer(pointer)? {
+            current = match &current.kind {
+                NodeKind::Object(members) => &members.iter().rev().find(|member| member.key == token)?.value,
+                NodeKind::Array(elements) => elements.get(parse_array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        return Some(current);
+    }
+
+    /**
+     * オブジェクトのメンバーの値や配列の要素を返す
+     */
+    fn children(&self) -> Box<dyn Iterator<Item = &Node> + '_> {
+        return match &self.kind {
+            NodeKind::Object(members) => Box::new(members.iter().map(|member| &member.value)),
+            NodeKind::Array(elements) => Box::new(elements.iter()),
+            _ => Box::new(std::iter::empty()),
+        };
+    }
+
+    fn child_mut(&mut self, index: usize) -> &mut Node {
+        return match &mut self.kind {
+            NodeKind::Object(members) => &mut members[index].value,
+            NodeKind::Array(elements) => &mut elements[index],
+            _ => unreachable!(),
+        };
+    }
+
+    /**
+     * 範囲 start..end を、括弧を含まずに内側に囲むオブジェクトや配列かどうか
+     */
+    fn encloses(&self, start: usize, end: usize) -> bool {
+        return matches!(self.kind, NodeKind::Object(_) | NodeKind::Array(_)) && self.start < start && end < self.end;
+    }
+}
+
+/**
+ * 編集に合わせて構文木を部分的に更新する文書 (エディタのプラグインなどで、大きな文書を編集のたびにパースし直さないために使う)
+ */
+pub struct Document {
+    text: String,                   // 文書の内容
+    root: Result<Node, ParseError>, // 構文木 (パースに失敗した場合は最初の誤り)
+    limits: Limits,                 // 文書の大きさの上限 (部分的にパースし直す場合も同じ上限を使う)
+}
+
+impl Document {
+    /**
+     * 文書の内容を limits の上限でパースして、新しい Document を生成する
+     */
+    pub fn new(text: String, limits: Limits) -> Self {
+        let root = parse_tree(&text, limits);
+        return Document { text, root, limits };
+    }
+
+    /**
+     * 文書の内容を返す
+     */
+    pub fn text(&self) -> &str {
+        return &self.text;
+    }
+
+    /**
+     * 構文木の根を返す (パースに失敗している場合は最初の誤り)
+     */
+    pub fn root(&self) -> Result<&Node, &ParseError> {
+        return self.root.as_ref();
+    }
+
+    /**
+     * 文書の start..end の範囲 (バイト単位) を new_text に置き換え、構文木を更新する
+     * 編集された範囲を内側に囲む最も深いオブジェクトか配列だけをパースし直し、それより後ろのノードは位置をずらす
+     * 部分的にパースし直せない場合 (括弧の対応が変わった場合や、直前のパースに失敗していた場合など) は文書全体をパースし直す
+     * 範囲が文書の外にある場合や、文字の境界にない場合はパニックする
+     */
+    pub fn edit(&mut self, start: usize, end: usize, new_text: &str) -> Result<&Node, &ParseError> {
+        self.text.replace_range(start..end, new_text);
+        let patched = match &mut self.root {
+            Ok(root) => patch(root, &self.text, start, end, new_text.len(), self.limits).is_some(),
+            Err(_) => false,
+        };
+        if !patched {
+            self.root = parse_tree(&self.text, self.limits);
+        }
+        return self.root.as_ref();
+    }
+}
+
+/**
+ * 編集後の text に合わせて root の構文木を更新し、パースし直したノードの範囲を返す
+ * 編集前の start..end が inserted バイトの文字列に置き換えられたものとする
+ * パースし直すノードの入れ子の深さから読み始め、深さの上限は文書全体をパースした場合と同じにする
+ * 編集された範囲を内側に囲むオブジェクトや配列がない場合や、パースし直せない場合は None を返す (構文木は使えなくなる)
+ */
+fn patch(
+    root: &mut Node,
+    text: &str,
+    start: usize,
+    end: usize,
+    inserted: usize,
+    limits: Limits,
+) -> Option<(usize, usize)> {
+    shift(root, end, end - start, inserted);
+    let edit_end = start + inserted;
+    if !root.encloses(start, edit_end) {
+        return None;
+    }
+    let mut target = root;
+    let mut depth = 0;
+    loop {
+        let Some(index) = target.children().position(|child| child.encloses(start, edit_end)) else {
+            break;
+        };
+        target = target.child_mut(index);
+        depth += 1;
+    }
+    *target = parse_node(&text[target.start..target.end], target.start, limits, depth).ok()?;
+    return Some((target.start, target.end));
+}
+
+/**
+ * 編集前の位置 from 以降にあるノードの位置を、removed バイトが inserted バイトに置き換えられた分だけずらす
+ */
+fn shift(node: &mut Node, from: usize, removed: usize, inserted: usize) {
+    if node.end < from {
+        return;
+    }
+    let moved = |position: usize| {
+        if position >= from {
+            return position - removed + inserted;
+        }
+        return position;
+    };
+    node.start = moved(node.start);
+    node.end = moved(node.end);
+    match &mut node.kind {
+        NodeKind::Object(members) => {
+            for member in members {
+                member.key_start = moved(member.key_start);
+                shift(&mut member.value, from, removed, inserted);
+            }
+        }
+        NodeKind::Array(elements) => {
+            for element in elements {
+                shift(element, from, removed, inserted);
+            }
+        }
+        _ => {}
+    }
+}
+
+/**
+ * 入力全体を1つの値として構文木にパースする
+ * 誤りの種類と位置や、上限の確認は Parser と同じになる
+ */
+pub fn parse_tree(input: &str, limits: Limits) -> Result<Node, ParseError> {
+    return parse_node(input, 0, limits, 0);
+}
+
+/**
+ * 入力を1つの値として構文木にパースする
+ * offset は入力の文書の先頭からの位置、depth は入力の値を囲むオブジェクトや配列の入れ子の深さ
+ * 値の後に余分な入力が残っている場合はエラーになる
+ */
+fn parse_node(input: &str, offset: usize, limits: Limits, depth: usize) -> Result<Node, ParseError> {
+    let mut parser = Parser::new(Lexer::new(input)).with_limits(limits).with_depth(depth);
+    return parser.parse_with(&TreeBuilder { offset }).map_err(|error| ParseError {
+        position: offset + error.position,
+        ..error
+    });
+}
+
+/**
+ * Parser が読み取ったトークンから構文木を組み立てる
+ */
+struct TreeBuilder {
+    offset: usize, // 入力の、文書の先頭からの位置
+}
+
+/**
+ * 組み立て中のオブジェクト
+ */
+struct TreeObject<'a> {
+    members: Vec<Member>,        // メンバー (重複したキーのメンバーも入力の順にすべて保持する)
+    keys: HashSet<Cow<'a, str>>, // これまでに現れたキー (重複を Parser に知らせるため)
+}
+
+/**
+ * 範囲を付ける前のノードを生成する (範囲は spanned で付ける)
+ */
+fn unspanned(kind: NodeKind) -> Node {
+    return Node { start: 0, end: 0, kind };
+}
+
+impl<'a> ValueBuilder<'a> for TreeBuilder {
+    type Value = Node;
+    type Object = TreeObject<'a>;
+    type Array = Vec<Node>;
+
+    fn string(&self, string: &Cow<'a, str>) -> Node {
+        return unspanned(NodeKind::String(string.to_string()));
+    }
+
+    fn number(&self, number: &Number) -> Node {
+        return unspanned(NodeKind::Number(number.clone()));
+    }
+
+    fn bool(&self, bool: bool) -> Node {
+        return unspanned(NodeKind::Bool(bool));
+    }
+
+    fn null(&self) -> Node {
+        return unspanned(NodeKind::Null);
+    }
+
+    fn object(&self) -> TreeObject<'a> {
+        return TreeObject {
+            members: Vec::new(),
+            keys: HashSet::new(),
+        };
+    }
+
+    fn insert(
+        &self,
+        object: &mut TreeObject<'a>,
+        key: Cow<'a, str>,
+        key_position: usize,
+        value: Node,
+    ) -> Option<String> {
+        let duplicate = !object.keys.insert(key.clone());
+        object.members.push(Member {
+            key: key.to_string(),
+            key_start: self.offset + key_position,
+            value,
+        });
+        return duplicate.then(|| key.into_owned());
+    }
+
+    fn finish_object(&self, object: TreeObject<'a>) -> Node {
+        return unspanned(NodeKind::Object(object.members));
+    }
+
+    fn array(&self) -> Vec<Node> {
+        return Vec::new();
+    }
+
+    fn push(&self, array: &mut Vec<Node>, value: Node) {
+        array.push(value);
+    }
+
+    fn finish_array(&self, array: Vec<Node>) -> Node {
+        return unspanned(NodeKind::Array(array));
+    }
+
+    fn spanned(&self, mut node: Node, start: usize, end: usize) -> Node {
+        node.start = self.offset + start;
+        node.end = self.offset + end;
+        return node;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParseErrorKind;
+
+    /**
+     * 編集を適用し、文書全体をパースし直した場合と同じ構文木になることを確かめる
+     */
+    fn assert_edit(document: &mut Document, start: usize, end: usize, new_text: &str) {
+        let edited = document.edit(start, end, new_text).cloned().map_err(Clone::clone);
+        assert_eq!(
+            edited,
+            parse_tree(document.text(), document.limits),
+            "{}",
+            document.text()
+        );
+    }
+
+    /**
+     * 文書中で最初に現れる old を new に置き換える編集を、assert_edit で確かめる
+     */
+    fn assert_replace(document: &mut Document, old: &str, new: &str) {
+        let start = document.text().find(old).unwrap();
+        assert_edit(document, start, start + old.len(), new);
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let root = parse_tree(r#" {"a": [1, "x"], "a": null} "#, Limits::default()).unwrap();
+        assert_eq!((root.start, root.end), (1, 27));
+        let NodeKind::Object(members) = &root.kind else {
+            panic!();
+        };
+        assert_eq!(members.len(), 2);
+        assert_eq!((members[1].key.as_str(), members[1].key_start), ("a", 17));
+        assert_eq!((members[0].value.start, members[0].value.end), (7, 15));
+        assert_eq!(root.to_value(), r#"{"a": null}"#.parse::<JsonValue>().unwrap());
+
+        // 誤りは Parser と同じ種類と位置で報告する
+        for input in ["", "[1 2]", "[1,]", r#"{"a" 1}"#, "{1: 2}", "[x]", "[] 1", "{"] {
+            assert_eq!(
+                parse_tree(input, Limits::default()).unwrap_err(),
+                input.parse::<JsonValue>().unwrap_err()
+            );
+        }
+        let deep = "[".repeat(200_000);
+        assert_eq!(
+            parse_tree(&deep, Limits::default()).unwrap_err(),
+            deep.parse::<JsonValue>().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_pointer() {
+        let text = r#"{"a": [1, {"b/c": "x"}], "d": 1, "d": [true]}"#;
+        let root = parse_tree(text, Limits::default()).unwrap();
+        let span = |pointer: &str| root.pointer(pointer).map(|node| &text[node.start..node.end]);
+        assert_eq!(span(""), Some(text));
+        assert_eq!(span("/a/1/b~1c"), Some(r#""x""#));
+        // キーが重複している場合は後のメンバーの値を指す
+        assert_eq!(span("/d/0"), Some("true"));
+        assert_eq!(span("/a/01"), None);
+        assert_eq!(span("/a/2"), None);
+        assert_eq!(span("a"), None);
+    }
+
+    #[test]
+    fn test_patch() {
+        let text = r#"{"a": [1, {"b": "x"}], "c": {"d": 2}}"#;
+        let mut root = parse_tree(text, Limits::default()).unwrap();
+        let mut edited = text.to_string();
+        // "x" を "xyz" に置き換えると、それを囲む {"b": ...} だけをパースし直す
+        edited.replace_range(17..18, "xyz");
+        assert_eq!(patch(&mut root, &edited, 17, 18, 3, Limits::default()), Some((10, 22)));
+        assert_eq!(root, parse_tree(&edited, Limits::default()).unwrap());
+
+        // 最上位の値の外側の編集は部分的にパースし直せない
+        let mut root = parse_tree("[1]", Limits::default()).unwrap();
+        assert_eq!(patch(&mut root, "[1] ", 3, 3, 1, Limits::default()), None);
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut document = Document::new(
+            r#"{"items": [1, 2, {"name": "a"}], "count": 3}"#.to_string(),
+            Limits::default(),
+        );
+        // 値、キー、要素の追加と削除
+        assert_replace(&mut document, r#""a""#, r#""abc""#);
+        assert_replace(&mut document, r#""name""#, r#""title""#);
+        assert_replace(&mut document, "2, ", "2, 1.5, ");
+        assert_replace(&mut document, "[1, 2, 1.5, ", "[");
+        assert_replace(&mut document, "3}", "[10, 20]}");
+
+        // 括弧の対応が崩れた場合はエラーになり、直すと文書全体をパースし直す
+        assert_replace(&mut document, "]}", "]");
+        assert_eq!(document.root().unwrap_err().kind, ParseErrorKind::UnexpectedEof);
+        assert_replace(&mut document, "20]", "20]}");
+        assert_eq!(
+            document.root().unwrap().to_value().to_string(),
+            r#"{"items":[{"title":"abc"}],"count":[10,20]}"#
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        // 上限や重複したキーの扱いは Parser と同じになる
+        let input = r#"{"a": [1, [2]], "a": 3}"#;
+        for limits in [
+            Limits {
+                max_depth: Some(2),
+                ..Limits::default()
+            },
+            Limits {
+                max_elements: Some(1),
+                ..Limits::default()
+            },
+            Limits {
+                max_nodes: Some(4),
+                ..Limits::default()
+            },
+        ] {
+            let expected = Parser::new(Lexer::new(input)).with_limits(limits).parse().unwrap_err();
+            assert_eq!(parse_tree(input, limits), Err(expected), "{:?}", limits);
+        }
+        let limits = Limits {
+            max_elements: Some(1),
+            ..Limits::default()
+        };
+        let root = parse_tree(r#"{"a": 1, "a": 2}"#, limits).unwrap();
+        assert!(matches!(&root.kind, NodeKind::Object(members) if members.len() == 2));
+    }
+
+    #[test]
+    fn test_edit_limits() {
+        // 部分的にパースし直す場合も、文書全体での深さで上限を確かめる
+        let limits = Limits {
+            max_depth: Some(3),
+            ..Limits::default()
+        };
+        let mut document = Document::new(r#"{"a": [[1], 2]}"#.to_string(), limits);
+        assert_replace(&mut document, "[1]", "[[1]]");
+        assert_eq!(document.root().unwrap_err().kind, ParseErrorKind::TooDeep);
+        assert_replace(&mut document, "[[1]]", "[1]");
+        assert!(document.root().is_ok());
+
+        // 部分的にパースし直した値の誤りの位置は文書の先頭から数える
+        assert_eq!(
+            parse_node("[[1]]", 7, limits, 2),
+            Err(ParseError {
+                kind: ParseErrorKind::TooDeep,
+                position: 8,
+            })
+        );
+    }
+}
@@ -1,112 +1,835 @@
 use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
+use std::iter;
 
-#[derive(Debug, PartialEq)]
+use crate::number::{
+    format_integer, format_shortest, is_negative_zero, non_finite_literal, ExponentNotation, NonFiniteOutput, Number,
+};
+use crate::theme::{Theme, TokenClass};
+
+/**
+ * JSON の値
+ * 比較ではオブジェクトのキーの順序を無視し、数値は Number の比較に従う (NaN は NaN 同士で等しい)
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JsonValue {
     Object(JsonObject), // {"key": "value"}
     Array(JsonArray),   // [1, 2, 3]
     String(String),     // "hello, world"
-    Number(f64),        // 123.456
-    True,               // true
-    False,              // false
+    Number(Number),     // 123, 123.456
+    Bool(bool),         // true, false
     Null,               // null
 }
 
 pub type JsonObject = IndexMap<String, JsonValue>;
 pub type JsonArray = Vec<JsonValue>;
 
+/**
+ * 整形時のオプション
+ */
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub theme: Option<Theme>,               // 色付けに使うテーマ (None の場合は色を付けない)
+    pub collapse: bool,                     // 1行に収まるオブジェクトや配列を1行にまとめるかどうか
+    pub max_width: usize,                   // 1行にまとめるモードで目標とする1行の最大幅
+    pub numeric_arrays: NumericArrayLayout, // 数値・真偽値のみからなる配列のレイアウト
+    pub indent_unit: String,                // 1段分の字下げに使う文字列
+    pub trailing_newline: bool,             // 出力の末尾に改行を付けるかどうか
+    pub line_ending: LineEnding,            // 改行コード
+    pub compact: bool,                      // 改行や字下げ、区切りの空白を一切含めずに出力するかどうか
+    pub escape: EscapeOptions,              // 文字列のエスケープの方法
+    pub max_depth: Option<usize>,           // この深さ以上にあるオブジェクトや配列を {…} や [… 34 items] に置き換える
+    pub max_array_items: Option<usize>,     // 配列の先頭から出力する要素の数 (残りの要素は省略し、その数を印で示す)
+    pub omission_marker: OmissionMarker,    // 配列の要素を省略した際の印の形式
+    pub exponent: ExponentNotation,         // 数値を指数表記にする条件
+    pub precision: Option<usize>, // 数値を丸めて出力する小数点以下の桁数 (None の場合は往復変換可能な最短の桁数)
+    pub non_finite: NonFiniteOutput, // 有限でない数値 (NaN、Infinity、-Infinity) の出力形式
+}
+
+/**
+ * 文字列のエスケープの方法 (他のシリアライザーの出力とバイト単位で揃えるために使う)
+ * デフォルトでは / をエスケープせず、制御文字は短縮形があればそれを使い、\u00XX 形式の16進数は小文字にする
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EscapeOptions {
+    pub escape_slash: bool,    // / を \/ とエスケープするかどうか
+    pub unicode_control: bool, // 短縮形 (\n や \t) のある制御文字も \u00XX 形式にするかどうか
+    pub uppercase_hex: bool,   // \u00XX 形式の16進数を大文字にするかどうか
+}
+
+/**
+ * 出力に使う改行コード
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    Lf,   // \n
+    Crlf, // \r\n
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        };
+    }
+}
+
+/**
+ * 数値・真偽値のみからなる配列のレイアウト
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericArrayLayout {
+    Expanded,       // 他の配列と同様に1行に1要素ずつ並べる
+    SingleLine,     // すべての要素を1行に並べる
+    Wrapped(usize), // 1行に指定した数ずつ要素を並べる
+}
+
+/**
+ * 配列の要素を省略した際に、省略した要素の数を示す印の形式
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OmissionMarker {
+    Comment, // 最後の要素の後の /* 995 more */ (JSON としては不正)
+    String,  // 最後の要素としての "… 995 more" (JSON として正しい)
+}
+
+/**
+ * 1行の最大幅のデフォルト値
+ */
+pub const DEFAULT_MAX_WIDTH: usize = 80;
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        return FormatOptions {
+            theme: None,
+            collapse: false,
+            max_width: DEFAULT_MAX_WIDTH,
+            numeric_arrays: NumericArrayLayout::Expanded,
+            indent_unit: "  ".to_string(),
+            trailing_newline: false,
+            line_ending: LineEnding::Lf,
+            compact: false,
+            escape: EscapeOptions::default(),
+            max_depth: None,
+            max_array_items: None,
+            omission_marker: OmissionMarker::Comment,
+            exponent: ExponentNotation::Auto,
+            precision: None,
+            non_finite: NonFiniteOutput::Literal,
+        };
+    }
+}
+
+impl FormatOptions {
+    /**
+     * 改行や空白を含めない1行の出力を行うオプションを返す
+     */
+    pub fn compact() -> Self {
+        return FormatOptions {
+            compact: true,
+            ..Default::default()
+        };
+    }
+
+    /**
+     * 字下げの指定から1段分の字下げ文字列を決定する
+     * 数値なら空白の数、"tab" ならタブ文字、それ以外は指定した文字列そのものとする
+     */
+    pub fn parse_indent_unit(spec: &str) -> String {
+        if let Ok(width) = spec.parse::<usize>() {
+            return " ".repeat(width);
+        }
+        return match spec {
+            "tab" | "\\t" => "\t".to_string(),
+            _ => spec.to_string(),
+        };
+    }
+
+    /**
+     * 指定した深さまで字下げした場合の幅 (文字数) を返す
+     */
+    fn indent_width(&self, indent: usize) -> usize {
+        return self.indent_unit.chars().count() * indent;
+    }
+}
+
 impl JsonValue {
     /**
      * JSON全体を整形した文字列を返す
+     * indent は開始時の字下げの空白の数で、2行目以降の各行の先頭に付け、1段ごとに空白2つ分字下げされる
      */
     pub fn format(&self, indent: usize) -> String {
+        let formatted = self.format_at_depth(0);
+        if indent == 0 {
+            return formatted;
+        }
+        return formatted.replace('\n', &format!("\n{}", " ".repeat(indent)));
+    }
+
+    /**
+     * ネストの深さ depth の位置にある値として、JSON全体を整形した文字列を返す
+     * 2行目以降は depth 段分から始めて、1段ごとに空白2つ分字下げされる
+     */
+    pub fn format_at_depth(&self, depth: usize) -> String {
+        let mut formatted = String::new();
+        self.format_value(depth, &FormatOptions::default(), &mut formatted);
+        return formatted;
+    }
+
+    /**
+     * オプションを指定して JSON 全体を整形した文字列を返す
+     */
+    pub fn format_with_options(&self, options: &FormatOptions) -> String {
         let mut formatted = String::new();
-        self.format_value(indent, &mut formatted);
+        self.format_into(options, &mut formatted);
         return formatted;
     }
 
+    /**
+     * オプションを指定して JSON 全体を整形し、formatted の末尾に追加する
+     * 入力の長さなどから出力の大きさが見積もれる場合は、容量を確保したバッファを渡すことで再確保を減らせる
+     */
+    pub fn format_into(&self, options: &FormatOptions, formatted: &mut String) {
+        self.format_tree(options, formatted);
+    }
+}
+
+/**
+ * 整形する値の中身
+ * JsonValue 以外の値の型 (BorrowedValue や ArenaValue) も FormatTree を実装すれば同じ整形処理を使える
+ */
+pub(crate) enum ValueRef<'v, V: FormatTree + 'v> {
+    Object(V::Members<'v>),  // オブジェクトのメンバー (キーと値) の並び
+    Array(&'v [V]),          // 配列の要素
+    String(&'v str),         // 文字列
+    Number(Cow<'v, Number>), // 数値
+    Bool(bool),              // 真偽値
+    Null,                    // null
+}
+
+/**
+ * FormatOptions に従って整形できる値
+ * value_ref で値の中身を返せば、整形処理はすべて既定の実装を使う
+ */
+pub(crate) trait FormatTree: Sized {
+    type Members<'v>: ExactSizeIterator<Item = (&'v str, &'v Self)>
+    where
+        Self: 'v;
+
+    /**
+     * 値の中身を返す
+     */
+    fn value_ref(&self) -> ValueRef<'_, Self>;
+
+    /**
+     * オプションを指定して値全体を整形し、formatted の末尾に追加する
+     */
+    fn format_tree(&self, options: &FormatOptions, formatted: &mut String) {
+        self.format_child(0, 0, 0, options, formatted);
+        if options.trailing_newline {
+            formatted.push_str(options.line_ending.as_str());
+        }
+    }
+
     /**
      * JSONに含まれる値を整形した文字列を返す
      * オブジェクトや配列の場合、再帰的に整形を繰り返す
      */
-    fn format_value(&self, indent: usize, formatted: &mut String) {
-        match self {
-            JsonValue::Object(obj) => {
-                self.push_str(formatted, "{\n");
-                for (i, (key, value)) in obj.iter().enumerate() {
-                    self.push_indent(formatted, indent + 2);
-                    self.push_str(formatted, "\"");
-                    self.push_str(formatted, key);
-                    self.push_str(formatted, "\"");
-                    self.push_str(formatted, ": ");
-                    value.format_value(indent + 2, formatted);
-                    if i < obj.len() - 1 {
-                        self.push_str(formatted, ",\n");
-                    } else {
-                        self.push_str(formatted, "\n");
+    fn format_value(&self, indent: usize, options: &FormatOptions, formatted: &mut String) {
+        match self.value_ref() {
+            ValueRef::Object(members) => {
+                let len = members.len();
+                push_token(formatted, options, TokenClass::Punctuation, "{");
+                push_newline(formatted, options);
+                for (i, (key, value)) in members.enumerate() {
+                    push_indent(formatted, options, indent + 1);
+                    let key_width = push_escaped_token(formatted, options, TokenClass::Key, key);
+                    push_token(formatted, options, TokenClass::Punctuation, ":");
+                    if !options.compact {
+                        formatted.push(' ');
+                    }
+                    let column = options.indent_width(indent + 1) + key_width + 2;
+                    let trailing = if i < len - 1 { 1 } else { 0 };
+                    value.format_child(indent + 1, column, trailing, options, formatted);
+                    if i < len - 1 {
+                        push_token(formatted, options, TokenClass::Punctuation, ",");
                     }
+                    push_newline(formatted, options);
                 }
-                self.push_indent(formatted, indent);
-                formatted.push_str("}")
-            }
-            JsonValue::Array(array) => {
-                self.push_str(formatted, "[\n");
-                for (i, value) in array.iter().enumerate() {
-                    self.push_indent(formatted, indent + 2);
-                    value.format_value(indent + 2, formatted);
-                    if i < array.len() - 1 {
-                        self.push_str(formatted, ",\n");
-                    } else {
-                        self.push_str(formatted, "\n");
+                push_indent(formatted, options, indent);
+                push_token(formatted, options, TokenClass::Punctuation, "}");
+            }
+            ValueRef::Array(array)
+                if !options.compact
+                    && options.numeric_arrays != NumericArrayLayout::Expanded
+                    && is_numeric_array(array) =>
+            {
+                self.format_numeric_array(array, indent, options, formatted);
+            }
+            ValueRef::Array(array) => {
+                let (items, omitted) = visible_items(array, options);
+                let count = separated_count(items, omitted, options);
+                push_token(formatted, options, TokenClass::Punctuation, "[");
+                push_newline(formatted, options);
+                for (i, value) in items.iter().enumerate() {
+                    push_indent(formatted, options, indent + 1);
+                    let trailing = if i < count - 1 { 1 } else { 0 };
+                    value.format_child(
+                        indent + 1,
+                        options.indent_width(indent + 1),
+                        trailing,
+                        options,
+                        formatted,
+                    );
+                    if i < count - 1 {
+                        push_token(formatted, options, TokenClass::Punctuation, ",");
+                    }
+                    push_newline(formatted, options);
+                }
+                if omitted > 0 {
+                    push_indent(formatted, options, indent + 1);
+                    push_omission(formatted, options, omitted);
+                    push_newline(formatted, options);
+                }
+                push_indent(formatted, options, indent);
+                push_token(formatted, options, TokenClass::Punctuation, "]");
+            }
+            ValueRef::String(str) => {
+                push_escaped_token(formatted, options, TokenClass::String, str);
+            }
+            ValueRef::Number(num) => match &options.theme {
+                Some(theme) => theme.paint(formatted, TokenClass::Number, &number_to_string(&num, options)),
+                None => push_formatted_number(formatted, &num, options),
+            },
+            ValueRef::Bool(bool) => {
+                let value = if bool { "true" } else { "false" };
+                push_token(formatted, options, TokenClass::Literal, value);
+            }
+            ValueRef::Null => {
+                push_token(formatted, options, TokenClass::Literal, "null");
+            }
+        }
+    }
+
+    /**
+     * オブジェクトや配列の要素となる値を整形する
+     * 1行にまとめるモードでは、開始位置 column から末尾の trailing 文字までが幅に収まる場合に1行で出力する
+     */
+    fn format_child(
+        &self,
+        indent: usize,
+        column: usize,
+        trailing: usize,
+        options: &FormatOptions,
+        formatted: &mut String,
+    ) {
+        if let Some(placeholder) = self.placeholder(indent, options) {
+            return push_token(formatted, options, TokenClass::Punctuation, &placeholder);
+        }
+        // 幅の計算は1行にまとめるモードの場合のみ行う
+        let fits = || {
+            options
+                .max_width
+                .checked_sub(column + trailing)
+                .is_some_and(|limit| self.inline_width(options, indent, limit).is_some())
+        };
+        if options.collapse && !options.compact && fits() {
+            self.format_inline(indent, options, formatted);
+        } else {
+            self.format_value(indent, options, formatted);
+        }
+    }
+
+    /**
+     * 数値・真偽値のみからなる配列を、オプションに応じて1行または指定した要素数ごとに折り返して整形する
+     */
+    fn format_numeric_array(&self, array: &[Self], indent: usize, options: &FormatOptions, formatted: &mut String) {
+        let (items, omitted) = visible_items(array, options);
+        // 1行に収まる要素数であれば折り返さない
+        let per_line = match options.numeric_arrays {
+            NumericArrayLayout::Wrapped(per_line) if items.len() > per_line => per_line.max(1),
+            _ => return self.format_inline(indent, options, formatted),
+        };
+        let count = separated_count(items, omitted, options);
+        push_token(formatted, options, TokenClass::Punctuation, "[");
+        push_newline(formatted, options);
+        for (i, value) in items.iter().enumerate() {
+            if i % per_line == 0 {
+                push_indent(formatted, options, indent + 1);
+            } else {
+                formatted.push(' ');
+            }
+            value.format_value(indent + 1, options, formatted);
+            if i < count - 1 {
+                push_token(formatted, options, TokenClass::Punctuation, ",");
+            }
+            if i % per_line == per_line - 1 || i == items.len() - 1 {
+                push_newline(formatted, options);
+            }
+        }
+        if omitted > 0 {
+            push_indent(formatted, options, indent + 1);
+            push_omission(formatted, options, omitted);
+            push_newline(formatted, options);
+        }
+        push_indent(formatted, options, indent);
+        push_token(formatted, options, TokenClass::Punctuation, "]");
+    }
+
+    /**
+     * 深さが max_depth 以上の空でないオブジェクトや配列であれば、中身の代わりに出力する {…} や [… 34 items] を返す
+     */
+    fn placeholder(&self, depth: usize, options: &FormatOptions) -> Option<String> {
+        if options.max_depth.is_none_or(|max_depth| depth < max_depth) {
+            return None;
+        }
+        return match self.value_ref() {
+            ValueRef::Object(members) if members.len() > 0 => Some("{…}".to_string()),
+            ValueRef::Array(array) if array.len() == 1 => Some("[… 1 item]".to_string()),
+            ValueRef::Array(array) if !array.is_empty() => Some(format!("[… {} items]", array.len())),
+            _ => None,
+        };
+    }
+
+    /**
+     * 値を改行を含めずに1行で整形する
+     * オブジェクトは { "key": value }、配列は [1, 2] の形式になる
+     */
+    fn format_inline(&self, depth: usize, options: &FormatOptions, formatted: &mut String) {
+        if let Some(placeholder) = self.placeholder(depth, options) {
+            return push_token(formatted, options, TokenClass::Punctuation, &placeholder);
+        }
+        match self.value_ref() {
+            ValueRef::Object(members) if members.len() > 0 => {
+                push_token(formatted, options, TokenClass::Punctuation, "{");
+                formatted.push(' ');
+                for (i, (key, value)) in members.enumerate() {
+                    if i > 0 {
+                        push_token(formatted, options, TokenClass::Punctuation, ",");
+                        formatted.push(' ');
                     }
+                    push_escaped_token(formatted, options, TokenClass::Key, key);
+                    push_token(formatted, options, TokenClass::Punctuation, ":");
+                    formatted.push(' ');
+                    value.format_inline(depth + 1, options, formatted);
                 }
-                self.push_indent(formatted, indent);
-                formatted.push_str("]")
+                formatted.push(' ');
+                push_token(formatted, options, TokenClass::Punctuation, "}");
             }
-            JsonValue::String(str) => {
-                formatted.push('"');
-                formatted.push_str(str);
-                formatted.push('"');
+            ValueRef::Object(_) => {
+                push_token(formatted, options, TokenClass::Punctuation, "{}");
             }
-            JsonValue::Number(num) => {
-                let value = &num.to_string();
-                self.push_str(formatted, value);
+            ValueRef::Array(array) => {
+                let (items, omitted) = visible_items(array, options);
+                push_token(formatted, options, TokenClass::Punctuation, "[");
+                for (i, value) in items.iter().enumerate() {
+                    if i > 0 {
+                        push_token(formatted, options, TokenClass::Punctuation, ",");
+                        formatted.push(' ');
+                    }
+                    value.format_inline(depth + 1, options, formatted);
+                }
+                if omitted > 0 && !items.is_empty() {
+                    if options.omission_marker == OmissionMarker::String {
+                        push_token(formatted, options, TokenClass::Punctuation, ",");
+                    }
+                    formatted.push(' ');
+                }
+                if omitted > 0 {
+                    push_omission(formatted, options, omitted);
+                }
+                push_token(formatted, options, TokenClass::Punctuation, "]");
             }
-            JsonValue::True => {
-                self.push_str(formatted, "true");
+            _ => self.format_value(0, options, formatted),
+        }
+    }
+
+    /**
+     * 1行で整形した場合の幅 (文字数) を返す
+     * 部分木ごとに幅を積み上げ、limit を超えた時点で None を返して打ち切る
+     */
+    fn inline_width(&self, options: &FormatOptions, depth: usize, limit: usize) -> Option<usize> {
+        let escape = &options.escape;
+        if let Some(placeholder) = self.placeholder(depth, options) {
+            let width = placeholder.chars().count();
+            return if width <= limit { Some(width) } else { None };
+        }
+        let width = match self.value_ref() {
+            ValueRef::Object(members) if members.len() == 0 => 2,
+            ValueRef::Object(members) => {
+                // "{ " と " }" および要素間の ", "
+                let mut width = 4 + (members.len() - 1) * 2;
+                for (key, value) in members {
+                    width += escaped_width(key, escape) + 2;
+                    width += value.inline_width(options, depth + 1, limit.checked_sub(width)?)?;
+                }
+                width
             }
-            JsonValue::False => {
-                self.push_str(formatted, "false");
+            ValueRef::Array(array) => {
+                // "[" と "]" および要素間の ", "
+                let (items, omitted) = visible_items(array, options);
+                let mut width = 2 + items.len().saturating_sub(1) * 2;
+                if omitted > 0 {
+                    // 省略の印と、要素との間の ", " または " "
+                    width += omission_text(omitted, options).chars().count();
+                    width += match options.omission_marker {
+                        _ if items.is_empty() => 0,
+                        OmissionMarker::Comment => 1,
+                        OmissionMarker::String => 2,
+                    };
+                }
+                for value in items {
+                    width += value.inline_width(options, depth + 1, limit.checked_sub(width)?)?;
+                }
+                width
             }
-            JsonValue::Null => {
-                self.push_str(formatted, "null");
+            ValueRef::String(str) => escaped_width(str, escape),
+            ValueRef::Number(num) => number_to_string(&num, options).len(),
+            ValueRef::Bool(true) => 4,
+            ValueRef::Bool(false) => 5,
+            ValueRef::Null => 4,
+        };
+        return if width <= limit { Some(width) } else { None };
+    }
+}
+
+impl FormatTree for JsonValue {
+    type Members<'v> = iter::Map<
+        indexmap::map::Iter<'v, String, JsonValue>,
+        fn((&'v String, &'v JsonValue)) -> (&'v str, &'v JsonValue),
+    >;
+
+    fn value_ref(&self) -> ValueRef<'_, Self> {
+        return match self {
+            JsonValue::Object(obj) => ValueRef::Object(obj.iter().map(member_ref as fn(_) -> _)),
+            JsonValue::Array(array) => ValueRef::Array(array),
+            JsonValue::String(str) => ValueRef::String(str),
+            JsonValue::Number(num) => ValueRef::Number(Cow::Borrowed(num)),
+            JsonValue::Bool(bool) => ValueRef::Bool(*bool),
+            JsonValue::Null => ValueRef::Null,
+        };
+    }
+}
+
+/**
+ * オブジェクトのメンバーのキーを &str にする (FormatTree::Members に書ける型にするため、クロージャではなく関数にする)
+ */
+fn member_ref<'v>((key, value): (&'v String, &'v JsonValue)) -> (&'v str, &'v JsonValue) {
+    return (key, value);
+}
+
+/**
+ * テーマが指定されていればトークンの種類に応じて色を付けて追加する
+ */
+fn push_token(formatted: &mut String, options: &FormatOptions, class: TokenClass, str: &str) {
+    match &options.theme {
+        Some(theme) => theme.paint(formatted, class, str),
+        None => formatted.push_str(str),
+    }
+}
+
+/**
+ * 文字列をエスケープしてトークンとして追加し、追加した部分の幅 (文字数) を返す
+ * 色を付けない場合は一時的な文字列を作らずに直接書き込む
+ */
+fn push_escaped_token(formatted: &mut String, options: &FormatOptions, class: TokenClass, str: &str) -> usize {
+    match &options.theme {
+        Some(theme) => {
+            let mut escaped = String::new();
+            push_escaped_string_with(&mut escaped, str, &options.escape);
+            theme.paint(formatted, class, &escaped);
+            return escaped.chars().count();
+        }
+        None => {
+            let start = formatted.len();
+            push_escaped_string_with(formatted, str, &options.escape);
+            return formatted[start..].chars().count();
+        }
+    }
+}
+
+/**
+ * 省略した要素の数を示す印を追加する
+ */
+fn push_omission(formatted: &mut String, options: &FormatOptions, omitted: usize) {
+    let text = omission_text(omitted, options);
+    match options.omission_marker {
+        OmissionMarker::Comment => push_token(formatted, options, TokenClass::Punctuation, &text),
+        OmissionMarker::String => push_token(formatted, options, TokenClass::String, &text),
+    }
+}
+
+fn push_newline(formatted: &mut String, options: &FormatOptions) {
+    if !options.compact {
+        formatted.push_str(options.line_ending.as_str());
+    }
+}
+
+fn push_indent(formatted: &mut String, options: &FormatOptions, indent: usize) {
+    if options.compact {
+        return;
+    }
+    for _ in 0..indent {
+        formatted.push_str(&options.indent_unit);
+    }
+}
+
+/**
+ * PartialEq と整合するハッシュ (HashSet の要素や HashMap のキーに使える)
+ * オブジェクトはキーの順序によらず同じハッシュになる
+ */
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            JsonValue::Object(obj) => {
+                // メンバーごとのハッシュ (固定の鍵の DefaultHasher で求める) を足し合わせて、順序によらない値にする
+                let sum = obj.iter().fold(0_u64, |sum, member| {
+                    let mut hasher = DefaultHasher::new();
+                    member.hash(&mut hasher);
+                    return sum.wrapping_add(hasher.finish());
+                });
+                obj.len().hash(state);
+                sum.hash(state);
             }
+            JsonValue::Array(array) => array.hash(state),
+            JsonValue::String(str) => str.hash(state),
+            JsonValue::Number(num) => num.hash(state),
+            JsonValue::Bool(bool) => bool.hash(state),
+            JsonValue::Null => {}
         }
     }
+}
 
-    fn push_str(&self, formatted: &mut String, str: &str) {
-        formatted.push_str(str);
+/**
+ * {} では改行や空白を含まない1行の JSON、{:#} では整形した JSON を出力する
+ */
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = if f.alternate() {
+            FormatOptions::default()
+        } else {
+            FormatOptions::compact()
+        };
+        return f.write_str(&self.format_with_options(&options));
     }
+}
+
+/**
+ * 文字列をダブルクォートで囲み、JSON として必要な文字をエスケープして追加する
+ * 制御文字は短縮形があればそれを使い、なければ \u00XX 形式にする
+ */
+pub fn push_escaped_string(formatted: &mut String, str: &str) {
+    push_escaped_string_with(formatted, str, &EscapeOptions::default());
+}
 
-    fn push_indent(&self, formatted: &mut String, indent: usize) {
-        for _ in 0..indent {
-            formatted.push_str(" ");
+/**
+ * push_escaped_string と同様に文字列を追加する (エスケープの方法を指定する)
+ */
+pub fn push_escaped_string_with(formatted: &mut String, str: &str, options: &EscapeOptions) {
+    formatted.push('"');
+    // エスケープが必要な文字はすべて ASCII なのでバイト単位で探し、その間の部分はまとめて追加する
+    let mut start = 0;
+    for (i, byte) in str.bytes().enumerate() {
+        let escaped = match byte {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'/' if options.escape_slash => "\\/",
+            _ if options.unicode_control && byte < 0x20 => "",
+            b'\x08' => "\\b",
+            b'\x0C' => "\\f",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x00..=0x1F => "",
+            _ => continue,
+        };
+        formatted.push_str(&str[start..i]);
+        if escaped.is_empty() && options.uppercase_hex {
+            write!(formatted, "\\u{:04X}", byte).unwrap();
+        } else if escaped.is_empty() {
+            write!(formatted, "\\u{:04x}", byte).unwrap();
+        } else {
+            formatted.push_str(escaped);
         }
+        start = i + 1;
     }
+    formatted.push_str(&str[start..]);
+    formatted.push('"');
+}
+
+/**
+ * 数値を、往復変換可能な最短の桁数で JavaScript の JSON.stringify と同じ表記 (1e+21 や 1e-7 など) で追加する
+ * f64 の Display は指数表記を使わないため、1.5e300 のような値が 300 桁以上になるのを避ける (-0 は -0 のまま出力する)
+ * 有限でない数値は NaN、Infinity、-Infinity のリテラルにする
+ */
+pub fn push_number(formatted: &mut String, num: &Number) {
+    push_number_with(formatted, num, ExponentNotation::Auto);
+}
+
+/**
+ * push_number と同様に数値を追加する (指数表記にする条件を指定する)
+ * 整数として正確に表せる値は、浮動小数点数の書式化よりも速い整数の書式化を使う
+ */
+pub fn push_number_with(formatted: &mut String, num: &Number, notation: ExponentNotation) {
+    let int = match *num {
+        Number::Int(int) => int as i128,
+        Number::UInt(uint) => uint as i128,
+        Number::Float(float) => return push_float(formatted, float, notation),
+        Number::Big(ref literal) => return formatted.push_str(literal),
+    };
+    let exponent = int.unsigned_abs().checked_ilog10().unwrap_or(0);
+    if notation.uses_exponent(exponent as i32) {
+        formatted.push_str(&format_integer(int, notation));
+    } else {
+        write!(formatted, "{}", int).unwrap();
+    }
+}
+
+fn push_float(formatted: &mut String, num: f64, notation: ExponentNotation) {
+    if num.fract() == 0.0 && num.abs() < 1e15 && !is_negative_zero(num) {
+        let exponent = (num.abs() as u64).checked_ilog10().unwrap_or(0);
+        if !notation.uses_exponent(exponent as i32) {
+            write!(formatted, "{}", num as i64).unwrap();
+            return;
+        }
+    }
+    if !num.is_finite() {
+        formatted.push_str(non_finite_literal(num));
+    } else if num != 0.0 {
+        formatted.push_str(&format_shortest(num, notation));
+    } else {
+        write!(formatted, "{}", num).unwrap();
+    }
+}
+
+/**
+ * 数値をオプションに従って追加する
+ * 桁数が指定されている場合は、指数表記にせず小数点以下をその桁数に丸めるか 0 で補う (3.1 は 3.100)
+ * 丸めは倍精度浮動小数点数の正確な値に対して行い、ちょうど中間の値は偶数側に丸める (0.125 は 0.12、0.375 は 0.38)
+ * 2.675 のように10進では中間に見えても、倍精度の値 (2.67499…) が中間より小さければ切り捨てる (2.67)
+ * 有限でない数値は non_finite の形式で、Big の数値はオプションによらずリテラルのまま出力する
+ */
+fn push_formatted_number(formatted: &mut String, num: &Number, options: &FormatOptions) {
+    match (options.precision, num) {
+        (_, &Number::Float(float)) if !float.is_finite() => match options.non_finite {
+            NonFiniteOutput::Literal => formatted.push_str(non_finite_literal(float)),
+            NonFiniteOutput::Null => formatted.push_str("null"),
+            NonFiniteOutput::String => write!(formatted, "\"{}\"", non_finite_literal(float)).unwrap(),
+        },
+        (Some(precision), &Number::Float(float)) => write!(formatted, "{:.*}", precision, float).unwrap(),
+        (Some(precision), Number::Int(_) | Number::UInt(_)) => {
+            push_number_with(formatted, num, ExponentNotation::Never);
+            if precision > 0 {
+                formatted.push('.');
+                formatted.push_str(&"0".repeat(precision));
+            }
+        }
+        _ => push_number_with(formatted, num, options.exponent),
+    }
+}
+
+/**
+ * push_formatted_number で追加される文字列を返す
+ */
+fn number_to_string(num: &Number, options: &FormatOptions) -> String {
+    let mut formatted = String::new();
+    push_formatted_number(&mut formatted, num, options);
+    return formatted;
+}
+
+/**
+ * push_escaped_string_with で追加される文字列の幅 (文字数) を返す
+ */
+fn escaped_width(str: &str, options: &EscapeOptions) -> usize {
+    let mut width = 2; // 前後の "
+    for ch in str.chars() {
+        width += match ch {
+            '"' | '\\' => 2,
+            '/' if options.escape_slash => 2,
+            c if (c as u32) < 0x20 && options.unicode_control => 6,
+            '\x08' | '\x0C' | '\n' | '\r' | '\t' => 2,
+            c if (c as u32) < 0x20 => 6,
+            _ => 1,
+        };
+    }
+    return width;
+}
+
+/**
+ * 配列のうち出力する先頭の要素と、省略する要素の数を返す
+ */
+fn visible_items<'a, V>(array: &'a [V], options: &FormatOptions) -> (&'a [V], usize) {
+    return match options.max_array_items {
+        Some(max_items) if array.len() > max_items => (&array[..max_items], array.len() - max_items),
+        _ => (array, 0),
+    };
+}
+
+/**
+ * カンマで区切る要素の数を返す (文字列の省略の印は、要素の1つとして区切る)
+ */
+fn separated_count<V>(items: &[V], omitted: usize, options: &FormatOptions) -> usize {
+    let marker = omitted > 0 && options.omission_marker == OmissionMarker::String;
+    return items.len() + if marker { 1 } else { 0 };
+}
+
+/**
+ * 省略した要素の数を示す印 (コメントの形式または "… 995 more" という文字列) を返す
+ */
+fn omission_text(omitted: usize, options: &FormatOptions) -> String {
+    return match options.omission_marker {
+        OmissionMarker::Comment => format!("/* {} more */", omitted),
+        OmissionMarker::String => format!("\"… {} more\"", omitted),
+    };
+}
+
+/**
+ * 空でなく、数値と真偽値のみからなる配列かどうか
+ */
+fn is_numeric_array<V: FormatTree>(array: &[V]) -> bool {
+    return !array.is_empty()
+        && array
+            .iter()
+            .all(|value| matches!(value.value_ref(), ValueRef::Number(_) | ValueRef::Bool(_)));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_hash() {
+        use std::collections::HashSet;
+        let values: Vec<JsonValue> = [
+            r#"{"a": 1, "b": [2, null]}"#,
+            r#"{"b": [2.0, null], "a": 1}"#,
+            r#"{"a": 1}"#,
+            "[1, 2]",
+            "[2, 1]",
+        ]
+        .iter()
+        .map(|input| input.parse().unwrap())
+        .collect();
+        let set: HashSet<&JsonValue> = values.iter().collect();
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&r#"{"b": [2, null], "a": 1.0}"#.parse::<JsonValue>().unwrap()));
+        assert!(HashSet::from([JsonValue::from(f64::NAN)]).contains(&JsonValue::from(f64::NAN)));
+    }
+
     #[test]
     fn test_format_value_true() {
-        let value = JsonValue::True;
+        let value = JsonValue::Bool(true);
         assert_eq!(value.format(0), "true");
     }
 
     #[test]
     fn test_format_value_false() {
-        let value = JsonValue::False;
+        let value = JsonValue::Bool(false);
         assert_eq!(value.format(0), "false");
     }
 
@@ -118,10 +841,10 @@ mod tests {
 
     #[test]
     fn test_format_value_number() {
-        let value1 = JsonValue::Number(123.0);
+        let value1 = JsonValue::from(123.0);
         assert_eq!(value1.format(0), "123");
 
-        let value2 = JsonValue::Number(123.456);
+        let value2 = JsonValue::from(123.456);
         assert_eq!(value2.format(0), "123.456");
     }
 
@@ -132,12 +855,204 @@ mod tests {
     }
 
     #[test]
-    fn test_format_value_array() {
+    fn test_format_value_string_escape() {
+        let value = JsonValue::String("say \"hi\"\n\\\u{1}".to_string());
+        assert_eq!(value.format(0), r#""say \"hi\"\n\\\u0001""#);
+    }
+
+    #[test]
+    fn test_format_value_string_escape_options() {
+        let value = JsonValue::String("a/b\n\u{1f}".to_string());
+        let format = |escape: EscapeOptions| {
+            return value.format_with_options(&FormatOptions {
+                escape,
+                ..Default::default()
+            });
+        };
+        assert_eq!(format(EscapeOptions::default()), r#""a/b\n\u001f""#);
+        let escape = EscapeOptions {
+            escape_slash: true,
+            unicode_control: true,
+            uppercase_hex: true,
+        };
+        assert_eq!(format(escape), r#""a\/b\u000A\u001F""#);
+    }
+
+    #[test]
+    fn test_escaped_width() {
+        let styles = [
+            EscapeOptions::default(),
+            EscapeOptions {
+                escape_slash: true,
+                unicode_control: true,
+                uppercase_hex: false,
+            },
+        ];
+        for str in ["", "日本語", "say \"hi\"\n\\\u{1}\u{1f}", "a/b\t"] {
+            for escape in &styles {
+                let mut escaped = String::new();
+                push_escaped_string_with(&mut escaped, str, escape);
+                assert_eq!(escaped_width(str, escape), escaped.chars().count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_value_number_integral() {
+        assert_eq!(JsonValue::from(42.0).format(0), "42");
+        assert_eq!(JsonValue::from(-0.0).format(0), "-0");
+        assert_eq!(JsonValue::from(-0.25).format(0), "-0.25");
+    }
+
+    #[test]
+    fn test_format_value_number_shortest() {
+        // 往復変換可能な最短の桁数で、大きな値や小さな値は指数表記にする
+        assert_eq!(JsonValue::from(1.5e300).format(0), "1.5e+300");
+        assert_eq!(JsonValue::from(1e21).format(0), "1e+21");
+        assert_eq!(JsonValue::from(1e20).format(0), "100000000000000000000");
+        assert_eq!(JsonValue::from(1e-7).format(0), "1e-7");
+        assert_eq!(JsonValue::from(0.1 + 0.2).format(0), "0.30000000000000004");
+        assert_eq!(JsonValue::from(-5e-324).format(0), "-5e-324");
+        for num in [1.5e300, 1e-7, 0.1 + 0.2, 123456.789, f64::MAX, f64::MIN_POSITIVE] {
+            assert_eq!(JsonValue::from(num).format(0).parse::<f64>().unwrap(), num);
+        }
+    }
+
+    #[test]
+    fn test_format_value_number_integer() {
+        // 64 ビットの整数は f64 を経由せず、そのままの桁で出力する
+        let json: JsonValue = "[9007199254740993, 12345678901234567890, -9223372036854775808, -0]"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            json.to_string(),
+            "[9007199254740993,12345678901234567890,-9223372036854775808,-0]"
+        );
+    }
+
+    #[test]
+    fn test_format_value_number_negative_zero() {
+        // -0 は 0 と区別し、どの書式でも符号を保つ
+        let json: JsonValue = "[-0, -0.0, -0e3, 0, 0.0]".parse().unwrap();
+        assert_eq!(json.to_string(), "[-0,-0,-0,0,0]");
+        let options = FormatOptions {
+            exponent: ExponentNotation::Threshold(0),
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(json.format_with_options(&options), "[-0,-0,-0,0,0]");
+        let options = FormatOptions {
+            precision: Some(1),
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(json.format_with_options(&options), "[-0.0,-0.0,-0.0,0.0,0.0]");
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_format_value_number_big() {
+        // f64 で表せない数値は、オプションによらずリテラルのまま出力する
+        let json: JsonValue = "[3.14159265358979323846264338327950288, 1e400, 0.1]".parse().unwrap();
+        let options = FormatOptions {
+            precision: Some(2),
+            ..FormatOptions::default()
+        };
+        assert_eq!(json.to_string(), "[3.14159265358979323846264338327950288,1e400,0.1]");
+        assert!(json.format_with_options(&options).contains("1e400"));
+    }
+
+    #[test]
+    fn test_format_with_options_precision() {
+        let value: JsonValue = "[3.1, 2.71828, 42, -0.0004]".parse().unwrap();
+        let options = FormatOptions {
+            precision: Some(3),
+            collapse: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[3.100, 2.718, 42.000, -0.000]");
+
+        let options = FormatOptions {
+            precision: Some(0),
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[3,3,42,-0]");
+    }
+
+    #[test]
+    fn test_format_with_options_precision_rounding() {
+        // ちょうど中間の値は偶数側に丸め、倍精度で中間より小さい値は切り捨てる
+        let value: JsonValue = "[0.125, 0.375, 2.675, -0.125]".parse().unwrap();
+        let options = FormatOptions {
+            precision: Some(2),
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[0.12,0.38,2.67,-0.12]");
+
+        let value: JsonValue = "[0.5, 1.5, 2.5, -2.5]".parse().unwrap();
+        let options = FormatOptions {
+            precision: Some(0),
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[0,2,2,-2]");
+    }
+
+    #[test]
+    fn test_format_with_options_non_finite() {
         let value = JsonValue::Array(vec![
-            JsonValue::Number(1.0),
-            JsonValue::Number(2.0),
-            JsonValue::Number(3.0),
+            JsonValue::from(f64::NAN),
+            JsonValue::from(f64::INFINITY),
+            JsonValue::from(f64::NEG_INFINITY),
         ]);
+        assert_eq!(value.to_string(), "[NaN,Infinity,-Infinity]");
+
+        let options = FormatOptions {
+            non_finite: NonFiniteOutput::Null,
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[null,null,null]");
+
+        let options = FormatOptions {
+            non_finite: NonFiniteOutput::String,
+            precision: Some(2),
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), r#"["NaN","Infinity","-Infinity"]"#);
+    }
+
+    #[test]
+    fn test_format_into() {
+        let value: JsonValue = r#"{"a": [1]}"#.parse().unwrap();
+        let mut formatted = String::from("> ");
+        value.format_into(&FormatOptions::default(), &mut formatted);
+
+        assert_eq!(formatted, "> {\n  \"a\": [\n    1\n  ]\n}");
+    }
+
+    #[test]
+    fn test_format_with_options_theme() {
+        let mut object = IndexMap::new();
+        object.insert(
+            "key".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Null]),
+        );
+        let value = JsonValue::Object(object);
+        let options = FormatOptions {
+            theme: Some(Theme::preset("default").unwrap()),
+            ..Default::default()
+        };
+        let expected = "{\n  \x1b[34m\"key\"\x1b[0m: [\n    \x1b[36m1\x1b[0m,\n    \x1b[33mnull\x1b[0m\n  ]\n}";
+        assert_eq!(value.format_with_options(&options), expected);
+    }
+
+    #[test]
+    fn test_format_value_array() {
+        let value = JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(2.0), JsonValue::from(3.0)]);
         let expected = r#"[
   1,
   2,
@@ -149,12 +1064,12 @@ mod tests {
     #[test]
     fn test_format_value_array_nested() {
         let value = JsonValue::Array(vec![
-            JsonValue::Number(1.1),
-            JsonValue::Number(1.2),
+            JsonValue::from(1.1),
+            JsonValue::from(1.2),
             JsonValue::Array(vec![
-                JsonValue::Number(2.1),
-                JsonValue::Number(2.2),
-                JsonValue::Array(vec![JsonValue::Number(3.1), JsonValue::Number(3.2)]),
+                JsonValue::from(2.1),
+                JsonValue::from(2.2),
+                JsonValue::Array(vec![JsonValue::from(3.1), JsonValue::from(3.2)]),
             ]),
         ]);
         let expected = r#"[
@@ -175,7 +1090,7 @@ mod tests {
     #[test]
     fn test_format_value_object() {
         let mut object = IndexMap::new();
-        object.insert("key1".to_string(), JsonValue::Number(123.0));
+        object.insert("key1".to_string(), JsonValue::from(123.0));
         object.insert("key2".to_string(), JsonValue::String("value".to_string()));
         let value = JsonValue::Object(object);
         let expected = r#"{
@@ -189,9 +1104,9 @@ mod tests {
     fn test_format_value_object_nested() {
         let mut object = IndexMap::new();
         let mut nested_object = IndexMap::new();
-        nested_object.insert("key3".to_string(), JsonValue::Number(456.0));
-        nested_object.insert("key4".to_string(), JsonValue::Number(789.0));
-        object.insert("key1".to_string(), JsonValue::Number(123.0));
+        nested_object.insert("key3".to_string(), JsonValue::from(456.0));
+        nested_object.insert("key4".to_string(), JsonValue::from(789.0));
+        object.insert("key1".to_string(), JsonValue::from(123.0));
         object.insert("key2".to_string(), JsonValue::Object(nested_object));
 
         let value = JsonValue::Object(object);
@@ -205,25 +1120,34 @@ mod tests {
         assert_eq!(value.format(0), expected);
     }
 
+    #[test]
+    fn test_format_indent() {
+        let value: JsonValue = r#"{"a": [1]}"#.parse().unwrap();
+
+        assert_eq!(value.format(3), "{\n     \"a\": [\n       1\n     ]\n   }");
+        assert_eq!(value.format_at_depth(1), "{\n    \"a\": [\n      1\n    ]\n  }");
+        assert_eq!(JsonValue::from("a\nb").format(3), r#""a\nb""#);
+    }
+
     #[test]
     fn test_format_value_mixed() {
         let mut object = IndexMap::new();
         let mut nested_object1 = IndexMap::new();
-        nested_object1.insert("key2".to_string(), JsonValue::Number(2.0));
-        nested_object1.insert("key3".to_string(), JsonValue::Number(3.0));
+        nested_object1.insert("key2".to_string(), JsonValue::from(2.0));
+        nested_object1.insert("key3".to_string(), JsonValue::from(3.0));
 
         let mut nested_object2 = IndexMap::new();
         nested_object2.insert(
             "key5".to_string(),
-            JsonValue::Array(vec![JsonValue::Number(5.0), JsonValue::Number(6.0)]),
+            JsonValue::Array(vec![JsonValue::from(5.0), JsonValue::from(6.0)]),
         );
 
         object.insert(
             "key1".to_string(),
             JsonValue::Array(vec![
-                JsonValue::Number(1.0),
+                JsonValue::from(1.0),
                 JsonValue::Object(nested_object1),
-                JsonValue::Array(vec![JsonValue::Number(4.0), JsonValue::Object(nested_object2)]),
+                JsonValue::Array(vec![JsonValue::from(4.0), JsonValue::Object(nested_object2)]),
             ]),
         );
         let value = JsonValue::Object(object);
@@ -247,4 +1171,298 @@ mod tests {
 }"#;
         assert_eq!(value.format(0), expected);
     }
+
+    #[test]
+    fn test_format_with_options_collapse() {
+        let mut point = IndexMap::new();
+        point.insert("x".to_string(), JsonValue::from(1.0));
+        point.insert("y".to_string(), JsonValue::from(2.0));
+
+        let mut object = IndexMap::new();
+        object.insert("point".to_string(), JsonValue::Object(point));
+        object.insert("empty".to_string(), JsonValue::Array(vec![]));
+        object.insert(
+            "long".to_string(),
+            JsonValue::Array(vec![
+                JsonValue::String("a".repeat(40)),
+                JsonValue::String("b".repeat(40)),
+            ]),
+        );
+        let value = JsonValue::Object(object);
+        let options = FormatOptions {
+            collapse: true,
+            ..Default::default()
+        };
+        let expected = format!(
+            r#"{{
+  "point": {{ "x": 1, "y": 2 }},
+  "empty": [],
+  "long": [
+    "{}",
+    "{}"
+  ]
+}}"#,
+            "a".repeat(40),
+            "b".repeat(40)
+        );
+        assert_eq!(value.format_with_options(&options), expected);
+    }
+
+    #[test]
+    fn test_format_with_options_collapse_root() {
+        let value = JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Bool(true)]);
+        let options = FormatOptions {
+            collapse: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[1, true]");
+    }
+
+    #[test]
+    fn test_format_with_options_max_width() {
+        let value = JsonValue::Array(vec![
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(2.0)]),
+            JsonValue::Array(vec![JsonValue::from(3.0), JsonValue::from(4.0)]),
+        ]);
+        let options = FormatOptions {
+            collapse: true,
+            max_width: 10,
+            ..Default::default()
+        };
+        let expected = r#"[
+  [1, 2],
+  [3, 4]
+]"#;
+        assert_eq!(value.format_with_options(&options), expected);
+    }
+
+    #[test]
+    fn test_format_with_options_max_depth() {
+        let value: JsonValue = r#"{"a": {"b": [1, 2, 3], "c": [4], "d": null}, "e": 5}"#.parse().unwrap();
+        let options = FormatOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let expected = r#"{
+  "a": {
+    "b": [… 3 items],
+    "c": [… 1 item],
+    "d": null
+  },
+  "e": 5
+}"#;
+        assert_eq!(value.format_with_options(&options), expected);
+
+        let options = FormatOptions {
+            max_depth: Some(1),
+            collapse: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), r#"{ "a": {…}, "e": 5 }"#);
+    }
+
+    #[test]
+    fn test_format_with_options_max_array_items() {
+        let value: JsonValue = r#"{"a": [1, 2, 3, 4, 5], "b": [[1], [2], [3]]}"#.parse().unwrap();
+        let options = FormatOptions {
+            max_array_items: Some(2),
+            ..Default::default()
+        };
+        let expected = r#"{
+  "a": [
+    1,
+    2
+    /* 3 more */
+  ],
+  "b": [
+    [
+      1
+    ],
+    [
+      2
+    ]
+    /* 1 more */
+  ]
+}"#;
+        assert_eq!(value.format_with_options(&options), expected);
+
+        // 文字列の印は JSON として正しい出力になる
+        let options = FormatOptions {
+            max_array_items: Some(2),
+            omission_marker: OmissionMarker::String,
+            collapse: true,
+            ..Default::default()
+        };
+        let formatted = value.format_with_options(&options);
+        assert_eq!(formatted, r#"{ "a": [1, 2, "… 3 more"], "b": [[1], [2], "… 1 more"] }"#);
+        assert!(formatted.parse::<JsonValue>().is_ok());
+
+        let options = FormatOptions {
+            max_array_items: Some(0),
+            numeric_arrays: NumericArrayLayout::SingleLine,
+            ..Default::default()
+        };
+        assert_eq!(
+            JsonValue::Array(vec![JsonValue::from(1.0)]).format_with_options(&options),
+            "[/* 1 more */]"
+        );
+    }
+
+    #[test]
+    fn test_inline_width() {
+        let mut object = IndexMap::new();
+        object.insert(
+            "a".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Null]),
+        );
+        let value = JsonValue::Object(object);
+
+        // { "a": [1, null] }
+        let options = FormatOptions::default();
+        assert_eq!(value.inline_width(&options, 0, 100), Some(18));
+        assert_eq!(value.inline_width(&options, 0, 18), Some(18));
+        assert_eq!(value.inline_width(&options, 0, 17), None);
+    }
+
+    #[test]
+    fn test_format_with_options_numeric_arrays_single_line() {
+        let mut object = IndexMap::new();
+        object.insert(
+            "matrix".to_string(),
+            JsonValue::Array(vec![
+                JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(0.0)]),
+                JsonValue::Array(vec![JsonValue::from(0.0), JsonValue::Bool(true)]),
+            ]),
+        );
+        object.insert(
+            "mixed".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Null]),
+        );
+        let value = JsonValue::Object(object);
+        let options = FormatOptions {
+            numeric_arrays: NumericArrayLayout::SingleLine,
+            ..Default::default()
+        };
+        let expected = r#"{
+  "matrix": [
+    [1, 0],
+    [0, true]
+  ],
+  "mixed": [
+    1,
+    null
+  ]
+}"#;
+        assert_eq!(value.format_with_options(&options), expected);
+    }
+
+    #[test]
+    fn test_format_with_options_numeric_arrays_wrapped() {
+        let value = JsonValue::Array(vec![
+            JsonValue::Array((1..=5).map(|i| JsonValue::from(i as f64)).collect()),
+            JsonValue::Array(vec![JsonValue::from(6.0), JsonValue::from(7.0)]),
+        ]);
+        let options = FormatOptions {
+            numeric_arrays: NumericArrayLayout::Wrapped(2),
+            ..Default::default()
+        };
+        let expected = r#"[
+  [
+    1, 2,
+    3, 4,
+    5
+  ],
+  [6, 7]
+]"#;
+        assert_eq!(value.format_with_options(&options), expected);
+    }
+
+    #[test]
+    fn test_format_with_options_indent_unit() {
+        let mut object = IndexMap::new();
+        object.insert("key".to_string(), JsonValue::Array(vec![JsonValue::from(1.0)]));
+        let value = JsonValue::Object(object);
+
+        let options1 = FormatOptions {
+            indent_unit: "\t".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options1), "{\n\t\"key\": [\n\t\t1\n\t]\n}");
+
+        let options2 = FormatOptions {
+            indent_unit: "  · ".to_string(),
+            ..Default::default()
+        };
+        let expected = r#"{
+  · "key": [
+  ·   · 1
+  · ]
+}"#;
+        assert_eq!(value.format_with_options(&options2), expected);
+    }
+
+    #[test]
+    fn test_format_with_options_trailing_newline() {
+        let value = JsonValue::Array(vec![JsonValue::Null]);
+        let options = FormatOptions {
+            trailing_newline: true,
+            ..Default::default()
+        };
+        assert_eq!(value.format_with_options(&options), "[\n  null\n]\n");
+        assert_eq!(value.format_with_options(&FormatOptions::default()), "[\n  null\n]");
+    }
+
+    #[test]
+    fn test_format_with_options_crlf() {
+        let mut object = IndexMap::new();
+        object.insert(
+            "key".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(2.0)]),
+        );
+        let value = JsonValue::Object(object);
+        let options = FormatOptions {
+            line_ending: LineEnding::Crlf,
+            trailing_newline: true,
+            numeric_arrays: NumericArrayLayout::Wrapped(1),
+            ..Default::default()
+        };
+        let expected = "{\r\n  \"key\": [\r\n    1,\r\n    2\r\n  ]\r\n}\r\n";
+        assert_eq!(value.format_with_options(&options), expected);
+    }
+
+    #[test]
+    fn test_format_with_options_compact() {
+        let mut object = IndexMap::new();
+        object.insert(
+            "a".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Bool(true)]),
+        );
+        object.insert("b".to_string(), JsonValue::Object(IndexMap::new()));
+        let value = JsonValue::Object(object);
+        let options = FormatOptions {
+            collapse: true,
+            numeric_arrays: NumericArrayLayout::SingleLine,
+            ..FormatOptions::compact()
+        };
+        assert_eq!(value.format_with_options(&options), r#"{"a":[1,true],"b":{}}"#);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut object = IndexMap::new();
+        object.insert(
+            "a".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Null]),
+        );
+        let value = JsonValue::Object(object);
+
+        assert_eq!(format!("{}", value), r#"{"a":[1,null]}"#);
+        let expected = r#"{
+  "a": [
+    1,
+    null
+  ]
+}"#;
+        assert_eq!(format!("{:#}", value), expected);
+    }
 }
@@ -0,0 +1,360 @@
+use std::mem;
+
+use crate::json::{push_escaped_string_with, FormatOptions, FormatTree, JsonValue};
+use crate::lexer::{Lexer, Spanned, Token};
+use crate::parser::{ParseError, Parser};
+use crate::theme::TokenClass;
+
+/**
+ * 独立した行にあるコメント
+ */
+struct LeadingComment<'a> {
+    text: &'a str,           // コメントの内容 (// や /* を含む)
+    blank_line_before: bool, // 入力でコメントの前に空行があったかどうか
+}
+
+/**
+ * コメント付きの JSON (JSONC) を、コメントを残したまま整形する
+ * VS Code の設定ファイルと同様に閉じ括弧の直前の余分な , も受け付け、整形後は取り除く
+ * 直前のトークンと同じ行にあるコメントは整形後もその行の末尾に、独立した行にあるコメントは
+ * 次のメンバーや要素 (なければ閉じ括弧) の前の行に、その位置の字下げに揃えて出力する
+ * レイアウトは JsonValue::format_with_options の標準のレイアウトと同じで、--collapse などには対応しない
+ */
+pub fn format_jsonc(input: &str, options: &FormatOptions) -> Result<String, ParseError> {
+    // 構文の検査は、コメントを読み飛ばして通常の JSON と同じパーサーで行う
+    parse_jsonc(input)?;
+
+    let tokens: Vec<Spanned<Token>> = jsonc_lexer(input).filter_map(Result::ok).collect();
+    let mut formatter = JsoncFormatter {
+        input,
+        options,
+        output: String::with_capacity(input.len() + input.len() / 2),
+        stack: Vec::new(),
+        line_open: false,
+        just_opened: false,
+        trailing: Vec::new(),
+        leading: Vec::new(),
+    };
+    formatter.run(&tokens);
+    return Ok(formatter.output);
+}
+
+/**
+ * コメント付きの JSON (JSONC) を、コメントを読み飛ばしてパースする
+ */
+pub fn parse_jsonc(input: &str) -> Result<JsonValue, ParseError> {
+    return Parser::new(jsonc_lexer(input)).with_trailing_commas().parse();
+}
+
+/**
+ * コメント付きの JSON (JSONC) をパースし、見つかった誤りをすべて返す (正しければ空)
+ */
+pub fn collect_jsonc_errors(input: &str) -> Vec<ParseError> {
+    return Parser::new(jsonc_lexer(input)).with_trailing_commas().collect_errors();
+}
+
+/**
+ * JSONC の字句解析を行う Lexer を生成する
+ * 手で編集する設定ファイル向けの形式のため、コメントに加えて数値の桁区切りの _ (1_000_000) も受け付ける
+ */
+fn jsonc_lexer(input: &str) -> Lexer<'_> {
+    return Lexer::new(input).with_comments().with_numeric_separators();
+}
+
+/**
+ * , の後に (コメントを除いて) 閉じ括弧が続くかどうか
+ */
+fn is_trailing_comma(rest: &[Spanned<Token>]) -> bool {
+    let next = rest.iter().find(|token| !matches!(token.value, Token::Comment(_)));
+    return next.is_some_and(|token| matches!(token.value, Token::RightBrace | Token::RightBracket));
+}
+
+struct JsoncFormatter<'a, 'o> {
+    input: &'a str,
+    options: &'o FormatOptions,
+    output: String,
+    stack: Vec<bool>,                 // 開いているオブジェクト (true) や配列 (false)
+    line_open: bool,                  // 出力中の行がまだ改行で終わっていないかどうか
+    just_opened: bool,                // 直前に { や [ を出力したかどうか (その直後には空行を入れない)
+    trailing: Vec<&'a str>,           // 出力中の行の末尾に付けるコメント
+    leading: Vec<LeadingComment<'a>>, // 次の行の前に出力するコメント
+}
+
+impl<'a> JsoncFormatter<'a, '_> {
+    fn run(&mut self, tokens: &[Spanned<Token<'a>>]) {
+        let mut previous_end = None; // 直前のトークン (コメントを含む) の終了位置
+        let mut line_start = true; // 次の値やキーが新しい行から始まるかどうか
+        for (i, Spanned { value: token, span }) in tokens.iter().enumerate() {
+            match token {
+                Token::Comment(text) => {
+                    let gap = &self.input[previous_end.unwrap_or(0)..span.start];
+                    if previous_end.is_some() && !gap.contains('\n') {
+                        self.trailing.push(text);
+                    } else {
+                        self.leading.push(LeadingComment {
+                            text,
+                            blank_line_before: gap.matches('\n').count() >= 2,
+                        });
+                    }
+                    previous_end = Some(span.end);
+                    continue;
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    self.stack.pop();
+                    let depth = self.stack.len();
+                    self.start_line(depth + 1, depth);
+                    self.push_token(
+                        TokenClass::Punctuation,
+                        if *token == Token::RightBrace { "}" } else { "]" },
+                    );
+                }
+                Token::LeftBrace | Token::LeftBracket => {
+                    self.begin_value(line_start);
+                    let object = *token == Token::LeftBrace;
+                    self.push_token(TokenClass::Punctuation, if object { "{" } else { "[" });
+                    self.stack.push(object);
+                    self.just_opened = true;
+                }
+                Token::Colon => {
+                    self.push_inline(TokenClass::Punctuation, ":");
+                    self.output.push(' ');
+                }
+                // 閉じ括弧の直前の余分な , は出力しない
+                Token::Comma if is_trailing_comma(&tokens[i + 1..]) => {}
+                Token::Comma => self.push_inline(TokenClass::Punctuation, ","),
+                Token::String(key) if line_start && self.stack.last() == Some(&true) => {
+                    let depth = self.stack.len();
+                    self.start_line(depth, depth);
+                    let mut escaped = String::new();
+                    push_escaped_string_with(&mut escaped, key, &self.options.escape);
+                    self.push_token(TokenClass::Key, &escaped);
+                }
+                scalar => {
+                    self.begin_value(line_start);
+                    let value = match scalar {
+                        Token::String(string) => JsonValue::String(string.to_string()),
+                        Token::Number(number) => JsonValue::Number(number.clone()),
+                        Token::True => JsonValue::Bool(true),
+                        Token::False => JsonValue::Bool(false),
+                        _ => JsonValue::Null,
+                    };
+                    value.format_value(0, self.options, &mut self.output);
+                }
+            }
+            line_start = matches!(token, Token::LeftBrace | Token::LeftBracket | Token::Comma);
+            previous_end = Some(span.end);
+        }
+
+        // 最上位の値の後のコメント
+        self.flush_trailing();
+        for comment in mem::take(&mut self.leading) {
+            self.output.push_str(self.options.line_ending.as_str());
+            if comment.blank_line_before {
+                self.output.push_str(self.options.line_ending.as_str());
+            }
+            self.output.push_str(comment.text);
+        }
+        if self.options.trailing_newline {
+            self.output.push_str(self.options.line_ending.as_str());
+        }
+    }
+
+    /**
+     * 値の先頭を出力する前の処理 (最上位の値や配列の要素であれば新しい行を始める)
+     */
+    fn begin_value(&mut self, line_start: bool) {
+        if line_start {
+            let depth = self.stack.len();
+            self.start_line(depth, depth);
+        } else {
+            self.move_leading_to_trailing();
+        }
+    }
+
+    /**
+     * 出力中の行を終え、溜めておいた独立した行のコメントを comment_depth の字下げで出力してから、
+     * depth の字下げで新しい行を始める
+     */
+    fn start_line(&mut self, comment_depth: usize, depth: usize) {
+        if self.line_open {
+            self.flush_trailing();
+            self.output.push_str(self.options.line_ending.as_str());
+        }
+        for comment in mem::take(&mut self.leading) {
+            if comment.blank_line_before && !self.just_opened && !self.output.is_empty() {
+                self.output.push_str(self.options.line_ending.as_str());
+            }
+            self.push_indent(comment_depth);
+            self.output.push_str(comment.text);
+            self.output.push_str(self.options.line_ending.as_str());
+            self.just_opened = false;
+        }
+        self.push_indent(depth);
+        self.line_open = true;
+        self.just_opened = false;
+    }
+
+    /**
+     * 新しい行を始めずに、出力中の行にトークンを続ける
+     */
+    fn push_inline(&mut self, class: TokenClass, str: &str) {
+        self.move_leading_to_trailing();
+        self.push_token(class, str);
+    }
+
+    /**
+     * 行の途中 (キーと値の間など) にあった独立した行のコメントは、その行の末尾に移す
+     * 整形後も値より前に置くと、行コメントがそれ以降の値を隠してしまうため
+     */
+    fn move_leading_to_trailing(&mut self) {
+        let leading = mem::take(&mut self.leading);
+        self.trailing.extend(leading.into_iter().map(|comment| comment.text));
+    }
+
+    /**
+     * 出力中の行の末尾にコメントを出力する
+     */
+    fn flush_trailing(&mut self) {
+        for text in mem::take(&mut self.trailing) {
+            self.output.push(' ');
+            self.output.push_str(text);
+        }
+    }
+
+    fn push_token(&mut self, class: TokenClass, str: &str) {
+        match &self.options.theme {
+            Some(theme) => theme.paint(&mut self.output, class, str),
+            None => self.output.push_str(str),
+        }
+    }
+
+    fn push_indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.output.push_str(&self.options.indent_unit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::LineEnding;
+    use crate::parser::ParseErrorKind;
+
+    fn format(input: &str) -> String {
+        return format_jsonc(input, &FormatOptions::default()).unwrap();
+    }
+
+    #[test]
+    fn test_format_without_comments() {
+        // コメントがなければ通常の整形と同じ出力になる
+        let input = r#"{"a": [1, {"b": null}, []], "c": {}, "d": "é\n"}"#;
+        let expected = input
+            .parse::<JsonValue>()
+            .unwrap()
+            .format_with_options(&FormatOptions::default());
+        assert_eq!(format(input), expected);
+    }
+
+    #[test]
+    fn test_format_tsconfig() {
+        let input = r#"{
+  // ビルドの設定
+  "compilerOptions": {
+      "target": "es2020", // 出力する JS のバージョン
+      /* 型チェック */ "strict": true,
+
+      // パスの解決
+      "paths": {"@/*": ["src/*"]} },
+  "include": [
+    "src" // ソース
+    // テスト
+  ]
+}
+// 末尾のコメント
+"#;
+        let expected = r#"{
+  // ビルドの設定
+  "compilerOptions": {
+    "target": "es2020", // 出力する JS のバージョン
+    /* 型チェック */
+    "strict": true,
+
+    // パスの解決
+    "paths": {
+      "@/*": [
+        "src/*"
+      ]
+    }
+  },
+  "include": [
+    "src" // ソース
+    // テスト
+  ]
+}
+// 末尾のコメント"#;
+        assert_eq!(format(input), expected);
+    }
+
+    #[test]
+    fn test_format_comments_in_awkward_positions() {
+        // 行の途中のコメントは行末に移し、行コメントが後続のトークンを隠さないようにする
+        assert_eq!(
+            format("{\"a\" // キー\n : 1 // 値\n , \"b\": /* 前 */ 2}"),
+            "{\n  \"a\": 1, // キー // 値\n  \"b\": 2 /* 前 */\n}"
+        );
+
+        // 中身がコメントだけのオブジェクトや配列
+        assert_eq!(format("[ /* 空 */ ]"), "[ /* 空 */\n]");
+        assert_eq!(format("{\n  // なし\n}"), "{\n  // なし\n}");
+
+        // 最上位の値の前後のコメント
+        assert_eq!(format("/* 先頭 */\n\n1 // 末尾"), "/* 先頭 */\n1 // 末尾");
+
+        // 末尾の改行と改行コードは FormatOptions に従う
+        let options = FormatOptions {
+            trailing_newline: true,
+            line_ending: LineEnding::Crlf,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format_jsonc("[1, // 一\r\n2]", &options).unwrap(),
+            "[\r\n  1, // 一\r\n  2\r\n]\r\n"
+        );
+    }
+
+    #[test]
+    fn test_format_numeric_separators() {
+        // 桁区切りの _ は取り除いて出力する
+        assert_eq!(
+            format_jsonc("{\"max\": 1_000_000 // 上限\n}", &FormatOptions::default()).unwrap(),
+            "{\n  \"max\": 1000000 // 上限\n}"
+        );
+        assert!(parse_jsonc("[1__0]").is_err());
+    }
+
+    #[test]
+    fn test_format_trailing_commas() {
+        let input = "{\n  \"a\": [1, 2,], // 配列\n  \"b\": {\"c\": true,},\n}";
+        assert_eq!(
+            format_jsonc(input, &FormatOptions::default()).unwrap(),
+            "{\n  \"a\": [\n    1,\n    2\n  ], // 配列\n  \"b\": {\n    \"c\": true\n  }\n}"
+        );
+        assert_eq!(parse_jsonc("[1,]").unwrap(), parse_jsonc("[1]").unwrap());
+        assert!(collect_jsonc_errors("[1, 2,]").is_empty());
+        assert!(parse_jsonc("[1,,]").is_err());
+        assert!(parse_jsonc("[,]").is_err());
+    }
+
+    #[test]
+    fn test_format_errors() {
+        // 構文の誤りは通常の JSON と同じエラーになる
+        let error = format_jsonc("{\"a\": 1 // コメント\n \"b\": 2}", &FormatOptions::default()).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::ExpectedCommaOrBrace);
+
+        // 閉じていないブロックコメント
+        let error = format_jsonc("[1 /* 閉じない", &FormatOptions::default()).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::InvalidToken);
+        assert_eq!(error.position, 3);
+    }
+}
@@ -0,0 +1,95 @@
+use rust_json_formatter::encoded::decode_base64;
+use rust_json_formatter::json::JsonValue;
+use std::io::Read;
+
+use crate::cli::Cli;
+use crate::error::CliError;
+use crate::messages::t;
+use crate::{
+    fail, format_options, open_stdin, open_stdout, parse_error, parse_json, print_warnings, render, write_output,
+};
+
+/**
+ * 標準入力の JWT (header.payload.signature の形式) のヘッダーとペイロードを復号し、
+ * {"header": ..., "payload": ...} のオブジェクトとして整形して出力する (署名は検証しない)
+ */
+pub fn run(cli: &Cli) {
+    let mut input = String::new();
+    if let Err(error) = open_stdin(cli).read_to_string(&mut input) {
+        fail(cli, CliError::io(t!(ReadFailed, error)));
+    }
+    let mut warnings = Vec::new();
+    let token = decode(cli, input.trim(), &mut warnings).unwrap_or_else(|error| fail(cli, error));
+    print_warnings(cli, None, &warnings);
+    let output = render(cli, &token, &format_options(cli), input.len()).unwrap_or_else(|error| fail(cli, error));
+    write_output(open_stdout(cli), output.as_bytes()).unwrap_or_else(|error| fail(cli, error));
+}
+
+/**
+ * JWT のヘッダーとペイロードを base64url として復号し、それぞれを JSON としてパースする
+ */
+fn decode(cli: &Cli, token: &str, warnings: &mut Vec<String>) -> Result<JsonValue, CliError> {
+    // 暗号化された JWT (JWE) は5つの部分からなるため、ここで不正として扱う
+    let segments: Vec<&str> = token.split('.').collect();
+    let [header, payload, _signature] = segments[..] else {
+        return Err(CliError::parse(t!(JwtMalformed)));
+    };
+    let mut token = JsonValue::Null;
+    for (key, name, segment) in [("header", t!(JwtHeader), header), ("payload", t!(JwtPayload), payload)] {
+        let text = decode_base64(segment)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| CliError::parse(t!(JwtSegmentInvalid, name)))?;
+        let value = parse_json(cli, &text, warnings).map_err(|e| parse_error(cli, &name, &e, &text))?;
+        token.insert(key, value);
+    }
+    return Ok(token);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ExitCode;
+    use clap::Parser;
+
+    fn decode_token(token: &str) -> Result<JsonValue, CliError> {
+        let cli = Cli::try_parse_from(["rust_json_formatter", "jwt"]).unwrap();
+        return decode(&cli, token, &mut Vec::new());
+    }
+
+    #[test]
+    fn test_decode() {
+        // 署名は検証しないため、任意の文字列でよい
+        let token = decode_token("eyJhbGciOiJub25lIn0.eyJzdWIiOiLjgYIiLCJuIjpbMSwyXX0.sig").unwrap();
+        assert_eq!(
+            token.to_string(),
+            r#"{"header":{"alg":"none"},"payload":{"sub":"あ","n":[1,2]}}"#
+        );
+
+        // base64url の - と _ 、省略されたパディングも扱う
+        let token = decode_token("eyJhIjoifn5-In0.eyJhIjoiw78_In0.").unwrap();
+        assert_eq!(token.to_string(), r#"{"header":{"a":"~~~"},"payload":{"a":"ÿ?"}}"#);
+    }
+
+    #[test]
+    fn test_decode_segment_count() {
+        for token in ["", "eyJhIjoxfQ", "eyJhIjoxfQ.e30", "a.b.c.d.e"] {
+            let error = decode_token(token).unwrap_err();
+            assert_eq!(error.code, ExitCode::ParseError);
+            assert_eq!(error.message, t!(JwtMalformed));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_base64url() {
+        let error = decode_token("e30.e$30.sig").unwrap_err();
+        assert_eq!(error.code, ExitCode::ParseError);
+        assert_eq!(error.message, t!(JwtSegmentInvalid, t!(JwtPayload)));
+
+        let error = decode_token("e+30.e30.sig").unwrap_err();
+        assert_eq!(error.message, t!(JwtSegmentInvalid, t!(JwtHeader)));
+
+        // base64url としては正しいが UTF-8 として不正な場合も同じ扱い
+        let error = decode_token("_w.e30.sig").unwrap_err();
+        assert_eq!(error.message, t!(JwtSegmentInvalid, t!(JwtHeader)));
+    }
+}
@@ -1,28 +1,111 @@
+use memchr::{memchr2, memchr_iter, memrchr};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::number::Number;
+
+/**
+ * UTF-8 の BOM (バイト順マーク)
+ */
+pub const BOM: char = '\u{FEFF}';
+
 #[derive(Debug, PartialEq)]
-pub enum Token {
-    LeftBrace,      // {
-    RightBrace,     // }
-    LeftBracket,    // [
-    RightBracket,   // ]
-    Colon,          // :
-    Comma,          // ,
-    String(String), // "string"
-    Number(f64),    // 123, 45.67
-    True,           // true
-    False,          // false
-    Null,           // null
+pub enum Token<'a> {
+    LeftBrace,            // {
+    RightBrace,           // }
+    LeftBracket,          // [
+    RightBracket,         // ]
+    Colon,                // :
+    Comma,                // ,
+    String(Cow<'a, str>), // "string" (エスケープを含まない場合は入力を借用する)
+    Number(Number),       // 123, 45.67
+    True,                 // true
+    False,                // false
+    Null,                 // null
+    Comment(&'a str),     // // から行末まで、または /* から */ まで (コメントを読み取る設定の場合のみ)
+}
+
+/**
+ * トークンの入力中の範囲
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,  // 開始位置 (バイト単位)
+    pub end: usize,    // 終了位置 (バイト単位、この位置の文字は含まない)
+    pub line: usize,   // 開始位置の行番号 (1 始まり)
+    pub column: usize, // 開始位置の列番号 (1 始まり、文字単位)
+}
+
+/**
+ * 入力中の範囲を付けた値
+ */
+#[derive(Debug, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,   // 値 (トークンなど)
+    pub span: Span, // 入力中の範囲
+}
+
+/**
+ * 字句解析に失敗した原因
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LexErrorKind {
+    InvalidToken,       // 字句解析できない文字やリテラル
+    UnexpectedBom,      // 入力の先頭以外に BOM がある
+    InvalidEscape,      // 文字列中の不正なエスケープシーケンス (\x や16進数字が4桁ない \u など)
+    LoneSurrogate,      // 対になるサロゲートのない \u エスケープ (\ud83d だけなど)
+    UnterminatedString, // 閉じる `"` がないまま入力が終わった文字列
+    ControlCharacter,   // 文字列中のエスケープされていない制御文字 (U+0000 から U+001F)
+    InvalidNumber,      // JSON の文法に合わない数値 (01、+1、1. など)
+}
+
+/**
+ * 字句解析エラー
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind, // エラーの原因
+    pub span: Span,         // 読み取れなかった入力中の範囲
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self.kind {
+            LexErrorKind::InvalidToken => "不正なトークンです",
+            LexErrorKind::UnexpectedBom => "入力の途中に BOM (U+FEFF) があります",
+            LexErrorKind::InvalidEscape => "不正なエスケープシーケンスです",
+            LexErrorKind::LoneSurrogate => "対になるサロゲートのない \\u エスケープです",
+            LexErrorKind::UnterminatedString => "文字列が閉じられていません",
+            LexErrorKind::ControlCharacter => "文字列中に制御文字がそのまま含まれています",
+            LexErrorKind::InvalidNumber => "不正な数値です",
+        };
+        return write!(f, "{} ({} 行 {} 列目)", message, self.span.line, self.span.column);
+    }
 }
 
+impl Error for LexError {}
+
 pub struct Lexer<'a> {
-    input: &'a str,       // 字句解析対象の文字列全体
-    position: usize,      // 解析中の現在の文字位置
-    read_position: usize, // 解析中の次の文字位置
-    ch: Option<char>,     // 現在解析中の文字 (None は EOF)
+    input: &'a str,          // 字句解析対象の文字列全体
+    position: usize,         // 解析中の現在の文字位置
+    read_position: usize,    // 解析中の次の文字位置
+    ch: Option<char>,        // 現在解析中の文字 (None は EOF)
+    line: usize,             // 現在の文字の行番号 (1 始まり)
+    column: usize,           // 現在の文字の列番号 (1 始まり、文字単位)
+    token_start: usize,      // 直前に読み取ったトークンの開始位置
+    token_line: usize,       // 直前に読み取ったトークンの開始位置の行番号
+    token_column: usize,     // 直前に読み取ったトークンの開始位置の列番号
+    comments: bool,          // コメントをトークンとして読み取るかどうか (JSONC 向け)
+    non_finite: bool,        // NaN、Infinity、-Infinity を数値として読み取るかどうか
+    separators: bool,        // 数値の桁区切りの _ (1_000_000) を読み飛ばすかどうか
+    error: Option<LexError>, // 直前のトークンを読み取れなかった原因と範囲 (トークンの一部だけが不正な場合)
 }
 
 impl<'a> Lexer<'a> {
     /**
      * 新しい Lexer を生成する
+     * 先頭の BOM は読み飛ばす (位置は BOM を含めた入力の先頭から数える)
      */
     pub fn new(input: &'a str) -> Self {
         let mut lexer = Lexer {
@@ -30,16 +113,72 @@ impl<'a> Lexer<'a> {
             position: 0,
             read_position: 0,
             ch: None,
+            line: 1,
+            column: 1,
+            token_start: 0,
+            token_line: 1,
+            token_column: 1,
+            comments: false,
+            non_finite: false,
+            separators: false,
+            error: None,
         };
-        lexer.read_char();
+        if input.starts_with(BOM) {
+            lexer.seek(BOM.len_utf8());
+        } else {
+            lexer.read_char();
+        }
+        return lexer;
+    }
+
+    /**
+     * コメント (// から行末までの行コメントとブロックコメント) を Token::Comment として読み取るようにする
+     * 指定しない場合、コメントは不正なトークンになる
+     */
+    pub fn with_comments(mut self) -> Self {
+        self.comments = true;
+        return self;
+    }
+
+    /**
+     * NaN、Infinity、-Infinity (Python の json モジュールなどが出力する、JSON では不正なリテラル) を数値として読み取るようにする
+     * 指定しない場合、これらは不正なトークンになる
+     */
+    pub fn with_non_finite(mut self) -> Self {
+        self.non_finite = true;
+        return self;
+    }
+
+    /**
+     * 数値の数字と数字の間にある桁区切りの _ (1_000_000) を読み飛ばすようにする (手で編集する設定ファイル向け)
+     * 指定しない場合、_ は不正なトークンになる
+     */
+    pub fn with_numeric_separators(mut self) -> Self {
+        self.separators = true;
+        return self;
+    }
+
+    /**
+     * 同じ入力と設定で、指定した位置 (バイト単位) から字句解析をやり直す Lexer を返す
+     */
+    pub(crate) fn relex_from(&self, position: usize) -> Lexer<'a> {
+        let mut lexer = Lexer::new(self.input);
+        lexer.comments = self.comments;
+        lexer.non_finite = self.non_finite;
+        lexer.separators = self.separators;
+        lexer.seek(position);
         return lexer;
     }
 
     /**
      * 次のトークンを取得する
      */
-    pub fn next_token(&mut self) -> Option<Token> {
+    pub fn next_token(&mut self) -> Option<Token<'a>> {
+        self.error = None;
         self.skip_whitespace();
+        self.token_start = self.position;
+        self.token_line = self.line;
+        self.token_column = self.column;
         let token: Option<Token> = match self.ch {
             Some('{') => {
                 self.read_char();
@@ -65,21 +204,30 @@ impl<'a> Lexer<'a> {
                 self.read_char();
                 Some(Token::Comma)
             }
-            Some('"') => {
-                let string = self.read_string();
-                Some(Token::String(string))
+            Some('"') => self.read_string().map(Token::String),
+            Some('-') if self.non_finite && self.input[self.position..].starts_with("-Infinity") => {
+                self.seek(self.position + "-Infinity".len());
+                Some(Token::Number(Number::Float(f64::NEG_INFINITY)))
             }
-            Some(c) if c.is_digit(10) || c == '-' || c == '+' => {
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
                 let string = self.read_number();
-                if let Ok(number) = string.parse::<f64>() {
-                    Some(Token::Number(number))
-                } else {
-                    None
+                match string.parse::<Number>() {
+                    Ok(number) if is_json_number(&string) => Some(Token::Number(number)),
+                    _ => {
+                        self.error = Some(LexError {
+                            kind: LexErrorKind::InvalidNumber,
+                            span: self.token_span(),
+                        });
+                        None
+                    }
                 }
             }
             Some(c) if c.is_alphabetic() => {
                 return self.read_literal();
             }
+            Some('/') if self.comments => {
+                return self.read_comment();
+            }
             None => return None,
             _ => {
                 // 未知の文字
@@ -90,10 +238,71 @@ impl<'a> Lexer<'a> {
         return token;
     }
 
+    /**
+     * 次のトークンを入力中の範囲と合わせて取得する
+     */
+    pub fn next_spanned(&mut self) -> Option<Spanned<Token<'a>>> {
+        let token = self.next_token()?;
+        return Some(Spanned {
+            value: token,
+            span: self.token_span(),
+        });
+    }
+
+    /**
+     * 直前に読み取ったトークンの入力中の範囲を返す (読み取れなかった場合は読み飛ばした範囲)
+     */
+    pub fn token_span(&self) -> Span {
+        return Span {
+            start: self.token_start,
+            end: self.position,
+            line: self.token_line,
+            column: self.token_column,
+        };
+    }
+
+    /**
+     * 直前に読み取ったトークンの開始位置 (バイト単位) を返す
+     */
+    pub fn token_start(&self) -> usize {
+        return self.token_start;
+    }
+
+    /**
+     * 字句解析対象の文字列全体を返す
+     */
+    pub fn input(&self) -> &'a str {
+        return self.input;
+    }
+
+    /**
+     * 直前に読み取れなかったトークンが BOM だったかどうか
+     */
+    pub fn is_bom(&self) -> bool {
+        return self.input[self.token_start..].starts_with(BOM);
+    }
+
+    /**
+     * 直前の next_token で入力の末尾に到達していたかどうか
+     * None が返された場合に、EOF と不正なトークンを区別するために使う
+     */
+    pub fn is_eof(&self) -> bool {
+        return self.token_start >= self.input.len();
+    }
+
     /**
      * 次の文字を読み込み、現在の位置を更新する
      */
     fn read_char(&mut self) {
+        // 読み終えた文字に応じて行番号と列番号を進める
+        match self.ch {
+            Some('\n') => {
+                self.line += 1;
+                self.column = 1;
+            }
+            Some(_) => self.column += 1,
+            None => {}
+        }
         // 既に末尾の場合、終了する
         if self.read_position >= self.input.len() {
             self.ch = None;
@@ -107,63 +316,131 @@ impl<'a> Lexer<'a> {
         self.read_position += self.ch.map_or(0, |c| c.len_utf8());
     }
 
+    /**
+     * 指定した位置 (バイト単位) の文字まで読み進める
+     */
+    fn seek(&mut self, position: usize) {
+        // 読み飛ばす範囲の改行と文字数から行番号と列番号を進める (後ろに戻る場合は先頭から数え直す)
+        let from = if position < self.position {
+            self.line = 1;
+            self.column = 1;
+            0
+        } else {
+            self.position
+        };
+        let skipped = &self.input.as_bytes()[from..position];
+        match memrchr(b'\n', skipped) {
+            Some(last) => {
+                self.line += memchr_iter(b'\n', skipped).count();
+                self.column = count_chars(&skipped[last + 1..]) + 1;
+            }
+            None => self.column += count_chars(skipped),
+        }
+        self.ch = None; // 読み飛ばした文字を read_char で二重に数えないようにする
+        self.read_position = position;
+        self.read_char();
+    }
+
     /**
      * 文字列リテラルを読み取る
      * `"` から `"` までの文字列を読み取る
+     * エスケープシーケンスを含まない場合は、コピーせずに入力の該当部分をそのまま返す
+     * 不正なエスケープシーケンスや制御文字があった場合も閉じる `"` まで読み進め、最初の誤りの範囲を記録して None を返す
+     * 閉じる `"` がないまま入力が終わった場合は、開始の `"` から末尾までを誤りの範囲とする
      */
-    fn read_string(&mut self) -> String {
+    fn read_string(&mut self) -> Option<Cow<'a, str>> {
+        let bytes = self.input.as_bytes();
+        let start = self.position + 1; // 先頭の `"` の次
+        let mut chunk_start = start;
         let mut result = String::new();
-        self.read_char(); // 現在地が先頭の `"` なので読み飛ばす
+        let mut invalid = None; // 最初の不正なエスケープシーケンスの原因と範囲
 
-        while let Some(ch) = self.ch {
+        // 次の `"` か `\` までは memchr でまとめて読み飛ばす
+        while let Some(offset) = memchr2(b'"', b'\\', &bytes[chunk_start..]) {
+            let end = chunk_start + offset;
+            if let Some(control) = find_control(&bytes[chunk_start..end]) {
+                let control = chunk_start + control;
+                invalid = invalid.or(Some((LexErrorKind::ControlCharacter, control, control + 1)));
+            }
             // 文字列の終端の場合そこで終了
-            if ch == '"' {
-                self.read_char();
-                break;
+            if bytes[end] == b'"' {
+                if let Some((kind, from, to)) = invalid {
+                    self.fail_at(kind, from, to, end + 1);
+                    return None;
+                }
+                self.seek(end + 1);
+                if chunk_start == start {
+                    return Some(Cow::Borrowed(&self.input[start..end]));
+                }
+                result.push_str(&self.input[chunk_start..end]);
+                return Some(Cow::Owned(result));
             }
-            // エスケープシーケンスの場合は処理
-            if ch == '\\' {
-                // 次の文字がシーケンスになるので、対応する文字コードに変換する
-                self.read_char();
-                if let Some(esc) = self.ch {
-                    match esc {
-                        '"' => result.push('"'),
-                        '\\' => result.push('\\'),
-                        '/' => result.push('/'),
-                        'b' => result.push('\x08'), // Backspace
-                        'f' => result.push('\x0C'), // Form feed
-                        'n' => result.push('\n'),   // Line feed
-                        'r' => result.push('\r'),   // Carriage return
-                        't' => result.push('\t'),   // Horizontal tab
-                        'u' => {
-                            // Unicode エスケープシーケンスの場合
-                            // 今回は簡易的に4文字読み飛ばすだけにする
-                            for _ in 0..4 {
-                                self.read_char();
-                            }
-                        }
-                        _ => {} // 未知のエスケープシーケンスは無視する
-                    }
+
+            // エスケープシーケンスの手前までを追加し、エスケープシーケンスを対応する文字に変換する
+            result.push_str(&self.input[chunk_start..end]);
+            let (decoded, len) = decode_escape(&self.input[end..]);
+            match decoded {
+                Ok(ch) => result.push(ch),
+                Err(kind) => {
+                    invalid = invalid.or(Some((kind, end, end + len)));
                 }
-            } else {
-                // 通常の文字の場合はそのまま追加
-                result.push(ch);
             }
-            self.read_char(); // 次の文字へ
+            chunk_start = end + len;
         }
-        return result;
+
+        // 閉じる `"` がないまま入力が終わった
+        self.fail_at(
+            LexErrorKind::UnterminatedString,
+            start - 1,
+            self.input.len(),
+            self.input.len(),
+        );
+        return None;
+    }
+
+    /**
+     * 入力中の from から to までを原因とする字句解析エラーを記録し、resume の位置まで読み進める
+     */
+    fn fail_at(&mut self, kind: LexErrorKind, from: usize, to: usize, resume: usize) {
+        self.seek(from);
+        let span = Span {
+            start: from,
+            end: to,
+            line: self.line,
+            column: self.column,
+        };
+        self.error = Some(LexError { kind, span });
+        self.seek(resume);
+    }
+
+    /**
+     * 現在の文字の次の文字が数字かどうか
+     */
+    fn next_is_digit(&self) -> bool {
+        return self
+            .input
+            .as_bytes()
+            .get(self.read_position)
+            .is_some_and(u8::is_ascii_digit);
     }
 
     /**
      * 数値リテラルを読み取る
      * 数値または "-" から始まる数値文字列を読み取る
+     * 桁区切りを読み飛ばす設定の場合、数字に挟まれた _ は取り除く
      */
     fn read_number(&mut self) -> String {
         let mut result = String::new();
         while let Some(ch) = self.ch {
-            if ch.is_digit(10) || ch == '.' || ch == '-' || ch == '+' || ch == 'e' || ch == 'E' {
+            if ch.is_ascii_digit() || ch == '.' || ch == '-' || ch == '+' || ch == 'e' || ch == 'E' {
                 result.push(ch);
                 self.read_char();
+            } else if ch == '_'
+                && self.separators
+                && result.ends_with(|c: char| c.is_ascii_digit())
+                && self.next_is_digit()
+            {
+                self.read_char();
             } else {
                 break;
             }
@@ -171,10 +448,24 @@ impl<'a> Lexer<'a> {
         return result;
     }
 
+    /**
+     * 指定した位置 (バイト単位) から識別子 (英数字、_、$ の並び) を読み取り、その直後まで読み進める
+     * クォートのないキーから回復する際に使う。識別子がない場合は何もせずに None を返す
+     */
+    pub(crate) fn read_identifier(&mut self, start: usize) -> Option<&'a str> {
+        let rest = &self.input[start..];
+        let len = identifier_len(rest);
+        if len == 0 {
+            return None;
+        }
+        self.seek(start + len);
+        return Some(&rest[..len]);
+    }
+
     /**
      * リテラル (true, false, null) を読み取る
      */
-    fn read_literal(&mut self) -> Option<Token> {
+    fn read_literal(&mut self) -> Option<Token<'a>> {
         let mut string = String::new();
         while let Some(ch) = self.ch {
             if ch.is_alphabetic() {
@@ -188,24 +479,207 @@ impl<'a> Lexer<'a> {
             "true" => Some(Token::True),
             "false" => Some(Token::False),
             "null" => Some(Token::Null),
+            "NaN" if self.non_finite => Some(Token::Number(Number::Float(f64::NAN))),
+            "Infinity" if self.non_finite => Some(Token::Number(Number::Float(f64::INFINITY))),
             _ => None, // 未知のリテラルは無視する
         };
     }
 
+    /**
+     * コメントを読み取る
+     * // の場合は行末 (改行の直前) まで、ブロックコメントの場合はそれを閉じる記号までをコメントとする
+     * ブロックコメントが閉じないまま入力が終わった場合や、/ の後がどちらでもない場合は None を返す
+     */
+    fn read_comment(&mut self) -> Option<Token<'a>> {
+        let start = self.position;
+        let rest = &self.input[start..];
+        let end = if rest.starts_with("//") {
+            start + rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            match body.find("*/") {
+                Some(offset) => start + 2 + offset + 2,
+                None => {
+                    self.seek(self.input.len());
+                    return None;
+                }
+            }
+        } else {
+            self.read_char();
+            return None;
+        };
+        self.seek(end);
+        return Some(Token::Comment(self.input[start..end].trim_end_matches('\r')));
+    }
+
     /**
      * ホワイトスペースの間は読み飛ばす
      */
     fn skip_whitespace(&mut self) {
-        while let Some(ch) = self.ch {
-            if ch.is_whitespace() {
-                self.read_char();
-            } else {
-                break;
+        loop {
+            // ASCII の空白はバイト単位でまとめて読み飛ばす
+            let rest = &self.input.as_bytes()[self.position..];
+            let skipped = rest
+                .iter()
+                .position(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                .unwrap_or(rest.len());
+            if skipped > 0 {
+                self.seek(self.position + skipped);
+            }
+            // それ以外の空白文字は1文字ずつ読み飛ばす
+            match self.ch {
+                Some(ch) if ch.is_whitespace() => self.read_char(),
+                _ => break,
             }
         }
     }
 }
 
+/**
+ * 入力の先頭から順にトークンを範囲付きで返す
+ * 読み取れない文字があった場合はエラーを返し、その文字を読み飛ばして続ける (入力の終わりで None を返す)
+ */
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Spanned<Token<'a>>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(spanned) = self.next_spanned() {
+            return Some(Ok(spanned));
+        }
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        if self.is_eof() {
+            return None;
+        }
+        let kind = if self.is_bom() {
+            LexErrorKind::UnexpectedBom
+        } else {
+            LexErrorKind::InvalidToken
+        };
+        return Some(Err(LexError {
+            kind,
+            span: self.token_span(),
+        }));
+    }
+}
+
+/**
+ * 文字列の先頭の \\ から始まるエスケープシーケンスを文字に変換し、変換結果と読み取ったバイト数を返す
+ * \\u は16進数字4桁で、上位サロゲートの直後に下位サロゲートの \\u が続く場合は2つを合わせて1文字にする
+ * 不正な場合は、その原因と、不正と判断できた位置までのバイト数を返す
+ */
+fn decode_escape(input: &str) -> (Result<char, LexErrorKind>, usize) {
+    let Some(esc) = input[1..].chars().next() else {
+        return (Err(LexErrorKind::InvalidEscape), 1);
+    };
+    let ch = match esc {
+        '"' => '"',
+        '\\' => '\\',
+        '/' => '/',
+        'b' => '\x08', // Backspace
+        'f' => '\x0C', // Form feed
+        'n' => '\n',   // Line feed
+        'r' => '\r',   // Carriage return
+        't' => '\t',   // Horizontal tab
+        'u' => return decode_unicode_escape(input),
+        _ => return (Err(LexErrorKind::InvalidEscape), 1 + esc.len_utf8()),
+    };
+    return (Ok(ch), 2);
+}
+
+/**
+ * 文字列の先頭の \\u エスケープ (サロゲートペアの場合は2つ続きのもの) を文字に変換する
+ */
+fn decode_unicode_escape(input: &str) -> (Result<char, LexErrorKind>, usize) {
+    let Some(code) = hex4(&input[2..]) else {
+        let digits = input[2..].bytes().take(4).take_while(u8::is_ascii_hexdigit).count();
+        return (Err(LexErrorKind::InvalidEscape), 2 + digits);
+    };
+    match code {
+        0xD800..=0xDBFF => {
+            let low = input[6..].strip_prefix("\\u").and_then(hex4);
+            return match low {
+                Some(low @ 0xDC00..=0xDFFF) => {
+                    let code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    (char::from_u32(code).ok_or(LexErrorKind::LoneSurrogate), 12)
+                }
+                _ => (Err(LexErrorKind::LoneSurrogate), 6),
+            };
+        }
+        0xDC00..=0xDFFF => return (Err(LexErrorKind::LoneSurrogate), 6),
+        _ => return (char::from_u32(code).ok_or(LexErrorKind::InvalidEscape), 6),
+    }
+}
+
+/**
+ * 文字列の先頭の16進数字4桁を数値に変換する (4桁ない場合は None)
+ */
+fn hex4(input: &str) -> Option<u32> {
+    let digits = input.get(..4)?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    return u32::from_str_radix(digits, 16).ok();
+}
+
+/**
+ * バイト列の中で最初の制御文字 (U+0000 から U+001F) の位置を返す
+ */
+fn find_control(bytes: &[u8]) -> Option<usize> {
+    return bytes.iter().position(|&b| b < 0x20);
+}
+
+/**
+ * 文字列が JSON の数値の文法 (-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?) に合うかどうか
+ */
+fn is_json_number(number: &str) -> bool {
+    let bytes = number.as_bytes();
+    let mut i = usize::from(bytes.first() == Some(&b'-'));
+    let digits = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let integer = digits(i);
+    if integer == 0 || (integer > 1 && bytes[i] == b'0') {
+        return false;
+    }
+    i += integer;
+    if bytes.get(i) == Some(&b'.') {
+        let fraction = digits(i + 1);
+        if fraction == 0 {
+            return false;
+        }
+        i += 1 + fraction;
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        let exponent = digits(i);
+        if exponent == 0 {
+            return false;
+        }
+        i += exponent;
+    }
+    return i == bytes.len();
+}
+
+/**
+ * UTF-8 のバイト列に含まれる文字数を数える (継続バイト以外を数える)
+ */
+fn count_chars(bytes: &[u8]) -> usize {
+    return bytes.iter().filter(|&&b| (b as i8) >= -0x40).count();
+}
+
+/**
+ * 文字列の先頭にある識別子 (英数字、_、$ の並び) のバイト数を返す
+ * クォートのないキーなど、JSON として不正だがよくある書き方を見分けるのに使う
+ */
+pub(crate) fn identifier_len(input: &str) -> usize {
+    return input
+        .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$'))
+        .unwrap_or(input.len());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,10 +706,10 @@ mod tests {
 
     #[test]
     fn test_next_token_string() {
-        let input = r#""Hello, World!"#;
+        let input = r#""Hello, World!""#;
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::String("Hello, World!".to_string())));
+        assert_eq!(lexer.next_token(), Some(Token::String("Hello, World!".into())));
         assert_eq!(lexer.next_token(), None);
     }
 
@@ -244,7 +718,7 @@ mod tests {
         let input = r#""Hello, \"World\"!""#;
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::String("Hello, \"World\"!".to_string())));
+        assert_eq!(lexer.next_token(), Some(Token::String("Hello, \"World\"!".into())));
         assert_eq!(lexer.next_token(), None);
     }
 
@@ -253,8 +727,101 @@ mod tests {
         let input = "\"\\b\\f\\n\\r\\t\"";
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::String("\x08\x0C\n\r\t".to_string())));
+        assert_eq!(lexer.next_token(), Some(Token::String("\x08\x0C\n\r\t".into())));
+        assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn test_next_token_string_unicode_escape() {
+        let input = r#""\u00e9" "\u0041BC" "\ud83d\ude00!" "\uD83D\uDE00""#;
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(lexer.next_token(), Some(Token::String("é".into())));
+        assert_eq!(lexer.next_token(), Some(Token::String("ABC".into())));
+        assert_eq!(lexer.next_token(), Some(Token::String("😀!".into())));
+        assert_eq!(lexer.next_token(), Some(Token::String("😀".into())));
+        assert_eq!(lexer.next_token(), None);
+        assert!(lexer.is_eof());
+    }
+
+    #[test]
+    fn test_next_token_string_invalid_escape() {
+        // 不正な箇所の範囲をエラーとし、閉じる `"` の後から読み進める
+        let input = r#"["a\xb", "\u12", "\ud83d", "\ude00x", "ok"]"#;
+        let results: Vec<_> = Lexer::new(input)
+            .map(|result| {
+                result
+                    .map(|spanned| spanned.value)
+                    .map_err(|error| (error.kind, error.span))
+            })
+            .collect();
+        let span = |start: usize, end: usize| Span {
+            start,
+            end,
+            line: 1,
+            column: start + 1,
+        };
+        assert_eq!(
+            results,
+            vec![
+                Ok(Token::LeftBracket),
+                Err((LexErrorKind::InvalidEscape, span(3, 5))),
+                Ok(Token::Comma),
+                Err((LexErrorKind::InvalidEscape, span(10, 14))),
+                Ok(Token::Comma),
+                Err((LexErrorKind::LoneSurrogate, span(18, 24))),
+                Ok(Token::Comma),
+                Err((LexErrorKind::LoneSurrogate, span(28, 34))),
+                Ok(Token::Comma),
+                Ok(Token::String("ok".into())),
+                Ok(Token::RightBracket),
+            ]
+        );
+
+        // 入力の末尾で途切れた \u は、閉じられていない文字列とする
+        let error = Lexer::new(r#""\u12"#).find_map(Result::err).unwrap();
+        assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+        assert_eq!((error.span.start, error.span.end), (0, 5));
+    }
+
+    #[test]
+    fn test_next_token_string_chunks() {
+        let input = "\"日本語\\n と \\\"引用\\\" の後\" \u{3000}\t: \"未終端";
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next_token(),
+            Some(Token::String("日本語\n と \"引用\" の後".into()))
+        );
+        assert_eq!(lexer.next_token(), Some(Token::Colon));
+        // 閉じられていない文字列は読み取れず、入力の末尾まで読み進める
+        assert_eq!(lexer.next_token(), None);
+        assert!(!lexer.is_eof());
         assert_eq!(lexer.next_token(), None);
+        assert!(lexer.is_eof());
+    }
+
+    #[test]
+    fn test_next_token_strict() {
+        let error = |input: &str| {
+            let error = Lexer::new(input).find_map(Result::err).unwrap();
+            return (error.kind, error.span.start, error.span.end);
+        };
+        assert_eq!(error(r#"["a"#), (LexErrorKind::UnterminatedString, 1, 3));
+        assert_eq!(error("[\"a\u{0}b\"]"), (LexErrorKind::ControlCharacter, 3, 4));
+        assert_eq!(error("\"a\tb\nc\""), (LexErrorKind::ControlCharacter, 2, 3));
+        assert_eq!(error("\"\\x\""), (LexErrorKind::InvalidEscape, 1, 3));
+        for number in ["01", "-01", "+1", "1.", "1.e5", "1e", "1e+", "-", "1-2", "1.2.3"] {
+            assert_eq!(
+                error(number),
+                (LexErrorKind::InvalidNumber, 0, number.len()),
+                "number: {}",
+                number
+            );
+        }
+        for number in ["0", "-0", "10", "0.5", "-1.25e-3", "1E+9", "1e09"] {
+            assert!(Lexer::new(number).all(|result| result.is_ok()), "number: {}", number);
+        }
     }
 
     #[test]
@@ -262,7 +829,7 @@ mod tests {
         let input = "12345";
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::Number(12345.0)));
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Int(12345))));
         assert_eq!(lexer.next_token(), None);
     }
 
@@ -271,7 +838,7 @@ mod tests {
         let input = "123.45";
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::Number(123.45)));
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Float(123.45))));
         assert_eq!(lexer.next_token(), None);
     }
 
@@ -280,16 +847,16 @@ mod tests {
         let input = "-123.45";
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::Number(-123.45)));
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Float(-123.45))));
         assert_eq!(lexer.next_token(), None);
     }
 
     #[test]
     fn test_next_token_number4() {
-        let input = "+123.45e6";
+        let input = "-123.45E+6";
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::Number(123450000.0)));
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Float(-123450000.0))));
         assert_eq!(lexer.next_token(), None);
     }
 
@@ -298,10 +865,58 @@ mod tests {
         let input = "-123.45E-3";
         let mut lexer = Lexer::new(input);
 
-        assert_eq!(lexer.next_token(), Some(Token::Number(-0.12345)));
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Float(-0.12345))));
+        assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn test_next_token_number_large_integer() {
+        let input = "[12345678901234567890, -9007199254740993]";
+        let mut lexer = Lexer::new(input);
+
+        lexer.next_token();
+        assert_eq!(
+            lexer.next_token(),
+            Some(Token::Number(Number::UInt(12345678901234567890)))
+        );
+        lexer.next_token();
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Int(-9007199254740993))));
+    }
+
+    #[test]
+    fn test_next_token_non_finite() {
+        let input = "[NaN, Infinity, -Infinity]";
+        let tokens: Vec<_> = Lexer::new(input)
+            .with_non_finite()
+            .filter_map(|result| result.ok())
+            .map(|spanned| spanned.value)
+            .collect();
+        assert!(matches!(tokens[1], Token::Number(Number::Float(num)) if num.is_nan()));
+        assert_eq!(tokens[3], Token::Number(Number::Float(f64::INFINITY)));
+        assert_eq!(tokens[5], Token::Number(Number::Float(f64::NEG_INFINITY)));
+
+        // 指定しない場合は不正なトークン
+        let mut lexer = Lexer::new("NaN");
         assert_eq!(lexer.next_token(), None);
     }
 
+    #[test]
+    fn test_next_token_numeric_separators() {
+        let mut lexer = Lexer::new("[1_000_000, 0.000_1e1_0]").with_numeric_separators();
+        lexer.next_token();
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Int(1000000))));
+        lexer.next_token();
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Float(0.0001e10))));
+
+        // 数字に挟まれていない _ は区切りとみなさない
+        for input in ["1__0", "1_", "1_.5", "-_1"] {
+            let tokens: Vec<_> = Lexer::new(input).with_numeric_separators().collect();
+            assert!(tokens.iter().any(Result::is_err), "{}", input);
+        }
+        // 指定しない場合は不正なトークン
+        assert!(Lexer::new("1_000").any(|result| result.is_err()));
+    }
+
     #[test]
     fn test_next_token_literal_true() {
         let input = "true";
@@ -320,6 +935,24 @@ mod tests {
         assert_eq!(lexer.next_token(), None);
     }
 
+    #[test]
+    fn test_token_start_and_eof() {
+        let input = r#"  [ 10, tru ]"#;
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(lexer.next_token(), Some(Token::LeftBracket));
+        assert_eq!(lexer.token_start(), 2);
+        assert_eq!(lexer.next_token(), Some(Token::Number(Number::Int(10))));
+        assert_eq!(lexer.token_start(), 4);
+        lexer.next_token();
+        assert_eq!(lexer.next_token(), None);
+        assert_eq!(lexer.token_start(), 8);
+        assert!(!lexer.is_eof());
+        assert_eq!(lexer.next_token(), Some(Token::RightBracket));
+        assert_eq!(lexer.next_token(), None);
+        assert!(lexer.is_eof());
+    }
+
     #[test]
     fn test_next_token_literal_null() {
         let input = "null";
@@ -328,4 +961,94 @@ mod tests {
         assert_eq!(lexer.next_token(), Some(Token::Null));
         assert_eq!(lexer.next_token(), None);
     }
+
+    #[test]
+    fn test_next_spanned() {
+        let input = "{\n  \"キー\": [1.5,\r\n\t\"a\\nb\"], \"長い 値\": null\n}";
+        let mut lexer = Lexer::new(input);
+        let mut spans = Vec::new();
+        while let Some(spanned) = lexer.next_spanned() {
+            let span = spanned.span;
+            spans.push((&input[span.start..span.end], span.line, span.column));
+        }
+        assert_eq!(
+            spans,
+            vec![
+                ("{", 1, 1),
+                ("\"キー\"", 2, 3),
+                (":", 2, 7),
+                ("[", 2, 9),
+                ("1.5", 2, 10),
+                (",", 2, 13),
+                ("\"a\\nb\"", 3, 2),
+                ("]", 3, 8),
+                (",", 3, 9),
+                ("\"長い 値\"", 3, 11),
+                (":", 3, 17),
+                ("null", 3, 19),
+                ("}", 4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_span_with_bom_and_invalid_token() {
+        // 列番号は ParseError::line_column と同じく BOM も1文字として数える
+        let input = "\u{FEFF}[\n  tru]";
+        let mut lexer = Lexer::new(input);
+
+        let bracket = lexer.next_spanned().unwrap();
+        assert_eq!(bracket.value, Token::LeftBracket);
+        assert_eq!(
+            bracket.span,
+            Span {
+                start: 3,
+                end: 4,
+                line: 1,
+                column: 2
+            }
+        );
+
+        // 読み取れなかったトークンも、読み飛ばした範囲を返す
+        assert_eq!(lexer.next_spanned(), None);
+        assert_eq!(
+            lexer.token_span(),
+            Span {
+                start: 7,
+                end: 10,
+                line: 2,
+                column: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_iterator() {
+        let input = "[1, tru\u{FEFF}]";
+        let tokens: Vec<_> = Lexer::new(input)
+            .map(|result| result.map(|spanned| spanned.value).map_err(|error| error.kind))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Ok(Token::LeftBracket),
+                Ok(Token::Number(Number::Int(1))),
+                Ok(Token::Comma),
+                Err(LexErrorKind::InvalidToken),
+                Err(LexErrorKind::UnexpectedBom),
+                Ok(Token::RightBracket),
+            ]
+        );
+
+        // 標準のイテレータアダプタと組み合わせられる
+        let strings: Vec<_> = Lexer::new(r#"{"a": "b", "c": 1}"#)
+            .filter_map(Result::ok)
+            .filter(|spanned| matches!(spanned.value, Token::String(_)))
+            .map(|spanned| spanned.span.column)
+            .collect();
+        assert_eq!(strings, vec![2, 7, 12]);
+
+        let error = Lexer::new("[\n  ?]").find_map(Result::err).unwrap();
+        assert_eq!(error.to_string(), "不正なトークンです (2 行 3 列目)");
+    }
 }
@@ -0,0 +1,47 @@
+#![allow(clippy::needless_return)]
+
+pub mod access;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod borrowed;
+pub mod canonical;
+pub mod coerce;
+pub mod convert;
+pub mod delete;
+pub mod encoded;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gron;
+pub mod highlight;
+pub mod incremental;
+pub mod json;
+pub mod jsonc;
+pub mod lexer;
+pub mod lsp;
+pub mod nested;
+pub mod normalize;
+pub mod number;
+pub mod parser;
+pub mod pick;
+pub mod plist;
+pub mod pull;
+pub mod range;
+pub mod rename;
+pub mod semantic;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod set;
+pub mod sort;
+pub mod stream;
+pub mod substitute;
+pub mod summary;
+pub mod table;
+pub mod theme;
+pub mod timestamp;
+pub mod utf16;
+pub mod utf8;
+pub mod walk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod weight;
+pub mod xml;
@@ -0,0 +1,35 @@
+use rust_json_formatter::access::parse_pointer;
+use rust_json_formatter::incremental::parse_tree;
+use rust_json_formatter::parser::line_column;
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::error::{CliError, ExitCode};
+use crate::messages::t;
+use crate::{document, fail, open_stdout, parse_error, write_output};
+
+/**
+ * 入力を読み込み、JSON Pointer で指定した値が入力中のどこにあるかを出力する
+ * 出力は「開始行:開始列-終了行:終了列」と「開始位置..終了位置」(バイト単位) をタブで区切った1行で、終了位置の文字は含まない
+ */
+pub fn run(cli: &Cli, file: Option<&Path>, pointer: &str) {
+    let span = locate(cli, file, pointer).unwrap_or_else(|error| fail(cli, error));
+    write_output(open_stdout(cli), span.as_bytes()).unwrap_or_else(|error| fail(cli, error));
+}
+
+fn locate(cli: &Cli, file: Option<&Path>, pointer: &str) -> Result<String, CliError> {
+    if parse_pointer(pointer).is_none() {
+        return Err(CliError::usage(t!(InvalidPointer, pointer)));
+    }
+    let input = document::read(cli, file)?;
+    let root = parse_tree(&input, cli.limits()).map_err(|e| parse_error(cli, "JSON", &[e], &input))?;
+    let node = root
+        .pointer(pointer)
+        .ok_or_else(|| CliError::new(ExitCode::NotFound, t!(PointerNotFound, pointer)))?;
+    let (start_line, start_column) = line_column(&input, node.start);
+    let (end_line, end_column) = line_column(&input, node.end);
+    return Ok(format!(
+        "{}:{}-{}:{}\t{}..{}\n",
+        start_line, start_column, end_line, end_column, node.start, node.end
+    ));
+}
@@ -0,0 +1,88 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crate::cli::Cli;
+
+/**
+ * -v の回数に応じたログを標準エラー出力に表示するロガー
+ * 各行には起動からの経過時間、レベル、出力したモジュールを付ける。例:
+ *   [   0.004s DEBUG files] a.json: pattern matched 3 files
+ * ログは不具合の報告にそのまま貼り付けられるよう、--lang によらず英語で出力する
+ */
+struct StderrLogger {
+    start: Instant, // 起動した時刻
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        return metadata.level() <= log::max_level();
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // 依存クレート (notify や ureq など) のログは出力しない
+        let Some(module) = record.target().strip_prefix(env!("CARGO_CRATE_NAME")) else {
+            return;
+        };
+        let module = module.trim_start_matches("::");
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let _ = writeln!(
+            io::stderr().lock(),
+            "[{:>9.3}s {:<5} {}] {}",
+            elapsed,
+            record.level(),
+            if module.is_empty() { "main" } else { module },
+            record.args()
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+/**
+ * -v の回数から表示するログのレベルを決定してロガーを登録する
+ * 何も指定しない場合や --quiet の場合はログを表示せず、パイプに流す出力を汚さない
+ *   -v: 処理時間や入力ファイルの件数などの概要 (info)
+ *   -vv: ファイルの探索や設定ファイル、誤りからの回復などの判断 (debug)
+ *   -vvv: 除外したパスなどの細かな経過 (trace)
+ */
+pub fn init(cli: &Cli) {
+    let level = level_filter(cli);
+    let logger = StderrLogger { start: Instant::now() };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/**
+ * -v の回数と --quiet から表示するログのレベルを返す
+ */
+fn level_filter(cli: &Cli) -> LevelFilter {
+    return match cli.verbose {
+        _ if cli.quiet => LevelFilter::Off,
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_level_filter() {
+        let level =
+            |args: &[&str]| level_filter(&Cli::try_parse_from([&["rust_json_formatter"], args].concat()).unwrap());
+        assert_eq!(level(&[]), LevelFilter::Off);
+        assert_eq!(level(&["-v"]), LevelFilter::Info);
+        assert_eq!(level(&["-vv"]), LevelFilter::Debug);
+        assert_eq!(level(&["-vvvv"]), LevelFilter::Trace);
+        assert_eq!(level(&["-q", "-vv"]), LevelFilter::Off);
+    }
+}
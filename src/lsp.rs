@@ -0,0 +1,600 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::json::{FormatOptions, FormatTree, JsonValue};
+use crate::lexer::Lexer;
+use crate::parser::{DuplicateKey, ParseError, ParseErrorKind, Parser, SuggestionKind};
+use crate::range::format_range;
+
+/**
+ * JSON-RPC のエラーコード
+ */
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/**
+ * 診断やエラー応答としてクライアントに送るメッセージ
+ * 既定の実装はライブラリの日本語のメッセージを返すので、利用側で必要なメソッドを実装して翻訳する
+ */
+pub trait Messages {
+    /**
+     * パースエラーの原因
+     */
+    fn parse_error(&self, kind: &ParseErrorKind) -> String {
+        return kind.message().to_string();
+    }
+
+    /**
+     * よくある誤りの修正方法
+     */
+    fn suggestion(&self, kind: &SuggestionKind) -> String {
+        return kind.message();
+    }
+
+    /**
+     * オブジェクトのキーの重複
+     */
+    fn duplicate_key(&self, duplicate: &DuplicateKey, input: &str) -> String {
+        return duplicate.message(input);
+    }
+
+    /**
+     * キーの重複の関連情報として、上書きされる最初のキーに添える説明
+     */
+    fn overwritten_key(&self) -> String {
+        return "上書きされる値のキー".to_string();
+    }
+
+    /**
+     * 未対応のメソッドを呼び出された場合のエラー
+     */
+    fn unknown_method(&self, method: &str) -> String {
+        return format!("未対応のメソッドです: {}", method);
+    }
+
+    /**
+     * JSON としてパースできないメッセージを受け取った場合のエラー
+     */
+    fn invalid_message(&self, error: &ParseError) -> String {
+        return error.to_string();
+    }
+}
+
+/**
+ * ライブラリの日本語のメッセージをそのまま使う Messages の実装
+ */
+pub struct DefaultMessages;
+
+impl Messages for DefaultMessages {}
+
+/**
+ * 標準入出力などを介して Language Server Protocol のメッセージをやり取りし、JSON の整形を提供する
+ * textDocument/formatting と textDocument/rangeFormatting に対応し、開いている文書のパースエラーを診断として通知する
+ * options は整形時の基本のオプションで、字下げはクライアントから指定された設定で上書きする
+ * 診断などのメッセージは messages から取得する
+ */
+pub fn run<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    options: &FormatOptions,
+    messages: &dyn Messages,
+) -> io::Result<()> {
+    let mut server = Server {
+        documents: HashMap::new(),
+        options,
+        messages,
+        exited: false,
+    };
+    while let Some(body) = read_message(&mut reader)? {
+        let outgoing = match body.parse::<JsonValue>() {
+            Ok(message) => server.handle(message),
+            Err(error) => vec![error_response(
+                JsonValue::Null,
+                PARSE_ERROR,
+                &messages.invalid_message(&error),
+            )],
+        };
+        for message in outgoing {
+            write_message(&mut writer, &message)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    return Ok(());
+}
+
+/**
+ * Content-Length ヘッダーに従って1つのメッセージの本文を読み取る
+ * 入力が終わっていれば None を返す
+ */
+fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let Some(length) = content_length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Content-Length ヘッダーがありません",
+        ));
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    return String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "メッセージが UTF-8 ではありません"));
+}
+
+fn write_message<W: Write>(writer: &mut W, message: &JsonValue) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    return writer.flush();
+}
+
+struct Server<'o> {
+    documents: HashMap<String, String>, // 開いている文書の URI と内容
+    options: &'o FormatOptions,         // 整形時の基本のオプション
+    messages: &'o dyn Messages,         // 診断などのメッセージ
+    exited: bool,                       // exit 通知を受け取ったかどうか
+}
+
+impl Server<'_> {
+    /**
+     * 受け取ったメッセージを処理し、送り返すメッセージ (応答や通知) を返す
+     */
+    fn handle(&mut self, mut message: JsonValue) -> Vec<JsonValue> {
+        let method = message["method"].as_str().unwrap_or_default().to_string();
+        let id = message.remove("id");
+        let params = message.remove("params").unwrap_or(JsonValue::Null);
+
+        let result = match method.as_str() {
+            "initialize" => Some(capabilities()),
+            "shutdown" => Some(JsonValue::Null),
+            "exit" => {
+                self.exited = true;
+                None
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let uri = document["uri"].as_str().unwrap_or_default().to_string();
+                let text = document["text"].as_str().unwrap_or_default().to_string();
+                return self.update_document(uri, text);
+            }
+            "textDocument/didChange" => {
+                // 同期方式は文書全体の送信 (Full) なので、最後の変更が文書全体の内容になる
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+                let changes = params["contentChanges"].as_array();
+                let Some(text) = changes.and_then(|changes| changes.last()?["text"].as_str()) else {
+                    return vec![];
+                };
+                return self.update_document(uri, text.to_string());
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+                self.documents.remove(&uri);
+                return vec![publish_diagnostics(&uri, JsonValue::Array(vec![]))];
+            }
+            "textDocument/formatting" => Some(self.format_document(&params)),
+            "textDocument/rangeFormatting" => Some(self.format_range(&params)),
+            _ => None,
+        };
+
+        // 通知 (id のないメッセージ) には応答しない
+        let Some(id) = id else {
+            return vec![];
+        };
+        return match result {
+            Some(result) => {
+                let mut response = JsonValue::Null;
+                response.insert("jsonrpc", "2.0");
+                response.insert("id", id);
+                response.insert("result", result);
+                vec![response]
+            }
+            None => vec![error_response(
+                id,
+                METHOD_NOT_FOUND,
+                &self.messages.unknown_method(&method),
+            )],
+        };
+    }
+
+    /**
+     * 文書の内容を更新し、パース結果の診断を通知する
+     */
+    fn update_document(&mut self, uri: String, text: String) -> Vec<JsonValue> {
+        let mut diagnostics = Vec::new();
+        let mut parser = Parser::new(Lexer::new(&text));
+        for error in parser.collect_errors() {
+            let mut diagnostic = JsonValue::Null;
+            diagnostic.insert("range", range(&text, error.position, error.position));
+            diagnostic.insert("severity", 1_i64); // Error
+            diagnostic.insert("source", "rust_json_formatter");
+            let mut message = self.messages.parse_error(&error.kind);
+            if let Some(suggestion) = error.suggestion(&text) {
+                message.push('\n');
+                message.push_str(&self.messages.suggestion(&suggestion.kind));
+            }
+            diagnostic.insert("message", message);
+            diagnostics.push(diagnostic);
+        }
+        // 重複したキーは、上書きされる最初のキーの位置を関連情報として添えて警告する
+        for duplicate in parser.duplicate_keys() {
+            let mut location = JsonValue::Null;
+            location.insert("uri", uri.as_str());
+            location.insert("range", range(&text, duplicate.first, duplicate.first));
+            let mut related = JsonValue::Null;
+            related.insert("location", location);
+            related.insert("message", self.messages.overwritten_key());
+
+            let mut diagnostic = JsonValue::Null;
+            diagnostic.insert("range", range(&text, duplicate.second, duplicate.second));
+            diagnostic.insert("severity", 2_i64); // Warning
+            diagnostic.insert("source", "rust_json_formatter");
+            diagnostic.insert("message", self.messages.duplicate_key(duplicate, &text));
+            diagnostic.insert("relatedInformation", JsonValue::Array(vec![related]));
+            diagnostics.push(diagnostic);
+        }
+        let notification = publish_diagnostics(&uri, JsonValue::Array(diagnostics));
+        self.documents.insert(uri, text);
+        return vec![notification];
+    }
+
+    /**
+     * クライアントから指定された字下げの設定を反映したオプションを返す
+     */
+    fn options_for(&self, params: &JsonValue) -> FormatOptions {
+        let mut options = FormatOptions {
+            theme: None,
+            ..self.options.clone()
+        };
+        let formatting = &params["options"];
+        if let Some(tab_size) = formatting["tabSize"].as_f64() {
+            options.indent_unit = match formatting["insertSpaces"].as_bool() {
+                Some(false) => "\t".to_string(),
+                _ => " ".repeat(tab_size as usize),
+            };
+        }
+        return options;
+    }
+
+    /**
+     * 文書全体を整形する編集を返す (パースできない場合は null)
+     */
+    fn format_document(&self, params: &JsonValue) -> JsonValue {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(text) = self.documents.get(uri) else {
+            return JsonValue::Null;
+        };
+        let Ok(json) = text.parse::<JsonValue>() else {
+            return JsonValue::Null;
+        };
+        let formatted = json.format_with_options(&self.options_for(params));
+        if &formatted == text {
+            return JsonValue::Array(vec![]);
+        }
+        return JsonValue::Array(vec![text_edit(text, 0, text.len(), formatted)]);
+    }
+
+    /**
+     * 選択範囲を整形する編集を返す
+     * 範囲を囲む値 (範囲がオブジェクトや配列の括弧の内側にある場合は、範囲に掛かるメンバーや要素) を、文書中の深さに合わせて整形する
+     * 文書全体をパースできない場合は、範囲内の文字列 (前後の空白を除く) が1つの JSON 値としてパースできるときのみ、開始行の字下げに合わせて整形する
+     */
+    fn format_range(&self, params: &JsonValue) -> JsonValue {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(text) = self.documents.get(uri) else {
+            return JsonValue::Null;
+        };
+        let start = position_to_offset(text, &params["range"]["start"]);
+        let end = position_to_offset(text, &params["range"]["end"]).max(start);
+        let options = self.options_for(params);
+        if let Ok(edits) = format_range(text, start, end, &options) {
+            let edits = edits
+                .into_iter()
+                .map(|edit| text_edit(text, edit.start, edit.end, edit.text))
+                .collect();
+            return JsonValue::Array(edits);
+        }
+
+        // 文書全体をパースできない場合は、選択した範囲だけを1つの値として整形する
+        let selected = &text[start..end];
+        let value_start = start + (selected.len() - selected.trim_start().len());
+        let value_end = start + selected.trim_end().len();
+        if value_start >= value_end {
+            return JsonValue::Array(vec![]);
+        }
+        let Ok(json) = text[value_start..value_end].parse::<JsonValue>() else {
+            return JsonValue::Null;
+        };
+
+        // 値の開始行の字下げから、ネストの深さを求める
+        let line_start = text[..value_start].rfind('\n').map_or(0, |i| i + 1);
+        let line = &text[line_start..];
+        let leading = line.len() - line.trim_start_matches([' ', '\t']).len();
+        let unit_width = options.indent_unit.len().max(1);
+        let mut formatted = String::new();
+        json.format_value(leading / unit_width, &options, &mut formatted);
+        return JsonValue::Array(vec![text_edit(text, value_start, value_end, formatted)]);
+    }
+}
+
+/**
+ * initialize の応答で通知するサーバーの機能
+ */
+fn capabilities() -> JsonValue {
+    let mut sync = JsonValue::Null;
+    sync.insert("openClose", true);
+    sync.insert("change", 1_i64); // Full
+    let mut capabilities = JsonValue::Null;
+    capabilities.insert("textDocumentSync", sync);
+    capabilities.insert("documentFormattingProvider", true);
+    capabilities.insert("documentRangeFormattingProvider", true);
+    let mut server_info = JsonValue::Null;
+    server_info.insert("name", env!("CARGO_PKG_NAME"));
+    server_info.insert("version", env!("CARGO_PKG_VERSION"));
+    let mut result = JsonValue::Null;
+    result.insert("capabilities", capabilities);
+    result.insert("serverInfo", server_info);
+    return result;
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: JsonValue) -> JsonValue {
+    let mut params = JsonValue::Null;
+    params.insert("uri", uri);
+    params.insert("diagnostics", diagnostics);
+    let mut notification = JsonValue::Null;
+    notification.insert("jsonrpc", "2.0");
+    notification.insert("method", "textDocument/publishDiagnostics");
+    notification.insert("params", params);
+    return notification;
+}
+
+fn error_response(id: JsonValue, code: i64, message: &str) -> JsonValue {
+    let mut error = JsonValue::Null;
+    error.insert("code", code);
+    error.insert("message", message);
+    let mut response = JsonValue::Null;
+    response.insert("jsonrpc", "2.0");
+    response.insert("id", id);
+    response.insert("error", error);
+    return response;
+}
+
+fn text_edit(text: &str, start: usize, end: usize, new_text: String) -> JsonValue {
+    let mut edit = JsonValue::Null;
+    edit.insert("range", range(text, start, end));
+    edit.insert("newText", new_text);
+    return edit;
+}
+
+/**
+ * バイト単位の位置の範囲を LSP の Range に変換する
+ */
+fn range(text: &str, start: usize, end: usize) -> JsonValue {
+    let mut range = JsonValue::Null;
+    range.insert("start", offset_to_position(text, start));
+    range.insert("end", offset_to_position(text, end));
+    return range;
+}
+
+/**
+ * バイト単位の位置を LSP の Position (行と UTF-16 での列) に変換する
+ */
+fn offset_to_position(text: &str, offset: usize) -> JsonValue {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let mut position = JsonValue::Null;
+    position.insert("line", before.matches('\n').count() as i64);
+    position.insert("character", before[line_start..].encode_utf16().count() as i64);
+    return position;
+}
+
+/**
+ * LSP の Position (行と UTF-16 での列) をバイト単位の位置に変換する
+ * 行や列が範囲外の場合は、行末や文書の末尾に丸める
+ */
+fn position_to_offset(text: &str, position: &JsonValue) -> usize {
+    let line = position["line"].as_f64().unwrap_or(0.0) as usize;
+    let character = position["character"].as_f64().unwrap_or(0.0) as usize;
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, ch) in text[line_start..].char_indices() {
+        if units >= character || ch == '\n' {
+            return line_start + i;
+        }
+        units += ch.len_utf16();
+    }
+    return text.len();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(body: &str) -> String {
+        return format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+    }
+
+    /**
+     * メッセージを順に送り、サーバーが送り返したメッセージを返す
+     */
+    fn exchange(messages: &[&str]) -> Vec<JsonValue> {
+        return exchange_with(&DefaultMessages, messages);
+    }
+
+    /**
+     * 診断などのメッセージを translations から取得して、メッセージを順に送る
+     */
+    fn exchange_with(translations: &dyn Messages, messages: &[&str]) -> Vec<JsonValue> {
+        let input: String = messages.iter().map(|message| frame(message)).collect();
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output, &FormatOptions::default(), translations).unwrap();
+
+        let mut reader = output.as_slice();
+        let mut responses = Vec::new();
+        while let Some(body) = read_message(&mut reader).unwrap() {
+            responses.push(body.parse::<JsonValue>().unwrap());
+        }
+        return responses;
+    }
+
+    #[test]
+    fn test_initialize_and_shutdown() {
+        let responses = exchange(&[
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#,
+            r#"{"jsonrpc": "2.0", "method": "initialized", "params": {}}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "unknown"}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "shutdown"}"#,
+            r#"{"jsonrpc": "2.0", "method": "exit"}"#,
+        ]);
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], JsonValue::from(1_i64));
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["documentFormattingProvider"],
+            JsonValue::Bool(true)
+        );
+        assert_eq!(responses[1]["error"]["code"], JsonValue::from(METHOD_NOT_FOUND));
+        assert_eq!(responses[2]["result"], JsonValue::Null);
+    }
+
+    #[test]
+    fn test_formatting() {
+        let responses = exchange(&[
+            r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.json", "text": "{\"a\":[1]}"}}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "textDocument/formatting", "params": {"textDocument": {"uri": "file:///a.json"}, "options": {"tabSize": 4, "insertSpaces": true}}}"#,
+        ]);
+
+        assert_eq!(responses[0]["params"]["diagnostics"], JsonValue::Array(vec![]));
+        let edit = &responses[1]["result"][0];
+        assert_eq!(edit["newText"].as_str(), Some("{\n    \"a\": [\n        1\n    ]\n}"));
+        assert_eq!(edit["range"]["end"]["character"], JsonValue::from(9_i64));
+    }
+
+    #[test]
+    fn test_range_formatting() {
+        let text = "{\n  \"a\": {\"b\":1},\n  \"c\": 2\n}";
+        let open = format!(
+            r#"{{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {{"textDocument": {{"uri": "file:///a.json", "text": {}}}}}}}"#,
+            JsonValue::from(text)
+        );
+        let responses = exchange(&[
+            &open,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "textDocument/rangeFormatting", "params": {"textDocument": {"uri": "file:///a.json"}, "range": {"start": {"line": 1, "character": 7}, "end": {"line": 1, "character": 14}}, "options": {"tabSize": 2, "insertSpaces": true}}}"#,
+        ]);
+
+        let edit = &responses[1]["result"][0];
+        assert_eq!(edit["newText"].as_str(), Some("{\n    \"b\": 1\n  }"));
+        assert_eq!(edit["range"]["start"]["line"], JsonValue::from(1_i64));
+        assert_eq!(edit["range"]["start"]["character"], JsonValue::from(7_i64));
+    }
+
+    #[test]
+    fn test_custom_messages() {
+        struct English;
+        impl Messages for English {
+            fn parse_error(&self, _: &ParseErrorKind) -> String {
+                return "parse error".to_string();
+            }
+            fn unknown_method(&self, method: &str) -> String {
+                return format!("unsupported method: {}", method);
+            }
+        }
+        let responses = exchange_with(
+            &English,
+            &[
+                r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.json", "text": "[1,]"}}}"#,
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "unknown"}"#,
+            ],
+        );
+
+        let diagnostic = &responses[0]["params"]["diagnostics"][0];
+        assert_eq!(
+            diagnostic["message"].as_str(),
+            Some("parse error\n閉じ括弧の直前の `,` を取り除いてください")
+        );
+        assert_eq!(
+            responses[1]["error"]["message"].as_str(),
+            Some("unsupported method: unknown")
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let responses = exchange(&[
+            r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.json", "text": "{\n  \"あ\": 1,\n  \"b\" 2\n}"}}}"#,
+        ]);
+
+        let diagnostic = &responses[0]["params"]["diagnostics"][0];
+        assert_eq!(diagnostic["range"]["start"]["line"], JsonValue::from(2_i64));
+        assert_eq!(diagnostic["range"]["start"]["character"], JsonValue::from(6_i64));
+        assert_eq!(diagnostic["message"].as_str(), Some("キーの後には `:` が必要です"));
+    }
+
+    #[test]
+    fn test_multiple_diagnostics() {
+        // 最初の誤りの後も読み進め、誤りごとに診断を返す
+        let responses = exchange(&[
+            r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.json", "text": "[\n  1 2,\n  {\"a\": },\n  3\n]"}}}"#,
+        ]);
+
+        let diagnostics = &responses[0]["params"]["diagnostics"];
+        let lines: Vec<_> = (0..2)
+            .map(|i| diagnostics[i]["range"]["start"]["line"].as_f64())
+            .collect();
+        assert_eq!(lines, vec![Some(1.0), Some(2.0)]);
+        assert_eq!(diagnostics[2], JsonValue::Null);
+    }
+
+    #[test]
+    fn test_duplicate_key_diagnostics() {
+        let responses = exchange(&[
+            r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.json", "text": "{\n  \"a\": 1,\n  \"a\": 2\n}"}}}"#,
+        ]);
+
+        let diagnostic = &responses[0]["params"]["diagnostics"][0];
+        assert_eq!(diagnostic["severity"], JsonValue::from(2_i64));
+        assert_eq!(diagnostic["range"]["start"]["line"], JsonValue::from(2_i64));
+        assert_eq!(
+            diagnostic["message"].as_str(),
+            Some("キー \"a\" が重複しています (2 行 3 列目の値は上書きされます)")
+        );
+        let related = &diagnostic["relatedInformation"][0]["location"];
+        assert_eq!(related["range"]["start"]["line"], JsonValue::from(1_i64));
+        assert_eq!(related["range"]["start"]["character"], JsonValue::from(2_i64));
+    }
+
+    #[test]
+    fn test_position_conversion() {
+        let text = "a😀b\ncd";
+        let position = offset_to_position(text, text.find('b').unwrap());
+        assert_eq!(position["character"], JsonValue::from(3_i64));
+        assert_eq!(position_to_offset(text, &position), text.find('b').unwrap());
+        assert_eq!(
+            position_to_offset(text, &offset_to_position(text, text.len())),
+            text.len()
+        );
+    }
+}
@@ -1,23 +1,939 @@
-mod json;
-mod lexer;
-mod parser;
+#![allow(clippy::needless_return)]
 
-use lexer::Lexer;
-use parser::Parser;
-use std::io::{self, Read};
+mod cli;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod compression;
+mod config;
+mod digest;
+mod document;
+mod error;
+mod files;
+#[cfg(feature = "http")]
+mod http;
+mod jwt;
+mod locate;
+mod logging;
+mod messages;
+mod pager;
+mod sarif;
+mod setop;
+mod validate;
+#[cfg(feature = "tui")]
+mod view;
+mod weigh;
+
+use clap::{CommandFactory, FromArgMatches};
+use cli::{
+    Cli, ClipboardMode, ColorChoice, Command, InputFormat, MissingEnv, NonFinitePolicy, OutputFormat, ReportFormat,
+    TimestampFormat,
+};
+use compression::Compression;
+use error::{CliError, Diagnostic, ExitCode};
+use messages::t;
+use rayon::prelude::*;
+use rust_json_formatter::gron::parse_gron;
+use rust_json_formatter::json::{FormatOptions, JsonValue};
+use rust_json_formatter::jsonc::{collect_jsonc_errors, format_jsonc};
+use rust_json_formatter::lexer::{Lexer, BOM};
+use rust_json_formatter::lsp;
+use rust_json_formatter::parser::{ParseError, Parser};
+use rust_json_formatter::range::{apply_edits, format_range};
+use rust_json_formatter::stream::{self, StreamError};
+use rust_json_formatter::table::TableError;
+use rust_json_formatter::theme::Theme;
+use rust_json_formatter::xml::parse_xml;
+use sarif::SarifReport;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Read, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/**
+ * 処理を行うスレッド (メインの処理と rayon のワーカー) のスタックの大きさ
+ * 整形や変換は入れ子の深さだけ再帰するため、--max-depth の上限 (MAX_DEPTH_CEILING) の入力でも足りる大きさにする
+ */
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/**
+ * JSON Lines モードで一度に並列処理する行数
+ */
+const NDJSON_BATCH_SIZE: usize = 4096;
+
+/**
+ * パースに失敗した場合に表示する誤りの最大件数 (誤りが連鎖して大量に見つかる場合に表示を打ち切る)
+ */
+const MAX_REPORTED_ERRORS: usize = 20;
 
 fn main() {
+    // 既定のスタック (メインスレッドは 8 MiB、rayon のワーカーは 2 MiB) では深い入力を処理しきれない
+    rayon::ThreadPoolBuilder::new()
+        .stack_size(STACK_SIZE)
+        .build_global()
+        .unwrap();
+    let worker = std::thread::Builder::new().stack_size(STACK_SIZE).spawn(run).unwrap();
+    if let Err(panic) = worker.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+/**
+ * コマンドライン引数に従って処理する (スタックを大きくしたスレッドで実行する)
+ */
+fn run() {
+    let mut cli = parse_args();
+
+    if let Some(Command::Completions { shell }) = cli.command {
+        clap_complete::generate(shell, &mut command(), env!("CARGO_BIN_NAME"), &mut io::stdout());
+        return;
+    }
+    if let Some(Command::Validate { files }) = &cli.command {
+        cli.files = files::expand_patterns(files).unwrap_or_else(|error| fail(&cli, error));
+        validate::run(&cli);
+        return;
+    }
+    if let Some(Command::View { file }) = &cli.command {
+        if let Err(error) = run_view(&cli, file.as_deref()) {
+            fail(&cli, error);
+        }
+        return;
+    }
+    if let Some(Command::Jwt) = &cli.command {
+        jwt::run(&cli);
+        return;
+    }
+    if let Some(Command::Weigh { file, path }) = &cli.command {
+        weigh::run(&cli, file.as_deref(), path);
+        return;
+    }
+    if let Some(Command::Digest { files, algo }) = &cli.command {
+        digest::run(&cli, files, *algo);
+        return;
+    }
+    if let Some(Command::Setop { operation, a, b }) = &cli.command {
+        setop::run(&cli, *operation, a, b);
+        return;
+    }
+    if let Some(Command::Locate { pointer, file }) = &cli.command {
+        locate::run(&cli, file.as_deref(), pointer);
+        return;
+    }
+    if let Some(Command::Summarize { file }) = &cli.command {
+        let value = document::load(&cli, file.as_deref()).unwrap_or_else(|error| fail(&cli, error));
+        write_output(open_stdout(&cli), value.summarize().as_bytes()).unwrap_or_else(|error| fail(&cli, error));
+        return;
+    }
+    if cli.report_format == ReportFormat::Sarif && !cli.check {
+        fail(&cli, CliError::usage(t!(SarifRequiresCheck)));
+    }
+    if let Some(Command::Lsp) = cli.command {
+        let options = FormatOptions {
+            theme: None,
+            ..format_options(&cli)
+        };
+        if let Err(error) = lsp::run(
+            io::stdin().lock(),
+            io::stdout().lock(),
+            &options,
+            &messages::LspMessages,
+        ) {
+            fail(&cli, CliError::io(t!(LspFailed, error)));
+        }
+        return;
+    }
+
+    if let Some(mode) = cli.clipboard {
+        // クリップボードに書き戻す場合は色を付けない
+        let options = FormatOptions {
+            theme: if mode == ClipboardMode::Write {
+                None
+            } else {
+                load_theme(&cli)
+            },
+            ..format_options(&cli)
+        };
+        if let Err(error) = run_clipboard(&cli, mode, &options) {
+            fail(&cli, error);
+        }
+        return;
+    }
+    if !cli.files.is_empty() {
+        cli.files = files::expand_patterns(&cli.files).unwrap_or_else(|error| fail(&cli, error));
+        // ファイルに書き込む場合は色を付けない
+        let options = FormatOptions {
+            theme: if cli.write { None } else { load_theme(&cli) },
+            ..format_options(&cli)
+        };
+        if cli.watch {
+            files::watch(&cli, &options);
+        } else {
+            files::format_files(&cli, &options);
+        }
+        return;
+    }
+    if cli.stream {
+        log::info!("reformatting standard input as a token stream");
+        let mut stdin = open_stdin(&cli);
+        let mut stdout = open_stdout(&cli);
+        copy_bom(&cli, &mut stdin, &mut stdout);
+        if let Err(error) = stream::reformat_sequence(stdin, stdout, &format_options(&cli), &cli.limits()) {
+            let error = match error {
+                // 読み手が先に終了して出力先のパイプが閉じられた場合は、他の出力と同じく静かに終了する
+                StreamError::Io(error) if error.kind() == io::ErrorKind::BrokenPipe => CliError::write(error),
+                StreamError::Io(error) => CliError::io(t!(StreamIoFailed, error)),
+                StreamError::Parse(error) => CliError::parse(t!(ParseFailed, "JSON", messages::parse_error(&error))),
+            };
+            fail(&cli, error);
+        }
+        return;
+    }
+    // --check の場合は入力全体と比べるため、行ごとに出力せずにまとめて整形する
+    if cli.from == InputFormat::Ndjson && !cli.check {
+        format_ndjson(&cli);
+        return;
+    }
+
     // 標準入力からJSON文字列を読み込む
     let mut input = String::new();
-    io::stdin()
-        .read_to_string(&mut input)
-        .expect("テキストの読み込みに失敗しました");
+    if let Err(error) = open_stdin(&cli).read_to_string(&mut input) {
+        fail(&cli, CliError::io(t!(ReadFailed, error)));
+    }
+
+    log::info!("read {} bytes from standard input", input.len());
+
+    // パース結果を標準出力 (--check の場合は入力と比べるだけで出力しない)
+    let mut warnings = Vec::new();
+    let start = Instant::now();
+    let result = format_input(&cli, cli.from, &format_options(&cli), &input, &mut warnings);
+    log::info!("processed standard input in {:.2?}", start.elapsed());
+    if cli.report_format == ReportFormat::Sarif {
+        let mut report = SarifReport::new();
+        match &result {
+            Ok(output) if *output != input => report.add_unformatted(None),
+            Ok(_) => {}
+            Err(error) => report.add_error(None, error),
+        }
+        report.print().unwrap_or_else(|error| fail(&cli, error));
+    }
+    let output = result.unwrap_or_else(|error| fail(&cli, error));
+    print_warnings(&cli, None, &warnings);
+    if cli.check {
+        if output != input {
+            fail(&cli, CliError::new(ExitCode::CheckFailed, t!(StdinUnformatted)));
+        }
+        return;
+    }
+    write_output(open_stdout(&cli), output.as_bytes()).unwrap_or_else(|error| fail(&cli, error));
+}
+
+/**
+ * 出力をすべて書き込み、書き込み先に書き出す
+ */
+fn write_output(mut writer: impl Write, output: &[u8]) -> Result<(), CliError> {
+    return writer
+        .write_all(output)
+        .and_then(|_| writer.flush())
+        .map_err(CliError::write);
+}
+
+/**
+ * エラーメッセージを表示し (--quiet の場合は表示しない)、エラーに応じた終了コードで終了する
+ */
+fn fail(cli: &Cli, error: CliError) -> ! {
+    if !cli.quiet && !error.message.is_empty() {
+        eprintln!("{}", error);
+    }
+    error.code.exit();
+}
+
+/**
+ * コマンドライン引数を解析する
+ * 設定ファイルがある場合はその内容をコマンドライン引数の前に置いて解析し直し、コマンドラインでの指定を優先する
+ */
+fn parse_args() -> Cli {
+    let cli = Cli::from_arg_matches(&command().get_matches()).unwrap_or_else(|error| error.exit());
+    let config = match cli.no_config {
+        true => None,
+        false => cli.config.clone().or_else(|| config::find_config(&cli)),
+    };
+    let Some(path) = config else {
+        init_output(&cli);
+        log::debug!("no config file found");
+        return cli;
+    };
+    let config_args = config::load_config(&path).unwrap_or_else(|message| {
+        init_output(&cli);
+        fail(&cli, CliError::usage(message).context(path.display()));
+    });
+    let mut args: Vec<OsString> = env::args_os().collect();
+    args.splice(1..1, config_args);
+    let matches = command().try_get_matches_from(args);
+    let cli = matches
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+        .unwrap_or_else(|error| {
+            init_output(&cli);
+            eprintln!("{}", t!(InvalidConfig, path.display()));
+            error.exit();
+        });
+    // 言語やログのレベルは設定ファイルでも指定できるため、設定ファイルを読み込んだ後で決定する
+    init_output(&cli);
+    log::debug!("loaded config file {}", path.display());
+    return cli;
+}
+
+/**
+ * 説明 (--help) をメッセージの言語に合わせたコマンドの定義を返す
+ */
+fn command() -> clap::Command {
+    let args: Vec<OsString> = env::args_os().collect();
+    return messages::localize_help(Cli::command(), messages::help_lang(&args));
+}
+
+/**
+ * コマンドライン引数に従って、メッセージの言語とログのレベルを決定する
+ */
+fn init_output(cli: &Cli) {
+    messages::init(cli.lang);
+    logging::init(cli);
+    log::debug!("message language: {:?}", messages::lang());
+}
+
+/**
+ * format の形式で入力をパースし、出力形式の指定に従って変換した文字列を返す
+ * パース時の警告 (--fix で直した誤りや重複したキー) は warnings に追加する
+ */
+fn format_input(
+    cli: &Cli,
+    format: InputFormat,
+    options: &FormatOptions,
+    input: &str,
+    warnings: &mut Vec<String>,
+) -> Result<String, CliError> {
+    // --range や --line-range の場合は範囲を囲む値だけを整形し、入力の残りの部分 (BOM を含む) はそのまま出力する
+    if let Some((start, end)) = cli.input_range(input)? {
+        let options = FormatOptions {
+            theme: None,
+            ..options.clone()
+        };
+        let edits = format_range(input, start, end, &options).map_err(|e| parse_error(cli, "JSON", &[e], input))?;
+        return Ok(apply_edits(input, &edits));
+    }
+
+    // 先頭の BOM はパース時に読み飛ばし、--keep-bom の場合は出力の先頭に付け直す
+    let mut output = String::new();
+    if cli.keep_bom && input.starts_with(BOM) {
+        output.push(BOM);
+    }
 
     // 字句解析+構文解析
-    let lexer = Lexer::new(&input);
-    let mut parser = Parser::new(lexer);
-    let json = parser.parse().expect("JSONのパースに失敗しました");
+    let mut json = match format {
+        InputFormat::Json => parse_json(cli, input, warnings).map_err(|e| parse_error(cli, "JSON", &e, input))?,
+        InputFormat::Jsonc => {
+            // コメントを残すため、値に変換せずにトークンの並びから直接整形する
+            let transforms = !cli.delete.is_empty()
+                || cli.substitute_env
+                || cli.parse_nested
+                || !cli.decode_base64.is_empty()
+                || cli.normalize_keys.is_some()
+                || cli.rename_mapping().is_some()
+                || !cli.pick.is_empty()
+                || !cli.coerce.is_empty()
+                || !cli.dedupe.is_empty()
+                || cli.timestamps.is_some()
+                || cli.sort_keys.is_some()
+                || !cli.key_order.is_empty()
+                || cli.key_order_schema.is_some()
+                || cli.key_order_example.is_some();
+            if cli.to != OutputFormat::Json
+                || cli.raw_output
+                || cli.canonical
+                || cli.collapse
+                || cli.max_display_depth.is_some()
+                || cli.max_array_items.is_some()
+                || cli.allow_non_finite
+                || cli.max_string_length.is_some()
+                || cli.max_elements.is_some()
+                || cli.max_nodes.is_some()
+                || cli.max_depth.is_some()
+                || transforms
+            {
+                return Err(CliError::usage(t!(JsoncConflicts)));
+            }
+            let formatted = format_jsonc(input, options)
+                .map_err(|_| parse_error(cli, "JSONC", &collect_jsonc_errors(input), input))?;
+            output.push_str(&formatted);
+            return Ok(output);
+        }
+        InputFormat::Gron => parse_gron_input(input)?,
+        InputFormat::Xml => parse_xml_input(input)?,
+        InputFormat::Ndjson => {
+            let options = FormatOptions {
+                trailing_newline: true,
+                ..options.clone()
+            };
+            for (i, line) in input.lines().enumerate() {
+                output.push_str(&format_ndjson_line(cli, &options, i + 1, line, warnings)?);
+            }
+            return Ok(output);
+        }
+    };
+    transform(cli, &mut json, warnings)?;
+    check_finite(cli, &json)?;
+    output.push_str(&render(cli, &json, options, input.len())?);
+    return Ok(output);
+}
+
+/**
+ * --non-finite error の場合、出力する値に有限でない数値 (NaN、Infinity、-Infinity) があればエラーを返す
+ */
+fn check_finite(cli: &Cli, json: &JsonValue) -> Result<(), CliError> {
+    if cli.non_finite != NonFinitePolicy::Error {
+        return Ok(());
+    }
+    return match json.find_non_finite() {
+        Some(path) => Err(CliError::parse(t!(NonFiniteNumber, messages::path_label(&path)))),
+        None => Ok(()),
+    };
+}
+
+/**
+ * --allow-non-finite の指定に従って、入力を字句解析する Lexer を生成する
+ */
+fn json_lexer<'a>(cli: &Cli, input: &'a str) -> Lexer<'a> {
+    let lexer = Lexer::new(input);
+    return if cli.allow_non_finite {
+        lexer.with_non_finite()
+    } else {
+        lexer
+    };
+}
+
+/**
+ * パースした値に、出力前の変換 (--delete、--substitute-env、--decode-base64、--parse-nested、--normalize-keys、--rename、--coerce、--timestamps、--dedupe、--pick、--sort-keys、--key-order-schema、--key-order-example、--key-order) を施す
+ * Base64 を復号した結果に埋め込まれた JSON も展開し、展開した値のキーの正規化や型の変換もできるよう、この順に行う
+ * 文字列の UNIX 時間 ("1700000000") も変換できるよう、--timestamps は --coerce の後に行う
+ * 変換時の警告 (正規化や名前の変更によるキーの重複、--pick で指定した値がないこと) は warnings に追加する
+ * --missing-env error で設定されていない環境変数があった場合はエラーを返す
+ */
+fn transform(cli: &Cli, json: &mut JsonValue, warnings: &mut Vec<String>) -> Result<(), CliError> {
+    if !cli.delete.is_empty() {
+        let deleted = json.delete_paths(&cli.delete);
+        log::debug!("deleted {} values", deleted.len());
+    }
+    if cli.substitute_env {
+        let substitution = json.substitute_vars(&|name| env::var(name).ok());
+        log::debug!(
+            "substituted environment variables in {} strings",
+            substitution.substituted.len()
+        );
+        for (path, name) in &substitution.missing {
+            match cli.missing_env {
+                MissingEnv::Error => return Err(CliError::usage(t!(EnvVarMissing, name, path))),
+                MissingEnv::Keep => warnings.push(t!(EnvVarMissing, name, path)),
+            }
+        }
+    }
+    if !cli.decode_base64.is_empty() {
+        let decoded = json.decode_base64_at(&cli.decode_base64);
+        log::debug!("decoded {} base64 strings", decoded.len());
+    }
+    if cli.parse_nested {
+        let decoded = json.parse_nested();
+        log::debug!("decoded {} embedded JSON strings", decoded.len());
+        if cli.restringify_nested {
+            json.stringify_nested(&decoded);
+        }
+    }
+    if let Some(form) = cli.normalization_form() {
+        let collisions = json.normalize_keys(form);
+        log::debug!(
+            "normalized object keys to {:?} with {} collisions",
+            form,
+            collisions.len()
+        );
+        warnings.extend(collisions.iter().map(|path| t!(KeyCollision, path)));
+    }
+    if let Some(mapping) = cli.rename_mapping() {
+        let collisions = json.rename_keys(&mapping);
+        log::debug!("renamed object keys with {} collisions", collisions.len());
+        warnings.extend(collisions.iter().map(|path| t!(RenameCollision, path)));
+    }
+    if !cli.coerce.is_empty() {
+        let coerced = json.coerce_scalars(&cli.coerce);
+        log::debug!("coerced {} string scalars", coerced.len());
+    }
+    if let Some(TimestampFormat::Iso8601) = cli.timestamps {
+        let converted = json.convert_timestamps(&cli.timestamp_key, &cli.timestamp_path);
+        log::debug!("converted {} timestamps", converted.len());
+    }
+    if !cli.dedupe.is_empty() {
+        let deduped = json.dedupe_arrays(&cli.dedupe, cli.dedupe_by.as_deref());
+        log::debug!("removed duplicate elements from {} arrays", deduped.len());
+    }
+    if !cli.pick.is_empty() {
+        for pointer in cli.pick.iter().filter(|pointer| json.pointer(pointer).is_none()) {
+            warnings.push(t!(PickNotFound, pointer));
+        }
+        *json = json.pick(&cli.pick, cli.pick_layout());
+    }
+    if let Some(order) = cli.key_order() {
+        json.sort_keys(order);
+    }
+    if let Some(schema) = &cli.key_order_schema {
+        json.order_keys_by_schema(schema);
+    }
+    if let Some(example) = &cli.key_order_example {
+        json.order_keys_by_example(example);
+    }
+    if !cli.key_order.is_empty() {
+        json.prioritize_keys(&cli.key_order);
+    }
+    return Ok(());
+}
+
+/**
+ * JSON をパースする
+ * --fix の場合はよくある誤りから回復しながらパースし、直した誤りを warnings に追加する
+ * オブジェクトのキーの重複は後の値で上書きされて黙って失われるため、常に warnings に追加する
+ * パースに失敗した場合は、一度で直せるよう最初の誤り以降も読み進めて見つかった誤りをすべて返す
+ */
+fn parse_json(cli: &Cli, input: &str, warnings: &mut Vec<String>) -> Result<JsonValue, Vec<ParseError>> {
+    let mut parser = Parser::new(json_lexer(cli, input)).with_limits(cli.limits());
+    let result = if cli.fix {
+        parser.parse_recovering().map(|(json, recovered)| {
+            if !recovered.is_empty() {
+                log::debug!("recovered from {} errors with --fix", recovered.len());
+            }
+            for warning in &recovered {
+                log::trace!("recovered from {:?} at byte {}", warning.kind, warning.position);
+            }
+            warnings.extend(recovered.iter().map(|warning| describe(warning, input)));
+            json
+        })
+    } else {
+        parser.parse()
+    };
+    let Ok(json) = result else {
+        // 最初の誤りで止まったパースをやり直し、誤りの後も読み進めてすべての誤りを集める
+        log::debug!("parse failed, reparsing to collect every error");
+        let mut parser = Parser::new(json_lexer(cli, input)).with_limits(cli.limits());
+        if cli.fix {
+            parser = parser.with_recovery();
+        }
+        let errors = parser.collect_errors();
+        log::debug!("found {} errors", errors.len());
+        return Err(errors);
+    };
+    let duplicates = parser.duplicate_keys();
+    if !duplicates.is_empty() {
+        log::debug!("found {} duplicate keys", duplicates.len());
+    }
+    warnings.extend(
+        duplicates
+            .iter()
+            .map(|duplicate| messages::duplicate_key(duplicate, input)),
+    );
+    return Ok(json);
+}
+
+/**
+ * gron 形式の入力をパースする
+ */
+fn parse_gron_input(input: &str) -> Result<JsonValue, CliError> {
+    return parse_gron(input.trim_start_matches(BOM)).ok_or_else(|| CliError::parse(t!(GronParseFailed)));
+}
+
+/**
+ * XML の入力をパースする
+ */
+fn parse_xml_input(input: &str) -> Result<JsonValue, CliError> {
+    let input = input.trim_start_matches(BOM);
+    return parse_xml(input).map_err(|error| {
+        let description = messages::with_source(input, error.position, &messages::xml_error_kind(&error.kind));
+        return CliError::parse(t!(ParseFailed, "XML", description));
+    });
+}
+
+/**
+ * パースエラーの原因に、入力の該当行とエラー箇所を指すキャレットを添えた複数行のメッセージを返す
+ */
+fn describe(error: &ParseError, input: &str) -> String {
+    return messages::with_source(input, error.position, &messages::parse_error_kind(&error.kind));
+}
+
+/**
+ * パースエラーを、入力の該当箇所と修正方法の提案を添えた CLI のエラーに変換する
+ * format には入力形式の名前 (JSON や JSONC) を、errors には見つかった誤り (1件以上) を指定する
+ */
+fn parse_error(cli: &Cli, format: &str, errors: &[ParseError], input: &str) -> CliError {
+    let describe_with_hint = |error: &ParseError| {
+        return format!("{}{}", describe(error, input), suggestion_message(cli, error, input));
+    };
+    let diagnostics = errors.iter().map(|error| diagnostic(cli, error, input)).collect();
+    if let [error] = errors {
+        return CliError::parse(t!(ParseFailed, format, describe_with_hint(error))).with_diagnostics(diagnostics);
+    }
+    let mut message = t!(ParseFailedMany, format, errors.len());
+    for error in errors.iter().take(MAX_REPORTED_ERRORS) {
+        message.push('\n');
+        message.push_str(&describe_with_hint(error));
+    }
+    if errors.len() > MAX_REPORTED_ERRORS {
+        message.push('\n');
+        message.push_str(&t!(ErrorsOmitted, errors.len() - MAX_REPORTED_ERRORS));
+    }
+    return CliError::parse(message).with_diagnostics(diagnostics);
+}
+
+/**
+ * パースエラーを、位置と修正方法の提案を添えた入力中の誤りの箇所に変換する
+ */
+fn diagnostic(cli: &Cli, error: &ParseError, input: &str) -> Diagnostic {
+    let (line, column) = error.line_column(input);
+    return Diagnostic {
+        message: format!(
+            "{}{}",
+            messages::parse_error_kind(&error.kind),
+            suggestion_message(cli, error, input)
+        ),
+        line,
+        column,
+    };
+}
+
+/**
+ * パースエラーの原因がよくある誤りであれば、修正方法の提案をエラーメッセージに添える文字列を返す
+ * --fix で直せる誤りの場合はそのことも案内する
+ */
+fn suggestion_message(cli: &Cli, error: &ParseError, input: &str) -> String {
+    let Some(suggestion) = error.suggestion(input) else {
+        return String::new();
+    };
+    let message = messages::suggestion(&suggestion.kind);
+    if suggestion.fixable && !cli.fix {
+        return format!("\n{}", t!(HintFixable, message));
+    }
+    return format!("\n{}", t!(Hint, message));
+}
+
+/**
+ * パース時の警告を標準エラー出力に表示する (--quiet の場合は何も表示しない)
+ * context にはどの入力についての警告か (ファイルのパスなど) を指定する
+ */
+fn print_warnings(cli: &Cli, context: Option<&dyn fmt::Display>, warnings: &[String]) {
+    if cli.quiet {
+        return;
+    }
+    for warning in warnings {
+        match context {
+            Some(context) => eprintln!("{}: {}", context, t!(Warning, warning)),
+            None => eprintln!("{}", t!(Warning, warning)),
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn run_view(cli: &Cli, file: Option<&Path>) -> Result<(), CliError> {
+    return view::run(cli, file);
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_view(_cli: &Cli, _file: Option<&Path>) -> Result<(), CliError> {
+    return Err(CliError::usage(t!(FeatureRequired, "view", "tui")));
+}
+
+#[cfg(feature = "clipboard")]
+fn run_clipboard(cli: &Cli, mode: ClipboardMode, options: &FormatOptions) -> Result<(), CliError> {
+    return clipboard::run(cli, mode, options);
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn run_clipboard(_cli: &Cli, _mode: ClipboardMode, _options: &FormatOptions) -> Result<(), CliError> {
+    return Err(CliError::usage(t!(FeatureRequired, "--clipboard", "clipboard")));
+}
+
+/**
+ * 標準入力を開く (gzip で圧縮されている場合は展開しながら読み込む)
+ */
+fn open_stdin(cli: &Cli) -> Box<dyn BufRead> {
+    let (reader, _) = compression::decompress(cli, io::stdin().lock(), None).unwrap_or_else(|e| {
+        fail(cli, CliError::io(t!(ReadFailed, e)));
+    });
+    return reader;
+}
+
+/**
+ * 標準出力を開く (--output-compression が指定されている場合は圧縮しながら書き込む)
+ * --write の場合は整形結果を標準出力に出力しないため圧縮しない。--quiet の場合は出力を捨てる
+ * 標準出力が端末の場合は、圧縮しなければページャーを通して表示する (--no-pager の場合を除く)
+ */
+fn open_stdout(cli: &Cli) -> Box<dyn Write> {
+    if cli.quiet {
+        return Box::new(io::sink());
+    }
+    let compression = match cli.output_compression {
+        Some(compression) if !cli.write => compression,
+        _ => Compression::None,
+    };
+    if compression == Compression::None {
+        if let Some(pager) = pager::spawn(cli) {
+            return Box::new(pager);
+        }
+    }
+    let stdout = BufWriter::new(io::stdout().lock());
+    return compression::compress(stdout, compression).unwrap_or_else(|e| {
+        fail(cli, CliError::io(t!(WriteFailedWith, e)));
+    });
+}
+
+/**
+ * --keep-bom が指定されていて標準入力が BOM で始まる場合は、出力の先頭に BOM を書き込む
+ * 入力側の BOM はパース時に読み飛ばされる
+ */
+fn copy_bom(cli: &Cli, stdin: &mut impl BufRead, stdout: &mut impl Write) {
+    let bom = BOM.to_string();
+    // 読み込みのエラーは続けて読み込む際に報告される
+    let has_bom = stdin.fill_buf().is_ok_and(|buf| buf.starts_with(bom.as_bytes()));
+    if cli.keep_bom && has_bom {
+        if let Err(error) = stdout.write_all(bom.as_bytes()) {
+            fail(cli, CliError::write(error));
+        }
+    }
+}
+
+/**
+ * コマンドライン引数から整形時のオプションを組み立てる
+ */
+fn format_options(cli: &Cli) -> FormatOptions {
+    return FormatOptions {
+        theme: load_theme(cli),
+        collapse: cli.collapse,
+        max_width: cli.max_width,
+        numeric_arrays: cli.numeric_array_layout(),
+        indent_unit: cli.indent_unit(),
+        trailing_newline: cli.trailing_newline(),
+        line_ending: cli.line_ending(),
+        compact: false,
+        escape: cli.escape_options(),
+        max_depth: cli.max_display_depth,
+        max_array_items: cli.max_array_items,
+        omission_marker: cli.omission_marker(),
+        exponent: cli.exponent,
+        precision: cli.number_precision,
+        non_finite: cli.non_finite_output(),
+    };
+}
+
+/**
+ * 出力形式の指定に従って値を出力用の文字列に変換する
+ * input_len は入力の長さで、出力用のバッファの容量の見積もりに使う
+ * XML の要素名や属性名にできないキーや、プロパティリストで表せない null がある場合、表に変換できない場合はエラーを返す
+ */
+fn render(cli: &Cli, json: &JsonValue, options: &FormatOptions, input_len: usize) -> Result<String, CliError> {
+    let mut output = match json {
+        // -r が指定されていて値が文字列の場合は、クォートやエスケープなしで中身をそのまま出力する
+        JsonValue::String(str) if cli.raw_output => str.clone(),
+        _ if cli.to == OutputFormat::Gron => return Ok(json.to_gron()),
+        _ if cli.to == OutputFormat::Xml => {
+            return json
+                .to_xml(&options.indent_unit)
+                .map_err(|error| CliError::usage(t!(XmlInvalidName, error.name, error.path)));
+        }
+        _ if cli.to == OutputFormat::Plist => {
+            return json
+                .to_plist(&options.indent_unit)
+                .map_err(|error| CliError::usage(t!(PlistNull, error.path)));
+        }
+        _ if cli.to == OutputFormat::Markdown => {
+            return json.to_markdown_table().map_err(|error| table_error("markdown", error));
+        }
+        _ if cli.to == OutputFormat::HtmlTable => {
+            return json
+                .to_html_table(&options.indent_unit)
+                .map_err(|error| table_error("html-table", error));
+        }
+        _ if cli.canonical => json
+            .format_canonical()
+            .map_err(|error| CliError::parse(t!(CanonicalNonFinite, messages::path_label(&error.path))))?,
+        _ => {
+            // 整形後の出力はおおむね入力と同程度の大きさになるため、入力の長さから容量を見積もっておく
+            let mut formatted = String::with_capacity(input_len + input_len / 2);
+            json.format_into(options, &mut formatted);
+            return Ok(formatted);
+        }
+    };
+    if options.trailing_newline {
+        output.push_str(options.line_ending.as_str());
+    }
+    return Ok(output);
+}
+
+/**
+ * 標準入力を1行に1つの JSON (JSON Lines) として読み込み、それぞれを整形して出力する
+ * 一定数の行ごとに複数のスレッドで並列に整形し、出力は入力と同じ順序に保つ
+ */
+fn format_ndjson(cli: &Cli) {
+    let options = FormatOptions {
+        trailing_newline: true, // 各値の出力は必ず改行で区切る
+        ..format_options(cli)
+    };
+    let mut stdin = open_stdin(cli);
+    let mut stdout = open_stdout(cli);
+    copy_bom(cli, &mut stdin, &mut stdout);
+    let mut lines = stdin.lines().enumerate();
+    loop {
+        let batch: Vec<(usize, String)> = lines
+            .by_ref()
+            .take(NDJSON_BATCH_SIZE)
+            .map(|(i, line)| {
+                let line = line.unwrap_or_else(|error| {
+                    fail(cli, CliError::io(t!(ReadFailed, error)));
+                });
+                return (i + 1, line);
+            })
+            .collect();
+        if batch.is_empty() {
+            break;
+        }
+        log::debug!(
+            "formatting lines {}-{} in parallel",
+            batch[0].0,
+            batch[batch.len() - 1].0
+        );
+
+        let outputs: Vec<(Result<String, CliError>, Vec<String>)> = batch
+            .par_iter()
+            .map(|(line_number, line)| {
+                let mut warnings = Vec::new();
+                let output = format_ndjson_line(cli, &options, *line_number, line, &mut warnings);
+                return (output, warnings);
+            })
+            .collect();
+
+        for (output, warnings) in outputs {
+            print_warnings(cli, None, &warnings);
+            match output {
+                Ok(output) => stdout
+                    .write_all(output.as_bytes())
+                    .unwrap_or_else(|error| fail(cli, CliError::write(error))),
+                Err(error) => {
+                    // エラーの行までの出力を済ませてから終了する (ページャーを使っている場合はその終了を待つ)
+                    stdout.flush().unwrap_or_else(|error| fail(cli, CliError::write(error)));
+                    drop(stdout);
+                    fail(cli, error);
+                }
+            }
+        }
+    }
+    stdout.flush().unwrap_or_else(|error| fail(cli, CliError::write(error)));
+}
+
+/**
+ * 表に変換できない値のエラーを、出力形式の名前 (markdown など) を添えた CLI のエラーに変換する
+ */
+fn table_error(format: &str, error: TableError) -> CliError {
+    return match error {
+        TableError::NotArray => CliError::usage(t!(TableNotArray, format)),
+        TableError::NotObject(index) => CliError::usage(t!(TableNotObject, format, index)),
+    };
+}
+
+/**
+ * JSON Lines の1行を整形する (空行は読み飛ばす)
+ */
+fn format_ndjson_line(
+    cli: &Cli,
+    options: &FormatOptions,
+    line_number: usize,
+    line: &str,
+    warnings: &mut Vec<String>,
+) -> Result<String, CliError> {
+    return match parse_ndjson_line(cli, line_number, line, warnings)? {
+        Some(mut json) => {
+            let mut transformed = Vec::new();
+            let result = transform(cli, &mut json, &mut transformed);
+            warnings.extend(
+                transformed
+                    .into_iter()
+                    .map(|warning| t!(LineContext, line_number, warning)),
+            );
+            result.map_err(|error| CliError::usage(t!(LineContext, line_number, error)))?;
+            check_finite(cli, &json).map_err(|error| CliError::parse(t!(LineContext, line_number, error)))?;
+            render(cli, &json, options, line.len())
+                .map_err(|error| CliError::usage(t!(LineContext, line_number, error)))
+        }
+        None => Ok(String::new()),
+    };
+}
+
+/**
+ * JSON Lines の1行をパースする (空行の場合は None を返す)
+ * パース時の警告は行番号を付けて warnings に追加する
+ */
+fn parse_ndjson_line(
+    cli: &Cli,
+    line_number: usize,
+    line: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Option<JsonValue>, CliError> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    // 先頭行の BOM はパース時に読み飛ばされる
+    let mut recovered = Vec::new();
+    let result = parse_json(cli, line, &mut recovered);
+    warnings.extend(
+        recovered
+            .into_iter()
+            .map(|warning| t!(LineContext, line_number, warning)),
+    );
+    return match result {
+        Ok(json) => Ok(Some(json)),
+        Err(errors) => {
+            // 行の中の位置を入力全体の行番号に読み替える
+            let diagnostics = errors
+                .iter()
+                .map(|error| Diagnostic {
+                    line: line_number,
+                    ..diagnostic(cli, error, line)
+                })
+                .collect();
+            let error = &errors[0];
+            let message = format!(
+                "{} {}{}",
+                messages::parse_error_kind(&error.kind),
+                t!(Position, error.position),
+                suggestion_message(cli, error, line)
+            );
+            Err(
+                CliError::parse(t!(LineContext, line_number, t!(ParseFailed, "JSON", message)))
+                    .with_diagnostics(diagnostics),
+            )
+        }
+    };
+}
 
-    // パース結果を標準出力
-    println!("{}", json.format(0));
+/**
+ * --color の指定と出力先に応じて、色付けに使うテーマを決定する
+ * auto の場合は標準出力が端末であり、NO_COLOR が設定されていない場合のみ色を付ける
+ */
+fn load_theme(cli: &Cli) -> Option<Theme> {
+    // --check では整形結果を入力と比べるため、色は付けない
+    if cli.check {
+        return None;
+    }
+    let enabled = match cli.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+    };
+    if !enabled {
+        return None;
+    }
+    return match Theme::load(&cli.theme) {
+        Ok(theme) => Some(theme),
+        Err(error) => fail(cli, CliError::usage(messages::theme_error(&error))),
+    };
 }
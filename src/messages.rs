@@ -0,0 +1,810 @@
+use clap::{Command, ValueEnum};
+use rust_json_formatter::json::JsonValue;
+use rust_json_formatter::lsp;
+use rust_json_formatter::parser::{
+    line_column, source_snippet, DuplicateKey, ParseError, ParseErrorKind, SuggestionKind,
+};
+use rust_json_formatter::theme::{ThemeError, PRESET_NAMES};
+use rust_json_formatter::walk::JsonPath;
+use rust_json_formatter::xml::XmlErrorKind;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/**
+ * CLI が表示するメッセージの言語
+ */
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Lang {
+    En, // 英語 (デフォルト)
+    Ja, // 日本語
+}
+
+/**
+ * 選択された言語 (init で一度だけ決定する)
+ */
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/**
+ * メッセージの言語を決定する
+ * --lang を指定しない場合は LC_ALL、LC_MESSAGES、LANG の順に環境変数を見て、ja で始まれば日本語、それ以外は英語にする
+ * 2回目以降の呼び出しは何もしない
+ */
+pub fn init(lang: Option<Lang>) {
+    LANG.get_or_init(|| lang.unwrap_or_else(detect));
+}
+
+fn detect() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    return match locale {
+        Some(locale) if locale.starts_with("ja") => Lang::Ja,
+        _ => Lang::En,
+    };
+}
+
+/**
+ * 選択された言語を返す
+ * init の前 (コマンドライン引数の解析中のエラーなど) は、コマンドライン引数の --lang と環境変数から決定する
+ */
+pub fn lang() -> Lang {
+    return match LANG.get() {
+        Some(lang) => *lang,
+        None => help_lang(&env::args_os().collect::<Vec<_>>()),
+    };
+}
+
+/**
+ * CLI が表示するメッセージの一覧
+ * 各メッセージは英語と日本語のテンプレートを持ち、{} (または {0} のような番号) の位置に引数を埋め込む
+ */
+#[allow(dead_code)] // 有効にした feature によっては使われないメッセージがある
+#[derive(Clone, Copy, Debug)]
+pub enum Message {
+    // 入出力
+    ReadFailed,
+    InputTooLarge,
+    WriteFailedWith,
+    ReadFileFailed,
+    ReadPathFailed,
+    WriteFileFailed,
+    NotFound,
+
+    // コマンドライン引数と設定ファイル
+    SarifRequiresCheck,
+    JsoncConflicts,
+    FeatureRequired,
+    InvalidConfig,
+    InvalidToml,
+    UnknownConfigKey,
+    InvalidConfigValue,
+    InvalidPattern,
+    InvalidRange,
+    InvalidDedupeKey,
+    InvalidRename,
+    InvalidPick,
+    InvalidPathPattern,
+    InvalidKeyPattern,
+    InvalidMaxDepth,
+    InvalidExponent,
+    InvalidRenameFile,
+    RangeOutOfBounds,
+    NoMatchingFiles,
+    CannotWriteUrl,
+    UrlRequiresHttp,
+
+    // テーマ
+    ThemeNotFound,
+    ThemeParseFailed,
+    ThemeNotObject,
+    ThemeInvalidColor,
+    ThemeNotString,
+    ThemeUnknownClass,
+
+    // パースエラーと警告
+    ParseFailed,
+    ParseFailedMany,
+    ErrorsOmitted,
+    GronParseFailed,
+    XmlInvalidName,
+    PlistNull,
+    TableNotArray,
+    TableNotObject,
+    Location,
+    Position,
+    LineContext,
+    DuplicateKey,
+    KeyCollision,
+    RenameCollision,
+    PickNotFound,
+    EnvVarMissing,
+    NonFiniteNumber,
+    CanonicalNonFinite,
+    InvalidUtf8Replaced,
+    Hint,
+    HintFixable,
+    Warning,
+    StreamIoFailed,
+    LspFailed,
+    LspOverwrittenKey,
+    LspUnknownMethod,
+
+    // 整形と検証の結果
+    StdinUnformatted,
+    StdinValid,
+    Unformatted,
+    Formatted,
+    Unchanged,
+    FormatSummary,
+    CheckSummary,
+    ValidateSummary,
+
+    // --watch
+    WatchStartFailed,
+    WatchPathFailed,
+    Watching,
+    WatchError,
+
+    // URL とクリップボード
+    FetchFailed,
+    HttpStatus,
+    ClipboardOpenFailed,
+    ClipboardReadFailed,
+    ClipboardWriteFailed,
+    ClipboardFormatted,
+    ClipboardCopyFailed,
+    ClipboardCopyRequiresFeature,
+
+    // SARIF のルールの説明
+    RuleParseError,
+    RuleUnformatted,
+    RuleFailure,
+
+    // jwt
+    JwtMalformed,
+    JwtSegmentInvalid,
+    JwtHeader,
+    JwtPayload,
+
+    // locate
+    InvalidPointer,
+    PointerNotFound,
+
+    // setop
+    SetopNotArray,
+
+    // weigh
+    RootPath,
+    WeighTotal,
+
+    // view
+    ViewNoInput,
+    TerminalInitFailed,
+    TerminalFailed,
+    SearchNotFound,
+    SearchEmpty,
+    SearchPosition,
+    Copied,
+    ViewHelp,
+    ObjectSummary,
+    ArraySummary,
+}
+
+impl Message {
+    /**
+     * メッセージの (英語, 日本語) のテンプレートを返す
+     */
+    fn templates(self) -> (&'static str, &'static str) {
+        return match self {
+            Message::ReadFailed => ("failed to read input: {}", "テキストの読み込みに失敗しました: {}"),
+            Message::InputTooLarge => (
+                "input is larger than {} bytes (--max-input-size)",
+                "入力が {} バイトを超えています (--max-input-size)",
+            ),
+            Message::WriteFailedWith => ("failed to write output: {}", "出力の書き込みに失敗しました: {}"),
+            Message::ReadFileFailed => ("failed to read: {}", "読み込みに失敗しました: {}"),
+            Message::ReadPathFailed => ("{}: failed to read: {}", "{}: 読み込みに失敗しました: {}"),
+            Message::WriteFileFailed => ("failed to write: {}", "書き込みに失敗しました: {}"),
+            Message::NotFound => ("{}: not found", "{}: 見つかりません"),
+
+            Message::SarifRequiresCheck => (
+                "--report-format sarif requires --check or the validate subcommand",
+                "--report-format sarif は --check か validate サブコマンドと併用してください",
+            ),
+            Message::JsoncConflicts => (
+                "--from jsonc cannot be combined with --to (other than json), --raw-output, --canonical, --collapse, --max-display-depth, --max-array-items, --delete, --substitute-env, --parse-nested, --decode-base64, --normalize-keys, --rename, --pick, --coerce, --dedupe, --timestamps, --sort-keys, --key-order, --key-order-schema, --key-order-example, --allow-non-finite, --max-string-length, --max-elements, --max-nodes or --max-depth",
+                "--from jsonc は --to (json 以外)、--raw-output、--canonical、--collapse、--max-display-depth、--max-array-items、--delete、--substitute-env、--parse-nested、--decode-base64、--normalize-keys、--rename、--pick、--coerce、--dedupe、--timestamps、--sort-keys、--key-order、--key-order-schema、--key-order-example、--allow-non-finite、--max-string-length、--max-elements、--max-nodes、--max-depth と併用できません",
+            ),
+            Message::FeatureRequired => (
+                "{} requires building with the {} feature enabled",
+                "{} を使うには {} feature を有効にしてビルドする必要があります",
+            ),
+            Message::InvalidConfig => ("{}: invalid configuration file", "{}: 設定ファイルの内容が不正です"),
+            Message::InvalidToml => ("invalid TOML: {}", "TOML として不正です: {}"),
+            Message::UnknownConfigKey => ("unknown setting: {}", "不明な設定項目です: {}"),
+            Message::InvalidConfigValue => ("{}: invalid value", "{}: 値の形式が不正です"),
+            Message::InvalidPattern => ("{}: invalid pattern: {}", "{}: パターンが不正です: {}"),
+            Message::InvalidRange => (
+                "specify START:END with END not less than START: {}",
+                "START:END の形式で、START 以上の END を指定してください: {}",
+            ),
+            Message::InvalidDedupeKey => (
+                "specify a dotted path like .id or a JSON Pointer like /id: {}",
+                ".id のような . 区切りの経路か、/id のような JSON Pointer を指定してください: {}",
+            ),
+            Message::InvalidRename => ("specify OLD=NEW: {}", "OLD=NEW の形式で指定してください: {}"),
+            Message::InvalidRenameFile => (
+                "specify an object of the form {{\"OLD\": \"NEW\", ...}}",
+                "{{\"OLD\": \"NEW\", ...}} の形式のオブジェクトを指定してください",
+            ),
+            Message::InvalidPick => (
+                "specify a JSON Pointer that is empty or starts with /: {}",
+                "空文字列か / で始まる JSON Pointer を指定してください: {}",
+            ),
+            Message::InvalidPathPattern => (
+                "a path pattern must start with /: {}",
+                "経路のパターンは / で始めてください: {}",
+            ),
+            Message::InvalidKeyPattern => (
+                "invalid glob pattern at character {1}: {0}",
+                "glob パターンの {1} 文字目が不正です: {0}",
+            ),
+            Message::InvalidMaxDepth => (
+                "specify an integer of at most {}: {}",
+                "{} 以下の整数を指定してください: {}",
+            ),
+            Message::InvalidExponent => (
+                "specify auto, never or an exponent threshold: {}",
+                "auto、never または指数の閾値の数値を指定してください: {}",
+            ),
+            Message::RangeOutOfBounds => (
+                "--range {}:{} is outside the input ({} bytes)",
+                "--range {}:{} が入力 ({} バイト) の範囲外です",
+            ),
+            Message::NoMatchingFiles => ("{}: no matching files", "{}: 一致するファイルがありません"),
+            Message::CannotWriteUrl => (
+                "content read from a URL cannot be overwritten",
+                "URL から読み込んだ内容は上書きできません",
+            ),
+            Message::UrlRequiresHttp => (
+                "reading from a URL requires building with the http feature enabled",
+                "URL から読み込むには http feature を有効にしてビルドする必要があります",
+            ),
+            Message::ThemeNotFound => (
+                "theme '{}' not found (built-in themes: {}): {}",
+                "テーマ '{}' が見つかりません (組み込みテーマ: {}): {}",
+            ),
+            Message::ThemeParseFailed => (
+                "failed to parse theme file '{}': {}",
+                "テーマファイル '{}' のパースに失敗しました: {}",
+            ),
+            Message::ThemeNotObject => (
+                "a theme must be an object",
+                "テーマ定義はオブジェクトである必要があります",
+            ),
+            Message::ThemeInvalidColor => ("invalid color: {}", "不正な色の指定です: {}"),
+            Message::ThemeNotString => (
+                "the color of '{}' must be a string",
+                "'{}' の色は文字列で指定してください",
+            ),
+            Message::ThemeUnknownClass => ("unknown token class: {}", "不明なトークンの種類です: {}"),
+
+            Message::ParseFailed => ("failed to parse {}: {}", "{}のパースに失敗しました: {}"),
+            Message::ParseFailedMany => (
+                "failed to parse {} ({} errors):",
+                "{}のパースに失敗しました ({} 件の誤り):",
+            ),
+            Message::ErrorsOmitted => ("{} more errors omitted", "ほか {} 件の誤りは省略しました"),
+            Message::GronParseFailed => ("failed to parse gron input", "gron形式のパースに失敗しました"),
+            Message::TableNotArray => (
+                "--to {} requires an array of objects at the top level",
+                "--to {} には最上位がオブジェクトの配列である必要があります",
+            ),
+            Message::TableNotObject => (
+                "--to {} requires an array of objects, but element {} is not an object",
+                "--to {} にはオブジェクトの配列が必要ですが、{} 番目の要素がオブジェクトではありません",
+            ),
+            Message::PlistNull => (
+                "cannot convert null at {} to a property list",
+                "{} の null はプロパティリストに変換できません",
+            ),
+            Message::XmlInvalidName => (
+                "cannot convert key \"{}\" at {} to an XML element or attribute name",
+                "{1} のキー \"{0}\" は XML の要素名や属性名に変換できません",
+            ),
+            Message::Location => ("(line {}, column {})", "({} 行 {} 列目)"),
+            Message::Position => ("(at byte {})", "(位置 {})"),
+            Message::LineContext => ("line {}: {}", "{} 行目: {}"),
+            Message::DuplicateKey => (
+                "duplicate key {} (the value at line {}, column {} is overwritten)",
+                "キー {} が重複しています ({} 行 {} 列目の値は上書きされます)",
+            ),
+            Message::RenameCollision => (
+                "keys collided at {} after renaming (the earlier value is overwritten)",
+                "名前の変更によって {} のキーが重複しました (前の値は上書きされます)",
+            ),
+            Message::PickNotFound => ("--pick: no value at {}", "--pick: {} に値がありません"),
+            Message::EnvVarMissing => (
+                "environment variable {} is not set (at {})",
+                "環境変数 {} が設定されていません ({})",
+            ),
+            Message::KeyCollision => (
+                "keys collided at {} after normalization (the earlier value is overwritten)",
+                "正規化によって {} のキーが重複しました (前の値は上書きされます)",
+            ),
+            Message::CanonicalNonFinite => (
+                "the number at {} is not finite, which the canonical form (RFC 8785) cannot represent",
+                "{} の数値は有限でないため、正規化形式 (RFC 8785) で表せません",
+            ),
+            Message::NonFiniteNumber => (
+                "the number at {} is NaN or Infinity, which JSON cannot represent (use --non-finite null or --non-finite string to replace it)",
+                "{} の数値は JSON で表せない NaN または Infinity です (--non-finite null または --non-finite string で置き換えられます)",
+            ),
+            Message::InvalidUtf8Replaced => (
+                "replaced an invalid UTF-8 sequence at byte {} with U+FFFD",
+                "{} バイト目の UTF-8 として不正なバイト列を U+FFFD に置き換えました",
+            ),
+            Message::Hint => ("hint: {}", "ヒント: {}"),
+            Message::HintFixable => (
+                "hint: {} (pass --fix to fix it and format)",
+                "ヒント: {} (--fix を指定すると直して整形します)",
+            ),
+            Message::Warning => ("warning: {}", "警告: {}"),
+            Message::StreamIoFailed => ("I/O failed: {}", "入出力に失敗しました: {}"),
+            Message::LspFailed => (
+                "LSP server communication failed: {}",
+                "LSP サーバーの通信に失敗しました: {}",
+            ),
+            Message::LspOverwrittenKey => ("the key of the overwritten value", "上書きされる値のキー"),
+            Message::LspUnknownMethod => ("unsupported method: {}", "未対応のメソッドです: {}"),
+
+            Message::StdinUnformatted => ("standard input is not formatted", "標準入力は整形されていません"),
+            Message::StdinValid => ("standard input is valid", "標準入力の形式は正しいです"),
+            Message::Unformatted => ("not formatted: {}", "整形されていません: {}"),
+            Message::Formatted => ("formatted: {}", "整形しました: {}"),
+            Message::Unchanged => ("unchanged: {}", "変更なし: {}"),
+            Message::FormatSummary => (
+                "formatted {1} of {0} files ({2} failed)",
+                "{0} 件中 {1} 件のファイルを整形しました (失敗 {2} 件)",
+            ),
+            Message::CheckSummary => (
+                "{1} of {0} files are not formatted ({2} failed)",
+                "{0} 件中 {1} 件のファイルが整形されていません (失敗 {2} 件)",
+            ),
+            Message::ValidateSummary => (
+                "files validated: {} ({} invalid)",
+                "{} 件のファイルを検証しました (不正 {} 件)",
+            ),
+
+            Message::WatchStartFailed => (
+                "failed to start watching files: {}",
+                "ファイルの監視を開始できませんでした: {}",
+            ),
+            Message::WatchPathFailed => ("{}: failed to start watching: {}", "{}: 監視を開始できませんでした: {}"),
+            Message::Watching => (
+                "watching {} paths (press Ctrl-C to exit)",
+                "{} 件のパスを監視しています (Ctrl-C で終了)",
+            ),
+            Message::WatchError => ("error while watching: {}", "監視中にエラーが発生しました: {}"),
+
+            Message::FetchFailed => ("failed to fetch: {}", "取得に失敗しました: {}"),
+            Message::HttpStatus => ("HTTP {} returned: {}", "HTTP {} が返されました: {}"),
+            Message::ClipboardOpenFailed => (
+                "could not open the clipboard: {}",
+                "クリップボードを開けませんでした: {}",
+            ),
+            Message::ClipboardReadFailed => (
+                "failed to read the clipboard: {}",
+                "クリップボードの読み込みに失敗しました: {}",
+            ),
+            Message::ClipboardWriteFailed => (
+                "failed to write to the clipboard: {}",
+                "クリップボードへの書き込みに失敗しました: {}",
+            ),
+            Message::ClipboardFormatted => ("formatted the clipboard", "クリップボードの内容を整形しました"),
+            Message::ClipboardCopyFailed => (
+                "could not copy to the clipboard: {}",
+                "クリップボードにコピーできませんでした: {}",
+            ),
+            Message::ClipboardCopyRequiresFeature => (
+                "copying to the clipboard requires building with the clipboard feature enabled",
+                "クリップボードにコピーするには clipboard feature を有効にしてビルドする必要があります",
+            ),
+
+            Message::RuleParseError => ("the input cannot be parsed", "入力をパースできません"),
+            Message::RuleUnformatted => ("the input is not formatted", "入力が整形されていません"),
+            Message::RuleFailure => ("the input cannot be processed", "入力を処理できません"),
+
+            Message::JwtMalformed => (
+                "expected a JWT of the form header.payload.signature",
+                "JWT は header.payload.signature の形式で渡してください",
+            ),
+            Message::JwtSegmentInvalid => ("the {} is not valid base64url", "{}が base64url として不正です"),
+            Message::JwtHeader => ("JWT header", "JWT のヘッダー"),
+            Message::JwtPayload => ("JWT payload", "JWT のペイロード"),
+
+            Message::InvalidPointer => (
+                "invalid JSON Pointer (must be empty or start with `/`): {}",
+                "JSON Pointer が不正です (空文字列か `/` で始めてください): {}",
+            ),
+            Message::PointerNotFound => ("no value at {}", "{} に値がありません"),
+
+            Message::SetopNotArray => (
+                "setop needs an array at the top level",
+                "setop の入力は最上位が配列である必要があります",
+            ),
+
+            Message::RootPath => ("(root)", "(ルート)"),
+            Message::WeighTotal => ("{} in total", "合計 {}"),
+
+            Message::ViewNoInput => (
+                "view needs a FILE or JSON on standard input",
+                "view には FILE を指定するか、標準入力から JSON を渡してください",
+            ),
+            Message::TerminalInitFailed => (
+                "could not initialize the terminal: {}",
+                "端末を初期化できませんでした: {}",
+            ),
+            Message::TerminalFailed => ("terminal operation failed: {}", "端末の操作に失敗しました: {}"),
+            Message::SearchNotFound => ("not found: {}", "見つかりませんでした: {}"),
+            Message::SearchEmpty => (
+                "no search term (press / to search)",
+                "検索語がありません (/ で検索します)",
+            ),
+            Message::SearchPosition => ("match {} of {}", "{} / {} 件目"),
+            Message::Copied => ("copied: {}", "コピーしました: {}"),
+            Message::ViewHelp => (
+                "{}  (q: quit  /: search  n/N: next/previous match  y: copy path)",
+                "{}  (q: 終了  /: 検索  n/N: 次/前の一致  y: パスをコピー)",
+            ),
+            Message::ObjectSummary => ("{{}} {} items", "{{}} {} 件"),
+            Message::ArraySummary => ("[] {} items", "[] {} 件"),
+        };
+    }
+}
+
+/**
+ * メッセージを選択された言語で、引数を埋め込んだ文字列にする
+ * テンプレートの {} は前から順に、{0} のような番号付きのものはその番号の引数に置き換え、{{ と }} は { と } にする
+ */
+pub fn text(message: Message, args: &[&dyn Display]) -> String {
+    let (en, ja) = message.templates();
+    let template = match lang() {
+        Lang::En => en,
+        Lang::Ja => ja,
+    };
+    let mut output = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            output.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let Some(end) = rest.find('}').filter(|_| rest.starts_with('{')) else {
+            output.push_str(&rest[..1]);
+            rest = &rest[1..];
+            continue;
+        };
+        let index = match rest[1..end].parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => {
+                next += 1;
+                next - 1
+            }
+        };
+        if let Some(arg) = args.get(index) {
+            output.push_str(&arg.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    return output;
+}
+
+/**
+ * メッセージの一覧から、選択された言語で引数を埋め込んだ文字列を作る
+ * 例: t!(ReadFailed, error)
+ */
+macro_rules! t {
+    ($message:ident $(, $arg:expr)* $(,)?) => {
+        $crate::messages::text($crate::messages::Message::$message, &[$(&$arg as &dyn ::std::fmt::Display),*])
+    };
+}
+pub(crate) use t;
+
+/**
+ * --help に表示するコマンドの英語の説明
+ */
+const ABOUT_EN: &str = "Format JSON read from standard input or files";
+
+/**
+ * --help に表示する、引数とサブコマンドの英語の説明
+ * 日本語の説明は cli.rs のドキュメントコメントに書き、英語を選択した場合はこちらに置き換える
+ * サブコマンドの引数は "サブコマンド名.引数名" で指定する
+ */
+const HELP_EN: &[(&str, &str)] = &[
+    ("files", "Files, directories, glob patterns or URLs to format (reads standard input if omitted)"),
+    ("write", "Overwrite the input files with the formatted output instead of printing it"),
+    ("check", "Only check whether the inputs are already formatted (exits with code 3 if any is not)"),
+    ("quiet", "Print nothing; report the result only through the exit code"),
+    ("verbose", "Print progress to standard error (-v for timings and counts, -vv also for file discovery and recovery decisions)"),
+    ("watch", "Watch the input files and directories and reformat them whenever they change"),
+    ("extensions", "File extensions to format when a directory is given (comma-separated)"),
+    ("ignore", "Glob patterns of paths to skip when walking directories, such as node_modules or **/fixtures/** (repeatable)"),
+    ("config", "Path to the config file (if omitted, .jsonfmt.toml is searched for from the input path upwards)"),
+    ("no_config", "Do not read any config file"),
+    ("clipboard", "Format the JSON in the clipboard (write puts it back into the clipboard, print prints it)"),
+    ("gzip", "Decompress the input as gzip (detected from the magic bytes or the .gz extension even if omitted)"),
+    ("invalid_utf8", "How to handle input that is not valid UTF-8 (replace substitutes U+FFFD with a warning, error fails)"),
+    ("output_compression", "Compression of the output (with --write, defaults to the compression of the input)"),
+    ("fix", "Fix common mistakes (missing or extra `,`, unquoted keys, missing closing brackets) and report each fix as a warning"),
+    ("allow_non_finite", "Accept NaN, Infinity and -Infinity as numbers (as written by Python's json module)"),
+    ("max_input_size", "Maximum size of the input in bytes after decompression; reading stops with an error beyond it"),
+    ("max_string_length", "Maximum length in bytes of a string (including keys) after unescaping"),
+    ("max_elements", "Maximum number of elements in one array or members in one object"),
+    ("max_nodes", "Maximum number of values in the whole input (objects and arrays count too)"),
+    ("max_depth", "Maximum nesting depth of objects and arrays (limited to 512 even if omitted, so that deep input cannot exhaust the stack; at most 10000)"),
+    ("report_format", "Format of the --check and validate report (sarif prints a SARIF 2.1.0 log to standard output)"),
+    ("from", "Input format"),
+    ("to", "Output format"),
+    ("raw_output", "If the value is a string, print its contents without quotes or escapes"),
+    ("parse_nested", "Parse JSON embedded in strings (objects or arrays like \"{\\\"a\\\":1}\") and format it as nested values"),
+    ("restringify_nested", "Turn values parsed by --parse-nested back into one-line JSON strings (to validate and normalize embedded JSON)"),
+    ("delete", "Delete values (object members or array elements) whose path matches the pattern before formatting (repeatable; array indices refer to the positions before deletion)"),
+    ("substitute_env", "Replace ${NAME} in string values with the environment variable ($${ becomes ${ without substitution)"),
+    ("missing_env", "What --substitute-env does with unset variables (keep leaves ${NAME} as is with a warning)"),
+    ("decode_base64", "Decode strings whose path matches the pattern (a JSON Pointer where * matches one segment and ** any depth) as Base64 (repeatable)"),
+    ("coerce", "Convert strings that read as JSON literals (\"42\", \"true\", \"null\", ...) into values of that type; --coerce=PATTERN converts only values whose path matches (repeatable)"),
+    ("dedupe", "Remove duplicate elements from arrays (ignoring key order and comparing numbers by value), keeping the first; --dedupe=PATTERN only affects arrays whose path matches (repeatable)"),
+    ("dedupe_by", "With --dedupe, treat elements as duplicates when the value at this path (.id, .user.id or the JSON Pointer /id) is equal"),
+    ("normalize_keys", "Normalize object keys to the given Unicode normalization form (keys that collide after normalization keep the later value, with a warning)"),
+    ("rename", "Rename object keys from OLD to NEW at any depth, keeping the member position (repeatable)"),
+    ("rename_file", "Read the --rename mapping from a JSON file of the form {\"OLD\": \"NEW\", ...} (--rename takes precedence)"),
+    ("pick", "Extract only the values at the given JSON Pointers, keeping their nesting (repeatable; arrays keep only the picked elements)"),
+    ("pick_flat", "Put the values extracted by --pick into a flat object keyed by JSON Pointer, in the given order"),
+    ("sort_keys", "Sort object members by key; --sort-keys=natural compares runs of digits as numbers (\"item2\" before \"item10\")"),
+    ("key_order", "Move members with the given keys (comma-separated) to the front of every object in that order; the rest keep their order (sorted with --sort-keys)"),
+    ("key_order_schema", "Order object members by the keys listed in the properties of a JSON Schema file; other members follow in their original order (--key-order is applied afterwards)"),
+    ("key_order_example", "Order object members like the object at the same position in an example JSON file; array elements follow the example element at the same position (or the first one)"),
+    ("timestamps", "Convert UNIX timestamps to strings in the given format (seconds, milliseconds, microseconds or nanoseconds are inferred from the magnitude)"),
+    ("timestamp_key", "Glob pattern of the keys whose values --timestamps converts (repeatable)"),
+    ("timestamp_path", "Path pattern of the values --timestamps converts regardless of their key (repeatable)"),
+    ("canonical", "Print the canonical form defined by RFC 8785 (JCS)"),
+    ("collapse", "Put objects and arrays that fit within the line width on one line"),
+    ("max_width", "Maximum line width for --collapse"),
+    ("max_display_depth", "Show objects and arrays at or below this depth as {…} or [… 34 items] (for skimming large documents; the output is not JSON)"),
+    ("max_array_items", "Show only this many leading array elements and mark the rest like /* 995 more */"),
+    ("array_marker", "How --max-array-items marks omitted elements (string adds a \"… 995 more\" element so the output stays valid JSON)"),
+    ("range", "Format only the values surrounding the byte range START:END (END exclusive) and copy the rest of the input as is"),
+    ("line_range", "Same as --range, but with line numbers (1-based, END inclusive)"),
+    ("compact_numeric_arrays", "Put arrays of only numbers and booleans on one line (wrapping every N elements if a value is given)"),
+    ("indent", "Indentation for one level (a number of spaces, \"tab\" for a tab, or any other string as is)"),
+    ("newline", "End the output with a newline (the default except with --canonical)"),
+    ("no_newline", "Do not end the output with a newline"),
+    ("keep_bom", "Keep the BOM at the start of the output if the input starts with one (removed by default)"),
+    ("escape_slash", "Escape / in strings as \\/"),
+    ("control_escapes", "How to escape control characters (short uses \\n, \\t and the like where available, unicode always uses \\u00XX)"),
+    ("hex_case", "Letter case of the hex digits in \\u00XX escapes"),
+    ("exponent", "When to use exponent notation (auto matches JSON.stringify, never always uses positional notation, a number N uses it when the exponent is at least N in absolute value)"),
+    ("number_precision", "Round or zero-pad every number to this many decimal places (3.1 becomes 3.100; exact halves round to even; overrides --exponent)"),
+    ("non_finite", "How to print non-finite numbers (error fails, null prints null, string prints strings like \"NaN\")"),
+    ("eol", "Line ending of the output"),
+    ("no_pager", "Do not use a pager ($PAGER, or less) even when standard output is a terminal"),
+    ("color", "Whether to color the output (auto only when standard output is a terminal)"),
+    ("stream", "Format token by token without reading the whole input (for huge input; concatenated values are formatted one by one; cannot be combined with layout options)"),
+    ("theme", "Color theme (a built-in theme name or the path to a theme JSON file)"),
+    ("lang", "Language of messages (if omitted, Japanese when LC_ALL, LC_MESSAGES or LANG starts with ja, English otherwise)"),
+    ("lsp", "Run a Language Server Protocol server over standard input and output to provide formatting to editors"),
+    ("completions", "Print a shell completion script"),
+    ("completions.shell", "Shell to print the completion script for"),
+    ("view", "Browse the input in the terminal as a collapsible tree with search"),
+    ("view.file", "File or URL to browse (reads standard input if omitted)"),
+    ("jwt", "Decode and format the header and payload of the JWT on standard input (the signature is not verified)"),
+    ("summarize", "Print a summary of the structure instead of the contents (array elements are counted per object shape)"),
+    ("summarize.file", "File or URL to summarize (reads standard input if omitted)"),
+    ("weigh", "Show how many bytes of the formatted output each value matching the path pattern takes, largest first"),
+    ("weigh.file", "File or URL to inspect (reads standard input if omitted)"),
+    ("weigh.path", "Path pattern of the values to weigh (defaults to the members or elements directly under the root)"),
+    ("digest", "Print the hash of the RFC 8785 canonical form (documents that differ only in whitespace or key order hash the same)"),
+    ("digest.files", "Files or URLs to hash (reads standard input if omitted)"),
+    ("digest.algo", "Hash function"),
+    ("setop", "Treat the elements of the top-level arrays of two inputs as sets and print the resulting array (ignoring key order and comparing numbers by value)"),
+    ("setop.operation", "Operation (union, intersect, or diff for the elements of A that are not in B)"),
+    ("setop.a", "File or URL of the first array"),
+    ("setop.b", "File or URL of the second array"),
+    ("locate", "Print where the value at a JSON Pointer is in the input (lines and columns, and the byte range)"),
+    ("locate.pointer", "JSON Pointer of the value to locate (for example /users/0/name; the empty string is the root)"),
+    ("locate.file", "JSON file or URL to search (reads standard input if omitted)"),
+    ("validate", "Only check whether the inputs are valid JSON (or the format given by --from) without printing them"),
+    ("validate.files", "Files, directories, glob patterns or URLs to check (reads standard input if omitted)"),
+];
+
+/**
+ * 設定ファイルを読み込む前に、コマンドライン引数の --lang と環境変数からメッセージの言語を決める
+ * --help は設定ファイルを読み込む前に表示されるため、設定ファイルの指定は見ない
+ */
+pub fn help_lang(args: &[OsString]) -> Lang {
+    let mut args = args.iter().filter_map(|arg| arg.to_str());
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next(),
+            Some(value) => value.strip_prefix('='),
+            None => None,
+        };
+        if let Some(lang) = value.and_then(|value| Lang::from_str(value, true).ok()) {
+            return lang;
+        }
+    }
+    return detect();
+}
+
+/**
+ * コマンドの定義の説明 (--help) を lang の言語にする
+ */
+pub fn localize_help(mut command: Command, lang: Lang) -> Command {
+    if lang == Lang::Ja {
+        return command;
+    }
+    command = command.about(ABOUT_EN);
+    for (id, help) in HELP_EN {
+        command = match id.split_once('.') {
+            Some((name, arg)) => command.mut_subcommand(name, |subcommand| subcommand.mut_arg(arg, |a| a.help(*help))),
+            None if command.find_subcommand(id).is_some() => {
+                command.mut_subcommand(id, |subcommand| subcommand.about(*help))
+            }
+            // feature によっては定義されない引数もある
+            None if command.get_arguments().any(|arg| arg.get_id() == id) => command.mut_arg(id, |arg| arg.help(*help)),
+            None => command,
+        };
+    }
+    return command;
+}
+
+/**
+ * パースエラーの原因を選択された言語で返す
+ */
+pub fn parse_error_kind(kind: &ParseErrorKind) -> String {
+    if lang() == Lang::Ja {
+        return kind.message().to_string();
+    }
+    return kind.english_message().to_string();
+}
+
+/**
+ * XML のパースエラーの原因を選択された言語で返す
+ */
+pub fn xml_error_kind(kind: &XmlErrorKind) -> String {
+    if lang() == Lang::Ja {
+        return kind.message().to_string();
+    }
+    let message = match kind {
+        XmlErrorKind::UnexpectedEof => "unexpected end of input",
+        XmlErrorKind::ExpectedRoot => "expected a root element",
+        XmlErrorKind::InvalidName => "invalid character in an element or attribute name",
+        XmlErrorKind::ExpectedEquals => "expected `=` after the attribute name",
+        XmlErrorKind::ExpectedQuote => "attribute values must be enclosed in quotes",
+        XmlErrorKind::DuplicateAttribute => "duplicate attribute",
+        XmlErrorKind::MismatchedTag => "end tag does not match the start tag",
+        XmlErrorKind::InvalidEntity => "invalid entity reference",
+        XmlErrorKind::TrailingCharacters => "unexpected characters after the root element",
+    };
+    return message.to_string();
+}
+
+/**
+ * よくある誤りの修正方法を選択された言語で返す
+ */
+pub fn suggestion(kind: &SuggestionKind) -> String {
+    if lang() == Lang::Ja {
+        return kind.message();
+    }
+    return match kind {
+        SuggestionKind::TrailingComma => "remove the `,` before the closing bracket".to_string(),
+        SuggestionKind::SingleQuote => "enclose strings in `\"` instead of `'`".to_string(),
+        SuggestionKind::UnquotedKey(key) => format!("enclose object keys in `\"`, like `\"{}\"`", key),
+        SuggestionKind::Literal { found, expected } => {
+            format!(
+                "write `{}` instead of `{}` (JSON literals are lowercase)",
+                expected, found
+            )
+        }
+    };
+}
+
+/**
+ * オブジェクトのキーの重複を、重複して現れた箇所の行とキャレットを添えて選択された言語で返す
+ */
+pub fn duplicate_key(duplicate: &DuplicateKey, input: &str) -> String {
+    return with_source(input, duplicate.second, &duplicate_key_message(duplicate, input));
+}
+
+/**
+ * オブジェクトのキーの重複を説明する1行のメッセージを選択された言語で返す
+ */
+fn duplicate_key_message(duplicate: &DuplicateKey, input: &str) -> String {
+    let (line, column) = line_column(input, duplicate.first);
+    let key = JsonValue::from(duplicate.key.as_str()).to_string();
+    return t!(DuplicateKey, key, line, column);
+}
+
+/**
+ * パースエラーの原因と位置を選択された言語で返す
+ */
+pub fn parse_error(error: &ParseError) -> String {
+    return format!("{} {}", parse_error_kind(&error.kind), t!(Position, error.position));
+}
+
+/**
+ * テーマの読み込みに失敗した原因を選択された言語で返す
+ */
+pub fn theme_error(error: &ThemeError) -> String {
+    return match error {
+        ThemeError::NotFound(name, error) => t!(ThemeNotFound, name, PRESET_NAMES.join(", "), error),
+        ThemeError::Parse(path, error) => t!(ThemeParseFailed, path, parse_error(error)),
+        ThemeError::NotObject => t!(ThemeNotObject),
+        ThemeError::InvalidColor(color) => t!(ThemeInvalidColor, color),
+        ThemeError::NotString(name) => t!(ThemeNotString, name),
+        ThemeError::UnknownClass(name) => t!(ThemeUnknownClass, name),
+    };
+}
+
+/**
+ * LSP サーバーが診断やエラー応答として送るメッセージを選択された言語にする
+ */
+pub struct LspMessages;
+
+impl lsp::Messages for LspMessages {
+    fn parse_error(&self, kind: &ParseErrorKind) -> String {
+        return parse_error_kind(kind);
+    }
+
+    fn suggestion(&self, kind: &SuggestionKind) -> String {
+        return suggestion(kind);
+    }
+
+    fn duplicate_key(&self, duplicate: &DuplicateKey, input: &str) -> String {
+        return duplicate_key_message(duplicate, input);
+    }
+
+    fn overwritten_key(&self) -> String {
+        return t!(LspOverwrittenKey);
+    }
+
+    fn unknown_method(&self, method: &str) -> String {
+        return t!(LspUnknownMethod, method);
+    }
+
+    fn invalid_message(&self, error: &ParseError) -> String {
+        return parse_error(error);
+    }
+}
+
+/**
+ * 値の経路を表示用に返す (ルートを指す空の経路は、空文字列の代わりに (root) と表示する)
+ */
+pub fn path_label(path: &JsonPath) -> String {
+    if path.is_root() {
+        return t!(RootPath);
+    }
+    return path.to_string();
+}
+
+/**
+ * メッセージに、入力中の位置 (行と列) と、該当行とその位置を指すキャレットを添えた複数行の文字列を返す
+ */
+pub fn with_source(input: &str, position: usize, message: &str) -> String {
+    let (line, column) = line_column(input, position);
+    return format!(
+        "{} {}\n{}",
+        message,
+        t!(Location, line, column),
+        source_snippet(input, position)
+    );
+}
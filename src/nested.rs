@@ -0,0 +1,105 @@
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathSegment};
+
+impl JsonValue {
+    /**
+     * 文字列の値のうち、JSON のオブジェクトや配列として読めるもの ("{\"a\":1}" など) をパースした値に置き換える
+     * 置き換えた値の中に埋め込まれた JSON も同様に置き換え、置き換えた値の経路を見つけた順 (外側が先) に返す
+     * 数値や真偽値として読める文字列 ("1" や "true") は意図した文字列である場合が多いため、置き換えない
+     */
+    pub fn parse_nested(&mut self) -> Vec<JsonPath> {
+        let mut decoded = Vec::new();
+        self.parse_nested_at(&mut JsonPath::root(), &mut decoded);
+        return decoded;
+    }
+
+    fn parse_nested_at(&mut self, path: &mut JsonPath, decoded: &mut Vec<JsonPath>) {
+        if let JsonValue::String(str) = self {
+            if let Some(value) = parse_embedded(str) {
+                *self = value;
+                decoded.push(path.clone());
+            }
+        }
+        match self {
+            JsonValue::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.parse_nested_at(path, decoded);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.parse_nested_at(path, decoded);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * parse_nested で置き換えた経路の値を、1行の JSON の文字列に戻す
+     * 内側の値から順に戻すため、paths には parse_nested が返した経路をそのまま渡す
+     */
+    pub fn stringify_nested(&mut self, paths: &[JsonPath]) {
+        for path in paths.iter().rev() {
+            if let Some(value) = self.get_path_mut(path) {
+                *value = JsonValue::String(value.to_string());
+            }
+        }
+    }
+
+    fn get_path_mut(&mut self, path: &JsonPath) -> Option<&mut JsonValue> {
+        let mut value = self;
+        for segment in path.segments() {
+            value = match segment {
+                PathSegment::Key(key) => value.as_object_mut()?.get_mut(key)?,
+                PathSegment::Index(index) => value.as_array_mut()?.get_mut(*index)?,
+            };
+        }
+        return Some(value);
+    }
+}
+
+/**
+ * 文字列が JSON のオブジェクトか配列であればパースした値を返す
+ */
+fn parse_embedded(str: &str) -> Option<JsonValue> {
+    let trimmed = str.trim();
+    if !trimmed.starts_with(['{', '[']) {
+        return None;
+    }
+    return trimmed.parse().ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested() {
+        let mut json: JsonValue = r#"{"payload": "{\"a\": 1, \"b\": \"[1, 2]\"}", "id": "1", "text": "{not json"}"#
+            .parse()
+            .unwrap();
+        let decoded = json.parse_nested();
+        assert_eq!(
+            decoded.iter().map(|path| path.to_string()).collect::<Vec<_>>(),
+            vec!["/payload", "/payload/b"]
+        );
+        assert_eq!(json["payload"]["a"], JsonValue::from(1.0));
+        assert_eq!(json["payload"]["b"][1], JsonValue::from(2.0));
+        // オブジェクトや配列でない文字列や、JSON として読めない文字列はそのまま残す
+        assert_eq!(json["id"], JsonValue::from("1"));
+        assert_eq!(json["text"], JsonValue::from("{not json"));
+    }
+
+    #[test]
+    fn test_stringify_nested() {
+        let mut json: JsonValue = r#"["{ \"a\" : \"[ 1 ]\" }", "x"]"#.parse().unwrap();
+        let decoded = json.parse_nested();
+        json.stringify_nested(&decoded);
+        assert_eq!(json.to_string(), r#"["{\"a\":\"[1]\"}","x"]"#);
+    }
+}
@@ -0,0 +1,114 @@
+use indexmap::IndexMap;
+use unicode_normalization::{is_nfc, is_nfd, is_nfkc, is_nfkd, UnicodeNormalization};
+
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathSegment};
+
+/**
+ * Unicode の正規化形式
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalizationForm {
+    Nfc,  // 正準等価な合成 (macOS の NFD のキーを Windows や Linux と揃える場合など)
+    Nfd,  // 正準等価な分解
+    Nfkc, // 互換等価な合成 (全角英数字なども揃える)
+    Nfkd, // 互換等価な分解
+}
+
+impl NormalizationForm {
+    fn is_normalized(self, str: &str) -> bool {
+        return match self {
+            NormalizationForm::Nfc => is_nfc(str),
+            NormalizationForm::Nfd => is_nfd(str),
+            NormalizationForm::Nfkc => is_nfkc(str),
+            NormalizationForm::Nfkd => is_nfkd(str),
+        };
+    }
+
+    /**
+     * 文字列をこの形式に正規化する
+     */
+    pub fn normalize(self, str: &str) -> String {
+        return match self {
+            NormalizationForm::Nfc => str.nfc().collect(),
+            NormalizationForm::Nfd => str.nfd().collect(),
+            NormalizationForm::Nfkc => str.nfkc().collect(),
+            NormalizationForm::Nfkd => str.nfkd().collect(),
+        };
+    }
+}
+
+impl JsonValue {
+    /**
+     * すべてのオブジェクトのキーを指定した形式に正規化する
+     * 正規化によって同じキーになったメンバーは、パース時の重複したキーと同様に後の値で上書きし、その経路を返す
+     */
+    pub fn normalize_keys(&mut self, form: NormalizationForm) -> Vec<JsonPath> {
+        let mut collisions = Vec::new();
+        self.normalize_keys_at(form, &mut JsonPath::root(), &mut collisions);
+        return collisions;
+    }
+
+    fn normalize_keys_at(&mut self, form: NormalizationForm, path: &mut JsonPath, collisions: &mut Vec<JsonPath>) {
+        match self {
+            JsonValue::Object(obj) => {
+                // 正規化済みのキーしかないオブジェクトは作り直さない
+                if !obj.keys().all(|key| form.is_normalized(key)) {
+                    let mut normalized = IndexMap::with_capacity(obj.len());
+                    for (key, value) in obj.drain(..) {
+                        let key = form.normalize(&key);
+                        if normalized.contains_key(&key) {
+                            collisions.push(path.child(PathSegment::Key(key.clone())));
+                        }
+                        normalized.insert(key, value);
+                    }
+                    *obj = normalized;
+                }
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.normalize_keys_at(form, path, collisions);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.normalize_keys_at(form, path, collisions);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_keys() {
+        // "cafe\u{301}" は NFD、"caf\u{e9}" は NFC の "café"
+        let mut json = JsonValue::Null;
+        json.insert("cafe\u{301}", 1);
+        json.insert("items", JsonValue::Array(vec![JsonValue::Null]));
+        json["items"][0].insert("e\u{301}", true);
+        let collisions = json.normalize_keys(NormalizationForm::Nfc);
+        assert!(collisions.is_empty());
+        assert_eq!(json["caf\u{e9}"], JsonValue::from(1.0));
+        assert_eq!(json["items"][0]["\u{e9}"], JsonValue::Bool(true));
+
+        let mut json = JsonValue::Null;
+        json.insert("caf\u{e9}", 1);
+        json.insert("cafe\u{301}", 2);
+        let collisions = json.normalize_keys(NormalizationForm::Nfc);
+        assert_eq!(
+            collisions.iter().map(|path| path.to_string()).collect::<Vec<_>>(),
+            vec!["/caf\u{e9}"]
+        );
+        assert_eq!(json.as_object().unwrap().len(), 1);
+        assert_eq!(json["caf\u{e9}"], JsonValue::from(2.0));
+
+        assert_eq!(NormalizationForm::Nfkc.normalize("ＡＢＣ"), "ABC");
+    }
+}
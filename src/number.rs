@@ -0,0 +1,484 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::ParseFloatError;
+use std::str::FromStr;
+
+use crate::json::{push_number, JsonValue};
+use crate::walk::JsonPath;
+
+/**
+ * JSON の数値
+ * 整数は 64 ビットの整数のまま保持するため、桁の多い ID (2^53 を超える整数) も誤差なく扱える
+ * f64 の範囲を超える数値 (1e400 など) はリテラルのまま保持する
+ * bignum 機能が有効な場合は、どの型でも値を変えずに表せない数値もリテラルのまま保持する
+ */
+#[derive(Debug, Clone)]
+pub enum Number {
+    Int(i64),      // i64 で表せる整数
+    UInt(u64),     // i64 の範囲を超える正の整数
+    Float(f64),    // 小数部や指数部を持つ数値、64 ビットの整数で表せない整数、-0
+    Big(Box<str>), // f64 の範囲を超える数値 (1e400 など) や、bignum 機能が有効な場合は f64 にすると値が変わってしまう数値のリテラル
+}
+
+impl Number {
+    /**
+     * i64 で表せる整数であればその値を返す (小数部や指数部を持つ数値は、値が整数でも None)
+     */
+    pub fn as_i64(&self) -> Option<i64> {
+        return match *self {
+            Number::Int(int) => Some(int),
+            _ => None,
+        };
+    }
+
+    /**
+     * u64 で表せる整数であればその値を返す (小数部や指数部を持つ数値は、値が整数でも None)
+     */
+    pub fn as_u64(&self) -> Option<u64> {
+        return match *self {
+            Number::Int(int) => u64::try_from(int).ok(),
+            Number::UInt(uint) => Some(uint),
+            _ => None,
+        };
+    }
+
+    /**
+     * f64 に変換した値を返す (絶対値が 2^53 を超える整数や、桁の多いリテラルは最も近い値に丸める)
+     */
+    pub fn as_f64(&self) -> f64 {
+        return match self {
+            Number::Int(int) => *int as f64,
+            Number::UInt(uint) => *uint as f64,
+            Number::Float(float) => *float,
+            Number::Big(literal) => literal.parse().unwrap_or(f64::NAN),
+        };
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        return Number::Int(value);
+    }
+}
+
+impl From<u64> for Number {
+    fn from(value: u64) -> Self {
+        return match i64::try_from(value) {
+            Ok(int) => Number::Int(int),
+            Err(_) => Number::UInt(value),
+        };
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        return Number::Float(value);
+    }
+}
+
+/**
+ * 数値としての値で比較する (1 と 1.0、0 と -0.0 は等しい)
+ * Eq の反射律を満たすため、NaN は NaN 同士で等しいとみなす (f64 の比較とは異なる)
+ */
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        return match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a == b,
+            (Number::UInt(a), Number::UInt(b)) => a == b,
+            (Number::Float(a), Number::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Number::Int(int), Number::Float(float)) | (Number::Float(float), Number::Int(int)) => {
+                float_equals_integer(*float, *int as i128)
+            }
+            (Number::UInt(uint), Number::Float(float)) | (Number::Float(float), Number::UInt(uint)) => {
+                float_equals_integer(*float, *uint as i128)
+            }
+            (Number::Big(a), Number::Big(b)) => a == b,
+            _ => false,
+        };
+    }
+}
+
+impl Eq for Number {}
+
+/**
+ * PartialEq と整合するハッシュ (等しい数値は同じハッシュになる)
+ * 整数と等しい値は型によらず整数としてハッシュし、それ以外の浮動小数点数はビット列 (NaN はすべて同じビット列) でハッシュする
+ */
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Number::Int(int) => (int as i128).hash(state),
+            Number::UInt(uint) => (uint as i128).hash(state),
+            Number::Float(float) => match integer_value(float) {
+                Some(int) => int.hash(state),
+                None if float.is_nan() => f64::NAN.to_bits().hash(state),
+                None => float.to_bits().hash(state),
+            },
+            Number::Big(ref literal) => literal.hash(state),
+        }
+    }
+}
+
+fn float_equals_integer(float: f64, int: i128) -> bool {
+    return integer_value(float) == Some(int);
+}
+
+/**
+ * 64 ビットの整数 (符号付き・符号なし) で表せる値の浮動小数点数であれば、その整数を返す
+ */
+fn integer_value(float: f64) -> Option<i128> {
+    // 64 ビットの整数は 2^64 未満のため、それ以上の浮動小数点数とは等しくならない
+    if float.fract() == 0.0 && float.abs() < 18446744073709551616.0 {
+        return Some(float as i128);
+    }
+    return None;
+}
+
+/**
+ * 数値のリテラルを読み取る
+ * 小数部も指数部もない整数は i64 (範囲を超える場合は u64) として読み取り、それ以外は f64 として読み取る
+ * f64 の範囲を超えるリテラルは、有限でない数値 (Infinity) と区別するためそのまま Big として保持する
+ * bignum 機能が有効な場合は、f64 にすると値が変わってしまうリテラルもそのまま Big として保持する
+ */
+impl FromStr for Number {
+    type Err = ParseFloatError;
+
+    fn from_str(literal: &str) -> Result<Self, Self::Err> {
+        if !literal.contains(['.', 'e', 'E']) {
+            if let Ok(int) = literal.parse::<i64>() {
+                // -0 は符号を保つため浮動小数点数として読み取る
+                if int == 0 && literal.starts_with('-') {
+                    return Ok(Number::Float(-0.0));
+                }
+                return Ok(Number::Int(int));
+            }
+            if let Ok(uint) = literal.parse::<u64>() {
+                return Ok(Number::UInt(uint));
+            }
+        }
+        let float: f64 = literal.parse()?;
+        if !float.is_finite() {
+            return Ok(Number::Big(literal.into()));
+        }
+        #[cfg(feature = "bignum")]
+        if decimal_parts(literal) != decimal_parts(&format_shortest(float, ExponentNotation::Never)) {
+            return Ok(Number::Big(literal.into()));
+        }
+        return Ok(Number::Float(float));
+    }
+}
+
+/**
+ * 数値のリテラルを、値が等しければ同じになる (負かどうか, 先頭と末尾の 0 を除いた桁, 10 進の指数) の組にする
+ * 1.50 と 15e-1 はどちらも (false, "15", 0) になる
+ */
+#[cfg(feature = "bignum")]
+fn decimal_parts(literal: &str) -> (bool, String, i64) {
+    let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().unwrap_or(0)),
+        None => (literal, 0),
+    };
+    let negative = mantissa.starts_with('-');
+    let mantissa = mantissa.trim_start_matches(['-', '+']);
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all_digits = format!("{}{}", int, frac);
+    let digits = all_digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return (false, String::new(), 0);
+    }
+    // 先頭の桁の位 (1.5 なら 0、0.015 なら -2)
+    let leading = int.len() as i64 - (all_digits.len() - digits.len()) as i64 - 1;
+    return (negative, digits.trim_end_matches('0').to_string(), exponent + leading);
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut formatted = String::new();
+        push_number(&mut formatted, self);
+        return f.write_str(&formatted);
+    }
+}
+
+/**
+ * 有限でない数値 (NaN、Infinity、-Infinity) の出力形式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NonFiniteOutput {
+    #[default]
+    Literal, // Python の json モジュールなどと同じ NaN、Infinity、-Infinity (JSON としては不正)
+    Null,   // null
+    String, // "NaN"、"Infinity"、"-Infinity" の文字列
+}
+
+/**
+ * 有限でない数値を表すリテラル (NaN、Infinity、-Infinity) を返す
+ */
+pub fn non_finite_literal(num: f64) -> &'static str {
+    return match num {
+        num if num.is_nan() => "NaN",
+        num if num > 0.0 => "Infinity",
+        _ => "-Infinity",
+    };
+}
+
+impl JsonValue {
+    /**
+     * 有限でない数値 (NaN、Infinity、-Infinity) のうち、最初に現れるものの経路を返す
+     */
+    pub fn find_non_finite(&self) -> Option<JsonPath> {
+        return self
+            .walk()
+            .find(|(_, value)| matches!(value, JsonValue::Number(Number::Float(num)) if !num.is_finite()))
+            .map(|(path, _)| path);
+    }
+}
+
+/**
+ * 負の 0 (-0) かどうか (-0 == 0 のため、符号も調べる)
+ */
+pub fn is_negative_zero(num: f64) -> bool {
+    return num == 0.0 && num.is_sign_negative();
+}
+
+/**
+ * 数値を指数表記 (1e+21 や 1e-7) で出力する条件
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExponentNotation {
+    #[default]
+    Auto, // JavaScript の JSON.stringify と同じ (1e21 以上と 1e-6 未満の値)
+    Never,          // 常に 0.0000001 のような位取りの表記にする
+    Threshold(u32), // 10 進の指数の絶対値が指定した値以上の値 (3 なら 1000 は 1e+3、0.001 は 1e-3)
+}
+
+impl ExponentNotation {
+    /**
+     * 指定の文字列 (auto、never、または指数の閾値の数値) を解析する
+     */
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        return match spec {
+            "auto" => Ok(ExponentNotation::Auto),
+            "never" => Ok(ExponentNotation::Never),
+            _ => spec
+                .parse()
+                .map(ExponentNotation::Threshold)
+                .map_err(|_| format!("auto、never または指数の閾値の数値を指定してください: {}", spec)),
+        };
+    }
+
+    /**
+     * 10 進の指数が exponent の値 (1.5e+300 なら 300) を指数表記にするかどうか
+     */
+    pub fn uses_exponent(self, exponent: i32) -> bool {
+        return match self {
+            ExponentNotation::Auto => !(-7 < exponent && exponent < 21),
+            ExponentNotation::Never => false,
+            ExponentNotation::Threshold(threshold) => exponent.unsigned_abs() >= threshold,
+        };
+    }
+}
+
+/**
+ * 有限な数値を、往復変換可能な最短の桁数で文字列化する
+ * 指数表記にするかどうかは notation に従い、指数表記は JSON.stringify と同じ 1.5e+300 や 1e-7 の形式にする
+ * -0 は "0" になる
+ */
+pub fn format_shortest(num: f64, notation: ExponentNotation) -> String {
+    if num == 0.0 {
+        return "0".to_string();
+    }
+
+    // 指数表記から最短の仮数部の桁と指数を取り出す (例: "1.2345e-3")
+    let scientific = format!("{:e}", num.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    return format_digits(num < 0.0, &digits, exponent.parse().unwrap(), notation);
+}
+
+/**
+ * 整数を、指数表記にするかどうかは notation に従って文字列化する
+ * f64 を経由しないため、桁の多い整数も誤差なく表す (1234567890123456789 は 1.234567890123456789e+18)
+ */
+pub fn format_integer(num: i128, notation: ExponentNotation) -> String {
+    if num == 0 {
+        return "0".to_string();
+    }
+    let all_digits = num.unsigned_abs().to_string();
+    let digits = all_digits.trim_end_matches('0');
+    return format_digits(num < 0, digits, all_digits.len() as i32 - 1, notation);
+}
+
+/**
+ * 値 = 0.digits × 10^(exponent + 1) の数値を文字列化する (digits は 0 で始まらず 0 で終わらない桁の並び)
+ */
+fn format_digits(negative: bool, digits: &str, exponent: i32, notation: ExponentNotation) -> String {
+    // 値 = 0.digits × 10^n となる n を求める
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let mut result = String::new();
+    if negative {
+        result.push('-');
+    }
+    if notation.uses_exponent(exponent) {
+        result.push_str(&digits[..1]);
+        if k > 1 {
+            result.push('.');
+            result.push_str(&digits[1..]);
+        }
+        result.push('e');
+        result.push(if exponent < 0 { '-' } else { '+' });
+        result.push_str(&exponent.abs().to_string());
+    } else if k <= n {
+        // 整数: 桁の後ろに 0 を補う
+        result.push_str(digits);
+        result.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n {
+        // 小数点が桁の途中に入る
+        result.push_str(&digits[..n as usize]);
+        result.push('.');
+        result.push_str(&digits[n as usize..]);
+    } else {
+        // 0.000digits の形式
+        result.push_str("0.");
+        result.push_str(&"0".repeat(-n as usize));
+        result.push_str(digits);
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ExponentNotation::parse("auto"), Ok(ExponentNotation::Auto));
+        assert_eq!(ExponentNotation::parse("never"), Ok(ExponentNotation::Never));
+        assert_eq!(ExponentNotation::parse("6"), Ok(ExponentNotation::Threshold(6)));
+        assert!(ExponentNotation::parse("-1").is_err());
+    }
+
+    #[test]
+    fn test_format_shortest() {
+        assert_eq!(format_shortest(1e-7, ExponentNotation::Auto), "1e-7");
+        assert_eq!(format_shortest(1e-7, ExponentNotation::Never), "0.0000001");
+        assert_eq!(format_shortest(1.5e300, ExponentNotation::Never).len(), 301);
+        assert_eq!(format_shortest(-1234.5, ExponentNotation::Threshold(3)), "-1.2345e+3");
+        assert_eq!(format_shortest(0.001, ExponentNotation::Threshold(3)), "1e-3");
+        assert_eq!(format_shortest(0.01, ExponentNotation::Threshold(3)), "0.01");
+        assert_eq!(format_shortest(999.0, ExponentNotation::Threshold(3)), "999");
+    }
+
+    #[test]
+    fn test_find_non_finite() {
+        let json: JsonValue = r#"[1, {"a": 2.5}]"#.parse().unwrap();
+        assert_eq!(json.find_non_finite(), None);
+
+        let json = JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(f64::NEG_INFINITY)]);
+        assert_eq!(
+            json.find_non_finite().map(|path| path.to_string()),
+            Some("/1".to_string())
+        );
+        assert_eq!(non_finite_literal(f64::NEG_INFINITY), "-Infinity");
+        assert_eq!(non_finite_literal(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn test_format_integer() {
+        assert_eq!(format_integer(0, ExponentNotation::Threshold(0)), "0");
+        assert_eq!(
+            format_integer(12345678901234567890, ExponentNotation::Auto),
+            "12345678901234567890"
+        );
+        assert_eq!(format_integer(-1500, ExponentNotation::Threshold(3)), "-1.5e+3");
+        assert_eq!(
+            format_integer(i64::MIN as i128, ExponentNotation::Threshold(3)),
+            "-9.223372036854775808e+18"
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!("42".parse::<Number>().map(|num| num.as_i64()), Ok(Some(42)));
+        assert_eq!(
+            "12345678901234567890".parse::<Number>().map(|num| num.as_u64()),
+            Ok(Some(12345678901234567890))
+        );
+        assert_eq!(
+            "-9223372036854775808".parse::<Number>().map(|num| num.as_i64()),
+            Ok(Some(i64::MIN))
+        );
+        assert!(matches!("1.0".parse(), Ok(Number::Float(_))));
+        assert!(matches!("1e2".parse(), Ok(Number::Float(_))));
+        // -0 は 0 と区別するため、符号を保った浮動小数点数として読み取る
+        for literal in ["-0", "-0.0", "-0e3", "-0.000E-2"] {
+            assert!(
+                matches!(literal.parse(), Ok(Number::Float(num)) if is_negative_zero(num)),
+                "{}",
+                literal
+            );
+        }
+        assert!(matches!("0".parse(), Ok(Number::Int(0))));
+        assert!(matches!("0.0".parse(), Ok(Number::Float(num)) if !is_negative_zero(num)));
+        // 64 ビットの整数の範囲を超える整数は f64 として読み取る (bignum 機能が有効な場合は Big)
+        #[cfg(not(feature = "bignum"))]
+        assert!(matches!("123456789012345678901234".parse(), Ok(Number::Float(_))));
+        // f64 の範囲を超える数値は、有限でない数値にせずリテラルのまま保持する
+        for literal in ["1E400", "-1e999", "1e400000000000000000000"] {
+            assert_eq!(literal.parse(), Ok(Number::Big(literal.into())));
+        }
+        assert!("1-2".parse::<Number>().is_err());
+    }
+
+    #[test]
+    fn test_number_eq() {
+        assert_eq!(Number::Int(1), Number::Float(1.0));
+        assert_eq!(
+            Number::from(12345678901234567890_u64),
+            Number::UInt(12345678901234567890)
+        );
+        assert_eq!(Number::from(5_u64), Number::Int(5));
+        // 2^53 を超える整数は、丸めた f64 とは等しくならない
+        assert_ne!(
+            Number::Int(9007199254740993),
+            Number::Float(9007199254740993_i64 as f64)
+        );
+        // NaN は NaN 同士で等しいとみなす
+        assert_eq!(Number::Float(f64::NAN), Number::Float(f64::NAN));
+        assert_eq!(Number::Float(-0.0), Number::Int(0));
+    }
+
+    #[test]
+    fn test_number_hash() {
+        use std::collections::HashSet;
+        let numbers = [
+            Number::Int(1),
+            Number::Float(1.0),
+            Number::Float(0.0),
+            Number::Float(-0.0),
+            Number::Int(0),
+            Number::Float(f64::NAN),
+            Number::Float(-f64::NAN),
+            Number::UInt(u64::MAX),
+            Number::Float(0.5),
+        ];
+        let set: HashSet<&Number> = numbers.iter().collect();
+        assert_eq!(set.len(), 5);
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_parse_big_number() {
+        let big = |literal: &str| Number::Big(literal.into());
+        assert_eq!("3.14159265358979323846".parse(), Ok(big("3.14159265358979323846")));
+        assert_eq!("123456789012345678901234".parse(), Ok(big("123456789012345678901234")));
+        assert_eq!("1e400".parse(), Ok(big("1e400")));
+        assert_eq!("1e-400".parse(), Ok(big("1e-400")));
+        // f64 で値を変えずに表せるリテラルは、桁の表し方が違っても Float にする
+        assert!(matches!("0.1".parse(), Ok(Number::Float(_))));
+        assert!(matches!("1.50".parse(), Ok(Number::Float(_))));
+        assert!(matches!("-0.0".parse(), Ok(Number::Float(_))));
+        assert!(matches!("25E-1".parse(), Ok(Number::Float(_))));
+    }
+}
@@ -0,0 +1,127 @@
+use std::env;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::cli::Cli;
+
+/**
+ * PAGER が設定されていない場合に使うページャー
+ */
+const DEFAULT_PAGER: &str = "less";
+
+/**
+ * LESS が設定されていない場合に less に渡すオプション (git と同じ)
+ * F: 1画面に収まる場合はすぐに終了する、R: 色のエスケープシーケンスをそのまま表示する、X: 終了時に画面を消さない
+ */
+const DEFAULT_LESS: &str = "FRX";
+
+/**
+ * ページャーの標準入力に出力を書き込むライター
+ * 破棄する際にページャーの標準入力を閉じ、ページャーが終了するまで待つ
+ */
+pub struct Pager {
+    child: Child,
+    stdin: Option<BufWriter<ChildStdin>>,
+}
+
+/**
+ * 標準出力が端末であれば、出力を表示するページャー ($PAGER、なければ less) を起動する
+ * --no-pager の場合や、ページャーを起動できなかった場合は None を返す
+ */
+pub fn spawn(cli: &Cli) -> Option<Pager> {
+    // --watch では出力が続くため、ページャーを使わない
+    if cli.no_pager || cli.watch || !io::stdout().is_terminal() {
+        return None;
+    }
+    let pager = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut command = command(&pager)?;
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", DEFAULT_LESS);
+    }
+    let mut child = command.spawn().ok()?;
+    log::debug!("paging output through {}", pager);
+    let stdin = child.stdin.take().map(BufWriter::new);
+    return Some(Pager { child, stdin });
+}
+
+/**
+ * PAGER の値 (プログラム名と空白区切りの引数) からページャーを起動するコマンドを作る
+ * 空の場合や cat の場合はページャーを使わないものとして None を返す
+ */
+fn command(pager: &str) -> Option<Command> {
+    let mut words = pager.split_whitespace();
+    let program = words.next()?;
+    if program == "cat" {
+        return None;
+    }
+    let mut command = Command::new(program);
+    command.args(words).stdin(Stdio::piped());
+    return Some(command);
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(stdin) = &mut self.stdin else {
+            return Ok(buf.len());
+        };
+        return match stdin.write(buf) {
+            // ページャーが途中で閉じられた場合は、残りの出力を捨てる
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {
+                self.stdin = None;
+                Ok(buf.len())
+            }
+            result => result,
+        };
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let Some(stdin) = &mut self.stdin else {
+            return Ok(());
+        };
+        return match stdin.flush() {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {
+                self.stdin = None;
+                Ok(())
+            }
+            result => result,
+        };
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        if let Some(mut stdin) = self.stdin.take() {
+            let _ = stdin.flush();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let less = command("less -S --mouse").unwrap();
+        assert_eq!(less.get_program(), "less");
+        assert_eq!(less.get_args().collect::<Vec<_>>(), ["-S", "--mouse"]);
+
+        assert!(command("").is_none());
+        assert!(command("  ").is_none());
+        assert!(command("cat").is_none());
+    }
+
+    #[test]
+    fn test_closed_pager() {
+        // すぐに終了するページャーに書き込んでもエラーにしない
+        let mut child = command("true").unwrap().spawn().unwrap();
+        let stdin = child.stdin.take().map(BufWriter::new);
+        let mut pager = Pager { child, stdin };
+        pager.child.wait().unwrap();
+        for _ in 0..100 {
+            pager.write_all(&[b'x'; 1024]).unwrap();
+        }
+        pager.flush().unwrap();
+    }
+}
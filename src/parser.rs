@@ -1,13 +1,560 @@
 use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
 
 use crate::{
-    json::{JsonArray, JsonObject, JsonValue},
-    lexer::{Lexer, Token},
+    json::{push_escaped_string, JsonArray, JsonObject, JsonValue},
+    lexer::{identifier_len, LexError, LexErrorKind, Lexer, Spanned, Token},
+    number::Number,
 };
 
 pub struct Parser<'a> {
-    lexer: Lexer<'a>,
-    current_token: Option<Token>,
+    pub(crate) lexer: Lexer<'a>,
+    pub(crate) current_token: Option<Token<'a>>,
+    lex_error: Option<LexError>, // 現在のトークンを読み取れなかった原因 (入力の終わりの場合は None)
+    recover: bool,               // よくある誤りから回復しながらパースするかどうか
+    trailing_commas: bool,       // 閉じ括弧の直前の余分な , を誤りとせずに受け付けるかどうか
+    warnings: Vec<ParseError>,   // 回復した誤り
+    collect: bool,               // 誤りの後も読み進めてすべての誤りを集めるかどうか
+    errors: Vec<ParseError>,     // 集めた誤り
+    duplicates: Vec<DuplicateKey>, // オブジェクトのキーの重複
+    limits: Limits,              // 入力の大きさの上限
+    nodes: usize,                // これまでに読み取った値の数
+    depth: usize,                // 読み取り中のオブジェクトや配列の入れ子の深さ
+    previous_end: usize,         // 直前に読み終えたトークンの終了位置
+}
+
+/**
+ * 入れ子の深さの既定の上限
+ * オブジェクトや配列を再帰してパースや整形をするため、深すぎる入力でスタックを使い果たさないよう既定でも制限する
+ */
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/**
+ * 信頼できない入力をパースする際の、入力の大きさの上限 (None は上限なし)
+ * 上限を超えた場合は、その時点でパースを打ち切ってエラーにする
+ * 既定では入れ子の深さだけを DEFAULT_MAX_DEPTH に制限する
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub max_input_size: Option<usize>,    // 入力全体のバイト数
+    pub max_string_length: Option<usize>, // 文字列 (キーを含む) のエスケープを解いた後のバイト数
+    pub max_elements: Option<usize>,      // 1つの配列の要素数や、1つのオブジェクトのメンバー数
+    pub max_nodes: Option<usize>,         // 入力全体に含まれる値の数 (オブジェクトや配列自体も1つと数える)
+    pub max_depth: Option<usize>,         // オブジェクトや配列の入れ子の深さ (最上位のオブジェクトや配列が1)
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        return Limits {
+            max_input_size: None,
+            max_string_length: None,
+            max_elements: None,
+            max_nodes: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+        };
+    }
+}
+
+impl Limits {
+    /**
+     * 入れ子の深さ depth のオブジェクトや配列の中に、さらにオブジェクトや配列を開けることを確かめる
+     * position は開こうとしているオブジェクトや配列の位置
+     */
+    pub(crate) fn check_depth(&self, depth: usize, position: usize) -> Result<(), ParseError> {
+        return match self.max_depth {
+            Some(max) if depth >= max => Err(ParseError {
+                kind: ParseErrorKind::TooDeep,
+                position,
+            }),
+            _ => Ok(()),
+        };
+    }
+}
+
+/**
+ * Parser が読み取ったトークンから値を組み立てる方法
+ * JsonValue や BorrowedValue など、組み立てる値の型が異なっても同じパース処理 (回復や上限の確認を含む) を使えるようにする
+ */
+pub(crate) trait ValueBuilder<'a> {
+    type Value; // 組み立てる値
+    type Object; // 組み立て中のオブジェクト
+    type Array; // 組み立て中の配列
+
+    fn string(&self, string: &Cow<'a, str>) -> Self::Value;
+    fn number(&self, number: &Number) -> Self::Value;
+    fn bool(&self, bool: bool) -> Self::Value;
+    fn null(&self) -> Self::Value;
+    fn object(&self) -> Self::Object;
+
+    /**
+     * オブジェクトにメンバーを追加する (key_position はキーの位置)
+     * 同じキーのメンバーがすでにある場合は、そのキーを返す
+     */
+    fn insert(
+        &self,
+        object: &mut Self::Object,
+        key: Cow<'a, str>,
+        key_position: usize,
+        value: Self::Value,
+    ) -> Option<String>;
+    fn finish_object(&self, object: Self::Object) -> Self::Value;
+    fn array(&self) -> Self::Array;
+    fn push(&self, array: &mut Self::Array, value: Self::Value);
+    fn finish_array(&self, array: Self::Array) -> Self::Value;
+
+    /**
+     * 読み終えた値に入力中の範囲 (start..end、バイト単位) を付ける
+     * 構文木のように位置を保持する値のためのもので、既定では何もしない
+     */
+    fn spanned(&self, value: Self::Value, _start: usize, _end: usize) -> Self::Value {
+        return value;
+    }
+}
+
+/**
+ * 文字列をすべて所有する JsonValue を組み立てる
+ */
+struct OwnedBuilder;
+
+impl<'a> ValueBuilder<'a> for OwnedBuilder {
+    type Value = JsonValue;
+    type Object = JsonObject;
+    type Array = JsonArray;
+
+    fn string(&self, string: &Cow<'a, str>) -> JsonValue {
+        return JsonValue::String(string.to_string());
+    }
+
+    fn number(&self, number: &Number) -> JsonValue {
+        return JsonValue::Number(number.clone());
+    }
+
+    fn bool(&self, bool: bool) -> JsonValue {
+        return JsonValue::Bool(bool);
+    }
+
+    fn null(&self) -> JsonValue {
+        return JsonValue::Null;
+    }
+
+    fn object(&self) -> JsonObject {
+        return IndexMap::new();
+    }
+
+    fn insert(
+        &self,
+        object: &mut JsonObject,
+        key: Cow<'a, str>,
+        _key_position: usize,
+        value: JsonValue,
+    ) -> Option<String> {
+        let (index, replaced) = object.insert_full(key.into_owned(), value);
+        return replaced.map(|_| object.get_index(index).unwrap().0.clone());
+    }
+
+    fn finish_object(&self, object: JsonObject) -> JsonValue {
+        return JsonValue::Object(object);
+    }
+
+    fn array(&self) -> JsonArray {
+        return Vec::new();
+    }
+
+    fn push(&self, array: &mut JsonArray, value: JsonValue) {
+        array.push(value);
+    }
+
+    fn finish_array(&self, array: JsonArray) -> JsonValue {
+        return JsonValue::Array(array);
+    }
+}
+
+/**
+ * validate で読み進めている途中のオブジェクトや配列
+ */
+enum ValidateFrame<'a> {
+    Object {
+        start: usize,                // { の位置
+        keys: HashSet<Cow<'a, str>>, // これまでに読み終えたメンバーのキー (重複を見つけるため入力から借用して控える)
+        key: Cow<'a, str>,           // 読み進めているメンバーのキー
+        key_position: usize,         // 読み進めているメンバーのキーの位置
+    },
+    Array {
+        count: usize,    // これまでに読み終えた要素の数
+        position: usize, // 読み進めている要素の位置
+    },
+}
+
+/**
+ * パースに失敗した原因
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedEof,          // 値の途中で入力が終わった
+    InvalidToken,           // 字句解析できない文字やリテラル
+    ExpectedValue,          // 値が来るべき位置に値以外のトークンがある
+    ExpectedKey,            // オブジェクトのキーが文字列ではない
+    ExpectedColon,          // キーの後に : がない
+    ExpectedCommaOrBrace,   // オブジェクトのメンバーの後に , も } もない
+    ExpectedCommaOrBracket, // 配列の要素の後に , も ] もない
+    TrailingCharacters,     // 値の後に余分な入力がある
+    UnexpectedBom,          // 入力の先頭以外に BOM がある
+    InvalidEscape,          // 文字列中の不正なエスケープシーケンス
+    LoneSurrogate,          // 対になるサロゲートのない \u エスケープ
+    UnterminatedString,     // 閉じる `"` がないまま入力が終わった文字列
+    ControlCharacter,       // 文字列中のエスケープされていない制御文字
+    InvalidNumber,          // JSON の文法に合わない数値
+    MissingComma,           // 要素やメンバーの間に , がない (回復した場合のみ)
+    TrailingComma,          // 閉じ括弧の直前に余分な , がある (回復した場合のみ)
+    UnquotedKey,            // オブジェクトのキーがクォートされていない (回復した場合のみ)
+    UnclosedObject,         // 閉じる } がないまま入力が終わった (回復した場合のみ)
+    UnclosedArray,          // 閉じる ] がないまま入力が終わった (回復した場合のみ)
+    InputTooLarge,          // 入力が上限のバイト数を超えている
+    StringTooLong,          // 文字列が上限のバイト数を超えている
+    TooManyElements,        // 配列の要素数やオブジェクトのメンバー数が上限を超えている
+    TooManyNodes,           // 入力全体の値の数が上限を超えている
+    TooDeep,                // オブジェクトや配列の入れ子が上限の深さを超えている
+}
+
+/**
+ * オブジェクトのキーの重複
+ * パース結果では後に現れた値で上書きされる
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateKey {
+    pub key: String,   // 重複したキー
+    pub first: usize,  // 最初に現れた位置 (バイト単位)
+    pub second: usize, // 重複して現れた位置 (バイト単位)
+}
+
+/**
+ * パースエラー
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind, // エラーの原因
+    pub position: usize,      // エラーが発生した入力中の位置 (バイト単位)
+}
+
+impl From<LexErrorKind> for ParseErrorKind {
+    fn from(kind: LexErrorKind) -> Self {
+        return match kind {
+            LexErrorKind::InvalidToken => ParseErrorKind::InvalidToken,
+            LexErrorKind::UnexpectedBom => ParseErrorKind::UnexpectedBom,
+            LexErrorKind::InvalidEscape => ParseErrorKind::InvalidEscape,
+            LexErrorKind::LoneSurrogate => ParseErrorKind::LoneSurrogate,
+            LexErrorKind::UnterminatedString => ParseErrorKind::UnterminatedString,
+            LexErrorKind::ControlCharacter => ParseErrorKind::ControlCharacter,
+            LexErrorKind::InvalidNumber => ParseErrorKind::InvalidNumber,
+        };
+    }
+}
+
+impl ParseErrorKind {
+    /**
+     * エラーの原因を説明するメッセージを返す
+     */
+    pub fn message(&self) -> &'static str {
+        return match self {
+            ParseErrorKind::UnexpectedEof => "予期しない入力の終わりです",
+            ParseErrorKind::InvalidToken => "不正なトークンです",
+            ParseErrorKind::ExpectedValue => "値が必要です",
+            ParseErrorKind::ExpectedKey => "オブジェクトのキーには文字列が必要です",
+            ParseErrorKind::ExpectedColon => "キーの後には `:` が必要です",
+            ParseErrorKind::ExpectedCommaOrBrace => "オブジェクトのメンバーの後には `,` か `}` が必要です",
+            ParseErrorKind::ExpectedCommaOrBracket => "配列の要素の後には `,` か `]` が必要です",
+            ParseErrorKind::TrailingCharacters => "値の後に余分な文字があります",
+            ParseErrorKind::UnexpectedBom => "入力の途中に BOM (U+FEFF) があります",
+            ParseErrorKind::InvalidEscape => "不正なエスケープシーケンスです",
+            ParseErrorKind::LoneSurrogate => "対になるサロゲートのない \\u エスケープです",
+            ParseErrorKind::UnterminatedString => "文字列が閉じられていません",
+            ParseErrorKind::ControlCharacter => "文字列中に制御文字がそのまま含まれています",
+            ParseErrorKind::InvalidNumber => "不正な数値です",
+            ParseErrorKind::MissingComma => "`,` が抜けています",
+            ParseErrorKind::TrailingComma => "閉じ括弧の直前に余分な `,` があります",
+            ParseErrorKind::UnquotedKey => "オブジェクトのキーが `\"` で囲まれていません",
+            ParseErrorKind::UnclosedObject => "閉じる `}` がないまま入力が終わりました",
+            ParseErrorKind::UnclosedArray => "閉じる `]` がないまま入力が終わりました",
+            ParseErrorKind::InputTooLarge => "入力が上限の大きさを超えています",
+            ParseErrorKind::StringTooLong => "文字列が上限の長さを超えています",
+            ParseErrorKind::TooManyElements => "配列の要素数かオブジェクトのメンバー数が上限を超えています",
+            ParseErrorKind::TooManyNodes => "値の数が上限を超えています",
+            ParseErrorKind::TooDeep => "オブジェクトや配列の入れ子が上限の深さを超えています",
+        };
+    }
+
+    /**
+     * エラーの原因を説明する英語のメッセージを返す (CLI が英語で表示するメッセージと同じ)
+     */
+    pub fn english_message(&self) -> &'static str {
+        return match self {
+            ParseErrorKind::UnexpectedEof => "unexpected end of input",
+            ParseErrorKind::InvalidToken => "invalid token",
+            ParseErrorKind::ExpectedValue => "expected a value",
+            ParseErrorKind::ExpectedKey => "expected a string as an object key",
+            ParseErrorKind::ExpectedColon => "expected `:` after the key",
+            ParseErrorKind::ExpectedCommaOrBrace => "expected `,` or `}` after an object member",
+            ParseErrorKind::ExpectedCommaOrBracket => "expected `,` or `]` after an array element",
+            ParseErrorKind::TrailingCharacters => "unexpected characters after the value",
+            ParseErrorKind::UnexpectedBom => "BOM (U+FEFF) in the middle of the input",
+            ParseErrorKind::InvalidEscape => "invalid escape sequence in a string",
+            ParseErrorKind::LoneSurrogate => "`\\u` escape of a surrogate without its pair",
+            ParseErrorKind::UnterminatedString => "string is not closed with `\"`",
+            ParseErrorKind::ControlCharacter => "unescaped control character in a string",
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::MissingComma => "missing `,`",
+            ParseErrorKind::TrailingComma => "extra `,` before the closing bracket",
+            ParseErrorKind::UnquotedKey => "object key is not enclosed in `\"`",
+            ParseErrorKind::UnclosedObject => "input ended without a closing `}`",
+            ParseErrorKind::UnclosedArray => "input ended without a closing `]`",
+            ParseErrorKind::InputTooLarge => "input is larger than --max-input-size",
+            ParseErrorKind::StringTooLong => "string is longer than --max-string-length",
+            ParseErrorKind::TooManyElements => "array or object has more elements than --max-elements",
+            ParseErrorKind::TooManyNodes => "input has more values than --max-nodes",
+            ParseErrorKind::TooDeep => "objects and arrays are nested deeper than --max-depth",
+        };
+    }
+
+    /**
+     * 入力の大きさの上限 (Limits) を超えたことによるエラーかどうか
+     */
+    pub fn is_limit(&self) -> bool {
+        return matches!(
+            self,
+            ParseErrorKind::InputTooLarge
+                | ParseErrorKind::StringTooLong
+                | ParseErrorKind::TooManyElements
+                | ParseErrorKind::TooManyNodes
+                | ParseErrorKind::TooDeep
+        );
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} (位置 {})", self.kind.message(), self.position);
+    }
+}
+
+impl Error for ParseError {}
+
+/**
+ * よくある誤りに対する修正の提案
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub kind: SuggestionKind, // 誤りの種類
+    pub message: String,      // 修正方法の説明
+    pub fixable: bool,        // parse_recovering で直せる誤りかどうか
+}
+
+/**
+ * 修正を提案できるよくある誤りの種類
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuggestionKind {
+    TrailingComma,       // 閉じ括弧の直前の余分な ,
+    SingleQuote,         // ' で囲まれた文字列
+    UnquotedKey(String), // " で囲まれていないキー
+    Literal {
+        found: String,          // 入力中のリテラル (True や None など)
+        expected: &'static str, // 正しいリテラル
+    },
+}
+
+impl SuggestionKind {
+    /**
+     * 修正方法を説明するメッセージを返す
+     */
+    pub fn message(&self) -> String {
+        return match self {
+            SuggestionKind::TrailingComma => "閉じ括弧の直前の `,` を取り除いてください".to_string(),
+            SuggestionKind::SingleQuote => "文字列は `'` ではなく `\"` で囲んでください".to_string(),
+            SuggestionKind::UnquotedKey(key) => {
+                format!("オブジェクトのキーは `\"{}\"` のように `\"` で囲んでください", key)
+            }
+            SuggestionKind::Literal { found, expected } => format!(
+                "`{}` ではなく `{}` と書いてください (JSON のリテラルは小文字です)",
+                found, expected
+            ),
+        };
+    }
+}
+
+/**
+ * エラー箇所の前後に表示する最大文字数 (1行が長い入力で行全体を表示しないようにする)
+ */
+const SNIPPET_CONTEXT: usize = 40;
+
+impl ParseError {
+    /**
+     * エラーの位置を 1 始まりの行番号と列番号 (文字単位) に変換する
+     */
+    pub fn line_column(&self, input: &str) -> (usize, usize) {
+        return line_column(input, self.position);
+    }
+
+    /**
+     * CLI が英語で表示するエラーメッセージの1行目と同じ、原因と行番号・列番号を含む英語のメッセージを返す
+     * 表示する言語を選べない WebAssembly や C の API から返すエラーに使う
+     */
+    pub fn english_message(&self, input: &str) -> String {
+        let (line, column) = self.line_column(input);
+        return format!(
+            "failed to parse JSON: {} (line {}, column {})",
+            self.kind.english_message(),
+            line,
+            column
+        );
+    }
+
+    /**
+     * エラー箇所の入力から、よくある誤り (シングルクォートの文字列、Python 風の True/None、
+     * 閉じ括弧の直前の余分な `,`、クォートのないキー) を見分けて修正方法を提案する
+     */
+    pub fn suggestion(&self, input: &str) -> Option<Suggestion> {
+        let position = self.position.min(input.len());
+        let rest = &input[position..];
+        let suggest = |kind: SuggestionKind, fixable: bool| {
+            let message = kind.message();
+            return Some(Suggestion { kind, message, fixable });
+        };
+
+        // 閉じ括弧の直前の余分な ,
+        if matches!(self.kind, ParseErrorKind::ExpectedKey | ParseErrorKind::ExpectedValue)
+            && rest.starts_with(['}', ']'])
+            && input[..position].trim_end().ends_with(',')
+        {
+            return suggest(SuggestionKind::TrailingComma, true);
+        }
+
+        // シングルクォートの文字列
+        if rest.starts_with('\'') {
+            return suggest(SuggestionKind::SingleQuote, false);
+        }
+
+        let identifier = &rest[..identifier_len(rest)];
+        if identifier.is_empty() {
+            return None;
+        }
+
+        // クォートのないキー
+        if rest[identifier.len()..].trim_start().starts_with(':') {
+            return suggest(SuggestionKind::UnquotedKey(identifier.to_string()), true);
+        }
+
+        // Python 風のリテラル (True, False, None) や大文字を含むリテラル
+        let literal = match identifier {
+            "None" => "null",
+            _ if identifier.eq_ignore_ascii_case("null") => "null",
+            _ if identifier.eq_ignore_ascii_case("true") => "true",
+            _ if identifier.eq_ignore_ascii_case("false") => "false",
+            _ => return None,
+        };
+        return suggest(
+            SuggestionKind::Literal {
+                found: identifier.to_string(),
+                expected: literal,
+            },
+            false,
+        );
+    }
+
+    /**
+     * エラーの内容に、入力の該当行とエラー箇所を指すキャレットを添えた複数行のメッセージを返す
+     * 例:
+     *   オブジェクトのメンバーの後には `,` か `}` が必要です (2 行 10 列目)
+     *    2 |   "a": 1 "b": 2
+     *      |          ^
+     */
+    pub fn display_with_source(&self, input: &str) -> String {
+        return display_at(input, self.position, self.kind.message());
+    }
+}
+
+impl DuplicateKey {
+    /**
+     * 重複を説明するメッセージを返す
+     */
+    pub fn message(&self, input: &str) -> String {
+        let (line, column) = line_column(input, self.first);
+        let mut key = String::new();
+        push_escaped_string(&mut key, &self.key);
+        return format!(
+            "キー {} が重複しています ({} 行 {} 列目の値は上書きされます)",
+            key, line, column
+        );
+    }
+
+    /**
+     * 重複の説明に、重複して現れた箇所の行とキャレットを添えた複数行のメッセージを返す
+     */
+    pub fn display_with_source(&self, input: &str) -> String {
+        return display_at(input, self.second, &self.message(input));
+    }
+}
+
+/**
+ * 位置を 1 始まりの行番号と列番号 (文字単位) に変換する
+ */
+pub fn line_column(input: &str, position: usize) -> (usize, usize) {
+    let before = &input[..position.min(input.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+    return (line, column);
+}
+
+/**
+ * メッセージに、入力の該当行と位置を指すキャレットを添えた複数行の文字列を返す
+ */
+fn display_at(input: &str, position: usize, message: &str) -> String {
+    let (line, column) = line_column(input, position);
+    return format!(
+        "{} ({} 行 {} 列目)\n{}",
+        message,
+        line,
+        column,
+        source_snippet(input, position)
+    );
+}
+
+/**
+ * 入力の位置を含む行と、その位置を指すキャレットの2行からなる抜粋を返す
+ * 例:
+ *    2 |   "a": 1 "b": 2
+ *      |          ^
+ */
+pub fn source_snippet(input: &str, position: usize) -> String {
+    let (line, column) = line_column(input, position);
+    let line_start = input[..position.min(input.len())].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input[line_start..].find('\n').map_or(input.len(), |i| line_start + i);
+    let text: Vec<char> = input[line_start..line_end].trim_end_matches('\r').chars().collect();
+
+    // エラー箇所の前後の一定の文字数だけを切り出す
+    let start = (column - 1).saturating_sub(SNIPPET_CONTEXT);
+    let end = (column - 1 + SNIPPET_CONTEXT).min(text.len());
+    let mut snippet: String = text[start..end].iter().collect();
+    let mut caret: String = text[start..column - 1]
+        .iter()
+        .map(|&ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+        caret.insert(0, ' ');
+    }
+    if end < text.len() {
+        snippet.push('…');
+    }
+    caret.push('^');
+
+    let number = line.to_string();
+    let margin = " ".repeat(number.len());
+    return format!(" {} | {}\n {} | {}", number, snippet, margin, caret);
 }
 
 impl<'a> Parser<'a> {
@@ -16,52 +563,147 @@ impl<'a> Parser<'a> {
      */
     pub fn new(lexer: Lexer<'a>) -> Self {
         let mut parser = Parser {
-            lexer: lexer,
+            lexer,
             current_token: None,
+            lex_error: None,
+            recover: false,
+            trailing_commas: false,
+            warnings: Vec::new(),
+            collect: false,
+            errors: Vec::new(),
+            duplicates: Vec::new(),
+            limits: Limits::default(),
+            nodes: 0,
+            depth: 0,
+            previous_end: 0,
         };
         parser.next_token();
         return parser;
     }
 
+    /**
+     * 入力の大きさに上限を設けた Parser にする (parse、parse_recovering、collect_errors で確かめる)
+     */
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        return self;
+    }
+
+    /**
+     * 入れ子の深さ depth のオブジェクトや配列の中から読み始める Parser にする
+     * 文書の一部だけをパースし直す場合に、深さの上限を文書全体をパースした場合と同じに保つ
+     */
+    pub(crate) fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        return self;
+    }
+
+    /**
+     * 入力全体を1つの JSON 値としてパースする
+     * 値の後に余分な入力が残っている場合はエラーになる
+     */
+    pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+        return self.parse_with(&OwnedBuilder);
+    }
+
+    /**
+     * 入力全体を1つの値としてパースし、builder で組み立てた値を返す
+     * 組み立てる値の型によらず、回復や上限の確認は parse と同じになる
+     */
+    pub(crate) fn parse_with<B: ValueBuilder<'a>>(&mut self, builder: &B) -> Result<B::Value, ParseError> {
+        self.check_input_size()?;
+        let value = self.parse_value(builder)?;
+        self.finish()?;
+        return Ok(value);
+    }
+
+    /**
+     * 閉じ括弧の直前の余分な , ([1, 2,] など) を受け付ける Parser にする (JSONC 向け)
+     * 回復する場合と異なり、誤りとして報告しない
+     */
+    pub fn with_trailing_commas(mut self) -> Self {
+        self.trailing_commas = true;
+        return self;
+    }
+
+    /**
+     * 入力全体を、よくある誤り (, の抜けや余分な ,、クォートのないキー、閉じ括弧のないまま終わった入力) から
+     * 回復しながら1つの JSON 値としてパースし、値と回復した誤りの一覧を返す
+     * 回復できない誤りがある場合はエラーになる
+     */
+    pub fn parse_recovering(&mut self) -> Result<(JsonValue, Vec<ParseError>), ParseError> {
+        self.recover = true;
+        let value = self.parse()?;
+        return Ok((value, std::mem::take(&mut self.warnings)));
+    }
+
+    /**
+     * よくある誤りから回復しながらパースする Parser にする (parse_recovering と同じ回復を collect_errors でも行う)
+     */
+    pub fn with_recovery(mut self) -> Self {
+        self.recover = true;
+        return self;
+    }
+
+    /**
+     * 入力全体をパースし、最初の誤りで止めずに見つかった誤りをすべて返す (正しい JSON であれば空)
+     * 誤りの後は、同じ階層の次の , か閉じ括弧まで読み飛ばしてパースを続ける
+     * 最初の誤りは parse が返すエラーと同じになる
+     */
+    pub fn collect_errors(&mut self) -> Vec<ParseError> {
+        self.collect = true;
+        if let Err(error) = self
+            .check_input_size()
+            .and_then(|_| self.parse_value(&OwnedBuilder))
+            .and_then(|_| self.finish())
+        {
+            self.push_error(error);
+        }
+        return std::mem::take(&mut self.errors);
+    }
+
     /**
      * JSON値をパースする
      */
-    pub fn parse(&mut self) -> Option<JsonValue> {
-        match &self.current_token {
-            Some(Token::LeftBrace) => self.parse_object(),  // { がオブジェクトの開始
-            Some(Token::LeftBracket) => self.parse_array(), // [ が配列の開始
-            Some(Token::String(string)) => {
-                let cloned_string = string.clone();
-                self.next_token();
-                Some(JsonValue::String(cloned_string))
-            }
-            Some(Token::Number(number)) => {
-                let copied_number = *number;
-                self.next_token();
-                Some(JsonValue::Number(copied_number))
-            }
-            Some(Token::True) => {
-                self.next_token();
-                Some(JsonValue::True)
-            }
-            Some(Token::False) => {
-                self.next_token();
-                Some(JsonValue::False)
+    fn parse_value<B: ValueBuilder<'a>>(&mut self, builder: &B) -> Result<B::Value, ParseError> {
+        self.count_node()?;
+        let start = self.lexer.token_start();
+        let value = match &self.current_token {
+            // { がオブジェクトの開始、[ が配列の開始
+            Some(token @ (Token::LeftBrace | Token::LeftBracket)) => {
+                let object = *token == Token::LeftBrace;
+                self.limits.check_depth(self.depth, start)?;
+                self.depth += 1;
+                let value = if object {
+                    self.parse_object(builder)
+                } else {
+                    self.parse_array(builder)
+                };
+                self.depth -= 1;
+                return Ok(builder.spanned(value?, start, self.previous_end));
             }
-            Some(Token::Null) => {
-                self.next_token();
-                Some(JsonValue::Null)
+            Some(Token::String(string)) => {
+                self.check_string_length(string)?;
+                builder.string(string)
             }
-            _ => None,
-        }
+            Some(Token::Number(number)) => builder.number(number),
+            Some(Token::True) => builder.bool(true),
+            Some(Token::False) => builder.bool(false),
+            Some(Token::Null) => builder.null(),
+            _ => return Err(self.error(ParseErrorKind::ExpectedValue)),
+        };
+        self.next_token();
+        return Ok(builder.spanned(value, start, self.previous_end));
     }
 
     /**
      * オブジェクトをパースする
      * 現在のトークンが { であることが前提
      */
-    fn parse_object(&mut self) -> Option<JsonValue> {
-        let mut object: JsonObject = IndexMap::new();
+    fn parse_object<B: ValueBuilder<'a>>(&mut self, builder: &B) -> Result<B::Value, ParseError> {
+        let mut object = builder.object();
+        let mut count = 0;
+        let start = self.lexer.token_start();
 
         // 先頭の { を読み飛ばす
         self.next_token();
@@ -69,47 +711,94 @@ impl<'a> Parser<'a> {
         // すぐに } が来る場合は空オブジェクトとして即終了
         if let Some(Token::RightBrace) = self.current_token {
             self.next_token();
-            return Some(JsonValue::Object(object));
+            return Ok(builder.finish_object(object));
         }
 
         // キーバリューのペアの数だけ繰り返す
         loop {
-            // 文字列のキーを控えておく
-            let key = if let Some(Token::String(s)) = &self.current_token {
-                s.clone()
-            } else {
-                return None;
-            };
-            self.next_token();
+            if self.recover_eof(ParseErrorKind::UnclosedObject) {
+                break;
+            }
 
-            // : (読み飛ばす)
-            self.next_token();
+            let key_position = self.lexer.token_start();
+            let (key, value) = match self.parse_member(builder) {
+                Ok(member) => member,
+                Err(error) => {
+                    if self.skip_invalid(error, Token::RightBrace)? {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            match builder.insert(&mut object, key, key_position, value) {
+                Some(key) => self.duplicates.push(DuplicateKey {
+                    first: self.first_key_position(start, &key).unwrap_or(start),
+                    key,
+                    second: key_position,
+                }),
+                None => count += 1,
+            }
+            self.check_elements(count, key_position)?;
 
-            // value (値がオブジェクトや配列である場合のためにここで再帰する)
-            if let Some(value) = self.parse() {
-                object.insert(key, value);
+            if self.recover_eof(ParseErrorKind::UnclosedObject) {
+                break;
             }
 
             // , なら次のキーバリューに続き } が来たらループ終了
             match &self.current_token {
                 Some(Token::Comma) => {
-                    self.next_token();
+                    if self.skip_comma(Token::RightBrace) {
+                        break;
+                    }
                 }
                 Some(Token::RightBrace) => {
                     self.next_token();
                     break;
                 }
-                _ => return None,
+                // 回復する場合、次のキーが始まっていれば , の抜けとみなす
+                Some(Token::String(_)) if self.recover => self.warn(ParseErrorKind::MissingComma),
+                _ if self.recover && self.at_identifier() => self.warn(ParseErrorKind::MissingComma),
+                _ => {
+                    if self.skip_invalid(self.error(ParseErrorKind::ExpectedCommaOrBrace), Token::RightBrace)? {
+                        break;
+                    }
+                }
             }
         }
-        return Some(JsonValue::Object(object));
+        return Ok(builder.finish_object(object));
+    }
+
+    /**
+     * オブジェクトのメンバー (キーと値のペア) をパースする
+     */
+    fn parse_member<B: ValueBuilder<'a>>(&mut self, builder: &B) -> Result<(Cow<'a, str>, B::Value), ParseError> {
+        // 文字列のキーを控えておく
+        let key = if let Some(Token::String(s)) = &self.current_token {
+            self.check_string_length(s)?;
+            let key = s.clone();
+            self.next_token();
+            key
+        } else {
+            self.recover_key()?
+        };
+
+        // : (読み飛ばす)
+        if self.current_token != Some(Token::Colon) {
+            return Err(self.error(ParseErrorKind::ExpectedColon));
+        }
+        self.next_token();
+
+        // value (値がオブジェクトや配列である場合のためにここで再帰する)
+        let value = self.parse_value(builder)?;
+        return Ok((key, value));
     }
 
     /**
      * 配列をパースする
      */
-    fn parse_array(&mut self) -> Option<JsonValue> {
-        let mut array: JsonArray = Vec::new();
+    fn parse_array<B: ValueBuilder<'a>>(&mut self, builder: &B) -> Result<B::Value, ParseError> {
+        let mut array = builder.array();
+        let mut count = 0;
 
         // 先頭の [ を読み飛ばす
         self.next_token();
@@ -117,37 +806,472 @@ impl<'a> Parser<'a> {
         // すぐに ] が来る場合は空配列として即終了
         if let Some(Token::RightBracket) = self.current_token {
             self.next_token();
-            return Some(JsonValue::Array(array));
+            return Ok(builder.finish_array(array));
         }
 
         // 配列の要素の数だけループする
         loop {
+            if self.recover_eof(ParseErrorKind::UnclosedArray) {
+                break;
+            }
+
             // value (値がオブジェクトや配列である場合のためにここで再帰する)
-            if let Some(value) = self.parse() {
-                array.push(value);
+            let position = self.lexer.token_start();
+            match self.parse_value(builder) {
+                Ok(value) => {
+                    builder.push(&mut array, value);
+                    count += 1;
+                    self.check_elements(count, position)?;
+                }
+                Err(error) => {
+                    if self.skip_invalid(error, Token::RightBracket)? {
+                        break;
+                    }
+                    continue;
+                }
+            }
+
+            if self.recover_eof(ParseErrorKind::UnclosedArray) {
+                break;
             }
 
             // , なら次の要素に続き ] が来たらループ終了
             match &self.current_token {
                 Some(Token::Comma) => {
-                    self.next_token();
+                    if self.skip_comma(Token::RightBracket) {
+                        break;
+                    }
                 }
                 Some(Token::RightBracket) => {
                     self.next_token();
                     break;
                 }
-                _ => return None,
+                // 回復する場合、次の値が始まっていれば , の抜けとみなす
+                Some(
+                    Token::LeftBrace
+                    | Token::LeftBracket
+                    | Token::String(_)
+                    | Token::Number(_)
+                    | Token::True
+                    | Token::False
+                    | Token::Null,
+                ) if self.recover => self.warn(ParseErrorKind::MissingComma),
+                _ => {
+                    if self.skip_invalid(self.error(ParseErrorKind::ExpectedCommaOrBracket), Token::RightBracket)? {
+                        break;
+                    }
+                }
             }
         }
 
-        return Some(JsonValue::Array(array));
+        return Ok(builder.finish_array(array));
+    }
+
+    /**
+     * start の位置から始まるオブジェクトの直下で、最初に key が現れた位置を探す
+     * キーの重複は稀なため、キーごとの位置は保持せずに重複を見つけたときだけ読み直す
+     */
+    fn first_key_position(&self, start: usize, key: &str) -> Option<usize> {
+        let mut depth = 0;
+        let mut key_expected = false;
+        for Spanned { value: token, span } in self.lexer.relex_from(start).filter_map(Result::ok) {
+            match &token {
+                Token::LeftBrace | Token::LeftBracket => depth += 1,
+                Token::RightBrace | Token::RightBracket => depth -= 1,
+                Token::String(string) if key_expected && string == key => return Some(span.start),
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+            key_expected = depth == 1 && matches!(token, Token::LeftBrace | Token::Comma);
+        }
+        return None;
+    }
+
+    /**
+     * パース中に見つかったオブジェクトのキーの重複を返す (値は後に現れたもので上書きされている)
+     */
+    pub fn duplicate_keys(&self) -> &[DuplicateKey] {
+        return &self.duplicates;
+    }
+
+    /**
+     * オブジェクトのメンバーや配列の要素の誤りを処理する
+     * 誤りを集めない場合はそのままエラーにする
+     * 集める場合は誤りを記録し、同じ階層の次の , か閉じ括弧まで読み飛ばして、オブジェクトや配列が終わったかどうかを返す
+     * 閉じ括弧 close と対応しない閉じ括弧や入力の終わりに達した場合は、閉じ括弧を読み飛ばさずに終わったものとする
+     */
+    fn skip_invalid(&mut self, error: ParseError, close: Token) -> Result<bool, ParseError> {
+        // 上限を超えた場合は、誤りを集める場合でも読み進めない
+        if !self.collect || error.kind.is_limit() {
+            return Err(error);
+        }
+        self.push_error(error);
+
+        let mut depth = 0;
+        loop {
+            match &self.current_token {
+                Some(Token::LeftBrace | Token::LeftBracket) => depth += 1,
+                Some(Token::RightBrace | Token::RightBracket) if depth > 0 => depth -= 1,
+                Some(Token::Comma | Token::RightBrace | Token::RightBracket) if depth == 0 => break,
+                None if self.at_eof() => break,
+                _ => {}
+            }
+            self.next_token();
+        }
+
+        return match &self.current_token {
+            Some(Token::Comma) => {
+                self.next_token();
+                Ok(false)
+            }
+            Some(token) if *token == close => {
+                self.next_token();
+                Ok(true)
+            }
+            _ => Ok(true),
+        };
+    }
+
+    /**
+     * 集めた誤りに追加する
+     * 入力の終わりなど、同じ位置の誤りが外側の階層で繰り返し見つかった場合は最初の1件だけを残す
+     */
+    fn push_error(&mut self, error: ParseError) {
+        if self.errors.last().map(|last| last.position) != Some(error.position) {
+            self.errors.push(error);
+        }
+    }
+
+    /**
+     * 現在の , を読み飛ばす
+     * 回復する場合や余分な , を受け付ける場合、直後に閉じ括弧 close が続くときは余分な , とみなして閉じ括弧も読み飛ばし、true を返す
+     */
+    fn skip_comma(&mut self, close: Token) -> bool {
+        let comma = self.lexer.token_start();
+        self.next_token();
+        if !(self.recover || self.trailing_commas) || self.current_token.as_ref() != Some(&close) {
+            return false;
+        }
+        if !self.trailing_commas {
+            self.warnings.push(ParseError {
+                kind: ParseErrorKind::TrailingComma,
+                position: comma,
+            });
+        }
+        self.next_token();
+        return true;
+    }
+
+    /**
+     * キーの位置に文字列以外がある場合に、回復できればキーを返す
+     * 回復する場合はクォートのないキー (識別子) をそのままキーとして扱う
+     */
+    fn recover_key(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        let error = self.error(ParseErrorKind::ExpectedKey);
+        if !self.recover {
+            return Err(error);
+        }
+        let Some(identifier) = self.lexer.read_identifier(error.position) else {
+            return Err(error);
+        };
+        self.warn(ParseErrorKind::UnquotedKey);
+        self.next_token();
+        return Ok(Cow::Borrowed(identifier));
+    }
+
+    /**
+     * 現在のトークンの位置から識別子 (クォートのないキー) が始まっているかどうか
+     */
+    fn at_identifier(&self) -> bool {
+        return !matches!(self.current_token, Some(Token::String(_)))
+            && identifier_len(&self.lexer.input()[self.lexer.token_start()..]) > 0;
+    }
+
+    /**
+     * 入力全体のバイト数が上限を超えていないことを確かめる
+     */
+    fn check_input_size(&self) -> Result<(), ParseError> {
+        return match self.limits.max_input_size {
+            Some(max) if self.lexer.input().len() > max => Err(ParseError {
+                kind: ParseErrorKind::InputTooLarge,
+                position: max,
+            }),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * 現在のトークンの文字列が上限の長さを超えていないことを確かめる
+     */
+    fn check_string_length(&self, string: &str) -> Result<(), ParseError> {
+        return match self.limits.max_string_length {
+            Some(max) if string.len() > max => Err(self.error(ParseErrorKind::StringTooLong)),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * 配列の要素数やオブジェクトのメンバー数 count が上限を超えていないことを確かめる
+     * position は最後に追加した要素やメンバーの位置
+     */
+    fn check_elements(&self, count: usize, position: usize) -> Result<(), ParseError> {
+        return match self.limits.max_elements {
+            Some(max) if count > max => Err(ParseError {
+                kind: ParseErrorKind::TooManyElements,
+                position,
+            }),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * 現在のトークンから始まる値を数え、入力全体の値の数が上限を超えていないことを確かめる
+     */
+    fn count_node(&mut self) -> Result<(), ParseError> {
+        if self.current_token.is_none() {
+            return Ok(());
+        }
+        self.nodes += 1;
+        return match self.limits.max_nodes {
+            Some(max) if self.nodes > max => Err(ParseError {
+                kind: ParseErrorKind::TooManyNodes,
+                position: self.lexer.token_start(),
+            }),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * 回復する場合、閉じ括弧がないまま入力が終わっていれば誤りを記録して true を返す
+     */
+    fn recover_eof(&mut self, kind: ParseErrorKind) -> bool {
+        if !self.recover || !self.at_eof() {
+            return false;
+        }
+        self.warn(kind);
+        return true;
+    }
+
+    /**
+     * 現在のトークンの位置で回復した誤りを記録する
+     */
+    fn warn(&mut self, kind: ParseErrorKind) {
+        self.warnings.push(ParseError {
+            kind,
+            position: self.lexer.token_start(),
+        });
+    }
+
+    /**
+     * 入力全体が1つの JSON 値として正しいかどうかだけを、値を生成せずに確かめる
+     * エラーは parse と同じになり、キーの重複も parse と同様に duplicate_keys で得られる
+     * 入れ子が深い入力でもスタックを使い果たさないよう、再帰せずに読み進めているオブジェクトや配列を積んでおく
+     */
+    pub fn validate(&mut self) -> Result<(), ParseError> {
+        self.check_input_size()?;
+        let mut stack: Vec<ValidateFrame<'a>> = Vec::new();
+        loop {
+            // 値を1つ読み進める (空でないオブジェクトや配列の場合は、積んでから最初の値に進む)
+            self.count_node()?;
+            match &self.current_token {
+                Some(Token::LeftBrace) => {
+                    let start = self.lexer.token_start();
+                    self.limits.check_depth(stack.len(), start)?;
+                    self.next_token();
+                    if self.current_token != Some(Token::RightBrace) {
+                        let (key, key_position) = self.validate_key()?;
+                        stack.push(ValidateFrame::Object {
+                            start,
+                            keys: HashSet::new(),
+                            key,
+                            key_position,
+                        });
+                        continue;
+                    }
+                }
+                Some(Token::LeftBracket) => {
+                    self.limits.check_depth(stack.len(), self.lexer.token_start())?;
+                    self.next_token();
+                    if self.current_token != Some(Token::RightBracket) {
+                        let position = self.lexer.token_start();
+                        stack.push(ValidateFrame::Array { count: 0, position });
+                        continue;
+                    }
+                }
+                Some(Token::String(string)) => self.check_string_length(string)?,
+                Some(Token::Number(_) | Token::True | Token::False | Token::Null) => {}
+                _ => return Err(self.error(ParseErrorKind::ExpectedValue)),
+            }
+            self.next_token();
+
+            // 値を読み終えたら、次の値が始まるまで , や閉じ括弧を読み進める
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    return self.finish();
+                };
+                match frame {
+                    ValidateFrame::Object {
+                        start,
+                        keys,
+                        key,
+                        key_position,
+                    } => {
+                        let member_key = std::mem::take(key);
+                        if keys.contains(&member_key) {
+                            self.duplicates.push(DuplicateKey {
+                                key: member_key.to_string(),
+                                first: self.first_key_position(*start, &member_key).unwrap_or(*start),
+                                second: *key_position,
+                            });
+                        } else {
+                            keys.insert(member_key);
+                        }
+                        self.check_elements(keys.len(), *key_position)?;
+                        match &self.current_token {
+                            Some(Token::Comma) => {
+                                self.next_token();
+                                (*key, *key_position) = self.validate_key()?;
+                                break;
+                            }
+                            Some(Token::RightBrace) => self.next_token(),
+                            _ => return Err(self.error(ParseErrorKind::ExpectedCommaOrBrace)),
+                        }
+                    }
+                    ValidateFrame::Array { count, position } => {
+                        *count += 1;
+                        self.check_elements(*count, *position)?;
+                        match &self.current_token {
+                            Some(Token::Comma) => {
+                                self.next_token();
+                                *position = self.lexer.token_start();
+                                break;
+                            }
+                            Some(Token::RightBracket) => self.next_token(),
+                            _ => return Err(self.error(ParseErrorKind::ExpectedCommaOrBracket)),
+                        }
+                    }
+                }
+                stack.pop();
+            }
+        }
+    }
+
+    /**
+     * オブジェクトのメンバーのキーと : を読み進め、キーとその位置を返す
+     */
+    fn validate_key(&mut self) -> Result<(Cow<'a, str>, usize), ParseError> {
+        let key_position = self.lexer.token_start();
+        let key = match &self.current_token {
+            Some(Token::String(key)) => key.clone(),
+            _ => return Err(self.error(ParseErrorKind::ExpectedKey)),
+        };
+        self.check_string_length(&key)?;
+        self.next_token();
+
+        if self.current_token != Some(Token::Colon) {
+            return Err(self.error(ParseErrorKind::ExpectedColon));
+        }
+        self.next_token();
+        return Ok((key, key_position));
     }
 
     /**
      * 次のトークンを取得する
      */
-    fn next_token(&mut self) {
-        self.current_token = self.lexer.next_token();
+    pub(crate) fn next_token(&mut self) {
+        if self.current_token.is_some() {
+            self.previous_end = self.lexer.token_span().end;
+        }
+        (self.current_token, self.lex_error) = loop {
+            break match self.lexer.next() {
+                // コメントを読み取る Lexer の場合、コメントは読み飛ばす
+                Some(Ok(Spanned {
+                    value: Token::Comment(_),
+                    ..
+                })) => continue,
+                Some(Ok(spanned)) => (Some(spanned.value), None),
+                Some(Err(error)) => (None, Some(error)),
+                None => (None, None),
+            };
+        };
+    }
+
+    /**
+     * 入力の終わりに到達したかどうか
+     */
+    fn at_eof(&self) -> bool {
+        return self.current_token.is_none() && self.lex_error.is_none();
+    }
+
+    /**
+     * 値を読み終えた後に入力が残っていないことを確かめる
+     */
+    pub(crate) fn finish(&self) -> Result<(), ParseError> {
+        if self.at_eof() {
+            return Ok(());
+        }
+        let kind = match &self.lex_error {
+            Some(error) if error.kind == LexErrorKind::UnexpectedBom => ParseErrorKind::UnexpectedBom,
+            _ => ParseErrorKind::TrailingCharacters,
+        };
+        return Err(ParseError {
+            kind,
+            position: self.lexer.token_start(),
+        });
+    }
+
+    /**
+     * 現在のトークンの位置でエラーを生成する
+     * トークンを読み取れなかった場合は、入力の終わりか不正なトークンかを原因とする (不正なトークンの場合は不正な箇所の位置にする)
+     */
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> ParseError {
+        let (kind, position) = match (&self.current_token, &self.lex_error) {
+            (Some(_), _) => (kind, self.lexer.token_start()),
+            (None, Some(lex_error)) => (lex_error.kind.into(), lex_error.span.start),
+            (None, None) => (ParseErrorKind::UnexpectedEof, self.lexer.token_start()),
+        };
+        return ParseError { kind, position };
+    }
+}
+
+/**
+ * 入力をよくある誤りから回復しながらパースし、値と回復した誤りの一覧を返す
+ * 詳細は Parser::parse_recovering を参照
+ */
+pub fn parse_recovering(input: &str) -> Result<(JsonValue, Vec<ParseError>), ParseError> {
+    let mut parser = Parser::new(Lexer::new(input));
+    return parser.parse_recovering();
+}
+
+/**
+ * 入力をパースし、見つかった誤りをすべて返す (正しい JSON であれば空)
+ * 詳細は Parser::collect_errors を参照
+ */
+pub fn collect_errors(input: &str) -> Vec<ParseError> {
+    let mut parser = Parser::new(Lexer::new(input));
+    return parser.collect_errors();
+}
+
+/**
+ * 入力が1つの JSON 値として正しいかどうかだけを、値を生成せずに確かめる
+ * 詳細は Parser::validate を参照
+ */
+pub fn validate_str(input: &str) -> Result<(), ParseError> {
+    let mut parser = Parser::new(Lexer::new(input));
+    return parser.validate();
+}
+
+/**
+ * "...".parse::<JsonValue>() で文字列を直接パースできるようにする
+ */
+impl FromStr for JsonValue {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(Lexer::new(s));
+        return parser.parse();
     }
 }
 
@@ -170,11 +1294,11 @@ mod tests {
 
         assert_eq!(parser.current_token, Some(Token::LeftBrace));
         parser.next_token();
-        assert_eq!(parser.current_token, Some(Token::String("key".to_string())));
+        assert_eq!(parser.current_token, Some(Token::String("key".into())));
         parser.next_token();
         assert_eq!(parser.current_token, Some(Token::Colon));
         parser.next_token();
-        assert_eq!(parser.current_token, Some(Token::String("value".to_string())));
+        assert_eq!(parser.current_token, Some(Token::String("value".into())));
         parser.next_token();
         assert_eq!(parser.current_token, Some(Token::RightBrace));
         parser.next_token();
@@ -184,19 +1308,19 @@ mod tests {
     #[test]
     fn test_parse_string_simple() {
         let mut parser1 = Parser::new(Lexer::new(r#""Hello, World!""#));
-        assert_eq!(parser1.parse(), Some(JsonValue::String("Hello, World!".to_string())));
+        assert_eq!(parser1.parse(), Ok(JsonValue::String("Hello, World!".to_string())));
 
         let mut parser2 = Parser::new(Lexer::new(r#"-123.1"#));
-        assert_eq!(parser2.parse(), Some(JsonValue::Number(-123.1)));
+        assert_eq!(parser2.parse(), Ok(JsonValue::from(-123.1)));
 
         let mut parser3 = Parser::new(Lexer::new(r#"true"#));
-        assert_eq!(parser3.parse(), Some(JsonValue::True));
+        assert_eq!(parser3.parse(), Ok(JsonValue::Bool(true)));
 
         let mut parser4 = Parser::new(Lexer::new(r#"false"#));
-        assert_eq!(parser4.parse(), Some(JsonValue::False));
+        assert_eq!(parser4.parse(), Ok(JsonValue::Bool(false)));
 
         let mut parser5 = Parser::new(Lexer::new(r#"null"#));
-        assert_eq!(parser5.parse(), Some(JsonValue::Null));
+        assert_eq!(parser5.parse(), Ok(JsonValue::Null));
     }
 
     #[test]
@@ -206,17 +1330,13 @@ mod tests {
 
         let mut expected_object = IndexMap::new();
         expected_object.insert("str".to_string(), JsonValue::String("hello".to_string()));
-        expected_object.insert("num".to_string(), JsonValue::Number(-32.054));
+        expected_object.insert("num".to_string(), JsonValue::from(-32.054));
         expected_object.insert(
             "array".to_string(),
-            JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::Number(2.0),
-                JsonValue::Number(3.0),
-            ]),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::from(2.0), JsonValue::from(3.0)]),
         );
 
-        assert_eq!(object, Some(JsonValue::Object(expected_object)));
+        assert_eq!(object, Ok(JsonValue::Object(expected_object)));
     }
 
     #[test]
@@ -230,7 +1350,7 @@ mod tests {
         let mut expected_object = IndexMap::new();
         expected_object.insert("key".to_string(), JsonValue::Object(nested_object));
 
-        assert_eq!(object, Some(JsonValue::Object(expected_object)));
+        assert_eq!(object, Ok(JsonValue::Object(expected_object)));
     }
 
     #[test]
@@ -239,11 +1359,11 @@ mod tests {
         let array = parser.parse();
 
         let expected_array = vec![
-            JsonValue::Number(1.0),
-            JsonValue::Number(-2.0),
-            JsonValue::Number(0.03),
-            JsonValue::True,
-            JsonValue::False,
+            JsonValue::from(1.0),
+            JsonValue::from(-2.0),
+            JsonValue::from(0.03),
+            JsonValue::Bool(true),
+            JsonValue::Bool(false),
             JsonValue::Null,
             JsonValue::Object(IndexMap::from([(
                 "key".to_string(),
@@ -251,7 +1371,7 @@ mod tests {
             )])),
         ];
 
-        assert_eq!(array, Some(JsonValue::Array(expected_array)));
+        assert_eq!(array, Ok(JsonValue::Array(expected_array)));
     }
 
     #[test]
@@ -260,16 +1380,498 @@ mod tests {
         let array = parser.parse();
 
         let expected_array = vec![
-            JsonValue::Number(1.0),
+            JsonValue::from(1.0),
             JsonValue::Array(vec![
-                JsonValue::Number(2.0),
-                JsonValue::Array(vec![
-                    JsonValue::Number(3.0),
-                    JsonValue::Array(vec![JsonValue::Number(4.0)]),
-                ]),
+                JsonValue::from(2.0),
+                JsonValue::Array(vec![JsonValue::from(3.0), JsonValue::Array(vec![JsonValue::from(4.0)])]),
             ]),
         ];
 
-        assert_eq!(array, Some(JsonValue::Array(expected_array)));
+        assert_eq!(array, Ok(JsonValue::Array(expected_array)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            (r#""#, ParseErrorKind::UnexpectedEof, 0),
+            (r#"[1, 2"#, ParseErrorKind::UnexpectedEof, 5),
+            (r#"{"a": }"#, ParseErrorKind::ExpectedValue, 6),
+            (r#"{1: 2}"#, ParseErrorKind::ExpectedKey, 1),
+            (r#"{"a" 2}"#, ParseErrorKind::ExpectedColon, 5),
+            (r#"{"a": 1 "b": 2}"#, ParseErrorKind::ExpectedCommaOrBrace, 8),
+            (r#"[1 2]"#, ParseErrorKind::ExpectedCommaOrBracket, 3),
+            (r#"[1, tru]"#, ParseErrorKind::InvalidToken, 4),
+            (r#"[1] [2]"#, ParseErrorKind::TrailingCharacters, 4),
+            (r#"[1] ?"#, ParseErrorKind::TrailingCharacters, 4),
+            ("[1, \u{FEFF}2]", ParseErrorKind::UnexpectedBom, 4),
+            ("[1]\u{FEFF}", ParseErrorKind::UnexpectedBom, 3),
+            (r#"{"a": "x\qy"}"#, ParseErrorKind::InvalidEscape, 8),
+            (r#"["\u12"]"#, ParseErrorKind::InvalidEscape, 2),
+            (r#"["\udc00"]"#, ParseErrorKind::LoneSurrogate, 2),
+        ];
+        for (input, kind, position) in cases {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(parser.parse(), Err(ParseError { kind, position }), "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_leading_bom() {
+        let value = "\u{FEFF}[1]".parse::<JsonValue>();
+        assert_eq!(value, Ok(JsonValue::Array(vec![JsonValue::from(1.0)])));
+
+        // エラーの位置は BOM を含めた入力の先頭から数える
+        let error = "\u{FEFF}[1,".parse::<JsonValue>().unwrap_err();
+        assert_eq!(error.position, 6);
+    }
+
+    #[test]
+    fn test_line_column() {
+        let input = "{\n  \"あ\": 1 \"b\": 2\n}";
+        let error = input.parse::<JsonValue>().unwrap_err();
+        assert_eq!(error.line_column(input), (2, 10));
+    }
+
+    #[test]
+    fn test_display_with_source() {
+        let input = "{\n  \"a\": 1 \"b\": 2\n}";
+        let error = input.parse::<JsonValue>().unwrap_err();
+        assert_eq!(
+            error.display_with_source(input),
+            "オブジェクトのメンバーの後には `,` か `}` が必要です (2 行 10 列目)\n 2 |   \"a\": 1 \"b\": 2\n   |          ^"
+        );
+
+        // 長い行はエラー箇所の前後だけを表示する
+        let input = format!("[{}1 2]", "1, ".repeat(30));
+        let error = input.parse::<JsonValue>().unwrap_err();
+        let message = error.display_with_source(&input);
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines[1], format!(" 1 | …{}", &input[53..]));
+        assert_eq!(lines[2], format!("   | {}^", " ".repeat(41)));
+
+        // 入力の終わりでのエラーは行末を指す
+        let error = "[1,".parse::<JsonValue>().unwrap_err();
+        assert!(error.display_with_source("[1,").ends_with(" 1 | [1,\n   |    ^"));
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let input = "{\n  \"a\": 1,\n  \"b\": {\"a\": [{\"a\": 0}], \"c\": 2, \"c\": 3},\n  \"a\": 4\n}";
+        let mut parser = Parser::new(Lexer::new(input));
+        let value = parser.parse().unwrap();
+
+        // 後に現れた値で上書きされ、キーの順序は最初に現れた位置のまま
+        assert_eq!(value.to_string(), r#"{"a":4,"b":{"a":[{"a":0}],"c":3}}"#);
+
+        // 入れ子のオブジェクトの同じ名前のキーは重複として扱わない
+        let duplicates: Vec<_> = parser
+            .duplicate_keys()
+            .iter()
+            .map(|d| {
+                (
+                    d.key.as_str(),
+                    line_column(input, d.first),
+                    line_column(input, d.second),
+                )
+            })
+            .collect();
+        assert_eq!(duplicates, vec![("c", (3, 26), (3, 34)), ("a", (2, 3), (4, 3))]);
+
+        assert_eq!(
+            parser.duplicate_keys()[1].display_with_source(input),
+            "キー \"a\" が重複しています (2 行 3 列目の値は上書きされます) (4 行 3 列目)\n 4 |   \"a\": 4\n   |   ^"
+        );
+    }
+
+    #[test]
+    fn test_suggestion() {
+        let suggestion = |input: &str| {
+            let error = input.parse::<JsonValue>().unwrap_err();
+            return error.suggestion(input).map(|s| (s.message, s.fixable));
+        };
+
+        assert_eq!(
+            suggestion("{'a': 1}"),
+            Some(("文字列は `'` ではなく `\"` で囲んでください".to_string(), false))
+        );
+        assert_eq!(
+            suggestion("[True, None]"),
+            Some((
+                "`True` ではなく `true` と書いてください (JSON のリテラルは小文字です)".to_string(),
+                false
+            ))
+        );
+        assert_eq!(
+            suggestion("[true, None]"),
+            Some((
+                "`None` ではなく `null` と書いてください (JSON のリテラルは小文字です)".to_string(),
+                false
+            ))
+        );
+        assert_eq!(
+            suggestion("{\"a\": [1, 2,],}"),
+            Some(("閉じ括弧の直前の `,` を取り除いてください".to_string(), true))
+        );
+        assert_eq!(
+            suggestion("{\"a\": 1,\n}"),
+            Some(("閉じ括弧の直前の `,` を取り除いてください".to_string(), true))
+        );
+        assert_eq!(
+            suggestion("{name : 1}"),
+            Some((
+                "オブジェクトのキーは `\"name\"` のように `\"` で囲んでください".to_string(),
+                true
+            ))
+        );
+
+        // 誤りの種類から、呼び出し側で独自のメッセージを組み立てられる
+        let input = "[None]";
+        let error = input.parse::<JsonValue>().unwrap_err();
+        assert_eq!(
+            error.suggestion(input).map(|s| s.kind),
+            Some(SuggestionKind::Literal {
+                found: "None".to_string(),
+                expected: "null"
+            })
+        );
+
+        // よくある誤りに当てはまらない場合は提案しない
+        assert_eq!(suggestion("[1 2]"), None);
+        assert_eq!(suggestion("[undefined]"), None);
+        assert_eq!(suggestion("[1,"), None);
+    }
+
+    #[test]
+    fn test_parse_recovering() {
+        let recovered = |input: &str| {
+            let (value, warnings) = parse_recovering(input).unwrap();
+            let kinds: Vec<_> = warnings.iter().map(|w| (w.kind, w.position)).collect();
+            return (value.to_string(), kinds);
+        };
+
+        // 正しい JSON はそのままパースでき、誤りは記録されない
+        assert_eq!(recovered(r#"{"a": [1, 2]}"#), (r#"{"a":[1,2]}"#.to_string(), vec![]));
+
+        // , の抜け
+        assert_eq!(
+            recovered(r#"{"a": 1 "b": [1 2]}"#),
+            (
+                r#"{"a":1,"b":[1,2]}"#.to_string(),
+                vec![(ParseErrorKind::MissingComma, 8), (ParseErrorKind::MissingComma, 16)]
+            )
+        );
+
+        // 閉じ括弧の直前の余分な ,
+        assert_eq!(
+            recovered(r#"{"a": [1,],}"#),
+            (
+                r#"{"a":[1]}"#.to_string(),
+                vec![(ParseErrorKind::TrailingComma, 8), (ParseErrorKind::TrailingComma, 10)]
+            )
+        );
+
+        // クォートのないキー (, の抜けと組み合わさっていても回復する)
+        assert_eq!(
+            recovered(r#"{a: 1 $b_2: true}"#),
+            (
+                r#"{"a":1,"$b_2":true}"#.to_string(),
+                vec![
+                    (ParseErrorKind::UnquotedKey, 1),
+                    (ParseErrorKind::MissingComma, 6),
+                    (ParseErrorKind::UnquotedKey, 6)
+                ]
+            )
+        );
+
+        // 閉じ括弧のないまま入力が終わった
+        assert_eq!(
+            recovered(r#"{"a": [1, {"b": 2"#),
+            (
+                r#"{"a":[1,{"b":2}]}"#.to_string(),
+                vec![
+                    (ParseErrorKind::UnclosedObject, 17),
+                    (ParseErrorKind::UnclosedArray, 17),
+                    (ParseErrorKind::UnclosedObject, 17)
+                ]
+            )
+        );
+        assert_eq!(
+            recovered("[1,"),
+            ("[1]".to_string(), vec![(ParseErrorKind::UnclosedArray, 3)])
+        );
+
+        // 回復できない誤りはエラーになる
+        assert_eq!(
+            parse_recovering("[1 : 2]").unwrap_err().kind,
+            ParseErrorKind::ExpectedCommaOrBracket
+        );
+        assert_eq!(
+            parse_recovering("{\"a\" 1}").unwrap_err().kind,
+            ParseErrorKind::ExpectedColon
+        );
+
+        // 回復しない通常のパースでは誤りのまま
+        assert_eq!(
+            r#"{"a": 1,}"#.parse::<JsonValue>().unwrap_err().kind,
+            ParseErrorKind::ExpectedKey
+        );
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        let errors = |input: &str| {
+            let errors = collect_errors(input);
+            return errors.iter().map(|e| (e.kind, e.position)).collect::<Vec<_>>();
+        };
+
+        // 正しい JSON には誤りがない
+        assert_eq!(errors(r#"{"a": [1, {"b": null}]}"#), vec![]);
+
+        // 誤りの後も同じ階層の次の , か閉じ括弧から読み進める
+        assert_eq!(
+            errors(r#"{"a": tru, "b" 2, "c": [1 2 3], "d": {"e": }, "f": 1,}"#),
+            vec![
+                (ParseErrorKind::InvalidToken, 6),
+                (ParseErrorKind::ExpectedColon, 15),
+                (ParseErrorKind::ExpectedCommaOrBracket, 26),
+                (ParseErrorKind::ExpectedValue, 43),
+                (ParseErrorKind::ExpectedKey, 53)
+            ]
+        );
+
+        // 読み飛ばす範囲のオブジェクトや配列は丸ごと読み飛ばす
+        assert_eq!(
+            errors(r#"[{"a" {"b": [1, 2]}, "c": 1}, x]"#),
+            vec![(ParseErrorKind::ExpectedColon, 6), (ParseErrorKind::InvalidToken, 30)]
+        );
+
+        // 対応しない閉じ括弧は外側の閉じ括弧とみなし、入力の終わりは1度だけ報告する
+        assert_eq!(
+            errors(r#"[{"a": [1, 2}, {"b": 1"#),
+            vec![
+                (ParseErrorKind::ExpectedCommaOrBracket, 12),
+                (ParseErrorKind::UnexpectedEof, 22)
+            ]
+        );
+        assert_eq!(errors("[1] 2"), vec![(ParseErrorKind::TrailingCharacters, 4)]);
+
+        // 最初の誤りは parse のエラーと同じになる
+        let input = r#"{"a": [1 2], "b": x}"#;
+        assert_eq!(collect_errors(input)[0], input.parse::<JsonValue>().unwrap_err());
+
+        // 回復する場合は、回復できない誤りだけを集める
+        let mut parser = Parser::new(Lexer::new(r#"{a: 1 "b": [1,], "c" 2}"#)).with_recovery();
+        let kinds: Vec<_> = parser.collect_errors().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![ParseErrorKind::ExpectedColon]);
+    }
+
+    #[test]
+    fn test_parse_with_limits() {
+        let parse = |input: &str, limits: Limits| {
+            return Parser::new(Lexer::new(input))
+                .with_limits(limits)
+                .parse()
+                .map_err(|e| (e.kind, e.position));
+        };
+        let input = r#"{"name": "abcd", "items": [1, 2, 3]}"#;
+        assert!(parse(input, Limits::default()).is_ok());
+
+        let limits = Limits {
+            max_input_size: Some(10),
+            ..Limits::default()
+        };
+        assert_eq!(parse(input, limits), Err((ParseErrorKind::InputTooLarge, 10)));
+
+        // 文字列の長さはエスケープを解いた後のバイト数で、キーも対象にする
+        let limits = Limits {
+            max_string_length: Some(5),
+            ..Limits::default()
+        };
+        assert!(parse(input, limits).is_ok());
+        assert!(parse(r#"["a\nb\nc"]"#, limits).is_ok());
+        assert_eq!(
+            parse(r#"{"a": "ééé"}"#, limits),
+            Err((ParseErrorKind::StringTooLong, 6))
+        );
+        assert_eq!(
+            parse(r#"{"abcdef": 1}"#, limits),
+            Err((ParseErrorKind::StringTooLong, 1))
+        );
+
+        let limits = Limits {
+            max_elements: Some(2),
+            ..Limits::default()
+        };
+        assert_eq!(parse(input, limits), Err((ParseErrorKind::TooManyElements, 33)));
+        assert_eq!(
+            parse(r#"{"a": 1, "b": 2, "c": 3}"#, limits),
+            Err((ParseErrorKind::TooManyElements, 17))
+        );
+
+        // オブジェクトや配列自体も1つの値と数える
+        let limits = Limits {
+            max_nodes: Some(5),
+            ..Limits::default()
+        };
+        assert_eq!(parse(input, limits), Err((ParseErrorKind::TooManyNodes, 33)));
+
+        // 入れ子の深さは最上位のオブジェクトや配列を1と数える
+        let depth_limits = Limits {
+            max_depth: Some(2),
+            ..Limits::default()
+        };
+        assert!(parse(input, depth_limits).is_ok());
+        assert_eq!(
+            parse(r#"{"a": [[1]]}"#, depth_limits),
+            Err((ParseErrorKind::TooDeep, 7))
+        );
+        assert_eq!(
+            parse(&"[".repeat(200_000), Limits::default()),
+            Err((ParseErrorKind::TooDeep, DEFAULT_MAX_DEPTH))
+        );
+
+        // 上限を超えた場合は、誤りを集める場合でもその時点で打ち切る
+        let mut parser = Parser::new(Lexer::new("[1, 2, x, 3, 4]")).with_limits(limits);
+        let kinds: Vec<_> = parser.collect_errors().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![ParseErrorKind::InvalidToken]);
+        let mut parser = Parser::new(Lexer::new("[1, x, 2, 3, 4, 5, y]")).with_limits(limits);
+        let kinds: Vec<_> = parser.collect_errors().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![ParseErrorKind::InvalidToken, ParseErrorKind::TooManyNodes]);
+    }
+
+    #[test]
+    fn test_validate_str() {
+        for input in [
+            r#"{"a": [1, 2.5, "x", true, null, {}], "b": {"c": []}}"#,
+            "\u{FEFF}[]",
+            "{",
+            r#"{"a" 1}"#,
+            r#"{"a": 1 "b": 2}"#,
+            r#"{1: 2}"#,
+            "[1 2]",
+            "[1,]",
+            "[1] 2",
+            "[x]",
+            "",
+        ] {
+            assert_eq!(validate_str(input), input.parse::<JsonValue>().map(|_| ()), "{}", input);
+        }
+
+        // 誤りのある入力では、Parser::validate や parse と同じエラーになる
+        for input in [
+            r#""a"#,
+            "01",
+            "[+1]",
+            r#"{"a": 1.}"#,
+            r#"["\x"]"#,
+            r#"["\ud800"]"#,
+            "[\"\u{0}\"]",
+            "[1, 2",
+            r#"{"a": [1, {"b": }]}"#,
+            r#"{"a": 1,}"#,
+            "[[[]]]]",
+            "[1, tru]",
+            "[1, \u{FEFF}2]",
+            "{\"a\": [], \"b\" [] }",
+        ] {
+            let expected = Parser::new(Lexer::new(input)).validate();
+            assert!(expected.is_err(), "{}", input);
+            assert_eq!(validate_str(input), expected, "{}", input);
+            assert_eq!(validate_str(input), input.parse::<JsonValue>().map(|_| ()), "{}", input);
+        }
+
+        // 入れ子の深さは既定で制限し、上限をなくしてもスタックを使い果たさない
+        let deep = format!("{}0{}", "[{\"a\":".repeat(100_000), "}]".repeat(100_000));
+        assert_eq!(validate_str(&deep).unwrap_err().kind, ParseErrorKind::TooDeep);
+        let unlimited = Limits {
+            max_depth: None,
+            ..Limits::default()
+        };
+        let mut parser = Parser::new(Lexer::new(&deep)).with_limits(unlimited);
+        assert_eq!(parser.validate(), Ok(()));
+        assert_eq!(
+            validate_str(&"[".repeat(200_000)),
+            Err(ParseError {
+                kind: ParseErrorKind::TooDeep,
+                position: DEFAULT_MAX_DEPTH,
+            })
+        );
+
+        let input = r#"{"a": 1, "b": {"a": 2}, "a": 3}"#;
+        let mut parser = Parser::new(Lexer::new(input));
+        parser.validate().unwrap();
+        assert_eq!(
+            parser.duplicate_keys(),
+            &[DuplicateKey {
+                key: "a".to_string(),
+                first: 1,
+                second: 24,
+            }]
+        );
+
+        let limits = Limits {
+            max_elements: Some(2),
+            ..Limits::default()
+        };
+        let mut parser = Parser::new(Lexer::new("[1, 2, 3]")).with_limits(limits);
+        assert_eq!(parser.validate().unwrap_err().kind, ParseErrorKind::TooManyElements);
+    }
+
+    #[test]
+    fn test_validate_strict() {
+        let cases = [
+            (r#""a"#, ParseErrorKind::UnterminatedString, 0),
+            (r#"{"a": "b}"#, ParseErrorKind::UnterminatedString, 6),
+            ("01", ParseErrorKind::InvalidNumber, 0),
+            ("[+1]", ParseErrorKind::InvalidNumber, 1),
+            (r#"{"a": 1.}"#, ParseErrorKind::InvalidNumber, 6),
+            (r#""\x""#, ParseErrorKind::InvalidEscape, 1),
+            ("[\"a\u{0}\"]", ParseErrorKind::ControlCharacter, 3),
+            ("\"\u{1f}\"", ParseErrorKind::ControlCharacter, 1),
+            ("\"line\nbreak\"", ParseErrorKind::ControlCharacter, 5),
+        ];
+        for (input, kind, position) in cases {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                parser.validate(),
+                Err(ParseError { kind, position }),
+                "input: {:?}",
+                input
+            );
+            assert_eq!(input.parse::<JsonValue>().unwrap_err().kind, kind, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        let value: JsonValue = r#"{"key": [1, true]}"#.parse().unwrap();
+
+        let mut expected_object = IndexMap::new();
+        expected_object.insert(
+            "key".to_string(),
+            JsonValue::Array(vec![JsonValue::from(1.0), JsonValue::Bool(true)]),
+        );
+        assert_eq!(value, JsonValue::Object(expected_object));
+
+        let error = "[1,".parse::<JsonValue>().unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof);
+        assert_eq!(error.to_string(), "予期しない入力の終わりです (位置 3)");
+    }
+
+    #[test]
+    fn test_parse_overflowing_number() {
+        // f64 の範囲を超える数値も JSON として正しいため、エラーにせず有限でない数値とも区別する
+        let input = "[1e400, -1E999]";
+        assert_eq!(validate_str(input), Ok(()));
+        let value: JsonValue = input.parse().unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Array(vec![
+                JsonValue::Number(Number::Big("1e400".into())),
+                JsonValue::Number(Number::Big("-1E999".into())),
+            ])
+        );
+        assert_eq!(value.find_non_finite(), None);
+        assert_eq!(value.to_string(), "[1e400,-1E999]");
     }
 }
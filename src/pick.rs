@@ -0,0 +1,113 @@
+use crate::access::{parse_array_index, parse_pointer};
+use crate::json::{JsonObject, JsonValue};
+
+/**
+ * 選んだ値を並べる形
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PickLayout {
+    Nested, // 元の入れ子の構造を保つ (配列は選んだ要素だけを詰めて並べる)
+    Flat,   // JSON Pointer をキーとする1段のオブジェクトにする
+}
+
+impl JsonValue {
+    /**
+     * JSON Pointer で指定した値だけからなる新しい値を返す (存在しない経路は無視する)
+     * Nested では元の入れ子の構造と順序を保ち、Flat では指定した順に JSON Pointer をキーとして並べる
+     * Nested で1つも値がない場合は、元の値と同じ種類の空のオブジェクトや配列を返す
+     */
+    pub fn pick(&self, pointers: &[String], layout: PickLayout) -> JsonValue {
+        if layout == PickLayout::Flat {
+            let mut picked = JsonObject::new();
+            for pointer in pointers {
+                if let Some(value) = self.pointer(pointer) {
+                    picked.insert(pointer.clone(), value.clone());
+                }
+            }
+            return JsonValue::Object(picked);
+        }
+        let paths: Vec<Vec<String>> = pointers.iter().filter_map(|pointer| parse_pointer(pointer)).collect();
+        let paths: Vec<&[String]> = paths.iter().map(Vec::as_slice).collect();
+        return match self.project(&paths) {
+            Some(picked) => picked,
+            None => match self {
+                JsonValue::Object(_) => JsonValue::Object(JsonObject::new()),
+                JsonValue::Array(_) => JsonValue::Array(Vec::new()),
+                _ => JsonValue::Null,
+            },
+        };
+    }
+
+    /**
+     * 経路 (トークンの並び) のいずれかが指す値だけを残した値を返す (どの経路の値もない場合は None)
+     */
+    fn project(&self, paths: &[&[String]]) -> Option<JsonValue> {
+        if paths.iter().any(|path| path.is_empty()) {
+            return Some(self.clone());
+        }
+        // 先頭のトークンが一致する経路の残りの部分
+        let rest = |matches: &dyn Fn(&str) -> bool| -> Vec<&[String]> {
+            return paths
+                .iter()
+                .filter(|path| matches(&path[0]))
+                .map(|path| &path[1..])
+                .collect();
+        };
+        return match self {
+            JsonValue::Object(obj) => {
+                let picked: JsonObject = obj
+                    .iter()
+                    .filter_map(|(key, value)| Some((key.clone(), value.project(&rest(&|token| token == key))?)))
+                    .collect();
+                (!picked.is_empty()).then_some(JsonValue::Object(picked))
+            }
+            JsonValue::Array(array) => {
+                let picked: Vec<JsonValue> = array
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, value)| value.project(&rest(&|token| parse_array_index(token) == Some(index))))
+                    .collect();
+                (!picked.is_empty()).then_some(JsonValue::Array(picked))
+            }
+            _ => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pick(pointers: &[&str], layout: PickLayout) -> String {
+        let value: JsonValue = r#"{"user": {"name": "a", "email": "b", "age": 3}, "items": [{"id": 1, "x": 0}, {"id": 2}, {"id": 3}], "n": 1}"#
+            .parse()
+            .unwrap();
+        let pointers: Vec<String> = pointers.iter().map(|pointer| pointer.to_string()).collect();
+        return value.pick(&pointers, layout).to_string();
+    }
+
+    #[test]
+    fn test_pick_nested() {
+        assert_eq!(
+            pick(
+                &["/user/email", "/user/name", "/items/2/id", "/items/0/id", "/missing"],
+                PickLayout::Nested
+            ),
+            r#"{"user":{"name":"a","email":"b"},"items":[{"id":1},{"id":3}]}"#
+        );
+        // 重なる経路は外側の値をすべて含める
+        assert_eq!(
+            pick(&["/user", "/user/name"], PickLayout::Nested),
+            r#"{"user":{"name":"a","email":"b","age":3}}"#
+        );
+        assert_eq!(pick(&["/n/x"], PickLayout::Nested), "{}");
+    }
+
+    #[test]
+    fn test_pick_flat() {
+        assert_eq!(
+            pick(&["/user/email", "/items/1", "/missing"], PickLayout::Flat),
+            r#"{"/user/email":"b","/items/1":{"id":2}}"#
+        );
+    }
+}
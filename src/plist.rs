@@ -0,0 +1,163 @@
+use crate::json::JsonValue;
+use crate::number::Number;
+use crate::walk::{JsonPath, PathSegment};
+use crate::xml::push_escaped_text;
+
+/**
+ * XML 形式のプロパティリストの先頭に置く宣言
+ */
+const PLIST_HEADER: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+    "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+    "<plist version=\"1.0\">\n",
+);
+
+/**
+ * プロパティリストで表せない null の値
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlistNullError {
+    pub path: JsonPath, // null の値の経路
+}
+
+impl JsonValue {
+    /**
+     * Apple の XML 形式のプロパティリストに変換した文字列を返す (indent_unit は1段分の字下げ)
+     * 文字列は <string>、整数は <integer>、それ以外の数値は <real>、真偽値は <true/> か <false/>、
+     * 配列は <array>、オブジェクトは <dict> にする
+     * プロパティリストには null にあたる型がないため、null の値があればエラーを返す
+     */
+    pub fn to_plist(&self, indent_unit: &str) -> Result<String, PlistNullError> {
+        let mut output = PLIST_HEADER.to_string();
+        write_plist_value(self, indent_unit, 0, &mut JsonPath::root(), &mut output)?;
+        output.push_str("</plist>\n");
+        return Ok(output);
+    }
+}
+
+fn write_plist_value(
+    value: &JsonValue,
+    indent_unit: &str,
+    depth: usize,
+    path: &mut JsonPath,
+    output: &mut String,
+) -> Result<(), PlistNullError> {
+    let indent = indent_unit.repeat(depth);
+    output.push_str(&indent);
+    match value {
+        JsonValue::Null => return Err(PlistNullError { path: path.clone() }),
+        JsonValue::Bool(true) => output.push_str("<true/>"),
+        JsonValue::Bool(false) => output.push_str("<false/>"),
+        JsonValue::Number(num) => push_plist_number(output, num),
+        JsonValue::String(str) => {
+            output.push_str("<string>");
+            push_escaped_text(output, str, false);
+            output.push_str("</string>");
+        }
+        JsonValue::Array(array) if array.is_empty() => output.push_str("<array/>"),
+        JsonValue::Array(array) => {
+            output.push_str("<array>\n");
+            for (index, element) in array.iter().enumerate() {
+                path.push(PathSegment::Index(index));
+                write_plist_value(element, indent_unit, depth + 1, path, output)?;
+                path.pop();
+            }
+            output.push_str(&indent);
+            output.push_str("</array>");
+        }
+        JsonValue::Object(obj) if obj.is_empty() => output.push_str("<dict/>"),
+        JsonValue::Object(obj) => {
+            output.push_str("<dict>\n");
+            for (key, member) in obj {
+                output.push_str(&indent);
+                output.push_str(indent_unit);
+                output.push_str("<key>");
+                push_escaped_text(output, key, false);
+                output.push_str("</key>\n");
+                path.push(PathSegment::Key(key.clone()));
+                write_plist_value(member, indent_unit, depth + 1, path, output)?;
+                path.pop();
+            }
+            output.push_str(&indent);
+            output.push_str("</dict>");
+        }
+    }
+    output.push('\n');
+    return Ok(());
+}
+
+/**
+ * 数値を <integer> か <real> の要素として書き込む (有限でない数値は nan、+infinity、-infinity にする)
+ * plist の <integer> は 64 ビットの符号付き整数のため、i64 の範囲を超える整数は <real> にする
+ */
+fn push_plist_number(output: &mut String, num: &Number) {
+    let (tag, text) = match num {
+        Number::Int(_) => ("integer", num.to_string()),
+        _ if num.as_f64().is_nan() => ("real", "nan".to_string()),
+        _ if num.as_f64() == f64::INFINITY => ("real", "+infinity".to_string()),
+        _ if num.as_f64() == f64::NEG_INFINITY => ("real", "-infinity".to_string()),
+        _ => ("real", num.to_string()),
+    };
+    output.push_str(&format!("<{}>{}</{}>", tag, text, tag));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_plist() {
+        let value: JsonValue =
+            r#"{"CFBundleName": "App & Co", "Version": 3, "Scale": 1.5, "Enabled": true, "Tags": ["a", []], "Extra": {}}"#
+                .parse()
+                .unwrap();
+        let expected = format!(
+            "{}{}",
+            PLIST_HEADER,
+            r#"<dict>
+	<key>CFBundleName</key>
+	<string>App &amp; Co</string>
+	<key>Version</key>
+	<integer>3</integer>
+	<key>Scale</key>
+	<real>1.5</real>
+	<key>Enabled</key>
+	<true/>
+	<key>Tags</key>
+	<array>
+		<string>a</string>
+		<array/>
+	</array>
+	<key>Extra</key>
+	<dict/>
+</dict>
+</plist>
+"#
+        );
+        assert_eq!(value.to_plist("\t").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_plist_scalar() {
+        let plist = JsonValue::Bool(false).to_plist("  ").unwrap();
+        assert_eq!(plist, format!("{}<false/>\n</plist>\n", PLIST_HEADER));
+    }
+
+    #[test]
+    fn test_to_plist_null() {
+        let value: JsonValue = r#"{"a": [1, null]}"#.parse().unwrap();
+        assert_eq!(value.to_plist("  ").unwrap_err().path.to_string(), "/a/1");
+    }
+
+    #[test]
+    fn test_to_plist_large_integer() {
+        let value: JsonValue = "[9223372036854775807, 9223372036854775808]".parse().unwrap();
+        assert_eq!(
+            value.to_plist("").unwrap(),
+            format!(
+                "{}<array>\n<integer>9223372036854775807</integer>\n<real>9223372036854775808</real>\n</array>\n</plist>\n",
+                PLIST_HEADER
+            )
+        );
+    }
+}
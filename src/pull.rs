@@ -0,0 +1,641 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::json::{JsonArray, JsonObject, JsonValue};
+use crate::lexer::{LexErrorKind, Lexer, Spanned, Token};
+use crate::number::Number;
+use crate::parser::{Limits, ParseError, ParseErrorKind};
+
+/**
+ * 値の種類
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    Object, // オブジェクト
+    Array,  // 配列
+    String, // 文字列
+    Number, // 数値
+    Bool,   // 真偽値
+    Null,   // null
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ValueKind::Object => "オブジェクト",
+            ValueKind::Array => "配列",
+            ValueKind::String => "文字列",
+            ValueKind::Number => "数値",
+            ValueKind::Bool => "真偽値",
+            ValueKind::Null => "null",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/**
+ * 逐次読み取りに失敗した原因
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum PullError {
+    Parse(ParseError), // 入力が JSON として不正
+    // 値の種類が読み取ろうとした種類と異なる
+    UnexpectedType {
+        expected: ValueKind, // 読み取ろうとした値の種類
+        found: ValueKind,    // 入力中の値の種類
+        position: usize,     // 値の位置 (バイト単位)
+    },
+    // 数値が読み取ろうとした型の整数で表せない
+    NumberOutOfRange {
+        position: usize, // 数値の位置 (バイト単位)
+    },
+}
+
+impl fmt::Display for PullError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            PullError::Parse(error) => write!(f, "JSONのパースに失敗しました: {}", error),
+            PullError::UnexpectedType {
+                expected,
+                found,
+                position,
+            } => write!(f, "{}が必要ですが{}があります (位置 {})", expected, found, position),
+            PullError::NumberOutOfRange { position } => {
+                write!(f, "数値を整数として読み取れません (位置 {})", position)
+            }
+        };
+    }
+}
+
+impl Error for PullError {}
+
+impl From<ParseError> for PullError {
+    fn from(error: ParseError) -> Self {
+        return PullError::Parse(error);
+    }
+}
+
+/**
+ * 値を生成せずに、入力を先頭から順に読み取るリーダー
+ * 巨大な入力から必要なメンバーだけを取り出す場合に使う
+ * 例:
+ *   reader.begin_object()?;
+ *   while let Some(key) = reader.next_key()? {
+ *       match key.as_ref() {
+ *           "id" => id = reader.read_i64()?,
+ *           _ => reader.skip_value()?,
+ *       }
+ *   }
+ *   reader.finish()?;
+ */
+pub struct PullReader<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Spanned<Token<'a>>>, // 先読みしたトークン
+    counts: Vec<usize>, // 読み取り中のオブジェクトや配列ごとの、読み始めたメンバーや要素の数 (外側から順)
+    limits: Limits,     // 入力の大きさの上限
+    nodes: usize,       // これまでに読み取った値の数
+}
+
+impl<'a> PullReader<'a> {
+    /**
+     * 新しい PullReader を生成する (コメントを読み取る Lexer の場合、コメントは読み飛ばす)
+     */
+    pub fn new(lexer: Lexer<'a>) -> Self {
+        return PullReader {
+            lexer,
+            peeked: None,
+            counts: Vec::new(),
+            limits: Limits::default(),
+            nodes: 0,
+        };
+    }
+
+    /**
+     * 入力の大きさに上限を設けた PullReader にする (上限は Parser と同じように確かめる)
+     */
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        return self;
+    }
+
+    /**
+     * 次の値の種類を返す (値は読み進めない)
+     */
+    pub fn peek(&mut self) -> Result<ValueKind, PullError> {
+        let token = self.peek_token()?;
+        return match value_kind(&token.value) {
+            Some(kind) => Ok(kind),
+            None => Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::ExpectedValue,
+                position: token.span.start,
+            })),
+        };
+    }
+
+    /**
+     * オブジェクトの開始 ({) を読み取る
+     * メンバーは next_key で順に読み取る
+     */
+    pub fn begin_object(&mut self) -> Result<(), PullError> {
+        self.expect_value(ValueKind::Object)?;
+        self.counts.push(0);
+        return Ok(());
+    }
+
+    /**
+     * オブジェクトの次のメンバーのキーを読み取る (続けてその値を読み取るか skip_value で読み飛ばす)
+     * オブジェクトが終わった場合は、閉じる } を読み取って None を返す
+     */
+    pub fn next_key(&mut self) -> Result<Option<Cow<'a, str>>, PullError> {
+        if !self.advance(Token::RightBrace, ParseErrorKind::ExpectedCommaOrBrace)? {
+            return Ok(None);
+        }
+        let Spanned { value, span } = self.next_token()?;
+        let Token::String(key) = value else {
+            return Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::ExpectedKey,
+                position: span.start,
+            }));
+        };
+        self.check_string_length(&key, span.start)?;
+        let Spanned { value, span } = self.next_token()?;
+        if value != Token::Colon {
+            return Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::ExpectedColon,
+                position: span.start,
+            }));
+        }
+        return Ok(Some(key));
+    }
+
+    /**
+     * 配列の開始 ([) を読み取る
+     * 要素は has_next で続きがあることを確かめてから順に読み取る
+     */
+    pub fn begin_array(&mut self) -> Result<(), PullError> {
+        self.expect_value(ValueKind::Array)?;
+        self.counts.push(0);
+        return Ok(());
+    }
+
+    /**
+     * 配列に次の要素があるかどうかを返す
+     * 配列が終わった場合は、閉じる ] を読み取って false を返す
+     */
+    pub fn has_next(&mut self) -> Result<bool, PullError> {
+        return self.advance(Token::RightBracket, ParseErrorKind::ExpectedCommaOrBracket);
+    }
+
+    /**
+     * 文字列の値を読み取る
+     */
+    pub fn read_string(&mut self) -> Result<Cow<'a, str>, PullError> {
+        let Token::String(string) = self.expect_value(ValueKind::String)? else {
+            unreachable!();
+        };
+        return Ok(string);
+    }
+
+    /**
+     * 数値の値を読み取る
+     */
+    pub fn read_number(&mut self) -> Result<Number, PullError> {
+        let Token::Number(number) = self.expect_value(ValueKind::Number)? else {
+            unreachable!();
+        };
+        return Ok(number);
+    }
+
+    /**
+     * 数値の値を f64 として読み取る
+     */
+    pub fn read_f64(&mut self) -> Result<f64, PullError> {
+        return Ok(self.read_number()?.as_f64());
+    }
+
+    /**
+     * 数値の値を i64 として読み取る (小数や範囲外の数値はエラーになる)
+     */
+    pub fn read_i64(&mut self) -> Result<i64, PullError> {
+        let position = self.peek_token()?.span.start;
+        return self
+            .read_number()?
+            .as_i64()
+            .ok_or(PullError::NumberOutOfRange { position });
+    }
+
+    /**
+     * 数値の値を u64 として読み取る (小数や負の数、範囲外の数値はエラーになる)
+     */
+    pub fn read_u64(&mut self) -> Result<u64, PullError> {
+        let position = self.peek_token()?.span.start;
+        return self
+            .read_number()?
+            .as_u64()
+            .ok_or(PullError::NumberOutOfRange { position });
+    }
+
+    /**
+     * 真偽値を読み取る
+     */
+    pub fn read_bool(&mut self) -> Result<bool, PullError> {
+        return Ok(self.expect_value(ValueKind::Bool)? == Token::True);
+    }
+
+    /**
+     * null を読み取る
+     */
+    pub fn read_null(&mut self) -> Result<(), PullError> {
+        self.expect_value(ValueKind::Null)?;
+        return Ok(());
+    }
+
+    /**
+     * 次の値全体を JsonValue として読み取る (取り出したい部分だけを値にする場合に使う)
+     */
+    pub fn read_value(&mut self) -> Result<JsonValue, PullError> {
+        return match self.peek()? {
+            ValueKind::Object => {
+                let mut object = JsonObject::new();
+                self.begin_object()?;
+                while let Some(key) = self.next_key()? {
+                    let value = self.read_value()?;
+                    object.insert(key.into_owned(), value);
+                }
+                Ok(JsonValue::Object(object))
+            }
+            ValueKind::Array => {
+                let mut array = JsonArray::new();
+                self.begin_array()?;
+                while self.has_next()? {
+                    array.push(self.read_value()?);
+                }
+                Ok(JsonValue::Array(array))
+            }
+            ValueKind::String => Ok(JsonValue::String(self.read_string()?.into_owned())),
+            ValueKind::Number => Ok(JsonValue::Number(self.read_number()?)),
+            ValueKind::Bool => Ok(JsonValue::Bool(self.read_bool()?)),
+            ValueKind::Null => {
+                self.read_null()?;
+                Ok(JsonValue::Null)
+            }
+        };
+    }
+
+    /**
+     * 次の値全体を読み飛ばす (オブジェクトや配列の中身も文法が正しいことは確かめる)
+     */
+    pub fn skip_value(&mut self) -> Result<(), PullError> {
+        match self.peek()? {
+            ValueKind::Object => {
+                self.begin_object()?;
+                while self.next_key()?.is_some() {
+                    self.skip_value()?;
+                }
+            }
+            ValueKind::Array => {
+                self.begin_array()?;
+                while self.has_next()? {
+                    self.skip_value()?;
+                }
+            }
+            kind => {
+                self.expect_value(kind)?;
+            }
+        }
+        return Ok(());
+    }
+
+    /**
+     * 値を読み終えた後に入力が残っていないことを確かめる
+     */
+    pub fn finish(&mut self) -> Result<(), PullError> {
+        let position = match self.peeked.take() {
+            Some(spanned) => spanned.span.start,
+            None => loop {
+                match self.lexer.next() {
+                    None => return Ok(()),
+                    Some(Ok(Spanned {
+                        value: Token::Comment(_),
+                        ..
+                    })) => continue,
+                    Some(Ok(spanned)) => break spanned.span.start,
+                    Some(Err(error)) if error.kind == LexErrorKind::UnexpectedBom => {
+                        return Err(PullError::Parse(ParseError {
+                            kind: ParseErrorKind::UnexpectedBom,
+                            position: error.span.start,
+                        }));
+                    }
+                    Some(Err(error)) => break error.span.start,
+                }
+            },
+        };
+        return Err(PullError::Parse(ParseError {
+            kind: ParseErrorKind::TrailingCharacters,
+            position,
+        }));
+    }
+
+    /**
+     * 読み取り中のオブジェクトや配列の、次のメンバーや要素の手前まで読み進め、続きがあるかどうかを返す
+     * 2つ目以降は区切りの , を読み取る。閉じ括弧 close の場合はそれを読み取って false を返す
+     */
+    fn advance(&mut self, close: Token, error_kind: ParseErrorKind) -> Result<bool, PullError> {
+        let count = self.counts.last().copied().unwrap_or(0);
+        let token = self.peek_token()?;
+        if token.value == close {
+            self.next_token()?;
+            self.counts.pop();
+            return Ok(false);
+        }
+        if count > 0 {
+            if token.value != Token::Comma {
+                return Err(PullError::Parse(ParseError {
+                    kind: error_kind,
+                    position: token.span.start,
+                }));
+            }
+            self.next_token()?;
+        }
+        if let Some(count) = self.counts.last_mut() {
+            *count += 1;
+            if self.limits.max_elements.is_some_and(|max| *count > max) {
+                return Err(PullError::Parse(ParseError {
+                    kind: ParseErrorKind::TooManyElements,
+                    position: self.peek_token()?.span.start,
+                }));
+            }
+        }
+        return Ok(true);
+    }
+
+    /**
+     * 次の値が expected の種類であることを確かめて、その先頭のトークンを読み取る
+     * 種類が異なる場合は読み進めずにエラーにする
+     * 値の数や入れ子の深さ、文字列の長さが上限を超えている場合もエラーにする
+     */
+    fn expect_value(&mut self, expected: ValueKind) -> Result<Token<'a>, PullError> {
+        let found = self.peek()?;
+        let position = self.peek_token()?.span.start;
+        if found != expected {
+            return Err(PullError::UnexpectedType {
+                expected,
+                found,
+                position,
+            });
+        }
+        self.nodes += 1;
+        if self.limits.max_nodes.is_some_and(|max| self.nodes > max) {
+            return Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::TooManyNodes,
+                position,
+            }));
+        }
+        if matches!(found, ValueKind::Object | ValueKind::Array) {
+            self.limits.check_depth(self.counts.len(), position)?;
+        }
+        let token = self.next_token()?.value;
+        if let Token::String(string) = &token {
+            self.check_string_length(string, position)?;
+        }
+        return Ok(token);
+    }
+
+    /**
+     * position から始まる文字列 (キーを含む) が上限の長さを超えていないことを確かめる
+     */
+    fn check_string_length(&self, string: &str, position: usize) -> Result<(), PullError> {
+        return match self.limits.max_string_length {
+            Some(max) if string.len() > max => Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::StringTooLong,
+                position,
+            })),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * 次のトークンを読み進めずに返す
+     */
+    fn peek_token(&mut self) -> Result<&Spanned<Token<'a>>, PullError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lex()?);
+        }
+        return Ok(self.peeked.as_ref().unwrap());
+    }
+
+    /**
+     * 次のトークンを読み取る
+     */
+    fn next_token(&mut self) -> Result<Spanned<Token<'a>>, PullError> {
+        return match self.peeked.take() {
+            Some(spanned) => Ok(spanned),
+            None => self.lex(),
+        };
+    }
+
+    /**
+     * Lexer から次のトークンを読み取る (コメントは読み飛ばし、入力の終わりはエラーにする)
+     */
+    fn lex(&mut self) -> Result<Spanned<Token<'a>>, PullError> {
+        if let Some(max) = self.limits.max_input_size.filter(|max| self.lexer.input().len() > *max) {
+            return Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::InputTooLarge,
+                position: max,
+            }));
+        }
+        loop {
+            return match self.lexer.next() {
+                Some(Ok(Spanned {
+                    value: Token::Comment(_),
+                    ..
+                })) => continue,
+                Some(Ok(spanned)) => Ok(spanned),
+                Some(Err(error)) => Err(PullError::Parse(ParseError {
+                    kind: error.kind.into(),
+                    position: error.span.start,
+                })),
+                None => Err(PullError::Parse(ParseError {
+                    kind: ParseErrorKind::UnexpectedEof,
+                    position: self.lexer.input().len(),
+                })),
+            };
+        }
+    }
+}
+
+/**
+ * 値の先頭のトークンから値の種類を返す (値の先頭でないトークンの場合は None)
+ */
+fn value_kind(token: &Token) -> Option<ValueKind> {
+    return match token {
+        Token::LeftBrace => Some(ValueKind::Object),
+        Token::LeftBracket => Some(ValueKind::Array),
+        Token::String(_) => Some(ValueKind::String),
+        Token::Number(_) => Some(ValueKind::Number),
+        Token::True | Token::False => Some(ValueKind::Bool),
+        Token::Null => Some(ValueKind::Null),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_read_selected_fields() {
+        let input = r#"{
+            "id": 42,
+            "name": "widget",
+            "tags": ["a", {"skip": [1, 2]}, "b"],
+            "price": 1.5,
+            "meta": {"owner": null, "active": true},
+            "extra": [[], {}]
+        }"#;
+        let mut reader = PullReader::new(Lexer::new(input));
+        let (mut id, mut tags, mut price, mut meta) = (0, Vec::new(), 0.0, JsonValue::Null);
+        reader.begin_object().unwrap();
+        while let Some(key) = reader.next_key().unwrap() {
+            match key.as_ref() {
+                "id" => id = reader.read_i64().unwrap(),
+                "tags" => {
+                    reader.begin_array().unwrap();
+                    while reader.has_next().unwrap() {
+                        match reader.peek().unwrap() {
+                            ValueKind::String => tags.push(reader.read_string().unwrap()),
+                            _ => reader.skip_value().unwrap(),
+                        }
+                    }
+                }
+                "price" => price = reader.read_f64().unwrap(),
+                "meta" => meta = reader.read_value().unwrap(),
+                _ => reader.skip_value().unwrap(),
+            }
+        }
+        reader.finish().unwrap();
+
+        assert_eq!(id, 42);
+        assert_eq!(tags, vec!["a", "b"]);
+        assert_eq!(price, 1.5);
+        assert_eq!(meta, r#"{"owner": null, "active": true}"#.parse::<JsonValue>().unwrap());
+    }
+
+    #[test]
+    fn test_read_scalars() {
+        let mut reader = PullReader::new(Lexer::new("[true, null, 18446744073709551615, -1, 1.5]"));
+        reader.begin_array().unwrap();
+        assert!(reader.has_next().unwrap());
+        assert!(reader.read_bool().unwrap());
+        assert!(reader.has_next().unwrap());
+        reader.read_null().unwrap();
+        assert!(reader.has_next().unwrap());
+        assert_eq!(reader.read_u64().unwrap(), u64::MAX);
+        assert!(reader.has_next().unwrap());
+        assert_eq!(reader.read_u64(), Err(PullError::NumberOutOfRange { position: 35 }));
+        assert!(reader.has_next().unwrap());
+        assert_eq!(reader.read_i64(), Err(PullError::NumberOutOfRange { position: 39 }));
+        assert!(!reader.has_next().unwrap());
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        // 種類が異なる場合は読み進めない
+        let mut reader = PullReader::new(Lexer::new(r#"{"a": 1}"#));
+        reader.begin_object().unwrap();
+        reader.next_key().unwrap();
+        assert_eq!(
+            reader.read_string(),
+            Err(PullError::UnexpectedType {
+                expected: ValueKind::String,
+                found: ValueKind::Number,
+                position: 6,
+            })
+        );
+        assert_eq!(reader.read_f64().unwrap(), 1.0);
+
+        // 文法の誤りは Parser と同じ種類と位置で報告する
+        let parse_error = |input: &str| {
+            let error = PullReader::new(Lexer::new(input)).skip_value().unwrap_err();
+            assert_eq!(
+                error,
+                PullError::Parse(input.parse::<JsonValue>().unwrap_err()),
+                "{}",
+                input
+            );
+        };
+        parse_error("[1 2]");
+        parse_error("[1,]");
+        parse_error(r#"{"a" 1}"#);
+        parse_error(r#"{"a": 1,}"#);
+        parse_error("{1: 2}");
+        parse_error("[x]");
+        parse_error("[");
+        parse_error(&"[".repeat(200_000));
+
+        let mut reader = PullReader::new(Lexer::new("[] 1"));
+        reader.skip_value().unwrap();
+        assert_eq!(
+            reader.finish(),
+            Err(PullError::Parse(ParseError {
+                kind: ParseErrorKind::TrailingCharacters,
+                position: 3,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        // 上限を超えた場合も Parser と同じ種類と位置で報告する
+        let input = r#"{"name": "abcd", "items": [1, [2, 3], 4]}"#;
+        for limits in [
+            Limits {
+                max_input_size: Some(10),
+                ..Limits::default()
+            },
+            Limits {
+                max_string_length: Some(4),
+                ..Limits::default()
+            },
+            Limits {
+                max_elements: Some(2),
+                ..Limits::default()
+            },
+            Limits {
+                max_nodes: Some(5),
+                ..Limits::default()
+            },
+            Limits {
+                max_depth: Some(2),
+                ..Limits::default()
+            },
+        ] {
+            let expected = Parser::new(Lexer::new(input)).with_limits(limits).parse().unwrap_err();
+            let mut reader = PullReader::new(Lexer::new(input)).with_limits(limits);
+            assert_eq!(reader.read_value(), Err(PullError::Parse(expected)), "{:?}", limits);
+        }
+    }
+
+    #[test]
+    fn test_borrowed_strings() {
+        // エスケープを含まないキーや文字列は入力を指したまま返す
+        let mut reader = PullReader::new(Lexer::new(r#"{"plain": "text", "esc\u0061ped": "a\nb"}"#));
+        reader.begin_object().unwrap();
+        assert!(matches!(reader.next_key().unwrap(), Some(Cow::Borrowed("plain"))));
+        assert!(matches!(reader.read_string().unwrap(), Cow::Borrowed("text")));
+        assert!(matches!(reader.next_key().unwrap(), Some(Cow::Owned(key)) if key == "escaped"));
+        assert!(matches!(reader.read_string().unwrap(), Cow::Owned(string) if string == "a\nb"));
+        assert_eq!(reader.next_key().unwrap(), None);
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn test_comments() {
+        let mut reader = PullReader::new(Lexer::new("// header\n{\"a\": /* one */ 1}").with_comments());
+        assert_eq!(reader.read_value().unwrap().to_string(), r#"{"a":1}"#);
+        reader.finish().unwrap();
+    }
+}
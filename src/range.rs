@@ -0,0 +1,132 @@
+use crate::incremental::{parse_tree, Node, NodeKind};
+use crate::json::{FormatOptions, FormatTree};
+use crate::parser::{Limits, ParseError};
+
+/**
+ * 入力の一部を置き換える編集
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeEdit {
+    pub start: usize, // 置き換える範囲の開始位置 (バイト単位)
+    pub end: usize,   // 置き換える範囲の終了位置 (バイト単位、この位置の文字は含まない)
+    pub text: String, // 置き換える文字列
+}
+
+/**
+ * 入力のうち start..end の範囲 (バイト単位) に関わる値だけを整形し、入力に適用する編集を先頭から順に返す
+ * 範囲を囲む最も小さい値を整形するが、範囲がオブジェクトや配列の括弧の内側にある場合は、範囲に掛かるメンバーや要素を1つずつ整形する
+ * 値の字下げは入力中の深さに合わせ、整形した値の外側の文字列はそのまま残す
+ */
+pub fn format_range(
+    input: &str,
+    start: usize,
+    end: usize,
+    options: &FormatOptions,
+) -> Result<Vec<RangeEdit>, ParseError> {
+    let root = parse_tree(input, Limits::default())?;
+    let (start, end) = (start.min(end), end.max(start));
+    let mut node = &root;
+    let mut depth = 0;
+    while let Some((_, child)) = items(node)
+        .into_iter()
+        .find(|(from, child)| *from <= start && end <= child.end)
+    {
+        node = child;
+        depth += 1;
+    }
+
+    let inside = node.start < start && end < node.end;
+    let overlapping: Vec<&Node> = items(node)
+        .into_iter()
+        .filter(|(from, child)| *from < end && start < child.end)
+        .map(|(_, child)| child)
+        .collect();
+    if !inside || overlapping.is_empty() {
+        return Ok(vec![format_node(input, node, depth, options)]);
+    }
+    return Ok(overlapping
+        .into_iter()
+        .map(|child| format_node(input, child, depth + 1, options))
+        .collect());
+}
+
+/**
+ * 編集を入力に適用した文字列を返す (編集は先頭から順に並び、重なっていないこと)
+ */
+pub fn apply_edits(input: &str, edits: &[RangeEdit]) -> String {
+    let mut applied = String::with_capacity(input.len());
+    let mut position = 0;
+    for edit in edits {
+        applied.push_str(&input[position..edit.start]);
+        applied.push_str(&edit.text);
+        position = edit.end;
+    }
+    applied.push_str(&input[position..]);
+    return applied;
+}
+
+/**
+ * オブジェクトのメンバーの値や配列の要素を、開始位置 (メンバーの場合はキーの開始位置) とともに返す
+ */
+fn items(node: &Node) -> Vec<(usize, &Node)> {
+    return match &node.kind {
+        NodeKind::Object(members) => members.iter().map(|member| (member.key_start, &member.value)).collect(),
+        NodeKind::Array(elements) => elements.iter().map(|element| (element.start, element)).collect(),
+        _ => vec![],
+    };
+}
+
+/**
+ * 深さ depth にある値を、入力中の開始位置の桁と後に続くカンマを考慮して整形する
+ */
+fn format_node(input: &str, node: &Node, depth: usize, options: &FormatOptions) -> RangeEdit {
+    let line_start = input[..node.start].rfind('\n').map_or(0, |i| i + 1);
+    let column = input[line_start..node.start].chars().count();
+    let trailing = usize::from(input[node.end..].trim_start().starts_with(','));
+    let mut text = String::new();
+    node.to_value()
+        .format_child(depth, column, trailing, options, &mut text);
+    return RangeEdit {
+        start: node.start,
+        end: node.end,
+        text,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(input: &str, selected: &str) -> String {
+        let start = input.find(selected).unwrap();
+        let edits = format_range(input, start, start + selected.len(), &FormatOptions::default()).unwrap();
+        return apply_edits(input, &edits);
+    }
+
+    #[test]
+    fn test_format_range() {
+        let input = "{\n  \"a\": {\"b\":1},\n  \"c\": [1,2]  ,\"d\":true\n}";
+        // 範囲を囲む最も小さい値だけを、入力中の深さに合わせて整形する
+        assert_eq!(
+            format(input, "{\"b\":1}"),
+            "{\n  \"a\": {\n    \"b\": 1\n  },\n  \"c\": [1,2]  ,\"d\":true\n}"
+        );
+        assert_eq!(format(input, "2"), input);
+        // 括弧の内側の範囲は、範囲に掛かるメンバーの値を1つずつ整形し、間の文字列は残す
+        assert_eq!(format(input, "\"b\":1"), input);
+        assert_eq!(
+            format(input, "{\"b\":1},\n  \"c\": [1"),
+            "{\n  \"a\": {\n    \"b\": 1\n  },\n  \"c\": [\n    1,\n    2\n  ]  ,\"d\":true\n}"
+        );
+        // 括弧を含む範囲は、その値全体を整形する
+        assert_eq!(
+            format(input, input),
+            "{\n  \"a\": {\n    \"b\": 1\n  },\n  \"c\": [\n    1,\n    2\n  ],\n  \"d\": true\n}"
+        );
+    }
+
+    #[test]
+    fn test_format_range_invalid() {
+        assert!(format_range("{\"a\": }", 0, 1, &FormatOptions::default()).is_err());
+    }
+}
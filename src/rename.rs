@@ -0,0 +1,85 @@
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathSegment};
+
+impl JsonValue {
+    /**
+     * すべてのオブジェクトのキーを対応表 (元のキーから新しいキーへの対応) に従って名前を変え、メンバーの位置は変えない
+     * 対応表は同時に適用する (a を b に、b を c に変える対応表では、a は c ではなく b になる)
+     * 名前を変えたことで同じキーになったメンバーは、パース時の重複したキーと同様に後の値で上書きし、その経路を返す
+     */
+    pub fn rename_keys(&mut self, mapping: &HashMap<String, String>) -> Vec<JsonPath> {
+        let mut collisions = Vec::new();
+        self.rename_keys_at(mapping, &mut JsonPath::root(), &mut collisions);
+        return collisions;
+    }
+
+    fn rename_keys_at(
+        &mut self,
+        mapping: &HashMap<String, String>,
+        path: &mut JsonPath,
+        collisions: &mut Vec<JsonPath>,
+    ) {
+        match self {
+            JsonValue::Object(obj) => {
+                // 名前を変えるキーがないオブジェクトは作り直さない
+                if obj.keys().any(|key| mapping.contains_key(key)) {
+                    let mut renamed = IndexMap::with_capacity(obj.len());
+                    for (key, value) in obj.drain(..) {
+                        let key = mapping.get(&key).cloned().unwrap_or(key);
+                        if renamed.contains_key(&key) {
+                            collisions.push(path.child(PathSegment::Key(key.clone())));
+                        }
+                        renamed.insert(key, value);
+                    }
+                    *obj = renamed;
+                }
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.rename_keys_at(mapping, path, collisions);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.rename_keys_at(mapping, path, collisions);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_keys() {
+        let mapping: HashMap<String, String> = [("a", "b"), ("b", "c"), ("old", "new")]
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect();
+        let mut value: JsonValue = r#"{"x": 0, "a": 1, "b": 2, "items": [{"old": true, "keep": null}]}"#
+            .parse()
+            .unwrap();
+        assert!(value.rename_keys(&mapping).is_empty());
+        assert_eq!(
+            value.to_string(),
+            r#"{"x":0,"b":1,"c":2,"items":[{"new":true,"keep":null}]}"#
+        );
+
+        // 同じキーになったメンバーは後の値で上書きする
+        let mut value: JsonValue = r#"{"old": 1, "new": 2}"#.parse().unwrap();
+        let collisions = value.rename_keys(&mapping);
+        assert_eq!(
+            collisions.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["/new"]
+        );
+        assert_eq!(value.to_string(), r#"{"new":2}"#);
+    }
+}
@@ -0,0 +1,158 @@
+use rust_json_formatter::json::{FormatOptions, JsonValue};
+use std::io;
+use std::path::Path;
+
+use crate::error::{CliError, ExitCode};
+use crate::messages::t;
+use crate::write_output;
+
+/**
+ * SARIF のスキーマの URI
+ */
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/**
+ * 報告する結果の種類 (SARIF のルール)
+ */
+#[derive(Clone, Copy)]
+enum Rule {
+    ParseError,  // 入力が JSON (または --from で指定した形式) として不正
+    Unformatted, // 入力が整形されていない (--check)
+    Failure,     // 読み込みの失敗などのその他の失敗
+}
+
+const RULES: [Rule; 3] = [Rule::ParseError, Rule::Unformatted, Rule::Failure];
+
+impl Rule {
+    fn id(self) -> &'static str {
+        return match self {
+            Rule::ParseError => "parse-error",
+            Rule::Unformatted => "unformatted",
+            Rule::Failure => "failure",
+        };
+    }
+
+    fn description(self) -> String {
+        return match self {
+            Rule::ParseError => t!(RuleParseError),
+            Rule::Unformatted => t!(RuleUnformatted),
+            Rule::Failure => t!(RuleFailure),
+        };
+    }
+}
+
+/**
+ * --check や validate の結果を SARIF 2.1.0 の形式で報告する
+ * GitHub の code scanning などが読み込めるよう、パースエラーは入力中の位置を添えて1件ずつ報告する
+ */
+pub struct SarifReport {
+    results: Vec<JsonValue>,
+}
+
+impl SarifReport {
+    pub fn new() -> Self {
+        return SarifReport { results: Vec::new() };
+    }
+
+    /**
+     * 入力 (標準入力の場合は path に None を指定する) の処理の失敗を追加する
+     */
+    pub fn add_error(&mut self, path: Option<&Path>, error: &CliError) {
+        let rule = if error.code == ExitCode::ParseError {
+            Rule::ParseError
+        } else {
+            Rule::Failure
+        };
+        if error.diagnostics.is_empty() {
+            self.add(rule, path, &error.message, None);
+        }
+        for diagnostic in &error.diagnostics {
+            self.add(
+                rule,
+                path,
+                &diagnostic.message,
+                Some((diagnostic.line, diagnostic.column)),
+            );
+        }
+    }
+
+    /**
+     * 整形されていない入力を追加する
+     */
+    pub fn add_unformatted(&mut self, path: Option<&Path>) {
+        self.add(Rule::Unformatted, path, &Rule::Unformatted.description(), None);
+    }
+
+    fn add(&mut self, rule: Rule, path: Option<&Path>, message: &str, position: Option<(usize, usize)>) {
+        let mut result = JsonValue::Null;
+        result.insert("ruleId", rule.id());
+        result.insert("level", "error");
+        let mut text = JsonValue::Null;
+        text.insert("text", message);
+        result.insert("message", text);
+
+        // 標準入力にはファイルの位置がないため、位置を添えない
+        if let Some(path) = path {
+            let mut artifact = JsonValue::Null;
+            // ./ から始まるパスは、リポジトリのルートからの相対パスとして読めるよう ./ を取り除く
+            let path = path.strip_prefix(".").unwrap_or(path);
+            artifact.insert("uri", path.to_string_lossy().replace('\\', "/"));
+            let mut physical = JsonValue::Null;
+            physical.insert("artifactLocation", artifact);
+            if let Some((line, column)) = position {
+                let mut region = JsonValue::Null;
+                region.insert("startLine", line as i64);
+                region.insert("startColumn", column as i64);
+                physical.insert("region", region);
+            }
+            let mut location = JsonValue::Null;
+            location.insert("physicalLocation", physical);
+            result.insert("locations", JsonValue::Array(vec![location]));
+        }
+        self.results.push(result);
+    }
+
+    /**
+     * SARIF のログ全体を JSON の値として返す
+     */
+    fn into_json(self) -> JsonValue {
+        let mut rules = JsonValue::Array(Vec::new());
+        for rule in RULES {
+            let mut description = JsonValue::Null;
+            description.insert("text", rule.description());
+            let mut descriptor = JsonValue::Null;
+            descriptor.insert("id", rule.id());
+            descriptor.insert("shortDescription", description);
+            rules.push(descriptor);
+        }
+        let mut driver = JsonValue::Null;
+        driver.insert("name", env!("CARGO_PKG_NAME"));
+        driver.insert("version", env!("CARGO_PKG_VERSION"));
+        driver.insert("rules", rules);
+        let mut tool = JsonValue::Null;
+        tool.insert("driver", driver);
+
+        let mut run = JsonValue::Null;
+        run.insert("tool", tool);
+        run.insert("columnKind", "unicodeCodePoints"); // 列番号は文字単位
+        run.insert("results", JsonValue::Array(self.results));
+
+        let mut log = JsonValue::Null;
+        log.insert("$schema", SCHEMA);
+        log.insert("version", "2.1.0");
+        log.insert("runs", JsonValue::Array(vec![run]));
+        return log;
+    }
+
+    /**
+     * SARIF のログを標準出力に出力する (--quiet の場合も出力する)
+     */
+    pub fn print(self) -> Result<(), CliError> {
+        let options = FormatOptions {
+            trailing_newline: true,
+            ..FormatOptions::default()
+        };
+        let output = self.into_json().format_with_options(&options);
+        return write_output(io::stdout().lock(), output.as_bytes());
+    }
+}
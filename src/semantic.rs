@@ -0,0 +1,151 @@
+use crate::json::JsonValue;
+use crate::number::Number;
+use crate::walk::{JsonPath, PathSegment};
+
+/**
+ * 値の意味的な比較のオプション
+ * デフォルトでは、オブジェクトのキーの順序を無視し、数値は値として等しい場合 (1 と 1.0 など) のみ等しいとみなす
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SemanticEqOptions {
+    pub epsilon: f64,            // 数値の差の絶対値がこの値以下であれば等しいとみなす
+    pub relative_epsilon: f64, // 数値の差の絶対値が、2つの数値の絶対値の大きい方に対してこの割合以下であれば等しいとみなす
+    pub normalize_numbers: bool, // 数値を f64 に丸めてから比較する (2^53 を超える整数や桁の多いリテラルを、丸めた値で比較する)
+}
+
+impl JsonValue {
+    /**
+     * オブジェクトのキーの順序を無視し、数値はオプションに従って比較した場合に等しいかどうか
+     * テストで整形結果を期待値と比較する場合などに使う
+     */
+    pub fn semantic_eq(&self, other: &JsonValue, options: &SemanticEqOptions) -> bool {
+        return self.semantic_diff(other, options).is_none();
+    }
+
+    /**
+     * semantic_eq と同じ方法で比較し、異なる最初の箇所の経路を返す (等しい場合は None)
+     * 一方にしかないキーは、そのキーの経路を返す
+     */
+    pub fn semantic_diff(&self, other: &JsonValue, options: &SemanticEqOptions) -> Option<JsonPath> {
+        let mut path = JsonPath::root();
+        if semantic_eq_at(self, other, options, &mut path) {
+            return None;
+        }
+        return Some(path);
+    }
+}
+
+/**
+ * 2つの値を比較し、異なる場合は path をその箇所の経路にして false を返す
+ */
+fn semantic_eq_at(a: &JsonValue, b: &JsonValue, options: &SemanticEqOptions, path: &mut JsonPath) -> bool {
+    match (a, b) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            for (key, value) in a {
+                path.push(PathSegment::Key(key.clone()));
+                match b.get(key) {
+                    Some(other) if semantic_eq_at(value, other, options, path) => {}
+                    _ => return false,
+                }
+                path.pop();
+            }
+            if let Some(key) = b.keys().find(|key| !a.contains_key(*key)) {
+                path.push(PathSegment::Key(key.clone()));
+                return false;
+            }
+            return true;
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            for (i, (value, other)) in a.iter().zip(b).enumerate() {
+                path.push(PathSegment::Index(i));
+                if !semantic_eq_at(value, other, options, path) {
+                    return false;
+                }
+                path.pop();
+            }
+            if a.len() != b.len() {
+                path.push(PathSegment::Index(a.len().min(b.len())));
+                return false;
+            }
+            return true;
+        }
+        (JsonValue::Number(a), JsonValue::Number(b)) => return numbers_eq(a, b, options),
+        _ => return a == b,
+    }
+}
+
+fn numbers_eq(a: &Number, b: &Number, options: &SemanticEqOptions) -> bool {
+    if a == b {
+        return true;
+    }
+    if options.epsilon == 0.0 && options.relative_epsilon == 0.0 && !options.normalize_numbers {
+        return false;
+    }
+    let (a, b) = (a.as_f64(), b.as_f64());
+    if a == b {
+        return true;
+    }
+    let difference = (a - b).abs();
+    return difference <= options.epsilon || difference <= options.relative_epsilon * a.abs().max(b.abs());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> JsonValue {
+        return input.parse().unwrap();
+    }
+
+    #[test]
+    fn test_semantic_eq() {
+        let options = SemanticEqOptions::default();
+        // キーの順序は無視する
+        assert!(parse(r#"{"a": 1, "b": [1.0, {"c": null}]}"#)
+            .semantic_eq(&parse(r#"{"b": [1, {"c": null}], "a": 1.0}"#), &options));
+        assert_eq!(
+            parse(r#"{"a": [1, 2]}"#)
+                .semantic_diff(&parse(r#"{"a": [1, 3]}"#), &options)
+                .map(|path| path.to_string()),
+            Some("/a/1".to_string())
+        );
+        assert_eq!(
+            parse(r#"{"a": 1}"#)
+                .semantic_diff(&parse(r#"{"a": 1, "b~": 2}"#), &options)
+                .map(|path| path.to_string()),
+            Some("/b~0".to_string())
+        );
+        assert_eq!(
+            parse("[1]")
+                .semantic_diff(&parse("[1, 2]"), &options)
+                .map(|path| path.to_string()),
+            Some("/1".to_string())
+        );
+        assert!(!parse("0.1").semantic_eq(&parse("0.1000001"), &options));
+    }
+
+    #[test]
+    fn test_semantic_eq_numbers() {
+        let absolute = SemanticEqOptions {
+            epsilon: 1e-6,
+            ..Default::default()
+        };
+        assert!(parse("[0.1]").semantic_eq(&parse("[0.1000001]"), &absolute));
+        assert!(!parse("[0.1]").semantic_eq(&parse("[0.10001]"), &absolute));
+
+        let relative = SemanticEqOptions {
+            relative_epsilon: 1e-9,
+            ..Default::default()
+        };
+        assert!(parse("1e20").semantic_eq(&parse("1.00000000001e20"), &relative));
+        assert!(!parse("1e-20").semantic_eq(&parse("2e-20"), &relative));
+
+        // f64 に丸めると等しくなる整数は、normalize_numbers の場合のみ等しいとみなす
+        let normalized = SemanticEqOptions {
+            normalize_numbers: true,
+            ..Default::default()
+        };
+        assert!(!parse("9007199254740993").semantic_eq(&parse("9007199254740992.0"), &SemanticEqOptions::default()));
+        assert!(parse("9007199254740993").semantic_eq(&parse("9007199254740992.0"), &normalized));
+    }
+}
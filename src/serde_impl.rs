@@ -0,0 +1,133 @@
+use indexmap::IndexMap;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::fmt;
+
+use crate::json::JsonValue;
+use crate::number::Number;
+
+/**
+ * serde の Serializer へ JsonValue を書き出す
+ * オブジェクトのキーの順序はそのまま保たれる
+ */
+impl Serialize for JsonValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return match self {
+            JsonValue::Object(obj) => {
+                let mut map = serializer.serialize_map(Some(obj.len()))?;
+                for (key, value) in obj {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            JsonValue::Array(array) => {
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                for value in array {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            JsonValue::String(str) => serializer.serialize_str(str),
+            JsonValue::Number(Number::Int(int)) => serializer.serialize_i64(*int),
+            JsonValue::Number(Number::UInt(uint)) => serializer.serialize_u64(*uint),
+            JsonValue::Number(num) => serializer.serialize_f64(num.as_f64()),
+            JsonValue::Bool(bool) => serializer.serialize_bool(*bool),
+            JsonValue::Null => serializer.serialize_unit(),
+        };
+    }
+}
+
+/**
+ * serde の Deserializer から JsonValue を読み込む
+ * 整数は i64 または u64 のまま保持する
+ */
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        return deserializer.deserialize_any(JsonValueVisitor);
+    }
+}
+
+struct JsonValueVisitor;
+
+impl<'de> Visitor<'de> for JsonValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return formatter.write_str("any JSON value");
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<JsonValue, E> {
+        return Ok(JsonValue::Bool(value));
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<JsonValue, E> {
+        return Ok(JsonValue::from(value));
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<JsonValue, E> {
+        return Ok(JsonValue::Number(Number::from(value)));
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<JsonValue, E> {
+        return Ok(JsonValue::from(value));
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<JsonValue, E> {
+        return Ok(JsonValue::String(value.to_string()));
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<JsonValue, E> {
+        return Ok(JsonValue::String(value));
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonValue, E> {
+        return Ok(JsonValue::Null);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<JsonValue, E> {
+        return Ok(JsonValue::Null);
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        return JsonValue::deserialize(deserializer);
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            array.push(value);
+        }
+        return Ok(JsonValue::Array(array));
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut object = IndexMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry::<String, JsonValue>()? {
+            object.insert(key, value);
+        }
+        return Ok(JsonValue::Object(object));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let value: JsonValue = r#"{"b": [1, 2.5, "x"], "a": {"t": true, "n": null}}"#.parse().unwrap();
+        let serialized = serde_json::to_string(&value).unwrap();
+
+        assert_eq!(serialized, r#"{"b":[1,2.5,"x"],"a":{"t":true,"n":null}}"#);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let value: JsonValue = serde_json::from_str(r#"{"z": [1, -2, 3.5], "y": {"f": false}, "x": null}"#).unwrap();
+
+        assert_eq!(value.to_string(), r#"{"z":[1,-2,3.5],"y":{"f":false},"x":null}"#);
+
+        let value: JsonValue = serde_json::from_str("18446744073709551615").unwrap();
+        assert_eq!(value.as_u64(), Some(u64::MAX));
+    }
+}
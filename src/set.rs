@@ -0,0 +1,137 @@
+use indexmap::IndexSet;
+use std::collections::HashSet;
+
+use crate::json::{JsonArray, JsonValue};
+use crate::walk::{JsonPath, PathPattern, PathSegment};
+
+/**
+ * 配列を集合とみなして行う演算
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetOperation {
+    Union,        // 和集合 (どちらかの配列にある要素)
+    Intersection, // 積集合 (両方の配列にある要素)
+    Difference,   // 差集合 (1つ目の配列にあり、2つ目の配列にない要素)
+}
+
+/**
+ * 2つの配列の要素を集合とみなして演算した結果を返す
+ * 要素は JsonValue の比較 (オブジェクトのキーの順序を無視し、数値は値で比較する) で同じものを1つにまとめ、
+ * 1つ目の配列、2つ目の配列の順に最初に現れた順序で並べる
+ */
+pub fn set_operation(operation: SetOperation, a: JsonArray, b: JsonArray) -> JsonArray {
+    let result: IndexSet<JsonValue> = match operation {
+        SetOperation::Union => a.into_iter().chain(b).collect(),
+        SetOperation::Intersection => {
+            let b: HashSet<JsonValue> = b.into_iter().collect();
+            a.into_iter().filter(|value| b.contains(value)).collect()
+        }
+        SetOperation::Difference => {
+            let b: HashSet<JsonValue> = b.into_iter().collect();
+            a.into_iter().filter(|value| !b.contains(value)).collect()
+        }
+    };
+    return result.into_iter().collect();
+}
+
+impl JsonValue {
+    /**
+     * 経路がいずれかのパターンに一致する配列 (パターンが空の場合はすべての配列) から重複した要素を取り除き、要素を取り除いた配列の経路を返す
+     * 要素は集合演算と同じ方法で比較し、最初に現れた要素を残して順序は変えない
+     * key (JSON Pointer) を指定した場合は、要素の中の key の値が同じ要素を重複とみなす (key の値がない要素は要素全体で比べる)
+     */
+    pub fn dedupe_arrays(&mut self, patterns: &[PathPattern], key: Option<&str>) -> Vec<JsonPath> {
+        let mut deduped = Vec::new();
+        self.dedupe_arrays_at(patterns, key, &mut JsonPath::root(), &mut deduped);
+        return deduped;
+    }
+
+    fn dedupe_arrays_at(
+        &mut self,
+        patterns: &[PathPattern],
+        key: Option<&str>,
+        path: &mut JsonPath,
+        deduped: &mut Vec<JsonPath>,
+    ) {
+        match self {
+            JsonValue::Object(obj) => {
+                for (name, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(name.clone()));
+                    value.dedupe_arrays_at(patterns, key, path, deduped);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                // 内側の配列の重複を先に取り除いてから比較する
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.dedupe_arrays_at(patterns, key, path, deduped);
+                    path.pop();
+                }
+                if !patterns.is_empty() && !patterns.iter().any(|pattern| pattern.matches(path)) {
+                    return;
+                }
+                let mut seen = HashSet::new();
+                let keep: Vec<bool> = array
+                    .iter()
+                    .map(|value| match key.and_then(|key| value.pointer(key)) {
+                        Some(identity) => seen.insert((true, identity)),
+                        None => seen.insert((false, value)),
+                    })
+                    .collect();
+                if keep.contains(&false) {
+                    let mut keep = keep.into_iter();
+                    array.retain(|_| keep.next().unwrap());
+                    deduped.push(path.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(operation: SetOperation, a: &str, b: &str) -> String {
+        let array = |input: &str| match input.parse::<JsonValue>().unwrap() {
+            JsonValue::Array(array) => array,
+            _ => panic!(),
+        };
+        return JsonValue::Array(set_operation(operation, array(a), array(b))).to_string();
+    }
+
+    #[test]
+    fn test_set_operation() {
+        let a = r#"[{"id": 1, "tags": ["x"]}, 2, 2.0, "a"]"#;
+        let b = r#"[{"tags": ["x"], "id": 1.0}, 3, "b"]"#;
+        assert_eq!(
+            apply(SetOperation::Union, a, b),
+            r#"[{"id":1,"tags":["x"]},2,"a",3,"b"]"#
+        );
+        assert_eq!(apply(SetOperation::Intersection, a, b), r#"[{"id":1,"tags":["x"]}]"#);
+        assert_eq!(apply(SetOperation::Difference, a, b), r#"[2,"a"]"#);
+    }
+
+    #[test]
+    fn test_dedupe_arrays() {
+        let input = r#"{"a": [1, 1.0, {"x": [2, 2]}, {"x": [2]}], "b": [{"id": 1, "n": "p"}, {"id": 1, "n": "q"}, {"n": "r"}]}"#;
+        let mut value: JsonValue = input.parse().unwrap();
+        let deduped = value.dedupe_arrays(&[], None);
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":[1,{"x":[2]}],"b":[{"id":1,"n":"p"},{"id":1,"n":"q"},{"n":"r"}]}"#
+        );
+        assert_eq!(
+            deduped.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["/a/2/x", "/a"]
+        );
+
+        // キーを指定すると、その値が同じ要素を重複とみなす
+        let mut value: JsonValue = input.parse().unwrap();
+        value.dedupe_arrays(&[PathPattern::parse("/b").unwrap()], Some("/id"));
+        assert_eq!(value["b"].to_string(), r#"[{"id":1,"n":"p"},{"n":"r"}]"#);
+        assert_eq!(value["a"].as_array().map(Vec::len), Some(4));
+    }
+}
@@ -0,0 +1,33 @@
+use rust_json_formatter::json::{JsonArray, JsonValue};
+use rust_json_formatter::set::{set_operation, SetOperation};
+use std::path::Path;
+
+use crate::cli::{Cli, SetOp};
+use crate::error::CliError;
+use crate::messages::t;
+use crate::{document, fail, format_options, open_stdout, render, write_output};
+
+/**
+ * 2つの入力の最上位の配列の要素を集合とみなして演算し、結果の配列を整形して出力する
+ */
+pub fn run(cli: &Cli, op: SetOp, a: &Path, b: &Path) {
+    let operation = match op {
+        SetOp::Union => SetOperation::Union,
+        SetOp::Intersect => SetOperation::Intersection,
+        SetOp::Diff => SetOperation::Difference,
+    };
+    let load = |path: &Path| load_array(cli, path).unwrap_or_else(|error| fail(cli, error));
+    let result = JsonValue::Array(set_operation(operation, load(a), load(b)));
+    let output = render(cli, &result, &format_options(cli), 0).unwrap_or_else(|error| fail(cli, error));
+    write_output(open_stdout(cli), output.as_bytes()).unwrap_or_else(|error| fail(cli, error));
+}
+
+/**
+ * 入力を読み込み、最上位の配列の要素を返す (最上位が配列でない場合はエラー)
+ */
+fn load_array(cli: &Cli, path: &Path) -> Result<JsonArray, CliError> {
+    return match document::load(cli, Some(path))? {
+        JsonValue::Array(array) => Ok(array),
+        _ => Err(CliError::usage(t!(SetopNotArray)).context(path.display())),
+    };
+}
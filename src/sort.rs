@@ -0,0 +1,376 @@
+use std::cmp::Ordering;
+
+use crate::json::JsonValue;
+
+/**
+ * オブジェクトのキーの並べ方
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyOrder {
+    Lexical, // 文字コード順 ("item10" は "item2" より前)
+    Natural, // 数字の並びを数値として比較する順 ("item2" は "item10" より前)
+}
+
+impl KeyOrder {
+    /**
+     * この並べ方で2つのキーを比較する
+     */
+    pub fn compare(self, a: &str, b: &str) -> Ordering {
+        return match self {
+            KeyOrder::Lexical => a.cmp(b),
+            KeyOrder::Natural => natural_cmp(a, b),
+        };
+    }
+}
+
+impl JsonValue {
+    /**
+     * すべてのオブジェクトのメンバーを、キーの指定した並べ方で並べ替える
+     */
+    pub fn sort_keys(&mut self, order: KeyOrder) {
+        match self {
+            JsonValue::Object(obj) => {
+                obj.sort_by(|a, _, b, _| order.compare(a, b));
+                for value in obj.values_mut() {
+                    value.sort_keys(order);
+                }
+            }
+            JsonValue::Array(array) => {
+                for value in array.iter_mut() {
+                    value.sort_keys(order);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * すべてのオブジェクトで、keys に含まれるキーのメンバーを keys の順に先頭へ移す
+     * それ以外のメンバーは、元の順序を保ったまま後ろに続ける (sort_keys の後に呼べば、並べ替えた順になる)
+     */
+    pub fn prioritize_keys(&mut self, keys: &[String]) {
+        match self {
+            JsonValue::Object(obj) => {
+                let rank = |key: &str| keys.iter().position(|k| k == key).unwrap_or(keys.len());
+                obj.sort_by(|a, _, b, _| rank(a).cmp(&rank(b)));
+                for value in obj.values_mut() {
+                    value.prioritize_keys(keys);
+                }
+            }
+            JsonValue::Array(array) => {
+                for value in array.iter_mut() {
+                    value.prioritize_keys(keys);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * すべてのオブジェクトのメンバーを、見本のドキュメント example の同じ位置にあるオブジェクトのキーの順に並べ替える
+     * 配列の要素は、見本の同じ位置の要素 (なければ先頭の要素) に合わせる
+     * 見本にないキーのメンバーは、元の順序を保ったまま後ろに続ける
+     */
+    pub fn order_keys_by_example(&mut self, example: &JsonValue) {
+        self.order_keys_like(KeyTemplate::Example(example));
+    }
+
+    /**
+     * すべてのオブジェクトのメンバーを、JSON Schema の properties に書かれたキーの順に並べ替える
+     * items / prefixItems / additionalProperties / allOf / anyOf / oneOf と、ドキュメント内を指す $ref をたどる
+     * スキーマにないキーのメンバーは、元の順序を保ったまま後ろに続ける
+     */
+    pub fn order_keys_by_schema(&mut self, schema: &JsonValue) {
+        self.order_keys_like(KeyTemplate::Schema { schema, root: schema });
+    }
+
+    fn order_keys_like(&mut self, template: KeyTemplate) {
+        match self {
+            JsonValue::Object(obj) => {
+                let keys = template.keys();
+                let rank = |key: &str| keys.iter().position(|k| *k == key).unwrap_or(keys.len());
+                obj.sort_by(|a, _, b, _| rank(a).cmp(&rank(b)));
+                for (key, value) in obj.iter_mut() {
+                    if let Some(child) = template.member(key) {
+                        value.order_keys_like(child);
+                    }
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    if let Some(child) = template.element(index) {
+                        value.order_keys_like(child);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * キーの並べ方の手本 (ドキュメントの値をたどるのに合わせて、手本の対応する位置をたどる)
+ */
+#[derive(Clone, Copy)]
+enum KeyTemplate<'a> {
+    Example(&'a JsonValue), // 見本のドキュメントの値
+    Schema {
+        schema: &'a JsonValue, // 値に対応するスキーマ
+        root: &'a JsonValue,   // $ref を解決するためのスキーマ全体
+    },
+}
+
+/**
+ * $ref をたどる回数の上限 (循環した参照で止まらなくなるのを防ぐ)
+ */
+const MAX_REF_DEPTH: usize = 32;
+
+impl<'a> KeyTemplate<'a> {
+    /**
+     * オブジェクトのキーを並べる順
+     */
+    fn keys(self) -> Vec<&'a str> {
+        return match self {
+            KeyTemplate::Example(example) => match example {
+                JsonValue::Object(obj) => obj.keys().map(String::as_str).collect(),
+                _ => vec![],
+            },
+            KeyTemplate::Schema { schema, root } => {
+                let mut keys = Vec::new();
+                for schema in subschemas(schema, root) {
+                    let properties = schema.get("properties").and_then(JsonValue::as_object);
+                    for key in properties.into_iter().flat_map(|properties| properties.keys()) {
+                        if !keys.contains(&key.as_str()) {
+                            keys.push(key.as_str());
+                        }
+                    }
+                }
+                keys
+            }
+        };
+    }
+
+    /**
+     * オブジェクトのキー key のメンバーに対応する手本
+     */
+    fn member(self, key: &str) -> Option<KeyTemplate<'a>> {
+        return match self {
+            KeyTemplate::Example(example) => example.get(key).map(KeyTemplate::Example),
+            KeyTemplate::Schema { schema, root } => {
+                let schemas = subschemas(schema, root);
+                let property = schemas.iter().find_map(|schema| schema.get("properties")?.get(key));
+                let additional = || {
+                    schemas
+                        .iter()
+                        .find_map(|schema| schema.get("additionalProperties").filter(|s| s.is_object()))
+                };
+                property
+                    .or_else(additional)
+                    .map(|schema| KeyTemplate::Schema { schema, root })
+            }
+        };
+    }
+
+    /**
+     * 配列の index 番目の要素に対応する手本
+     */
+    fn element(self, index: usize) -> Option<KeyTemplate<'a>> {
+        return match self {
+            KeyTemplate::Example(example) => {
+                let array = example.as_array()?;
+                array.get(index).or(array.first()).map(KeyTemplate::Example)
+            }
+            KeyTemplate::Schema { schema, root } => subschemas(schema, root)
+                .into_iter()
+                .find_map(|schema| {
+                    let prefix = schema.get("prefixItems").and_then(|prefix| prefix.get(index));
+                    let items = schema.get("items")?;
+                    return prefix.or(match items {
+                        JsonValue::Array(tuple) => tuple.get(index),
+                        _ => Some(items),
+                    });
+                })
+                .map(|schema| KeyTemplate::Schema { schema, root }),
+        };
+    }
+}
+
+/**
+ * スキーマと、allOf / anyOf / oneOf で組み合わせたスキーマを、$ref を解決して順に返す
+ */
+fn subschemas<'a>(schema: &'a JsonValue, root: &'a JsonValue) -> Vec<&'a JsonValue> {
+    let mut schemas = Vec::new();
+    collect_subschemas(schema, root, 0, &mut schemas);
+    return schemas;
+}
+
+fn collect_subschemas<'a>(schema: &'a JsonValue, root: &'a JsonValue, depth: usize, out: &mut Vec<&'a JsonValue>) {
+    if depth > MAX_REF_DEPTH {
+        return;
+    }
+    let reference = schema.get("$ref").and_then(JsonValue::as_str);
+    if let Some(target) = reference.and_then(|reference| root.pointer(reference.strip_prefix('#')?)) {
+        collect_subschemas(target, root, depth + 1, out);
+    }
+    out.push(schema);
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        for subschema in schema.get(keyword).and_then(JsonValue::as_array).into_iter().flatten() {
+            collect_subschemas(subschema, root, depth + 1, out);
+        }
+    }
+}
+
+/**
+ * 数字の並びを数値として比較する、自然な順序で2つの文字列を比較する
+ * 数値として等しい数字の並び ("7" と "007") は、先頭の 0 が少ない方を前にする
+ */
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a_rest, mut b_rest) = (a, b);
+    loop {
+        let (a_chunk, b_chunk) = match (next_chunk(a_rest), next_chunk(b_rest)) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_chunk), Some(b_chunk)) => (a_chunk, b_chunk),
+        };
+        let ordering = match (is_digits(a_chunk), is_digits(b_chunk)) {
+            (true, true) => compare_digits(a_chunk, b_chunk),
+            _ => a_chunk.cmp(b_chunk),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        a_rest = &a_rest[a_chunk.len()..];
+        b_rest = &b_rest[b_chunk.len()..];
+    }
+}
+
+/**
+ * 文字列の先頭から、数字だけか数字以外だけからなる最長の部分を取り出す
+ */
+fn next_chunk(str: &str) -> Option<&str> {
+    let digit = str.bytes().next()?.is_ascii_digit();
+    let len = str.bytes().take_while(|b| b.is_ascii_digit() == digit).count();
+    return Some(&str[..len]);
+}
+
+fn is_digits(chunk: &str) -> bool {
+    return chunk.bytes().all(|b| b.is_ascii_digit());
+}
+
+/**
+ * 数字の並びを数値として比較する (桁数に上限はない)
+ */
+fn compare_digits(a: &str, b: &str) -> Ordering {
+    let (a_trimmed, b_trimmed) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+    return a_trimmed
+        .len()
+        .cmp(&b_trimmed.len())
+        .then_with(|| a_trimmed.cmp(b_trimmed))
+        .then_with(|| a.len().cmp(&b.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("item2", "item10"), Ordering::Less);
+        assert_eq!(natural_cmp("10", "9"), Ordering::Greater);
+        assert_eq!(natural_cmp("7", "007"), Ordering::Less);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("item", "item"), Ordering::Equal);
+        assert_eq!(
+            natural_cmp("99999999999999999999", "100000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let mut json: JsonValue = r#"{"item10": 1, "item2": {"b": 1, "a": 2}, "item1": [{"10": 1, "9": 2}]}"#
+            .parse()
+            .unwrap();
+        json.sort_keys(KeyOrder::Natural);
+        assert_eq!(
+            json.to_string(),
+            r#"{"item1":[{"9":2,"10":1}],"item2":{"a":2,"b":1},"item10":1}"#
+        );
+
+        json.sort_keys(KeyOrder::Lexical);
+        assert_eq!(
+            json.to_string(),
+            r#"{"item1":[{"10":1,"9":2}],"item10":1,"item2":{"a":2,"b":1}}"#
+        );
+    }
+
+    #[test]
+    fn test_prioritize_keys() {
+        let mut json: JsonValue =
+            r#"{"version": "1.0", "scripts": {"z": 1, "name": 2}, "name": "app", "license": "MIT"}"#
+                .parse()
+                .unwrap();
+        json.prioritize_keys(&["name".to_string(), "version".to_string()]);
+        // 指定していないキーは元の順序のまま
+        assert_eq!(
+            json.to_string(),
+            r#"{"name":"app","version":"1.0","scripts":{"name":2,"z":1},"license":"MIT"}"#
+        );
+    }
+
+    #[test]
+    fn test_order_keys_by_example() {
+        let mut json: JsonValue = r#"{"extra": 0, "items": [{"b": 1, "a": 2}, {"c": 3, "b": 4, "a": 5}], "name": "x"}"#
+            .parse()
+            .unwrap();
+        let example: JsonValue = r#"{"name": "", "items": [{"a": 0, "b": 0}]}"#.parse().unwrap();
+        json.order_keys_by_example(&example);
+        // 見本にないキーは元の順序のまま後ろに続け、配列の要素は見本の先頭の要素に合わせる
+        assert_eq!(
+            json.to_string(),
+            r#"{"name":"x","items":[{"a":2,"b":1},{"a":5,"b":4,"c":3}],"extra":0}"#
+        );
+    }
+
+    #[test]
+    fn test_order_keys_by_schema() {
+        let schema: JsonValue = r##"{
+            "$defs": {"node": {"properties": {"id": {}, "children": {"items": {"$ref": "#/$defs/node"}}}}},
+            "allOf": [{"properties": {"name": {}}}],
+            "properties": {
+                "version": {},
+                "root": {"$ref": "#/$defs/node"},
+                "env": {"additionalProperties": {"properties": {"host": {}, "port": {}}}}
+            }
+        }"##
+        .parse()
+        .unwrap();
+        let mut json: JsonValue = r#"{
+            "env": {"dev": {"port": 80, "host": "a"}},
+            "root": {"children": [{"children": [], "id": 2}], "id": 1},
+            "name": "x",
+            "other": true,
+            "version": 1
+        }"#
+        .parse()
+        .unwrap();
+        json.order_keys_by_schema(&schema);
+        // $ref をたどって再帰的な構造にも合わせ、allOf のキーは properties のキーの後ろに続ける
+        assert_eq!(
+            json.to_string(),
+            r#"{"version":1,"root":{"id":1,"children":[{"id":2,"children":[]}]},"env":{"dev":{"host":"a","port":80}},"name":"x","other":true}"#
+        );
+    }
+
+    #[test]
+    fn test_order_keys_by_schema_tuple() {
+        let schema: JsonValue =
+            r#"{"prefixItems": [{"properties": {"b": {}, "a": {}}}], "items": {"properties": {"a": {}, "b": {}}}}"#
+                .parse()
+                .unwrap();
+        let mut json: JsonValue = r#"[{"a": 1, "b": 2}, {"b": 3, "a": 4}]"#.parse().unwrap();
+        json.order_keys_by_schema(&schema);
+        assert_eq!(json.to_string(), r#"[{"b":2,"a":1},{"a":4,"b":3}]"#);
+    }
+}
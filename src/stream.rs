@@ -0,0 +1,677 @@
+use memchr::memchr2;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::json::{push_escaped_string_with, FormatOptions, FormatTree, JsonValue};
+use crate::lexer::{Lexer, Token, BOM};
+use crate::parser::{Limits, ParseError, ParseErrorKind};
+use crate::theme::TokenClass;
+
+/**
+ * 出力をまとめて書き込むまでに溜めておく大きさ (バイト単位)
+ */
+const FLUSH_THRESHOLD: usize = 64 * 1024;
+
+/**
+ * 逐次整形に失敗した原因
+ */
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),     // 入出力のエラー
+    Parse(ParseError), // 入力が JSON として不正
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            StreamError::Io(error) => write!(f, "入出力に失敗しました: {}", error),
+            StreamError::Parse(error) => write!(f, "JSONのパースに失敗しました: {}", error),
+        };
+    }
+}
+
+impl Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        return StreamError::Io(error);
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(error: ParseError) -> Self {
+        return StreamError::Parse(error);
+    }
+}
+
+/**
+ * 入力から読み取った字句
+ */
+enum Event {
+    Token(Token<'static>), // 字句解析できたトークン
+    Invalid,               // 字句解析できない文字やリテラル
+    Bom,                   // 入力の途中の BOM
+    Eof,                   // 入力の終わり
+}
+
+/**
+ * 開いているオブジェクトや配列
+ */
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+/**
+ * 次に来るべきトークン
+ */
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Value { allow_close: bool }, // 値 ([ の直後は ] も可)
+    Key { allow_close: bool },   // オブジェクトのキー ({ の直後は } も可)
+    Colon,                       // キーの後の :
+    AfterValue,                  // 値の後の , や閉じ括弧 (最上位の値の後は入力の終わり)
+}
+
+/**
+ * 入力全体を読み込まずに、1トークンずつ読み取りながら整形して書き出す
+ * メモリに保持するのは開いているオブジェクトや配列の種類の並びだけなので、巨大な入力でも使用量が増えない
+ * 出力は JsonValue::format_with_options と同じになるが、--collapse や数値配列のレイアウトのような
+ * 後続の要素を見てから決めるレイアウトには対応しない
+ */
+pub fn reformat<R: BufRead, W: Write>(reader: R, writer: W, options: &FormatOptions) -> Result<(), StreamError> {
+    return reformat_with_limits(reader, writer, options, &Limits::default());
+}
+
+/**
+ * ホワイトスペースで区切って連結した複数の JSON の値 ({"a": 1} [2] のような入力) を、1つずつ reformat する
+ * 値ごとに改行で区切り、読み終えた値はすぐに書き出す。不正な値があればそこで止まり、それより前の値は出力済みになる
+ * 上限は値ごとに確かめる (入力の大きさだけは入力全体で数える)
+ */
+pub fn reformat_sequence<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    options: &FormatOptions,
+    limits: &Limits,
+) -> Result<(), StreamError> {
+    let mut formatter = StreamFormatter::new(reader, writer, options, limits);
+    formatter.sequence = true;
+    return formatter.run();
+}
+
+/**
+ * 入力の大きさに上限を設けて reformat する (上限は Parser と同じように確かめる)
+ * オブジェクトのメンバー数は、重複したキーも別のメンバーとして数える
+ */
+pub fn reformat_with_limits<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    options: &FormatOptions,
+    limits: &Limits,
+) -> Result<(), StreamError> {
+    let mut formatter = StreamFormatter::new(reader, writer, options, limits);
+    return formatter.run();
+}
+
+/**
+ * 読み取った位置を数えながら入力を1バイトずつ読み進める
+ */
+struct Input<R: BufRead> {
+    reader: R,       // 入力
+    position: usize, // 次に読み取るバイトの位置
+}
+
+impl<R: BufRead> Input<R> {
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        return Ok(self.reader.fill_buf()?.first().copied());
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.reader.consume(len);
+        self.position += len;
+    }
+
+    /**
+     * ホワイトスペースを読み飛ばす
+     */
+    fn skip_whitespace(&mut self) -> io::Result<()> {
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            let skipped = buf
+                .iter()
+                .position(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                .unwrap_or(buf.len());
+            let done = skipped < buf.len();
+            self.advance(skipped);
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /**
+     * 次のトークンを読み取る
+     * トークンの文字列を切り出した後の解釈は Lexer に任せ、DOM を構築する場合と同じ結果にする
+     */
+    fn next_event(&mut self) -> io::Result<Event> {
+        let Some(first) = self.peek()? else {
+            return Ok(Event::Eof);
+        };
+        let text = match first {
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => {
+                self.advance(1);
+                vec![first]
+            }
+            b'"' => self.read_string()?,
+            b'0'..=b'9' | b'-' | b'+' => self.read_while(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))?,
+            b if b.is_ascii_alphabetic() => self.read_while(|b| b.is_ascii_alphabetic())?,
+            _ if self.skip_bom()? => return Ok(Event::Bom),
+            _ => {
+                self.advance(1);
+                return Ok(Event::Invalid);
+            }
+        };
+        let Ok(text) = String::from_utf8(text) else {
+            return Ok(Event::Invalid);
+        };
+        let Some(Ok(spanned)) = Lexer::new(&text).next() else {
+            return Ok(Event::Invalid);
+        };
+        let token = match spanned.value {
+            Token::String(string) => Token::String(string.into_owned().into()),
+            Token::LeftBrace => Token::LeftBrace,
+            Token::RightBrace => Token::RightBrace,
+            Token::LeftBracket => Token::LeftBracket,
+            Token::RightBracket => Token::RightBracket,
+            Token::Colon => Token::Colon,
+            Token::Comma => Token::Comma,
+            Token::Number(number) => Token::Number(number),
+            Token::True => Token::True,
+            Token::False => Token::False,
+            Token::Null => Token::Null,
+            // コメントを読み取らない Lexer では現れない
+            Token::Comment(_) => return Ok(Event::Invalid),
+        };
+        return Ok(Event::Token(token));
+    }
+
+    /**
+     * 次が BOM であれば読み飛ばす
+     */
+    fn skip_bom(&mut self) -> io::Result<bool> {
+        let bom = BOM.to_string();
+        if !self.reader.fill_buf()?.starts_with(bom.as_bytes()) {
+            return Ok(false);
+        }
+        self.advance(bom.len());
+        return Ok(true);
+    }
+
+    /**
+     * 条件を満たすバイトが続く間読み取る
+     */
+    fn read_while(&mut self, predicate: impl Fn(u8) -> bool) -> io::Result<Vec<u8>> {
+        let mut text = Vec::new();
+        while let Some(byte) = self.peek()? {
+            if !predicate(byte) {
+                break;
+            }
+            text.push(byte);
+            self.advance(1);
+        }
+        return Ok(text);
+    }
+
+    /**
+     * 閉じる " までの文字列リテラルを、エスケープシーケンスを含めてそのまま読み取る
+     */
+    fn read_string(&mut self) -> io::Result<Vec<u8>> {
+        let mut text = vec![b'"'];
+        self.advance(1);
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(text);
+            }
+            // 次の " か \ までをまとめて読み取る
+            let Some(offset) = memchr2(b'"', b'\\', buf) else {
+                let len = buf.len();
+                text.extend_from_slice(buf);
+                self.advance(len);
+                continue;
+            };
+            let special = buf[offset];
+            text.extend_from_slice(&buf[..=offset]);
+            self.advance(offset + 1);
+            if special == b'"' {
+                return Ok(text);
+            }
+            // エスケープされた文字は終端として扱わない
+            if let Some(escaped) = self.peek()? {
+                text.push(escaped);
+                self.advance(1);
+            }
+        }
+    }
+}
+
+struct StreamFormatter<'o, R: BufRead, W: Write> {
+    input: Input<R>,
+    writer: W,
+    options: &'o FormatOptions,
+    limits: &'o Limits,             // 入力の大きさの上限
+    output: String,                 // 書き込み待ちの出力
+    stack: Vec<(Container, usize)>, // 開いているオブジェクトや配列と、読み始めたメンバーや要素の数
+    state: State,                   // 次に来るべきトークン
+    nodes: usize,                   // これまでに読み取った値の数
+    sequence: bool,                 // 最上位の値の後に続く値も読み取るかどうか
+}
+
+impl<'o, R: BufRead, W: Write> StreamFormatter<'o, R, W> {
+    fn new(reader: R, writer: W, options: &'o FormatOptions, limits: &'o Limits) -> Self {
+        return StreamFormatter {
+            input: Input { reader, position: 0 },
+            writer,
+            options,
+            limits,
+            output: String::new(),
+            stack: Vec::new(),
+            state: State::Value { allow_close: false },
+            nodes: 0,
+            sequence: false,
+        };
+    }
+
+    fn run(&mut self) -> Result<(), StreamError> {
+        // 先頭の BOM は読み飛ばす
+        self.input.skip_bom()?;
+        loop {
+            self.input.skip_whitespace()?;
+            if self.sequence && self.state == State::AfterValue && self.stack.is_empty() && self.input.peek()?.is_some()
+            {
+                // 読み終えた値を書き出し、次の値を最上位の値として読み始める
+                self.output.push_str(self.options.line_ending.as_str());
+                self.flush()?;
+                self.writer.flush()?;
+                self.state = State::Value { allow_close: false };
+                self.nodes = 0;
+            }
+            let position = self.input.position;
+            let event = self.input.next_event()?;
+            let error = |kind| ParseError { kind, position };
+            if let Some(max) = self.limits.max_input_size.filter(|max| self.input.position > *max) {
+                return Err(ParseError {
+                    kind: ParseErrorKind::InputTooLarge,
+                    position: max,
+                }
+                .into());
+            }
+
+            match (self.state, event) {
+                // 最上位の値を読み終えた後は入力の終わりだけを受け付ける
+                (State::AfterValue, Event::Eof) if self.stack.is_empty() => break,
+                (_, Event::Bom) => return Err(error(ParseErrorKind::UnexpectedBom).into()),
+                (State::AfterValue, _) if self.stack.is_empty() => {
+                    return Err(error(ParseErrorKind::TrailingCharacters).into());
+                }
+                (_, Event::Eof) => return Err(error(ParseErrorKind::UnexpectedEof).into()),
+                (_, Event::Invalid) => return Err(error(ParseErrorKind::InvalidToken).into()),
+
+                (State::Value { allow_close: true }, Event::Token(Token::RightBracket)) => self.close(Container::Array),
+                (State::Value { .. }, Event::Token(token)) => self.begin_value(token, position)?,
+
+                (State::Key { allow_close: true }, Event::Token(Token::RightBrace)) => self.close(Container::Object),
+                (State::Key { .. }, Event::Token(Token::String(key))) => {
+                    self.count_element(position)?;
+                    self.check_string_length(&key, position)?;
+                    self.push_indent(self.stack.len());
+                    let mut escaped = String::new();
+                    push_escaped_string_with(&mut escaped, &key, &self.options.escape);
+                    self.push_token(TokenClass::Key, &escaped);
+                    self.state = State::Colon;
+                }
+                (State::Key { .. }, Event::Token(_)) => return Err(error(ParseErrorKind::ExpectedKey).into()),
+
+                (State::Colon, Event::Token(Token::Colon)) => {
+                    self.push_token(TokenClass::Punctuation, ":");
+                    if !self.options.compact {
+                        self.output.push(' ');
+                    }
+                    self.state = State::Value { allow_close: false };
+                }
+                (State::Colon, Event::Token(_)) => return Err(error(ParseErrorKind::ExpectedColon).into()),
+
+                (State::AfterValue, Event::Token(token)) => {
+                    let (container, _) = *self.stack.last().unwrap();
+                    match (container, token) {
+                        (Container::Object, Token::Comma) => {
+                            self.push_token(TokenClass::Punctuation, ",");
+                            self.push_newline();
+                            self.state = State::Key { allow_close: false };
+                        }
+                        (Container::Array, Token::Comma) => {
+                            self.push_token(TokenClass::Punctuation, ",");
+                            self.push_newline();
+                            self.state = State::Value { allow_close: false };
+                        }
+                        (Container::Object, Token::RightBrace) => {
+                            self.push_newline();
+                            self.close(Container::Object);
+                        }
+                        (Container::Array, Token::RightBracket) => {
+                            self.push_newline();
+                            self.close(Container::Array);
+                        }
+                        (Container::Object, _) => return Err(error(ParseErrorKind::ExpectedCommaOrBrace).into()),
+                        (Container::Array, _) => return Err(error(ParseErrorKind::ExpectedCommaOrBracket).into()),
+                    }
+                }
+            }
+
+            if self.output.len() >= FLUSH_THRESHOLD {
+                self.flush()?;
+            }
+        }
+
+        if self.options.trailing_newline {
+            self.output.push_str(self.options.line_ending.as_str());
+        }
+        self.flush()?;
+        self.writer.flush()?;
+        return Ok(());
+    }
+
+    /**
+     * 値の先頭のトークンを処理する
+     * 配列の要素であれば字下げしてから出力する
+     */
+    fn begin_value(&mut self, token: Token<'static>, position: usize) -> Result<(), ParseError> {
+        let in_array = matches!(self.stack.last(), Some((Container::Array, _)));
+        if in_array {
+            self.count_element(position)?;
+        }
+        self.count_node(position)?;
+        if in_array {
+            self.push_indent(self.stack.len());
+        }
+        let scalar = match token {
+            Token::LeftBrace => {
+                self.limits.check_depth(self.stack.len(), position)?;
+                self.push_token(TokenClass::Punctuation, "{");
+                self.push_newline();
+                self.stack.push((Container::Object, 0));
+                self.state = State::Key { allow_close: true };
+                return Ok(());
+            }
+            Token::LeftBracket => {
+                self.limits.check_depth(self.stack.len(), position)?;
+                self.push_token(TokenClass::Punctuation, "[");
+                self.push_newline();
+                self.stack.push((Container::Array, 0));
+                self.state = State::Value { allow_close: true };
+                return Ok(());
+            }
+            Token::String(string) => {
+                self.check_string_length(&string, position)?;
+                JsonValue::String(string.into_owned())
+            }
+            Token::Number(number) => JsonValue::Number(number),
+            Token::True => JsonValue::Bool(true),
+            Token::False => JsonValue::Bool(false),
+            Token::Null => JsonValue::Null,
+            _ => {
+                return Err(ParseError {
+                    kind: ParseErrorKind::ExpectedValue,
+                    position,
+                })
+            }
+        };
+        scalar.format_value(0, self.options, &mut self.output);
+        self.state = State::AfterValue;
+        return Ok(());
+    }
+
+    /**
+     * 開いているオブジェクトや配列を閉じる
+     */
+    fn close(&mut self, container: Container) {
+        self.stack.pop();
+        self.push_indent(self.stack.len());
+        match container {
+            Container::Object => self.push_token(TokenClass::Punctuation, "}"),
+            Container::Array => self.push_token(TokenClass::Punctuation, "]"),
+        }
+        self.state = State::AfterValue;
+    }
+
+    /**
+     * 開いているオブジェクトや配列のメンバーや要素を1つ数え、上限を超えていないことを確かめる
+     * position は数えたメンバーや要素の位置
+     */
+    fn count_element(&mut self, position: usize) -> Result<(), ParseError> {
+        let Some((_, count)) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        *count += 1;
+        return match self.limits.max_elements {
+            Some(max) if *count > max => Err(ParseError {
+                kind: ParseErrorKind::TooManyElements,
+                position,
+            }),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * position から始まる値を数え、入力全体の値の数が上限を超えていないことを確かめる
+     */
+    fn count_node(&mut self, position: usize) -> Result<(), ParseError> {
+        self.nodes += 1;
+        return match self.limits.max_nodes {
+            Some(max) if self.nodes > max => Err(ParseError {
+                kind: ParseErrorKind::TooManyNodes,
+                position,
+            }),
+            _ => Ok(()),
+        };
+    }
+
+    /**
+     * position から始まる文字列 (キーを含む) が上限の長さを超えていないことを確かめる
+     */
+    fn check_string_length(&self, string: &str, position: usize) -> Result<(), ParseError> {
+        return match self.limits.max_string_length {
+            Some(max) if string.len() > max => Err(ParseError {
+                kind: ParseErrorKind::StringTooLong,
+                position,
+            }),
+            _ => Ok(()),
+        };
+    }
+
+    fn push_token(&mut self, class: TokenClass, str: &str) {
+        match &self.options.theme {
+            Some(theme) => theme.paint(&mut self.output, class, str),
+            None => self.output.push_str(str),
+        }
+    }
+
+    fn push_newline(&mut self) {
+        if !self.options.compact {
+            self.output.push_str(self.options.line_ending.as_str());
+        }
+    }
+
+    fn push_indent(&mut self, indent: usize) {
+        if self.options.compact {
+            return;
+        }
+        for _ in 0..indent {
+            self.output.push_str(&self.options.indent_unit);
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.write_all(self.output.as_bytes())?;
+        self.output.clear();
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::LineEnding;
+    use crate::parser::Parser;
+    use crate::theme::Theme;
+
+    fn reformat_str(input: &str, options: &FormatOptions) -> Result<String, StreamError> {
+        let mut output = Vec::new();
+        reformat(input.as_bytes(), &mut output, options)?;
+        return Ok(String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_reformat_matches_dom() {
+        let inputs = [
+            r#"{"a": [1, {"b": null}, [], {}], "c": "d\n\"e\"", "f": -1.5e3, "g": [true, false]}"#,
+            "[]",
+            "{}",
+            r#""日本語""#,
+            "[[[]], [{}]]",
+            "[-0, -0.0, 0, -0.5]",
+        ];
+        let options = [
+            FormatOptions::default(),
+            FormatOptions::compact(),
+            FormatOptions {
+                theme: Some(Theme::preset("monokai").unwrap()),
+                indent_unit: "\t".to_string(),
+                line_ending: LineEnding::Crlf,
+                trailing_newline: true,
+                ..Default::default()
+            },
+        ];
+        for input in inputs {
+            let value: JsonValue = input.parse().unwrap();
+            for options in &options {
+                assert_eq!(
+                    reformat_str(input, options).unwrap(),
+                    value.format_with_options(options),
+                    "{}",
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reformat_errors_match_dom() {
+        let inputs = [
+            r#"{"a" 1}"#,
+            r#"{"a": 1,}"#,
+            "[1, 2",
+            "[1 2]",
+            r#"{"a": 1 "b": 2}"#,
+            "{1: 2}",
+            "[1] 2",
+            "[tru]",
+            "",
+            "\u{FEFF}[1,",
+            "[1, \u{FEFF}2]",
+        ];
+        for input in inputs {
+            let expected = input.parse::<JsonValue>().unwrap_err();
+            match reformat_str(input, &FormatOptions::default()) {
+                Err(StreamError::Parse(error)) => assert_eq!(error, expected, "{}", input),
+                other => panic!("{}: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reformat_limits() {
+        // 上限を超えた場合も Parser と同じ種類と位置で報告する
+        let input = r#"{"name": "abcd", "items": [1, [2, 3], 4]}"#;
+        for limits in [
+            Limits {
+                max_input_size: Some(10),
+                ..Limits::default()
+            },
+            Limits {
+                max_string_length: Some(4),
+                ..Limits::default()
+            },
+            Limits {
+                max_elements: Some(2),
+                ..Limits::default()
+            },
+            Limits {
+                max_nodes: Some(5),
+                ..Limits::default()
+            },
+            Limits {
+                max_depth: Some(2),
+                ..Limits::default()
+            },
+        ] {
+            let expected = Parser::new(Lexer::new(input)).with_limits(limits).parse().unwrap_err();
+            let mut output = Vec::new();
+            match reformat_with_limits(input.as_bytes(), &mut output, &FormatOptions::default(), &limits) {
+                Err(StreamError::Parse(error)) => assert_eq!(error, expected, "{:?}", limits),
+                other => panic!("{:?}: {:?}", limits, other),
+            }
+        }
+
+        // 深すぎる入力は、字下げが膨らむ前に既定の上限で打ち切る
+        let deep = "[".repeat(200_000);
+        match reformat_str(&deep, &FormatOptions::default()) {
+            Err(StreamError::Parse(error)) => assert_eq!(error, deep.parse::<JsonValue>().unwrap_err()),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reformat_sequence() {
+        // 値ごとに改行で区切り、不正な値の前までを書き出す
+        let mut output = Vec::new();
+        let input = "{\"a\": 1} [1, 2]\n\"x\" [1,, 2] null";
+        let result = reformat_sequence(
+            input.as_bytes(),
+            &mut output,
+            &FormatOptions::compact(),
+            &Limits::default(),
+        );
+        match result {
+            Err(StreamError::Parse(error)) => assert_eq!(error.position, 23),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(String::from_utf8(output).unwrap(), "{\"a\":1}\n[1,2]\n\"x\"\n");
+
+        // 上限は値ごとに確かめる
+        let limits = Limits {
+            max_nodes: Some(2),
+            ..Limits::default()
+        };
+        let mut output = Vec::new();
+        reformat_sequence("[1] [2]".as_bytes(), &mut output, &FormatOptions::compact(), &limits).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "[1]\n[2]");
+    }
+
+    #[test]
+    fn test_reformat_small_buffer() {
+        // バッファの境界をまたぐ文字列やエスケープも正しく読み取れる
+        let input = r#"{"key with \"quotes\"": ["abc\\", "日本語"]}"#;
+        let reader = io::BufReader::with_capacity(3, input.as_bytes());
+        let mut output = Vec::new();
+        reformat(reader, &mut output, &FormatOptions::compact()).unwrap();
+
+        let value: JsonValue = input.parse().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), value.to_string());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathSegment};
+
+/**
+ * 変数の置き換えの結果
+ */
+#[derive(Debug, Default, PartialEq)]
+pub struct Substitution {
+    pub substituted: Vec<JsonPath>,       // 変数を置き換えた文字列の経路
+    pub missing: Vec<(JsonPath, String)>, // 値がなく、置き換えずに残した変数の経路と名前
+}
+
+impl JsonValue {
+    /**
+     * 文字列の値に含まれる ${NAME} を、lookup で得た変数の値に置き換える (キーは置き換えない)
+     * NAME は英字か _ で始まる英数字と _ の並びで、$${ は置き換えずに ${ にする
+     * lookup が None を返した変数は ${NAME} のまま残し、結果の missing に加える
+     */
+    pub fn substitute_vars(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> Substitution {
+        let mut substitution = Substitution::default();
+        self.substitute_vars_at(lookup, &mut JsonPath::root(), &mut substitution);
+        return substitution;
+    }
+
+    fn substitute_vars_at(
+        &mut self,
+        lookup: &dyn Fn(&str) -> Option<String>,
+        path: &mut JsonPath,
+        substitution: &mut Substitution,
+    ) {
+        match self {
+            JsonValue::String(str) => {
+                if !str.contains("${") {
+                    return;
+                }
+                let mut missing = Vec::new();
+                let substituted = substitute_str(str, lookup, &mut missing);
+                substitution
+                    .missing
+                    .extend(missing.into_iter().map(|name| (path.clone(), name)));
+                if substituted != *str {
+                    *str = substituted;
+                    substitution.substituted.push(path.clone());
+                }
+            }
+            JsonValue::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.substitute_vars_at(lookup, path, substitution);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.substitute_vars_at(lookup, path, substitution);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * 文字列の ${NAME} を置き換えた文字列を返し、値がなかった変数の名前を missing に加える
+ */
+fn substitute_str(str: &str, lookup: &dyn Fn(&str) -> Option<String>, missing: &mut Vec<String>) -> String {
+    let mut substituted = String::with_capacity(str.len());
+    let mut rest = str;
+    while let Some(start) = rest.find('$') {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            substituted.push_str("${");
+            rest = after;
+            continue;
+        }
+        let name = rest
+            .strip_prefix("${")
+            .and_then(|after| Some(&after[..after.find('}')?]))
+            .filter(|name| is_var_name(name));
+        let Some(name) = name else {
+            substituted.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+        let placeholder = &rest[..name.len() + 3];
+        match lookup(name) {
+            Some(value) => substituted.push_str(&value),
+            None => {
+                substituted.push_str(placeholder);
+                missing.push(name.to_string());
+            }
+        }
+        rest = &rest[placeholder.len()..];
+    }
+    substituted.push_str(rest);
+    return substituted;
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    return chars.next().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute_vars() {
+        let lookup = |name: &str| match name {
+            "HOST" => Some("db.example.com".to_string()),
+            "PORT" => Some("5432".to_string()),
+            _ => None,
+        };
+        let mut value: JsonValue =
+            r#"{"url": "postgres://${HOST}:${PORT}/app", "${HOST}": ["$${HOST}", "${MISSING}", "$5 ${1X} ${HOST"]}"#
+                .parse()
+                .unwrap();
+        let substitution = value.substitute_vars(&lookup);
+        assert_eq!(
+            value.to_string(),
+            r#"{"url":"postgres://db.example.com:5432/app","${HOST}":["${HOST}","${MISSING}","$5 ${1X} ${HOST"]}"#
+        );
+        let paths = |paths: Vec<JsonPath>| paths.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(paths(substitution.substituted), vec!["/url", "/${HOST}/0"]);
+        assert_eq!(
+            substitution
+                .missing
+                .iter()
+                .map(|(path, name)| (path.to_string(), name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("/${HOST}/1".to_string(), "MISSING")]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::json::JsonValue;
+
+/**
+ * 値の形 (要約の際に同じ形の値を1行にまとめるために使う)
+ */
+#[derive(Debug, PartialEq)]
+enum Shape<'a> {
+    Object(Vec<&'a str>), // オブジェクト (キーの集合で区別し、最初に現れた順序で表示する)
+    Array,                // 配列 (要素をまとめて要約する)
+    Scalar(&'static str), // 文字列、数値、真偽値、null
+}
+
+/**
+ * 同じ形の値の集まり
+ */
+struct Group<'a> {
+    shape: Shape<'a>,
+    sorted_keys: Vec<&'a str>, // オブジェクトの場合、形の比較に使うソートしたキー
+    values: Vec<&'a JsonValue>,
+}
+
+impl JsonValue {
+    /**
+     * 値の中身ではなく構造を要約した文字列を返す
+     * 配列の要素は同じ形 (オブジェクトならキーの集合、それ以外は型) ごとに1行にまとめ、"items[]: 12,000 × {id, name, price}" のように数を示す
+     * オブジェクトや配列を含むメンバーは、その下に1段字下げして同様に要約する
+     */
+    pub fn summarize(&self) -> String {
+        let mut summary = String::new();
+        summarize_values(&[self], "", false, 0, &mut summary);
+        return summary;
+    }
+}
+
+/**
+ * 値の集まりを形ごとに要約して追加する
+ * label は親から見た値の名前 (キーに配列の要素を表す [] を付けたもの)、repeated は配列の要素として繰り返し現れる値かどうか
+ */
+fn summarize_values(values: &[&JsonValue], label: &str, repeated: bool, indent: usize, summary: &mut String) {
+    for group in group_by_shape(values) {
+        if group.shape == Shape::Array {
+            let elements: Vec<&JsonValue> = group
+                .values
+                .iter()
+                .flat_map(|value| value.as_array().unwrap())
+                .collect();
+            if !elements.is_empty() {
+                summarize_values(&elements, &format!("{}[]", label), true, indent, summary);
+                continue;
+            }
+        }
+        summary.push_str(&"  ".repeat(indent));
+        if !label.is_empty() {
+            summary.push_str(label);
+            summary.push_str(": ");
+        }
+        if repeated {
+            summary.push_str(&format_count(group.values.len()));
+            summary.push_str(" × ");
+        }
+        match &group.shape {
+            Shape::Object(keys) => summary.push_str(&format!("{{{}}}", keys.join(", "))),
+            Shape::Array => summary.push_str("[]"),
+            Shape::Scalar(name) => summary.push_str(name),
+        }
+        summary.push('\n');
+
+        // オブジェクトや配列を含むメンバーだけを、さらに要約する
+        if let Shape::Object(keys) = &group.shape {
+            for key in keys {
+                let members: Vec<&JsonValue> = group.values.iter().map(|value| &value[*key]).collect();
+                if members.iter().any(|member| member.is_object() || member.is_array()) {
+                    summarize_values(&members, key, repeated, indent + 1, summary);
+                }
+            }
+        }
+    }
+}
+
+/**
+ * 値を形ごとにまとめる (まとまりは最初に現れた順に並べる)
+ */
+fn group_by_shape<'a>(values: &[&'a JsonValue]) -> Vec<Group<'a>> {
+    let mut groups: Vec<Group<'a>> = Vec::new();
+    for value in values {
+        let (shape, sorted_keys) = match value {
+            JsonValue::Object(obj) => {
+                let keys: Vec<&str> = obj.keys().map(|key| key.as_str()).collect();
+                let mut sorted_keys = keys.clone();
+                sorted_keys.sort_unstable();
+                (Shape::Object(keys), sorted_keys)
+            }
+            JsonValue::Array(_) => (Shape::Array, Vec::new()),
+            JsonValue::String(_) => (Shape::Scalar("string"), Vec::new()),
+            JsonValue::Number(_) => (Shape::Scalar("number"), Vec::new()),
+            JsonValue::Bool(_) => (Shape::Scalar("boolean"), Vec::new()),
+            JsonValue::Null => (Shape::Scalar("null"), Vec::new()),
+        };
+        let same_shape = |group: &&mut Group<'a>| match (&group.shape, &shape) {
+            (Shape::Object(_), Shape::Object(_)) => group.sorted_keys == sorted_keys,
+            _ => group.shape == shape,
+        };
+        match groups.iter_mut().find(same_shape) {
+            Some(group) => group.values.push(value),
+            None => groups.push(Group {
+                shape,
+                sorted_keys,
+                values: vec![value],
+            }),
+        }
+    }
+    return groups;
+}
+
+/**
+ * 数を3桁ごとにカンマで区切って表す (12000 は "12,000")
+ */
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(ch);
+    }
+    return formatted;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(12000), "12,000");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_summarize() {
+        let json: JsonValue = r#"{
+            "items": [
+                {"id": 1, "name": "a", "tags": ["x", "y"]},
+                {"name": "b", "id": 2, "tags": []},
+                {"id": 3, "error": null}
+            ],
+            "meta": {"total": 3, "owner": null},
+            "empty": []
+        }"#
+        .parse()
+        .unwrap();
+        let expected = "\
+{items, meta, empty}
+  items[]: 2 × {id, name, tags}
+    tags[]: 2 × string
+  items[]: 1 × {id, error}
+  meta: {total, owner}
+  empty: []
+";
+        assert_eq!(json.summarize(), expected);
+
+        assert_eq!(JsonValue::from("a").summarize(), "string\n");
+        assert_eq!(
+            "[[1, 2], [3]]".parse::<JsonValue>().unwrap().summarize(),
+            "[][]: 3 × number\n"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::json::{JsonObject, JsonValue};
+use crate::xml::push_escaped_text;
+
+/**
+ * HTML の表の要素に付けるスタイル (貼り付け先にスタイルシートがなくても罫線が付くよう、style 属性で指定する)
+ */
+const TABLE_STYLE: &str = "border-collapse: collapse";
+const CELL_STYLE: &str = "border: 1px solid #d0d7de; padding: 4px 8px";
+const HEADER_STYLE: &str = "border: 1px solid #d0d7de; padding: 4px 8px; background: #f6f8fa";
+
+/**
+ * 表に変換できない値
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableError {
+    NotArray,         // 最上位が配列ではない
+    NotObject(usize), // 配列のこの位置の要素がオブジェクトではない
+}
+
+/**
+ * 表の列の文字列の寄せ方
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,  // 左寄せ
+    Right, // 右寄せ (値がすべて数値の列)
+}
+
+/**
+ * 最上位の配列の要素のオブジェクトを行とする表
+ * 列は、いずれかの行に現れたキーを最初に現れた順に並べたもの
+ */
+struct Table<'a> {
+    columns: Vec<&'a str>,
+    rows: Vec<&'a JsonObject>,
+}
+
+impl<'a> Table<'a> {
+    fn new(value: &'a JsonValue) -> Result<Self, TableError> {
+        let array = value.as_array().ok_or(TableError::NotArray)?;
+        let mut columns: Vec<&str> = Vec::new();
+        let mut rows = Vec::with_capacity(array.len());
+        for (index, element) in array.iter().enumerate() {
+            let row = element.as_object().ok_or(TableError::NotObject(index))?;
+            for key in row.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+            rows.push(row);
+        }
+        return Ok(Table { columns, rows });
+    }
+
+    /**
+     * 列の値がすべて数値 (値のない行を除く) であれば右寄せにする
+     */
+    fn align(&self, column: &str) -> Align {
+        let mut values = self
+            .rows
+            .iter()
+            .filter_map(|row| row.get(column))
+            .filter(|v| !v.is_null());
+        let numeric = values.clone().next().is_some() && values.all(|v| matches!(v, JsonValue::Number(_)));
+        return if numeric { Align::Right } else { Align::Left };
+    }
+}
+
+/**
+ * セルに表示する文字列 (null や値のない場合は空、文字列はそのまま、それ以外は1行の JSON)
+ */
+fn cell_text(value: Option<&JsonValue>) -> String {
+    return match value {
+        None | Some(JsonValue::Null) => String::new(),
+        Some(JsonValue::String(str)) => str.clone(),
+        Some(value) => value.to_string(),
+    };
+}
+
+impl JsonValue {
+    /**
+     * 最上位の配列の要素のオブジェクトを行とする GitHub Flavored Markdown の表に変換した文字列を返す
+     * 値がすべて数値の列は右寄せにし、列の幅を揃える
+     * セル中の | はエスケープし、改行は <br> にする (空の配列の場合は空文字列を返す)
+     */
+    pub fn to_markdown_table(&self) -> Result<String, TableError> {
+        let table = Table::new(self)?;
+        if table.columns.is_empty() {
+            return Ok(String::new());
+        }
+        let escape = |text: &str| text.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>");
+        let header: Vec<String> = table.columns.iter().map(|column| escape(column)).collect();
+        let cells: Vec<Vec<String>> = table
+            .rows
+            .iter()
+            .map(|row| {
+                table
+                    .columns
+                    .iter()
+                    .map(|column| escape(&cell_text(row.get(*column))))
+                    .collect()
+            })
+            .collect();
+        let aligns: Vec<Align> = table.columns.iter().map(|column| table.align(column)).collect();
+        let widths: Vec<usize> = (0..table.columns.len())
+            .map(|i| {
+                let cell_width = cells.iter().map(|row| row[i].chars().count()).max().unwrap_or(0);
+                return cell_width.max(header[i].chars().count()).max(3);
+            })
+            .collect();
+
+        let mut output = String::new();
+        push_markdown_row(&mut output, &header, &widths, &aligns);
+        let separator: Vec<String> = widths
+            .iter()
+            .zip(&aligns)
+            .map(|(width, align)| match align {
+                Align::Left => "-".repeat(*width),
+                Align::Right => format!("{}:", "-".repeat(width - 1)),
+            })
+            .collect();
+        push_markdown_row(&mut output, &separator, &widths, &aligns);
+        for row in &cells {
+            push_markdown_row(&mut output, row, &widths, &aligns);
+        }
+        return Ok(output);
+    }
+}
+
+impl JsonValue {
+    /**
+     * 最上位の配列の要素のオブジェクトを行とする HTML の <table> に変換した文字列を返す (indent_unit は1段分の字下げ)
+     * 罫線などのスタイルは style 属性で指定し、値がすべて数値の列は右寄せにする
+     * 入れ子のオブジェクトや配列は1行の JSON を <code> で囲んで表示し、改行は <br> にする (空の配列の場合は空文字列を返す)
+     */
+    pub fn to_html_table(&self, indent_unit: &str) -> Result<String, TableError> {
+        let table = Table::new(self)?;
+        if table.columns.is_empty() {
+            return Ok(String::new());
+        }
+        let aligns: Vec<Align> = table.columns.iter().map(|column| table.align(column)).collect();
+        let indent = |depth: usize| indent_unit.repeat(depth);
+        let mut output = format!("<table style=\"{}\">\n", TABLE_STYLE);
+        output.push_str(&format!("{}<thead>\n{}<tr>\n", indent(1), indent(2)));
+        for (column, align) in table.columns.iter().zip(&aligns) {
+            output.push_str(&indent(3));
+            push_html_cell(&mut output, "th", HEADER_STYLE, *align, |output| {
+                push_html_text(output, column);
+            });
+        }
+        output.push_str(&format!(
+            "{}</tr>\n{}</thead>\n{}<tbody>\n",
+            indent(2),
+            indent(1),
+            indent(1)
+        ));
+        for row in &table.rows {
+            output.push_str(&format!("{}<tr>\n", indent(2)));
+            for (column, align) in table.columns.iter().zip(&aligns) {
+                let value = row.get(*column);
+                output.push_str(&indent(3));
+                push_html_cell(&mut output, "td", CELL_STYLE, *align, |output| match value {
+                    Some(JsonValue::Object(_) | JsonValue::Array(_)) => {
+                        output.push_str("<code>");
+                        push_html_text(output, &cell_text(value));
+                        output.push_str("</code>");
+                    }
+                    _ => push_html_text(output, &cell_text(value)),
+                });
+            }
+            output.push_str(&format!("{}</tr>\n", indent(2)));
+        }
+        output.push_str(&format!("{}</tbody>\n</table>\n", indent(1)));
+        return Ok(output);
+    }
+}
+
+/**
+ * <th> や <td> のセルを1行で書き込む (push_content でセルの中身を書き込む)
+ */
+fn push_html_cell(output: &mut String, tag: &str, style: &str, align: Align, push_content: impl FnOnce(&mut String)) {
+    let align_style = match align {
+        Align::Left => "",
+        Align::Right => "; text-align: right",
+    };
+    output.push_str(&format!("<{} style=\"{}{}\">", tag, style, align_style));
+    push_content(output);
+    output.push_str(&format!("</{}>\n", tag));
+}
+
+/**
+ * テキストを HTML のエスケープを施して書き込み、改行を <br> にする
+ */
+fn push_html_text(output: &mut String, text: &str) {
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            output.push_str("<br>");
+        }
+        push_escaped_text(output, line.strip_suffix('\r').unwrap_or(line), false);
+    }
+}
+
+/**
+ * 表の1行を、列の幅と寄せ方に合わせて空白で埋めて書き込む
+ */
+fn push_markdown_row(output: &mut String, cells: &[String], widths: &[usize], aligns: &[Align]) {
+    output.push('|');
+    for ((cell, width), align) in cells.iter().zip(widths).zip(aligns) {
+        let padding = " ".repeat(width - cell.chars().count());
+        output.push(' ');
+        match align {
+            Align::Left => {
+                output.push_str(cell);
+                output.push_str(&padding);
+            }
+            Align::Right => {
+                output.push_str(&padding);
+                output.push_str(cell);
+            }
+        }
+        output.push_str(" |");
+    }
+    output.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_markdown_table() {
+        let value: JsonValue = r#"[
+            {"name": "alice", "age": 30, "tags": ["a", "b"]},
+            {"name": "bob|sue", "note": "line1\nline2", "age": null},
+            {"name": "名前", "age": 4}
+        ]"#
+        .parse()
+        .unwrap();
+        let expected = "\
+| name     | age | tags      | note           |
+| -------- | --: | --------- | -------------- |
+| alice    |  30 | [\"a\",\"b\"] |                |
+| bob\\|sue |     |           | line1<br>line2 |
+| 名前       |   4 |           |                |
+";
+        assert_eq!(value.to_markdown_table().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_markdown_table_invalid() {
+        let not_array: JsonValue = r#"{"a": 1}"#.parse().unwrap();
+        assert_eq!(not_array.to_markdown_table(), Err(TableError::NotArray));
+        let not_object: JsonValue = r#"[{"a": 1}, 2]"#.parse().unwrap();
+        assert_eq!(not_object.to_markdown_table(), Err(TableError::NotObject(1)));
+        assert_eq!(JsonValue::Array(vec![]).to_markdown_table(), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_to_html_table() {
+        let value: JsonValue = r#"[{"name": "<b>", "n": 1}, {"n": 2.5, "tags": ["a"], "note": "x\ny"}]"#
+            .parse()
+            .unwrap();
+        let th = |text: &str, align: &str| format!("      <th style=\"{}{}\">{}</th>\n", HEADER_STYLE, align, text);
+        let td = |text: &str, align: &str| format!("      <td style=\"{}{}\">{}</td>\n", CELL_STYLE, align, text);
+        let right = "; text-align: right";
+        let expected = [
+            format!("<table style=\"{}\">\n  <thead>\n    <tr>\n", TABLE_STYLE),
+            th("name", ""),
+            th("n", right),
+            th("tags", ""),
+            th("note", ""),
+            "    </tr>\n  </thead>\n  <tbody>\n    <tr>\n".to_string(),
+            td("&lt;b&gt;", ""),
+            td("1", right),
+            td("", ""),
+            td("", ""),
+            "    </tr>\n    <tr>\n".to_string(),
+            td("", ""),
+            td("2.5", right),
+            td("<code>[\"a\"]</code>", ""),
+            td("x<br>y", ""),
+            "    </tr>\n  </tbody>\n</table>\n".to_string(),
+        ]
+        .concat();
+        assert_eq!(value.to_html_table("  ").unwrap(), expected);
+    }
+}
@@ -0,0 +1,260 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::json::JsonValue;
+use crate::parser::ParseError;
+
+/**
+ * 色分けの対象となるトークンの種類
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenClass {
+    Key,         // オブジェクトのキー
+    String,      // 文字列の値
+    Number,      // 数値
+    Literal,     // true, false, null
+    Punctuation, // { } [ ] : ,
+}
+
+/**
+ * 端末に出力する色
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Ansi(u8),        // 30-37, 90-97 の SGR コード
+    Indexed(u8),     // 256 色パレットの番号
+    Rgb(u8, u8, u8), // トゥルーカラー
+}
+
+impl Color {
+    /**
+     * 色名・パレット番号・#rrggbb 形式の文字列から色を生成する
+     */
+    pub fn parse(str: &str) -> Option<Color> {
+        let names = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+        if let Some(i) = names.iter().position(|name| *name == str) {
+            return Some(Color::Ansi(30 + i as u8));
+        }
+        if let Some(name) = str.strip_prefix("bright-") {
+            if let Some(i) = names.iter().position(|n| *n == name) {
+                return Some(Color::Ansi(90 + i as u8));
+            }
+        }
+        if let Some(hex) = str.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return None;
+            }
+            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+            return Some(Color::Rgb(r, g, b));
+        }
+        return str.parse::<u8>().ok().map(Color::Indexed);
+    }
+
+    /**
+     * 色を開始するエスケープシーケンスを返す
+     */
+    fn escape(&self) -> String {
+        return match self {
+            Color::Ansi(code) => format!("\x1b[{}m", code),
+            Color::Indexed(index) => format!("\x1b[38;5;{}m", index),
+            Color::Rgb(r, g, b) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+        };
+    }
+}
+
+/**
+ * トークンの種類ごとの色の割り当て
+ * None の種類は色を付けずに出力する
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub key: Option<Color>,
+    pub string: Option<Color>,
+    pub number: Option<Color>,
+    pub literal: Option<Color>,
+    pub punctuation: Option<Color>,
+}
+
+/**
+ * 組み込みのテーマ名の一覧
+ */
+pub const PRESET_NAMES: [&str; 3] = ["default", "monokai", "solarized"];
+
+/**
+ * テーマの読み込みに失敗した原因
+ */
+#[derive(Debug)]
+pub enum ThemeError {
+    NotFound(String, io::Error), // 組み込みテーマにもファイルにも見つからない (テーマ名、読み込み時のエラー)
+    Parse(String, ParseError),   // テーマファイルが JSON としてパースできない (パス、パースエラー)
+    NotObject,                   // テーマ定義がオブジェクトでない
+    InvalidColor(String),        // 色として解釈できない文字列
+    NotString(String),           // 色が文字列でも null でもないトークンの種類
+    UnknownClass(String),        // 不明なトークンの種類
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            ThemeError::NotFound(name, error) => write!(
+                f,
+                "テーマ '{}' が見つかりません (組み込みテーマ: {}): {}",
+                name,
+                PRESET_NAMES.join(", "),
+                error
+            ),
+            ThemeError::Parse(path, error) => write!(f, "テーマファイル '{}' のパースに失敗しました: {}", path, error),
+            ThemeError::NotObject => write!(f, "テーマ定義はオブジェクトである必要があります"),
+            ThemeError::InvalidColor(color) => write!(f, "不正な色の指定です: {}", color),
+            ThemeError::NotString(name) => write!(f, "'{}' の色は文字列で指定してください", name),
+            ThemeError::UnknownClass(name) => write!(f, "不明なトークンの種類です: {}", name),
+        };
+    }
+}
+
+impl Error for ThemeError {}
+
+impl Theme {
+    /**
+     * 組み込みのテーマを名前から取得する
+     */
+    pub fn preset(name: &str) -> Option<Theme> {
+        return match name {
+            "default" => Some(Theme {
+                key: Some(Color::Ansi(34)),
+                string: Some(Color::Ansi(32)),
+                number: Some(Color::Ansi(36)),
+                literal: Some(Color::Ansi(33)),
+                punctuation: None,
+            }),
+            "monokai" => Some(Theme {
+                key: Some(Color::Rgb(0x66, 0xd9, 0xef)),
+                string: Some(Color::Rgb(0xe6, 0xdb, 0x74)),
+                number: Some(Color::Rgb(0xae, 0x81, 0xff)),
+                literal: Some(Color::Rgb(0xf9, 0x26, 0x72)),
+                punctuation: Some(Color::Rgb(0xf8, 0xf8, 0xf2)),
+            }),
+            "solarized" => Some(Theme {
+                key: Some(Color::Rgb(0x26, 0x8b, 0xd2)),
+                string: Some(Color::Rgb(0x2a, 0xa1, 0x98)),
+                number: Some(Color::Rgb(0xd3, 0x36, 0x82)),
+                literal: Some(Color::Rgb(0xcb, 0x4b, 0x16)),
+                punctuation: Some(Color::Rgb(0x93, 0xa1, 0xa1)),
+            }),
+            _ => None,
+        };
+    }
+
+    /**
+     * 組み込みのテーマ名、またはテーマ定義ファイルのパスからテーマを読み込む
+     */
+    pub fn load(name_or_path: &str) -> Result<Theme, ThemeError> {
+        if let Some(theme) = Theme::preset(name_or_path) {
+            return Ok(theme);
+        }
+        let text = fs::read_to_string(name_or_path).map_err(|e| ThemeError::NotFound(name_or_path.to_string(), e))?;
+        let json = text
+            .parse::<JsonValue>()
+            .map_err(|e| ThemeError::Parse(name_or_path.to_string(), e))?;
+        return Theme::from_json(&json);
+    }
+
+    /**
+     * {"key": "blue", "string": "#a6e22e"} のような JSON からテーマを生成する
+     * 指定のない種類は default テーマの色を引き継ぎ、null を指定すると色なしになる
+     */
+    pub fn from_json(json: &JsonValue) -> Result<Theme, ThemeError> {
+        let JsonValue::Object(object) = json else {
+            return Err(ThemeError::NotObject);
+        };
+        let mut theme = Theme::preset("default").unwrap();
+        for (name, value) in object {
+            let color = match value {
+                JsonValue::String(str) => Some(Color::parse(str).ok_or_else(|| ThemeError::InvalidColor(str.clone()))?),
+                JsonValue::Null => None,
+                _ => return Err(ThemeError::NotString(name.clone())),
+            };
+            match name.as_str() {
+                "key" => theme.key = color,
+                "string" => theme.string = color,
+                "number" => theme.number = color,
+                "literal" => theme.literal = color,
+                "punctuation" => theme.punctuation = color,
+                _ => return Err(ThemeError::UnknownClass(name.clone())),
+            }
+        }
+        return Ok(theme);
+    }
+
+    /**
+     * トークンの種類に応じた色を付けて文字列を追加する
+     */
+    pub fn paint(&self, formatted: &mut String, class: TokenClass, str: &str) {
+        let color = match class {
+            TokenClass::Key => self.key,
+            TokenClass::String => self.string,
+            TokenClass::Number => self.number,
+            TokenClass::Literal => self.literal,
+            TokenClass::Punctuation => self.punctuation,
+        };
+        match color {
+            Some(color) => {
+                formatted.push_str(&color.escape());
+                formatted.push_str(str);
+                formatted.push_str("\x1b[0m");
+            }
+            None => formatted.push_str(str),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_parse() {
+        assert_eq!(Color::parse("red"), Some(Color::Ansi(31)));
+        assert_eq!(Color::parse("bright-blue"), Some(Color::Ansi(94)));
+        assert_eq!(Color::parse("208"), Some(Color::Indexed(208)));
+        assert_eq!(Color::parse("#FF8000"), Some(Color::Rgb(255, 128, 0)));
+        assert_eq!(Color::parse("#ff80"), None);
+        assert_eq!(Color::parse("purple"), None);
+    }
+
+    #[test]
+    fn test_theme_from_json() {
+        let json = r##"{"key": "#010203", "number": null}"##.parse().unwrap();
+        let theme = Theme::from_json(&json).unwrap();
+
+        assert_eq!(theme.key, Some(Color::Rgb(1, 2, 3)));
+        assert_eq!(theme.number, None);
+        assert_eq!(theme.string, Theme::preset("default").unwrap().string);
+    }
+
+    #[test]
+    fn test_theme_from_json_invalid() {
+        let json1 = r#"{"keys": "red"}"#.parse().unwrap();
+        assert!(matches!(Theme::from_json(&json1), Err(ThemeError::UnknownClass(name)) if name == "keys"));
+
+        let json2 = r#"{"key": "purple"}"#.parse().unwrap();
+        assert!(matches!(Theme::from_json(&json2), Err(ThemeError::InvalidColor(color)) if color == "purple"));
+
+        let json3 = r#"{"key": 1}"#.parse().unwrap();
+        assert!(matches!(Theme::from_json(&json3), Err(ThemeError::NotString(name)) if name == "key"));
+    }
+
+    #[test]
+    fn test_theme_paint() {
+        let theme = Theme::preset("default").unwrap();
+        let mut formatted = String::new();
+        theme.paint(&mut formatted, TokenClass::Number, "1");
+        theme.paint(&mut formatted, TokenClass::Punctuation, ",");
+
+        assert_eq!(formatted, "\x1b[36m1\x1b[0m,");
+    }
+}
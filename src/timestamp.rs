@@ -0,0 +1,181 @@
+use glob::Pattern;
+
+use crate::json::JsonValue;
+use crate::walk::{JsonPath, PathPattern, PathSegment};
+
+/**
+ * ISO 8601 の形式で表せる範囲 (0000-01-01 から 9999-12-31 まで) の UNIX 時間 (ミリ秒)
+ */
+const MIN_MILLIS: i64 = -62167219200000;
+const MAX_MILLIS: i64 = 253402300799999;
+
+impl JsonValue {
+    /**
+     * UNIX 時間とみなす数値を ISO 8601 の文字列 (UTC) に置き換え、置き換えた値の経路を返す
+     * キーがいずれかの keys のパターン (*_at など) に一致するメンバーの値と、経路がいずれかの paths のパターンに一致する値を対象にする
+     * 単位 (秒、ミリ秒、マイクロ秒、ナノ秒) は値の大きさから推定する
+     */
+    pub fn convert_timestamps(&mut self, keys: &[Pattern], paths: &[PathPattern]) -> Vec<JsonPath> {
+        let mut converted = Vec::new();
+        self.convert_timestamps_at(keys, paths, &mut JsonPath::root(), &mut converted);
+        return converted;
+    }
+
+    fn convert_timestamps_at(
+        &mut self,
+        keys: &[Pattern],
+        paths: &[PathPattern],
+        path: &mut JsonPath,
+        converted: &mut Vec<JsonPath>,
+    ) {
+        match self {
+            JsonValue::Number(num) => {
+                let key_matched = match path.segments().last() {
+                    Some(PathSegment::Key(key)) => keys.iter().any(|pattern| pattern.matches(key)),
+                    _ => false,
+                };
+                if !key_matched && !paths.iter().any(|pattern| pattern.matches(path)) {
+                    return;
+                }
+                if let Some(timestamp) = format_iso8601(epoch_millis(num.as_f64())) {
+                    *self = JsonValue::String(timestamp);
+                    converted.push(path.clone());
+                }
+            }
+            JsonValue::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.convert_timestamps_at(keys, paths, path, converted);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(array) => {
+                for (index, value) in array.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.convert_timestamps_at(keys, paths, path, converted);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * UNIX 時間の値の大きさから単位を推定し、ミリ秒に換算する (マイクロ秒以下の端数は小数として残す)
+ * 秒で 1e11 (西暦5138年) 以上になる値はミリ秒、1e14 以上はマイクロ秒、1e17 以上はナノ秒とみなす
+ */
+fn epoch_millis(num: f64) -> f64 {
+    let scale = match num.abs() {
+        abs if abs < 1e11 => 1e3,
+        abs if abs < 1e14 => 1.0,
+        abs if abs < 1e17 => 1e-3,
+        _ => 1e-6,
+    };
+    return num * scale;
+}
+
+/**
+ * UNIX 時間 (ミリ秒) を ISO 8601 の文字列 (2024-01-02T03:04:05Z の形式の UTC) にする
+ * 秒の端数がある場合は 2024-01-02T03:04:05.678Z のように小数で表し、マイクロ秒の端数 (ミリ秒の小数部) まであれば
+ * 2024-01-02T03:04:05.678901Z のように6桁にする (それより細かい端数は丸める)
+ * 0000年から9999年までの範囲にない値や、有限でない値は None を返す
+ */
+pub fn format_iso8601(millis: f64) -> Option<String> {
+    if !millis.is_finite() || millis < MIN_MILLIS as f64 || millis >= (MAX_MILLIS + 1) as f64 {
+        return None;
+    }
+    // 大きな値を1000倍すると倍精度では誤差が出るため、ミリ秒の整数部と小数部を分けてマイクロ秒にする
+    let whole = millis.floor();
+    let micros = whole as i64 * 1000 + ((millis - whole) * 1000.0).round() as i64;
+    if micros > MAX_MILLIS * 1000 + 999 {
+        return None;
+    }
+    let days = micros.div_euclid(86_400_000_000);
+    let time = micros.rem_euclid(86_400_000_000) / 1_000_000;
+    let fraction = micros.rem_euclid(1_000_000);
+    let (year, month, day) = civil_from_days(days);
+    let (hour, minute, second) = (time / 3600, time / 60 % 60, time % 60);
+
+    let mut formatted = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    );
+    if fraction % 1000 != 0 {
+        formatted.push_str(&format!(".{:06}", fraction));
+    } else if fraction != 0 {
+        formatted.push_str(&format!(".{:03}", fraction / 1000));
+    }
+    formatted.push('Z');
+    return Some(formatted);
+}
+
+/**
+ * 1970-01-01 からの日数を、グレゴリオ暦の年月日にする
+ * (Howard Hinnant の civil_from_days のアルゴリズム)
+ */
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    return (year, month, day);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_iso8601() {
+        assert_eq!(format_iso8601(0.0).unwrap(), "1970-01-01T00:00:00Z");
+        assert_eq!(format_iso8601(1709210096789.0).unwrap(), "2024-02-29T12:34:56.789Z");
+        assert_eq!(format_iso8601(-1000.0).unwrap(), "1969-12-31T23:59:59Z");
+        assert_eq!(format_iso8601(1709210096789.5).unwrap(), "2024-02-29T12:34:56.789500Z");
+        assert_eq!(format_iso8601(0.001).unwrap(), "1970-01-01T00:00:00.000001Z");
+        assert_eq!(format_iso8601(-0.5).unwrap(), "1969-12-31T23:59:59.999500Z");
+        assert_eq!(format_iso8601(MAX_MILLIS as f64).unwrap(), "9999-12-31T23:59:59.999Z");
+        assert_eq!(format_iso8601(MIN_MILLIS as f64).unwrap(), "0000-01-01T00:00:00Z");
+        assert_eq!(format_iso8601(MAX_MILLIS as f64 + 1.0), None);
+        assert_eq!(format_iso8601(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_epoch_millis() {
+        // 秒、ミリ秒、マイクロ秒、ナノ秒
+        assert_eq!(epoch_millis(1700000000.0), 1700000000000.0);
+        assert_eq!(epoch_millis(1700000000123.0), 1700000000123.0);
+        assert_eq!(epoch_millis(1.7e18), 1.7e12);
+        // マイクロ秒やナノ秒の端数は、ミリ秒の小数部として残す
+        let millis = epoch_millis(1700000000123456.0);
+        assert_eq!(format_iso8601(millis).unwrap(), "2023-11-14T22:13:20.123456Z");
+        let millis = epoch_millis(1700000000123456789.0);
+        assert_eq!(format_iso8601(millis).unwrap(), "2023-11-14T22:13:20.123457Z");
+    }
+
+    #[test]
+    fn test_convert_timestamps() {
+        let mut json: JsonValue =
+            r#"{"created_at": 1700000000, "count": 3, "meta": {"ts": 1700000000000, "updated_at": "yesterday"}}"#
+                .parse()
+                .unwrap();
+        let converted = json.convert_timestamps(
+            &[Pattern::new("*_at").unwrap()],
+            &[PathPattern::parse("/meta/ts").unwrap()],
+        );
+        assert_eq!(
+            converted.iter().map(|path| path.to_string()).collect::<Vec<_>>(),
+            vec!["/created_at", "/meta/ts"]
+        );
+        assert_eq!(json["created_at"], JsonValue::from("2023-11-14T22:13:20Z"));
+        assert_eq!(json["meta"]["ts"], JsonValue::from("2023-11-14T22:13:20Z"));
+        // 数値でない値や、パターンに一致しない値はそのまま残す
+        assert_eq!(json["meta"]["updated_at"], JsonValue::from("yesterday"));
+        assert_eq!(json["count"], JsonValue::from(3.0));
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::{self, BufRead, Read};
+
+/**
+ * UTF-16 のバイト順
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ByteOrder {
+    LittleEndian, // UTF-16LE (BOM は FF FE)
+    BigEndian,    // UTF-16BE (BOM は FE FF)
+}
+
+/**
+ * 先頭のバイト列が UTF-16 の BOM であれば、そのバイト順を返す
+ */
+pub fn detect_bom(head: &[u8]) -> Option<ByteOrder> {
+    return match head {
+        [0xFF, 0xFE, ..] => Some(ByteOrder::LittleEndian),
+        [0xFE, 0xFF, ..] => Some(ByteOrder::BigEndian),
+        _ => None,
+    };
+}
+
+/**
+ * UTF-16 のバイト列を読み込み、UTF-8 に変換しながら返すリーダー
+ * 先頭の BOM は U+FEFF として UTF-8 の BOM に変換する
+ * UTF-16 として不正な入力 (対になっていないサロゲートや奇数長のバイト列) は InvalidData のエラーになる
+ */
+pub struct Utf16Decoder<R: BufRead> {
+    inner: R,
+    order: ByteOrder,
+    pending: Vec<u8>, // まだ変換していない UTF-16 のバイト列 (読み込みの境界で分かれた符号単位やサロゲートペア)
+    decoded: Vec<u8>, // 変換済みで、まだ返していない UTF-8 のバイト列
+    position: usize,  // decoded のうち返し終えたバイト数
+}
+
+impl<R: BufRead> Utf16Decoder<R> {
+    pub fn new(inner: R, order: ByteOrder) -> Self {
+        return Utf16Decoder {
+            inner,
+            order,
+            pending: Vec::new(),
+            decoded: Vec::new(),
+            position: 0,
+        };
+    }
+
+    /**
+     * 入力を読み込んで変換し、decoded を補充する (入力の終わりに達した場合は空のまま返す)
+     */
+    fn refill(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.position = 0;
+        while self.decoded.is_empty() {
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                if !self.pending.is_empty() {
+                    return Err(invalid_utf16());
+                }
+                return Ok(());
+            }
+            self.pending.extend_from_slice(chunk);
+            let len = chunk.len();
+            self.inner.consume(len);
+
+            let mut units: Vec<u16> = self
+                .pending
+                .chunks_exact(2)
+                .map(|pair| match self.order {
+                    ByteOrder::LittleEndian => u16::from_le_bytes([pair[0], pair[1]]),
+                    ByteOrder::BigEndian => u16::from_be_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            // 末尾の上位サロゲートは、対になる下位サロゲートを読み込むまで変換しない
+            if units.last().is_some_and(|unit| (0xD800..0xDC00).contains(unit)) {
+                units.pop();
+            }
+            for ch in char::decode_utf16(units.iter().copied()) {
+                let ch = ch.map_err(|_| invalid_utf16())?;
+                let mut buf = [0; 4];
+                self.decoded.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+            self.pending.drain(..units.len() * 2);
+        }
+        return Ok(());
+    }
+}
+
+impl<R: BufRead> Read for Utf16Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.decoded.len() {
+            self.refill()?;
+        }
+        let rest = &self.decoded[self.position..];
+        let len = rest.len().min(buf.len());
+        buf[..len].copy_from_slice(&rest[..len]);
+        self.position += len;
+        return Ok(len);
+    }
+}
+
+fn invalid_utf16() -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-16");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn encode(str: &str, order: ByteOrder) -> Vec<u8> {
+        return str
+            .encode_utf16()
+            .flat_map(|unit| match order {
+                ByteOrder::LittleEndian => unit.to_le_bytes(),
+                ByteOrder::BigEndian => unit.to_be_bytes(),
+            })
+            .collect();
+    }
+
+    fn decode(bytes: &[u8], order: ByteOrder, capacity: usize) -> io::Result<String> {
+        let mut decoded = String::new();
+        Utf16Decoder::new(BufReader::with_capacity(capacity, bytes), order).read_to_string(&mut decoded)?;
+        return Ok(decoded);
+    }
+
+    #[test]
+    fn test_detect_bom() {
+        assert_eq!(detect_bom(&[0xFF, 0xFE, b'{', 0]), Some(ByteOrder::LittleEndian));
+        assert_eq!(detect_bom(&[0xFE, 0xFF]), Some(ByteOrder::BigEndian));
+        assert_eq!(detect_bom(&[0xEF, 0xBB, 0xBF]), None);
+        assert_eq!(detect_bom(b"{}"), None);
+    }
+
+    #[test]
+    fn test_decode() {
+        let input = "\u{FEFF}{\"名前\": \"😀\"}";
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            // 読み込みの境界で符号単位やサロゲートペアが分かれても正しく変換する
+            for capacity in [1, 3, 8192] {
+                assert_eq!(decode(&encode(input, order), order, capacity).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        // 対になっていないサロゲート
+        let lone_surrogate = [0x3D, 0xD8, b'a', 0];
+        assert!(decode(&lone_surrogate, ByteOrder::LittleEndian, 8192).is_err());
+        // 奇数長のバイト列
+        assert!(decode(&[b'a', 0, b'b'], ByteOrder::LittleEndian, 8192).is_err());
+    }
+}
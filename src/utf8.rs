@@ -0,0 +1,122 @@
+use std::io::{self, BufRead, Read};
+use std::str;
+
+/**
+ * UTF-8 として不正なバイト列を U+FFFD に置き換えながら読み込むリーダー
+ * 置き換えるたびに、そのバイト列の入力の先頭からの位置 (バイト数) を引数として on_invalid を呼ぶ
+ * 置き換え方は String::from_utf8_lossy と同じ
+ */
+pub struct LossyUtf8Reader<R: BufRead, F: FnMut(usize)> {
+    inner: R,
+    on_invalid: F,
+    pending: Vec<u8>, // まだ変換していないバイト列 (読み込みの境界で分かれた文字)
+    offset: usize,    // pending の先頭の、入力の先頭からの位置
+    decoded: Vec<u8>, // 変換済みで、まだ返していないバイト列
+    position: usize,  // decoded のうち返し終えたバイト数
+}
+
+impl<R: BufRead, F: FnMut(usize)> LossyUtf8Reader<R, F> {
+    pub fn new(inner: R, on_invalid: F) -> Self {
+        return LossyUtf8Reader {
+            inner,
+            on_invalid,
+            pending: Vec::new(),
+            offset: 0,
+            decoded: Vec::new(),
+            position: 0,
+        };
+    }
+
+    /**
+     * 入力を読み込んで変換し、decoded を補充する (入力の終わりに達した場合は空のまま返す)
+     */
+    fn refill(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.position = 0;
+        while self.decoded.is_empty() {
+            let chunk = self.inner.fill_buf()?;
+            let eof = chunk.is_empty();
+            self.pending.extend_from_slice(chunk);
+            let len = chunk.len();
+            self.inner.consume(len);
+
+            let mut start = 0;
+            while start < self.pending.len() {
+                match str::from_utf8(&self.pending[start..]) {
+                    Ok(valid) => {
+                        self.decoded.extend_from_slice(valid.as_bytes());
+                        start = self.pending.len();
+                    }
+                    Err(error) => {
+                        let valid_end = start + error.valid_up_to();
+                        self.decoded.extend_from_slice(&self.pending[start..valid_end]);
+                        // 末尾で途切れた文字は、続きを読み込むまで変換しない (入力の終わりであれば不正なバイト列とする)
+                        let invalid_len = match error.error_len() {
+                            Some(invalid_len) => invalid_len,
+                            None if eof => self.pending.len() - valid_end,
+                            None => {
+                                start = valid_end;
+                                break;
+                            }
+                        };
+                        self.decoded
+                            .extend_from_slice(char::REPLACEMENT_CHARACTER.encode_utf8(&mut [0; 4]).as_bytes());
+                        (self.on_invalid)(self.offset + valid_end);
+                        start = valid_end + invalid_len;
+                    }
+                }
+            }
+            self.pending.drain(..start);
+            self.offset += start;
+            if eof {
+                break;
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl<R: BufRead, F: FnMut(usize)> Read for LossyUtf8Reader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.decoded.len() {
+            self.refill()?;
+        }
+        let rest = &self.decoded[self.position..];
+        let len = rest.len().min(buf.len());
+        buf[..len].copy_from_slice(&rest[..len]);
+        self.position += len;
+        return Ok(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn decode(bytes: &[u8], capacity: usize) -> (String, Vec<usize>) {
+        let mut offsets = Vec::new();
+        let mut decoded = String::new();
+        LossyUtf8Reader::new(BufReader::with_capacity(capacity, bytes), |offset| offsets.push(offset))
+            .read_to_string(&mut decoded)
+            .unwrap();
+        return (decoded, offsets);
+    }
+
+    #[test]
+    fn test_decode_lossy() {
+        let input = b"{\"a\": \"\xff\xe3\x81\x82\xe3\x81\"}\xf0\x9f\x98";
+        // 読み込みの境界で文字が分かれても、String::from_utf8_lossy と同じ結果にする
+        for capacity in [1, 2, 8192] {
+            let (decoded, offsets) = decode(input, capacity);
+            assert_eq!(decoded, String::from_utf8_lossy(input));
+            assert_eq!(offsets, vec![7, 11, 15]);
+        }
+    }
+
+    #[test]
+    fn test_decode_valid() {
+        let input = "{\"名前\": \"😀\"}";
+        assert_eq!(decode(input.as_bytes(), 3), (input.to_string(), vec![]));
+    }
+}
@@ -0,0 +1,135 @@
+use rayon::prelude::*;
+use rust_json_formatter::jsonc::collect_jsonc_errors;
+use rust_json_formatter::parser::Parser;
+use std::io::Read;
+use std::time::Instant;
+
+use crate::cli::{Cli, InputFormat, ReportFormat};
+use crate::error::{CliError, ExitCode};
+use crate::files::{expand_directories, input_format, read_source};
+use crate::messages::{self, t};
+use crate::sarif::SarifReport;
+use crate::{
+    fail, json_lexer, open_stdin, parse_error, parse_gron_input, parse_json, parse_ndjson_line, parse_xml_input,
+    print_warnings,
+};
+
+/**
+ * 入力が --from で指定した形式として正しいかどうかだけを確かめる (整形はせず、--report-format sarif の場合を除いて標準出力には何も出力しない)
+ * ファイルを指定した場合は並列に確かめて不正なファイルをすべて報告し、最初に失敗したファイルの終了コードで終了する
+ */
+pub fn run(cli: &Cli) {
+    if cli.files.is_empty() {
+        let mut input = String::new();
+        if let Err(error) = open_stdin(cli).read_to_string(&mut input) {
+            fail(cli, CliError::io(t!(ReadFailed, error)));
+        }
+        let mut warnings = Vec::new();
+        let result = validate_input(cli, cli.from, &input, &mut warnings);
+        if cli.report_format == ReportFormat::Sarif {
+            let mut sarif = SarifReport::new();
+            if let Err(error) = &result {
+                sarif.add_error(None, error);
+            }
+            sarif.print().unwrap_or_else(|error| fail(cli, error));
+        }
+        print_warnings(cli, None, &warnings);
+        if let Err(error) = result {
+            fail(cli, error);
+        }
+        if !cli.quiet {
+            eprintln!("{}", t!(StdinValid));
+        }
+        return;
+    }
+
+    let paths = expand_directories(cli).unwrap_or_else(|error| fail(cli, error));
+    let start = Instant::now();
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|path| {
+            let mut warnings = Vec::new();
+            let result = read_source(cli, path)
+                .and_then(|(content, _)| validate_input(cli, input_format(cli, path), &content, &mut warnings));
+            return (result, warnings);
+        })
+        .collect();
+    log::info!("validated {} files in {:.2?}", paths.len(), start.elapsed());
+
+    let mut sarif = SarifReport::new();
+    let mut failed = 0;
+    let mut exit_code = ExitCode::Success;
+    for (path, (result, warnings)) in paths.iter().zip(results) {
+        print_warnings(cli, Some(&path.display()), &warnings);
+        if let Err(error) = result {
+            sarif.add_error(Some(path), &error);
+            if !cli.quiet {
+                eprintln!("{}: {}", path.display(), error);
+            }
+            if failed == 0 {
+                exit_code = error.code;
+            }
+            failed += 1;
+        }
+    }
+    if cli.report_format == ReportFormat::Sarif {
+        sarif.print().unwrap_or_else(|error| fail(cli, error));
+    }
+    if !cli.quiet {
+        eprintln!("{}", t!(ValidateSummary, paths.len(), failed));
+    }
+    if exit_code != ExitCode::Success {
+        exit_code.exit();
+    }
+}
+
+/**
+ * 入力を format の形式でパースし、正しいかどうかを確かめる
+ * パース時の警告 (重複したキーなど) は warnings に追加する
+ */
+fn validate_input(cli: &Cli, format: InputFormat, input: &str, warnings: &mut Vec<String>) -> Result<(), CliError> {
+    match format {
+        InputFormat::Json if !cli.fix => validate_json(cli, input, warnings)?,
+        InputFormat::Json => {
+            parse_json(cli, input, warnings).map_err(|e| parse_error(cli, "JSON", &e, input))?;
+        }
+        InputFormat::Jsonc => {
+            let errors = collect_jsonc_errors(input);
+            if !errors.is_empty() {
+                return Err(parse_error(cli, "JSONC", &errors, input));
+            }
+        }
+        InputFormat::Gron => {
+            parse_gron_input(input)?;
+        }
+        InputFormat::Xml => {
+            parse_xml_input(input)?;
+        }
+        InputFormat::Ndjson => {
+            for (i, line) in input.lines().enumerate() {
+                parse_ndjson_line(cli, i + 1, line, warnings)?;
+            }
+        }
+    }
+    return Ok(());
+}
+
+/**
+ * JSON の入力が正しいかどうかを、値を生成せずに確かめる
+ * 誤りがある場合は、すべての誤りを報告するためにパースし直す
+ */
+fn validate_json(cli: &Cli, input: &str, warnings: &mut Vec<String>) -> Result<(), CliError> {
+    let mut parser = Parser::new(json_lexer(cli, input)).with_limits(cli.limits());
+    if parser.validate().is_err() {
+        return parse_json(cli, input, warnings)
+            .map(|_| ())
+            .map_err(|e| parse_error(cli, "JSON", &e, input));
+    }
+    warnings.extend(
+        parser
+            .duplicate_keys()
+            .iter()
+            .map(|duplicate| messages::duplicate_key(duplicate, input)),
+    );
+    return Ok(());
+}
@@ -0,0 +1,495 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::{DefaultTerminal, Frame};
+use rust_json_formatter::json::JsonValue;
+use rust_json_formatter::walk::{JsonPath, PathSegment};
+use std::collections::HashSet;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::document;
+use crate::error::CliError;
+use crate::messages::t;
+
+/**
+ * ツリーの1行に表示する値
+ */
+struct Row<'a> {
+    depth: usize,               // 字下げの深さ
+    label: Option<PathSegment>, // 親から見たキーや添字 (ルートの場合は None)
+    value: &'a JsonValue,       // 値
+    parent: Option<usize>,      // 親の値の行番号
+}
+
+/**
+ * 入力モード
+ */
+enum Mode {
+    Browse,         // ツリーの操作
+    Search(String), // 検索語の入力中
+}
+
+/**
+ * ツリー表示の状態
+ */
+struct Viewer<'a> {
+    root: &'a JsonValue,
+    expanded: HashSet<*const JsonValue>, // 展開しているオブジェクトや配列 (値のアドレスで識別する)
+    rows: Vec<Row<'a>>,                  // 表示している行 (展開の状態が変わるたびに作り直す)
+    selected: usize,                     // 選択している行
+    offset: usize,                       // 画面の先頭に表示している行
+    mode: Mode,
+    matches: Vec<JsonPath>, // 直前の検索に一致した値の経路 (文書中の順)
+    current_match: usize,   // 選択している一致
+    message: String,        // ステータス行に表示するメッセージ
+}
+
+/**
+ * 入力を読み込み、折りたたみ可能なツリーとして端末に表示する
+ * 整形して端末に流すには大きすぎる入力を、展開や検索をしながら閲覧するためのもの
+ */
+pub fn run(cli: &Cli, file: Option<&Path>) -> Result<(), CliError> {
+    if file.is_none() && io::stdin().is_terminal() {
+        return Err(CliError::usage(t!(ViewNoInput)));
+    }
+    let value = document::load(cli, file)?;
+
+    let mut terminal = ratatui::try_init().map_err(|e| CliError::io(t!(TerminalInitFailed, e)))?;
+    let result = Viewer::new(&value).run(&mut terminal);
+    ratatui::restore();
+    return result.map_err(|e| CliError::io(t!(TerminalFailed, e)));
+}
+
+impl<'a> Viewer<'a> {
+    fn new(root: &'a JsonValue) -> Self {
+        let mut viewer = Viewer {
+            root,
+            expanded: HashSet::from([root as *const JsonValue]),
+            rows: Vec::new(),
+            selected: 0,
+            offset: 0,
+            mode: Mode::Browse,
+            matches: Vec::new(),
+            current_match: 0,
+            message: String::new(),
+        };
+        viewer.rebuild();
+        return viewer;
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let height = terminal.size()?.height.saturating_sub(1) as usize;
+            let quit = match self.mode {
+                Mode::Browse => self.handle_browse(key, height),
+                Mode::Search(_) => {
+                    self.handle_search(key);
+                    false
+                }
+            };
+            if quit {
+                return Ok(());
+            }
+        }
+    }
+
+    /**
+     * ツリーの操作中のキー入力を処理し、終了する場合は true を返す
+     */
+    fn handle_browse(&mut self, key: KeyEvent, height: usize) -> bool {
+        self.message.clear();
+        let last = self.rows.len() - 1;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::PageDown => self.selected = (self.selected + height).min(last),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(height),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = last,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
+                self.set_expanded(true);
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.collapse(),
+            KeyCode::Char('/') => self.mode = Mode::Search(String::new()),
+            KeyCode::Char('n') => self.jump_to_match(1),
+            KeyCode::Char('N') => self.jump_to_match(self.matches.len().saturating_sub(1)),
+            KeyCode::Char('y') => self.copy_path(),
+            _ => {}
+        }
+        return false;
+    }
+
+    /**
+     * 検索語の入力中のキー入力を処理する
+     */
+    fn handle_search(&mut self, key: KeyEvent) {
+        let Mode::Search(query) = &mut self.mode else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(ch) => query.push(ch),
+            KeyCode::Enter => {
+                let query = std::mem::take(query);
+                self.mode = Mode::Browse;
+                self.search(&query);
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * 選択している行のオブジェクトや配列を展開または折りたたみ、状態が変わった場合は true を返す
+     */
+    fn set_expanded(&mut self, expanded: bool) -> bool {
+        let value = self.rows[self.selected].value;
+        if !is_container(value) {
+            return false;
+        }
+        let changed = if expanded {
+            self.expanded.insert(value)
+        } else {
+            self.expanded.remove(&(value as *const JsonValue))
+        };
+        if changed {
+            self.rebuild();
+        }
+        return changed;
+    }
+
+    /**
+     * 選択している行を折りたたむ (展開していなければ親の行に移る)
+     */
+    fn collapse(&mut self) {
+        if self.set_expanded(false) {
+            return;
+        }
+        if let Some(parent) = self.rows[self.selected].parent {
+            self.selected = parent;
+        }
+    }
+
+    /**
+     * 展開の状態から表示する行を作り直す
+     */
+    fn rebuild(&mut self) {
+        let mut rows = Vec::new();
+        let mut stack = vec![(0, None, self.root, None)];
+        while let Some((depth, label, value, parent)) = stack.pop() {
+            let index = rows.len();
+            rows.push(Row {
+                depth,
+                label,
+                value,
+                parent,
+            });
+            if !self.expanded.contains(&(value as *const JsonValue)) {
+                continue;
+            }
+            // 子要素は先頭から表示するために逆順で積む
+            match value {
+                JsonValue::Object(object) => {
+                    for (key, child) in object.iter().rev() {
+                        stack.push((depth + 1, Some(PathSegment::Key(key.clone())), child, Some(index)));
+                    }
+                }
+                JsonValue::Array(array) => {
+                    for (i, child) in array.iter().enumerate().rev() {
+                        stack.push((depth + 1, Some(PathSegment::Index(i)), child, Some(index)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len() - 1);
+    }
+
+    /**
+     * 行の値のルートからの経路を返す
+     */
+    fn path_of(&self, row: usize) -> JsonPath {
+        let mut segments = Vec::new();
+        let mut current = Some(row);
+        while let Some(index) = current {
+            segments.extend(self.rows[index].label.clone());
+            current = self.rows[index].parent;
+        }
+        let mut path = JsonPath::root();
+        for segment in segments.into_iter().rev() {
+            path.push(segment);
+        }
+        return path;
+    }
+
+    /**
+     * キーか値 (文字列、数値、真偽値、null) に query を含む値を、折りたたまれた部分も含めて探す
+     */
+    fn search(&mut self, query: &str) {
+        if query.is_empty() {
+            return;
+        }
+        self.matches = self
+            .root
+            .walk()
+            .filter(|(path, value)| {
+                let key_matches = match path.segments().last() {
+                    Some(PathSegment::Key(key)) => key.contains(query),
+                    _ => false,
+                };
+                return key_matches || (!is_container(value) && value.to_string().contains(query));
+            })
+            .map(|(path, _)| path)
+            .collect();
+        if self.matches.is_empty() {
+            self.message = t!(SearchNotFound, query);
+            return;
+        }
+        self.current_match = self.matches.len() - 1;
+        self.jump_to_match(1);
+    }
+
+    /**
+     * 現在の一致から step 件先の一致に移り、その値が見えるよう祖先を展開する
+     */
+    fn jump_to_match(&mut self, step: usize) {
+        if self.matches.is_empty() {
+            self.message = t!(SearchEmpty);
+            return;
+        }
+        self.current_match = (self.current_match + step) % self.matches.len();
+        let path = &self.matches[self.current_match];
+
+        let mut value = self.root;
+        for segment in path.segments() {
+            self.expanded.insert(value);
+            value = match segment {
+                PathSegment::Key(key) => &value[key.as_str()],
+                PathSegment::Index(index) => &value[*index],
+            };
+        }
+        self.rebuild();
+        if let Some(row) = self.rows.iter().position(|row| std::ptr::eq(row.value, value)) {
+            self.selected = row;
+        }
+        self.message = t!(SearchPosition, self.current_match + 1, self.matches.len());
+    }
+
+    /**
+     * 選択している値の経路 (JSON Pointer) をクリップボードにコピーする
+     */
+    fn copy_path(&mut self) {
+        let path = self.path_of(self.selected).to_string();
+        self.message = match copy_to_clipboard(&path) {
+            Ok(()) => t!(Copied, path),
+            Err(error) => format!("{} ({})", error, path),
+        };
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tree, status] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        // 選択している行が画面に収まるようにスクロールする
+        let height = tree.height as usize;
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+        let lines: Vec<Line> = (self.offset..self.rows.len().min(self.offset + height))
+            .map(|index| {
+                let line = self.render_row(&self.rows[index]);
+                if index == self.selected {
+                    return line.style(Style::default().add_modifier(Modifier::REVERSED));
+                }
+                return line;
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), tree);
+
+        let status_line = match &self.mode {
+            Mode::Search(query) => format!("/{}", query),
+            Mode::Browse if !self.message.is_empty() => self.message.clone(),
+            Mode::Browse => {
+                let path = self.path_of(self.selected);
+                t!(
+                    ViewHelp,
+                    if path.is_root() {
+                        "/".to_string()
+                    } else {
+                        path.to_string()
+                    }
+                )
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(status_line).style(Style::default().add_modifier(Modifier::REVERSED)),
+            status,
+        );
+    }
+
+    /**
+     * 1行分の表示 (字下げ、展開の状態、キー、値または要素数) を組み立てる
+     */
+    fn render_row(&self, row: &Row) -> Line<'static> {
+        let mut spans = vec![Span::raw("  ".repeat(row.depth))];
+        let expanded = self.expanded.contains(&(row.value as *const JsonValue));
+        spans.push(Span::raw(match row.value {
+            JsonValue::Object(_) | JsonValue::Array(_) if expanded => "▾ ",
+            JsonValue::Object(_) | JsonValue::Array(_) => "▸ ",
+            _ => "  ",
+        }));
+        match &row.label {
+            Some(PathSegment::Key(key)) => {
+                spans.push(Span::styled(
+                    JsonValue::from(key.as_str()).to_string(),
+                    Style::default().fg(Color::Blue),
+                ));
+                spans.push(Span::raw(": "));
+            }
+            Some(PathSegment::Index(index)) => {
+                spans.push(Span::styled(
+                    format!("[{}]", index),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                spans.push(Span::raw(": "));
+            }
+            None => {}
+        }
+        let (text, color) = match row.value {
+            JsonValue::Object(object) => (t!(ObjectSummary, object.len()), Color::Reset),
+            JsonValue::Array(array) => (t!(ArraySummary, array.len()), Color::Reset),
+            JsonValue::String(_) => (row.value.to_string(), Color::Green),
+            JsonValue::Number(_) => (row.value.to_string(), Color::Yellow),
+            JsonValue::Bool(_) | JsonValue::Null => (row.value.to_string(), Color::Magenta),
+        };
+        spans.push(Span::styled(text, Style::default().fg(color)));
+        return Line::from(spans);
+    }
+}
+
+fn is_container(value: &JsonValue) -> bool {
+    return matches!(value, JsonValue::Object(_) | JsonValue::Array(_));
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    return arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| t!(ClipboardCopyFailed, e));
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_text: &str) -> Result<(), String> {
+    return Err(t!(ClipboardCopyRequiresFeature));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_json_formatter::lexer::Lexer;
+    use rust_json_formatter::parser::Parser;
+
+    fn parse(input: &str) -> JsonValue {
+        return Parser::new(Lexer::new(input)).parse().unwrap();
+    }
+
+    fn press(viewer: &mut Viewer, code: KeyCode) -> bool {
+        return viewer.handle_browse(KeyEvent::new(code, KeyModifiers::NONE), 10);
+    }
+
+    /**
+     * 表示している行を、字下げの深さとルートからの経路の組で返す
+     */
+    fn rows(viewer: &Viewer) -> Vec<(usize, String)> {
+        return (0..viewer.rows.len())
+            .map(|row| (viewer.rows[row].depth, viewer.path_of(row).to_string()))
+            .collect();
+    }
+
+    #[test]
+    fn test_expand_and_collapse() {
+        let value = parse(r#"{"a": {"b": 1}, "c": [true, null]}"#);
+        let mut viewer = Viewer::new(&value);
+        assert_eq!(
+            rows(&viewer),
+            [(0, ""), (1, "/a"), (1, "/c")].map(|(d, p)| (d, p.to_string()))
+        );
+
+        press(&mut viewer, KeyCode::Char('G'));
+        press(&mut viewer, KeyCode::Enter);
+        assert_eq!(
+            rows(&viewer),
+            [(0, ""), (1, "/a"), (1, "/c"), (2, "/c/0"), (2, "/c/1")].map(|(d, p)| (d, p.to_string()))
+        );
+
+        // 子の行で折りたたむと親の行に移り、もう一度折りたたむと親が閉じる
+        press(&mut viewer, KeyCode::Down);
+        assert_eq!(viewer.selected, 3);
+        press(&mut viewer, KeyCode::Left);
+        assert_eq!(viewer.selected, 2);
+        press(&mut viewer, KeyCode::Left);
+        assert_eq!(rows(&viewer).len(), 3);
+
+        press(&mut viewer, KeyCode::Down);
+        assert_eq!(viewer.selected, 2);
+        assert!(press(&mut viewer, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_search() {
+        let value = parse(r#"{"a": {"name": "x"}, "b": [{"name": "y"}]}"#);
+        let mut viewer = Viewer::new(&value);
+
+        viewer.search("name");
+        assert_eq!(viewer.matches.len(), 2);
+        assert_eq!(viewer.path_of(viewer.selected).to_string(), "/a/name");
+
+        // 折りたたまれていた祖先を展開して次の一致に移り、最後の一致の次は先頭に戻る
+        press(&mut viewer, KeyCode::Char('n'));
+        assert_eq!(viewer.path_of(viewer.selected).to_string(), "/b/0/name");
+        press(&mut viewer, KeyCode::Char('n'));
+        assert_eq!(viewer.path_of(viewer.selected).to_string(), "/a/name");
+        press(&mut viewer, KeyCode::Char('N'));
+        assert_eq!(viewer.path_of(viewer.selected).to_string(), "/b/0/name");
+
+        viewer.search("z");
+        assert_eq!(viewer.matches.len(), 0);
+        assert_eq!(viewer.path_of(viewer.selected).to_string(), "/b/0/name");
+    }
+
+    #[test]
+    fn test_search_input() {
+        let value = parse(r#"{"key": "value"}"#);
+        let mut viewer = Viewer::new(&value);
+        press(&mut viewer, KeyCode::Char('/'));
+        for code in [
+            KeyCode::Char('v'),
+            KeyCode::Char('x'),
+            KeyCode::Backspace,
+            KeyCode::Char('a'),
+        ] {
+            viewer.handle_search(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+        assert!(matches!(&viewer.mode, Mode::Search(query) if query == "va"));
+        viewer.handle_search(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(matches!(viewer.mode, Mode::Browse));
+        assert_eq!(viewer.path_of(viewer.selected).to_string(), "/key");
+    }
+}
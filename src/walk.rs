@@ -0,0 +1,324 @@
+use std::fmt;
+
+use crate::json::{JsonArray, JsonObject, JsonValue};
+
+/**
+ * ルートから値までの経路を構成する要素
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),  // オブジェクトのキー
+    Index(usize), // 配列の添字
+}
+
+/**
+ * ルートから値までの経路
+ * Display では JSON Pointer (RFC 6901) 形式 (例: /users/0/name) になる
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct JsonPath {
+    segments: Vec<PathSegment>,
+}
+
+impl JsonPath {
+    /**
+     * ルートを指す空の経路を生成する
+     */
+    pub fn root() -> Self {
+        return JsonPath::default();
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        return &self.segments;
+    }
+
+    pub fn is_root(&self) -> bool {
+        return self.segments.is_empty();
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        return self.segments.pop();
+    }
+
+    /**
+     * 末尾に要素を追加した新しい経路を返す
+     */
+    pub fn child(&self, segment: PathSegment) -> JsonPath {
+        let mut path = self.clone();
+        path.push(segment);
+        return path;
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(key) => write!(f, "/{}", key.replace('~', "~0").replace('/', "~1"))?,
+                PathSegment::Index(index) => write!(f, "/{}", index)?,
+            }
+        }
+        return Ok(());
+    }
+}
+
+/**
+ * 深さ優先 (行きがけ順) で値を辿るイテレータ
+ * 親の値が子要素よりも先に、子要素はオブジェクトや配列の中の順序で返される
+ */
+pub struct Walk<'a> {
+    stack: Vec<(JsonPath, &'a JsonValue)>, // これから返す値 (末尾から取り出す)
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (JsonPath, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, value) = self.stack.pop()?;
+        // 子要素は先頭から返すために逆順で積む
+        match value {
+            JsonValue::Object(obj) => {
+                for (key, child) in obj.iter().rev() {
+                    self.stack.push((path.child(PathSegment::Key(key.clone())), child));
+                }
+            }
+            JsonValue::Array(array) => {
+                for (i, child) in array.iter().enumerate().rev() {
+                    self.stack.push((path.child(PathSegment::Index(i)), child));
+                }
+            }
+            _ => {}
+        }
+        return Some((path, value));
+    }
+}
+
+/**
+ * JsonValue::visit で木を辿る際に呼ばれるフック
+ * 必要なメソッドだけを実装すればよい
+ */
+pub trait Visitor {
+    fn enter_object(&mut self, _path: &JsonPath, _object: &JsonObject) {}
+    fn leave_object(&mut self, _path: &JsonPath, _object: &JsonObject) {}
+    fn enter_array(&mut self, _path: &JsonPath, _array: &JsonArray) {}
+    fn leave_array(&mut self, _path: &JsonPath, _array: &JsonArray) {}
+    fn visit_scalar(&mut self, _path: &JsonPath, _value: &JsonValue) {}
+}
+
+impl JsonValue {
+    /**
+     * 自身を含むすべての値を (経路, 値) の組で深さ優先に辿るイテレータを返す
+     */
+    pub fn walk(&self) -> Walk<'_> {
+        return Walk {
+            stack: vec![(JsonPath::root(), self)],
+        };
+    }
+
+    /**
+     * 深さ優先で木を辿り、オブジェクトや配列の出入りと末端の値ごとに visitor のフックを呼ぶ
+     */
+    pub fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.visit_at(&mut JsonPath::root(), visitor);
+    }
+
+    fn visit_at<V: Visitor>(&self, path: &mut JsonPath, visitor: &mut V) {
+        match self {
+            JsonValue::Object(obj) => {
+                visitor.enter_object(path, obj);
+                for (key, value) in obj {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.visit_at(path, visitor);
+                    path.pop();
+                }
+                visitor.leave_object(path, obj);
+            }
+            JsonValue::Array(array) => {
+                visitor.enter_array(path, array);
+                for (i, value) in array.iter().enumerate() {
+                    path.push(PathSegment::Index(i));
+                    value.visit_at(path, visitor);
+                    path.pop();
+                }
+                visitor.leave_array(path, array);
+            }
+            _ => visitor.visit_scalar(path, self),
+        }
+    }
+}
+
+/**
+ * JSON Pointer に * と ** を加えた、経路のパターン
+ * * は任意のキーか添字1つに、** は0個以上の任意の要素に一致する (data の直下のすべての値や、どの深さにある payload なども表せる)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathPattern {
+    segments: Vec<PatternSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PatternSegment {
+    Literal(String), // 同じ名前のキー、または同じ値の添字
+    Any,             // 任意のキーか添字1つ (*)
+    AnyDepth,        // 0個以上の任意の要素 (**)
+}
+
+impl PathPattern {
+    /**
+     * パターンの文字列を解析する (空文字列はルートに一致する)
+     * JSON Pointer と同様に / で始め、キーの中の ~ と / は ~0 と ~1 で表す
+     */
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Ok(PathPattern { segments: Vec::new() });
+        }
+        let Some(rest) = pattern.strip_prefix('/') else {
+            return Err(format!("経路のパターンは / で始めてください: {}", pattern));
+        };
+        let segments = rest
+            .split('/')
+            .map(|segment| match segment {
+                "*" => PatternSegment::Any,
+                "**" => PatternSegment::AnyDepth,
+                _ => PatternSegment::Literal(segment.replace("~1", "/").replace("~0", "~")),
+            })
+            .collect();
+        return Ok(PathPattern { segments });
+    }
+
+    /**
+     * 経路がパターンに一致するかどうか
+     */
+    pub fn matches(&self, path: &JsonPath) -> bool {
+        return matches_segments(&self.segments, path.segments());
+    }
+}
+
+fn matches_segments(pattern: &[PatternSegment], path: &[PathSegment]) -> bool {
+    let Some((first, rest)) = pattern.split_first() else {
+        return path.is_empty();
+    };
+    if *first == PatternSegment::AnyDepth {
+        return (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..]));
+    }
+    let Some((segment, path)) = path.split_first() else {
+        return false;
+    };
+    let matched = match (first, segment) {
+        (PatternSegment::Literal(name), PathSegment::Key(key)) => name == key,
+        (PatternSegment::Literal(name), PathSegment::Index(index)) => *name == index.to_string(),
+        _ => true,
+    };
+    return matched && matches_segments(rest, path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_display() {
+        let path = JsonPath::root()
+            .child(PathSegment::Key("a/b".to_string()))
+            .child(PathSegment::Index(0))
+            .child(PathSegment::Key("c~d".to_string()));
+
+        assert_eq!(path.to_string(), "/a~1b/0/c~0d");
+        assert_eq!(JsonPath::root().to_string(), "");
+    }
+
+    #[test]
+    fn test_walk() {
+        let value: JsonValue = r#"{"a": [1, {"b": null}], "c": "d"}"#.parse().unwrap();
+        let visited: Vec<(String, String)> = value
+            .walk()
+            .map(|(path, value)| (path.to_string(), value.to_string()))
+            .collect();
+
+        let expected = vec![
+            ("", r#"{"a":[1,{"b":null}],"c":"d"}"#),
+            ("/a", r#"[1,{"b":null}]"#),
+            ("/a/0", "1"),
+            ("/a/1", r#"{"b":null}"#),
+            ("/a/1/b", "null"),
+            ("/c", r#""d""#),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .into_iter()
+            .map(|(path, value)| (path.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn test_visit() {
+        struct Recorder {
+            events: Vec<String>,
+        }
+        impl Visitor for Recorder {
+            fn enter_object(&mut self, path: &JsonPath, _object: &JsonObject) {
+                self.events.push(format!("enter_object {}", path));
+            }
+            fn leave_object(&mut self, path: &JsonPath, _object: &JsonObject) {
+                self.events.push(format!("leave_object {}", path));
+            }
+            fn enter_array(&mut self, path: &JsonPath, _array: &JsonArray) {
+                self.events.push(format!("enter_array {}", path));
+            }
+            fn leave_array(&mut self, path: &JsonPath, _array: &JsonArray) {
+                self.events.push(format!("leave_array {}", path));
+            }
+            fn visit_scalar(&mut self, path: &JsonPath, value: &JsonValue) {
+                self.events.push(format!("scalar {} {}", path, value));
+            }
+        }
+
+        let value: JsonValue = r#"{"a": [1, true]}"#.parse().unwrap();
+        let mut recorder = Recorder { events: Vec::new() };
+        value.visit(&mut recorder);
+
+        assert_eq!(
+            recorder.events,
+            vec![
+                "enter_object ",
+                "enter_array /a",
+                "scalar /a/0 1",
+                "scalar /a/1 true",
+                "leave_array /a",
+                "leave_object ",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_pattern() {
+        let path = |pointer: &str| {
+            let mut path = JsonPath::root();
+            for segment in pointer.split('/').skip(1) {
+                path.push(match segment.parse() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(segment.to_string()),
+                });
+            }
+            return path;
+        };
+        let matches = |pattern: &str, pointer: &str| PathPattern::parse(pattern).unwrap().matches(&path(pointer));
+
+        assert!(matches("/data/token", "/data/token"));
+        assert!(matches("/data/*", "/data/token"));
+        assert!(!matches("/data/*", "/data"));
+        assert!(!matches("/data/*", "/data/a/b"));
+        assert!(matches("/items/0", "/items/0"));
+        assert!(matches("/**/payload", "/payload"));
+        assert!(matches("/**/payload", "/a/0/payload"));
+        assert!(!matches("/**/payload", "/a/payload/b"));
+        assert!(matches("", ""));
+        let escaped = JsonPath::root().child(PathSegment::Key("a/b".to_string()));
+        assert!(PathPattern::parse("/a~1b").unwrap().matches(&escaped));
+        assert!(PathPattern::parse("data").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use wasm_bindgen::prelude::*;
+
+use crate::json::{FormatOptions, JsonValue, LineEnding, NumericArrayLayout};
+
+/**
+ * JavaScript から指定する整形時のオプション
+ * 各項目は CLI の同名のオプションに対応し、指定しなかった項目は CLI のデフォルトと同じになる
+ */
+#[wasm_bindgen(js_name = FormatOptions)]
+pub struct WasmFormatOptions {
+    options: FormatOptions,
+}
+
+impl Default for WasmFormatOptions {
+    fn default() -> Self {
+        return WasmFormatOptions {
+            options: FormatOptions {
+                trailing_newline: true,
+                ..Default::default()
+            },
+        };
+    }
+}
+
+#[wasm_bindgen(js_class = FormatOptions)]
+impl WasmFormatOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmFormatOptions {
+        return WasmFormatOptions::default();
+    }
+
+    /**
+     * --indent と同じく、数値なら空白の数、"tab" ならタブ文字、それ以外は文字列そのものを字下げに使う
+     */
+    #[wasm_bindgen(js_name = setIndent)]
+    pub fn set_indent(&mut self, indent: &str) {
+        self.options.indent_unit = FormatOptions::parse_indent_unit(indent);
+    }
+
+    #[wasm_bindgen(js_name = setCollapse)]
+    pub fn set_collapse(&mut self, collapse: bool) {
+        self.options.collapse = collapse;
+    }
+
+    #[wasm_bindgen(js_name = setMaxWidth)]
+    pub fn set_max_width(&mut self, max_width: usize) {
+        self.options.max_width = max_width;
+    }
+
+    /**
+     * --compact-numeric-arrays と同じく、数値・真偽値のみからなる配列を1行にまとめる
+     * per_line を指定するとその要素数ごとに折り返す
+     */
+    #[wasm_bindgen(js_name = setCompactNumericArrays)]
+    pub fn set_compact_numeric_arrays(&mut self, per_line: Option<usize>) {
+        self.options.numeric_arrays = match per_line {
+            None => NumericArrayLayout::SingleLine,
+            Some(per_line) => NumericArrayLayout::Wrapped(per_line),
+        };
+    }
+
+    #[wasm_bindgen(js_name = setTrailingNewline)]
+    pub fn set_trailing_newline(&mut self, trailing_newline: bool) {
+        self.options.trailing_newline = trailing_newline;
+    }
+
+    #[wasm_bindgen(js_name = setCrlf)]
+    pub fn set_crlf(&mut self, crlf: bool) {
+        self.options.line_ending = if crlf { LineEnding::Crlf } else { LineEnding::Lf };
+    }
+
+    #[wasm_bindgen(js_name = setCompact)]
+    pub fn set_compact(&mut self, compact: bool) {
+        self.options.compact = compact;
+    }
+}
+
+/**
+ * 入力を JSON としてパースし、オプションに従って整形した文字列を返す
+ * パースに失敗した場合は、CLI が英語で表示するエラーメッセージの1行目と同じく、原因と行番号・列番号を含むメッセージを返す
+ */
+#[wasm_bindgen]
+pub fn format(input: &str, options: &WasmFormatOptions) -> Result<String, String> {
+    let json = input.parse::<JsonValue>().map_err(|e| e.english_message(input))?;
+    return Ok(json.format_with_options(&options.options));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let mut options = WasmFormatOptions::new();
+        options.set_indent("tab");
+        options.set_compact_numeric_arrays(None);
+
+        assert_eq!(
+            format(r#"{"a": [1, 2]}"#, &options),
+            Ok("{\n\t\"a\": [1, 2]\n}\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_error() {
+        assert_eq!(
+            format("[1,\n", &WasmFormatOptions::new()),
+            Err("failed to parse JSON: unexpected end of input (line 2, column 1)".to_string())
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use rust_json_formatter::json::FormatOptions;
+use rust_json_formatter::walk::PathPattern;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::messages::{self, t};
+use crate::{document, fail, format_options, open_stdout, write_output};
+
+/**
+ * 入力を読み込み、経路がパターンに一致する値ごとに、整形した出力に占める大きさと割合を大きい順に出力する
+ */
+pub fn run(cli: &Cli, file: Option<&Path>, pattern: &PathPattern) {
+    let value = document::load(cli, file).unwrap_or_else(|error| fail(cli, error));
+    // 色付けのエスケープシーケンスと末尾の改行は大きさに含めない
+    let options = FormatOptions {
+        theme: None,
+        trailing_newline: false,
+        ..format_options(cli)
+    };
+    let total = value.format_with_options(&options).len();
+
+    let mut report = String::new();
+    for (path, size) in value.weigh(pattern, &options) {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            size as f64 * 100.0 / total as f64
+        };
+        let path = messages::path_label(&path);
+        writeln!(report, "{:>10}  {:>5.1}%  {}", format_size(size), percent, path).unwrap();
+    }
+    writeln!(report, "{}", t!(WeighTotal, format_size(total))).unwrap();
+    write_output(open_stdout(cli), report.as_bytes()).unwrap_or_else(|error| fail(cli, error));
+}
+
+/**
+ * バイト数を 412 B や 38.2 MiB のように読みやすい単位で表す
+ */
+fn format_size(size: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    return format!("{:.1} {}", value, UNITS[unit]);
+}
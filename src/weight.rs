@@ -0,0 +1,48 @@
+use crate::json::{FormatOptions, FormatTree, JsonValue};
+use crate::walk::{JsonPath, PathPattern};
+
+impl JsonValue {
+    /**
+     * 経路がパターンに一致する値ごとに、オプションに従って整形した場合のバイト数を求め、大きい順に返す (同じ大きさの値は文書中の順)
+     * 値は文書中の深さで字下げして整形した大きさとし、キーや区切りのカンマは含めない
+     */
+    pub fn weigh(&self, pattern: &PathPattern, options: &FormatOptions) -> Vec<(JsonPath, usize)> {
+        let mut weights: Vec<(JsonPath, usize)> = self
+            .walk()
+            .filter(|(path, _)| pattern.matches(path))
+            .map(|(path, value)| {
+                let mut formatted = String::new();
+                value.format_value(path.segments().len(), options, &mut formatted);
+                return (path, formatted.len());
+            })
+            .collect();
+        weights.sort_by(|(_, a), (_, b)| b.cmp(a));
+        return weights;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weigh() {
+        let json: JsonValue = r#"{"id": 1, "items": [1, 2], "name": "a"}"#.parse().unwrap();
+        let weights = json.weigh(&PathPattern::parse("/*").unwrap(), &FormatOptions::default());
+        // "[\n    1,\n    2\n  ]" は 18 バイト
+        assert_eq!(
+            weights
+                .iter()
+                .map(|(path, size)| (path.to_string(), *size))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/items".to_string(), 18),
+                ("/name".to_string(), 3),
+                ("/id".to_string(), 1)
+            ]
+        );
+
+        let weights = json.weigh(&PathPattern::parse("/items/*").unwrap(), &FormatOptions::compact());
+        assert_eq!(weights.len(), 2);
+    }
+}
@@ -0,0 +1,765 @@
+use indexmap::IndexMap;
+
+use crate::json::JsonValue;
+use crate::parser::line_column;
+use crate::walk::{JsonPath, PathSegment};
+
+/**
+ * 属性を表すメンバーのキーの接頭辞 (<a id="1"/> は {"a": {"@id": "1"}} になる)
+ */
+pub const ATTRIBUTE_PREFIX: &str = "@";
+
+/**
+ * 属性や子要素と並ぶテキストを表すメンバーのキー (<a id="1">x</a> は {"a": {"@id": "1", "#text": "x"}} になる)
+ */
+pub const TEXT_KEY: &str = "#text";
+
+/**
+ * テキストと子要素が混在する要素の内容を、出現順に並べた配列のメンバーのキー
+ * テキストは文字列、子要素は要素名をキーとする1つのメンバーからなるオブジェクトにする
+ * (<p>a <b>b</b> c</p> は {"p": {"#content": ["a ", {"b": "b"}, " c"]}} になる)
+ */
+pub const CONTENT_KEY: &str = "#content";
+
+/**
+ * JSON を XML に変換する際、ルートの要素が1つに決まらない場合に全体を囲む要素の名前
+ */
+pub const XML_ROOT: &str = "root";
+
+/**
+ * JSON を XML に変換する際、配列の中の配列の要素を表す要素の名前
+ */
+pub const XML_ITEM: &str = "item";
+
+/**
+ * XML のパースエラーの原因
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XmlErrorKind {
+    UnexpectedEof,      // 予期しない入力の終わり
+    ExpectedRoot,       // ルートの要素がない
+    InvalidName,        // 要素名や属性名として使えない文字がある
+    ExpectedEquals,     // 属性名の後に = がない
+    ExpectedQuote,      // 属性の値がクォートされていない
+    DuplicateAttribute, // 同じ要素に同じ名前の属性がある
+    MismatchedTag,      // 終了タグの名前が開始タグと一致しない
+    InvalidEntity,      // 不明な実体参照や不正な文字参照がある
+    TrailingCharacters, // ルートの要素の後に余分な入力がある
+}
+
+impl XmlErrorKind {
+    /**
+     * エラーの原因を説明するメッセージを返す
+     */
+    pub fn message(&self) -> &'static str {
+        return match self {
+            XmlErrorKind::UnexpectedEof => "予期しない入力の終わりです",
+            XmlErrorKind::ExpectedRoot => "ルートの要素が必要です",
+            XmlErrorKind::InvalidName => "要素名や属性名として使えない文字です",
+            XmlErrorKind::ExpectedEquals => "属性名の後には `=` が必要です",
+            XmlErrorKind::ExpectedQuote => "属性の値はクォートで囲む必要があります",
+            XmlErrorKind::DuplicateAttribute => "同じ名前の属性が重複しています",
+            XmlErrorKind::MismatchedTag => "終了タグの名前が開始タグと一致しません",
+            XmlErrorKind::InvalidEntity => "不正な実体参照です",
+            XmlErrorKind::TrailingCharacters => "ルートの要素の後に余分な文字があります",
+        };
+    }
+}
+
+/**
+ * XML のパースエラー
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlError {
+    pub kind: XmlErrorKind, // エラーの原因
+    pub position: usize,    // エラーが発生した入力中の位置 (バイト単位)
+}
+
+impl XmlError {
+    /**
+     * エラーの位置を 1 始まりの行番号と列番号 (文字単位) に変換する
+     */
+    pub fn line_column(&self, input: &str) -> (usize, usize) {
+        return line_column(input, self.position);
+    }
+}
+
+/**
+ * XML の要素名として使えないキー
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlNameError {
+    pub name: String,   // 要素名や属性名にしようとしたキー
+    pub path: JsonPath, // キーのメンバーの経路
+}
+
+/**
+ * XML を読み込み、ルートの要素名をキーとする1つのメンバーからなるオブジェクトに変換する
+ * 属性は "@" を付けた名前のメンバー、子要素は要素名のメンバーにする (同じ名前の子要素が複数あれば配列にまとめる)
+ * 属性も子要素もない要素はテキストの文字列 (空の場合は null) にし、属性とテキストだけの要素はテキストを "#text" のメンバーにする
+ * テキストと子要素が混在する要素は、順序を保つため内容を "#content" の配列にする (改行を含む空白だけのテキスト (字下げ) は除く)
+ * 子要素だけを持つ要素の空白だけのテキスト (字下げ) は無視する
+ * 値はすべて文字列のまま扱い、コメント・処理命令・XML 宣言・DOCTYPE 宣言は読み飛ばす
+ */
+pub fn parse_xml(input: &str) -> Result<JsonValue, XmlError> {
+    let mut parser = XmlParser { input, position: 0 };
+    parser.skip_misc()?;
+    if !parser.rest().starts_with('<') {
+        return Err(parser.error(XmlErrorKind::ExpectedRoot));
+    }
+    let (name, value) = parser.parse_element()?;
+    parser.skip_misc()?;
+    if parser.position < input.len() {
+        return Err(parser.error(XmlErrorKind::TrailingCharacters));
+    }
+    let mut root = IndexMap::new();
+    root.insert(name, value);
+    return Ok(JsonValue::Object(root));
+}
+
+/**
+ * 要素の内容 (開始タグと終了タグの間) の一部
+ */
+enum Content {
+    Text(String),               // テキスト (CDATA を含み、隣り合うものはまとめる)
+    Element(String, JsonValue), // 子要素の要素名と値
+}
+
+struct XmlParser<'a> {
+    input: &'a str,
+    position: usize, // 次に読み込む位置 (バイト単位)
+}
+
+impl<'a> XmlParser<'a> {
+    fn rest(&self) -> &'a str {
+        return &self.input[self.position..];
+    }
+
+    fn error(&self, kind: XmlErrorKind) -> XmlError {
+        return XmlError {
+            kind,
+            position: self.position,
+        };
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start_matches([' ', '\t', '\r', '\n']).len();
+    }
+
+    /**
+     * terminator の直後まで読み飛ばす (見つからなければエラー)
+     */
+    fn skip_past(&mut self, terminator: &str) -> Result<&'a str, XmlError> {
+        let rest = self.rest();
+        let end = rest.find(terminator).ok_or(XmlError {
+            kind: XmlErrorKind::UnexpectedEof,
+            position: self.input.len(),
+        })?;
+        self.position += end + terminator.len();
+        return Ok(&rest[..end]);
+    }
+
+    /**
+     * 要素の外側にある空白、コメント、処理命令 (XML 宣言を含む)、DOCTYPE 宣言を読み飛ばす
+     */
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.skip_doctype()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /**
+     * DOCTYPE 宣言を、[ ] で囲まれた内部サブセットも含めて読み飛ばす
+     */
+    fn skip_doctype(&mut self) -> Result<(), XmlError> {
+        let mut depth = 0;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '>' if depth == 0 => {
+                    self.position += i + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        return Err(XmlError {
+            kind: XmlErrorKind::UnexpectedEof,
+            position: self.input.len(),
+        });
+    }
+
+    fn parse_name(&mut self) -> Result<&'a str, XmlError> {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| !is_name_char(c, i == 0))
+            .map_or(rest.len(), |(i, _)| i);
+        if len == 0 {
+            return Err(self.error(XmlErrorKind::InvalidName));
+        }
+        self.position += len;
+        return Ok(&rest[..len]);
+    }
+
+    /**
+     * < から始まる要素を、要素名とその値の組として読み込む
+     */
+    fn parse_element(&mut self) -> Result<(String, JsonValue), XmlError> {
+        self.position += 1;
+        let name = self.parse_name()?;
+        let mut members = IndexMap::new();
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.position += 2;
+                return Ok((name.to_string(), element_value(members, Vec::new())));
+            }
+            if rest.starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            if rest.is_empty() {
+                return Err(self.error(XmlErrorKind::UnexpectedEof));
+            }
+            let attribute_start = self.position;
+            let attribute = format!("{}{}", ATTRIBUTE_PREFIX, self.parse_name()?);
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error(XmlErrorKind::ExpectedEquals));
+            }
+            self.position += 1;
+            self.skip_whitespace();
+            let value = self.parse_attribute_value()?;
+            if members.insert(attribute, JsonValue::String(value)).is_some() {
+                return Err(XmlError {
+                    kind: XmlErrorKind::DuplicateAttribute,
+                    position: attribute_start,
+                });
+            }
+        }
+
+        let mut content = Vec::new();
+        loop {
+            let rest = self.rest();
+            let text_len = rest.find('<').unwrap_or(rest.len());
+            if text_len > 0 {
+                let start = self.position;
+                self.position += text_len;
+                push_text(&mut content, &decode_entities(&rest[..text_len], start)?);
+                continue;
+            }
+            if rest.is_empty() {
+                return Err(self.error(XmlErrorKind::UnexpectedEof));
+            }
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.position += "<![CDATA[".len();
+                let cdata = self.skip_past("]]>")?;
+                push_text(&mut content, cdata);
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("</") {
+                let end_start = self.position;
+                self.position += 2;
+                if self.parse_name()? != name {
+                    return Err(XmlError {
+                        kind: XmlErrorKind::MismatchedTag,
+                        position: end_start,
+                    });
+                }
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(self.error(XmlErrorKind::UnexpectedEof));
+                }
+                self.position += 1;
+                return Ok((name.to_string(), element_value(members, content)));
+            } else {
+                let (child_name, child) = self.parse_element()?;
+                content.push(Content::Element(child_name, child));
+            }
+        }
+    }
+
+    fn parse_attribute_value(&mut self) -> Result<String, XmlError> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error(XmlErrorKind::ExpectedQuote)),
+        };
+        self.position += 1;
+        let start = self.position;
+        let raw = self.skip_past(&quote.to_string())?;
+        return decode_entities(raw, start);
+    }
+}
+
+/**
+ * 要素の内容にテキストを追加する (直前もテキストであれば続ける)
+ */
+fn push_text(content: &mut Vec<Content>, text: &str) {
+    match content.last_mut() {
+        Some(Content::Text(last)) => last.push_str(text),
+        _ => content.push(Content::Text(text.to_string())),
+    }
+}
+
+/**
+ * 要素の属性のメンバーと内容から要素の値を組み立てる
+ */
+fn element_value(mut members: IndexMap<String, JsonValue>, content: Vec<Content>) -> JsonValue {
+    let has_children = content.iter().any(|part| matches!(part, Content::Element(..)));
+    let is_blank = |text: &str| text.trim_matches([' ', '\t', '\r', '\n']).is_empty();
+    let mixed = has_children
+        && content
+            .iter()
+            .any(|part| matches!(part, Content::Text(text) if !is_blank(text)));
+    if mixed {
+        let items = content
+            .into_iter()
+            .filter_map(|part| match part {
+                Content::Text(text) if is_blank(&text) && text.contains('\n') => None,
+                Content::Text(text) => Some(JsonValue::String(text)),
+                Content::Element(name, value) => Some(JsonValue::Object(IndexMap::from([(name, value)]))),
+            })
+            .collect();
+        members.insert(CONTENT_KEY.to_string(), JsonValue::Array(items));
+        return JsonValue::Object(members);
+    }
+
+    // 子要素だけを持つ要素の空白だけのテキスト (字下げ) は無視する
+    let mut text = String::new();
+    for part in content {
+        match part {
+            Content::Text(part) if !has_children => text.push_str(&part),
+            Content::Text(_) => {}
+            Content::Element(child_name, child) => match members.get_mut(&child_name) {
+                Some(JsonValue::Array(siblings)) => siblings.push(child),
+                Some(existing) => {
+                    let first = std::mem::replace(existing, JsonValue::Null);
+                    *existing = JsonValue::Array(vec![first, child]);
+                }
+                None => {
+                    members.insert(child_name, child);
+                }
+            },
+        }
+    }
+    if members.is_empty() {
+        return if text.is_empty() {
+            JsonValue::Null
+        } else {
+            JsonValue::String(text)
+        };
+    }
+    if !text.is_empty() {
+        // 子要素がなければテキストは空白も含めてそのまま残し、属性の後に置く
+        members.insert(TEXT_KEY.to_string(), JsonValue::String(text));
+    }
+    return JsonValue::Object(members);
+}
+
+/**
+ * テキストや属性の値の実体参照と文字参照を復号し、改行を \n に揃える
+ * start はエラーの位置を求めるための、raw の入力中の開始位置
+ */
+fn decode_entities(raw: &str, start: usize) -> Result<String, XmlError> {
+    let raw = raw.replace("\r\n", "\n");
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw.as_str();
+    while let Some(ampersand) = rest.find('&') {
+        decoded.push_str(&rest[..ampersand]);
+        let invalid = XmlError {
+            kind: XmlErrorKind::InvalidEntity,
+            position: start + (raw.len() - rest.len()) + ampersand,
+        };
+        let len = rest[ampersand..].find(';').ok_or(invalid.clone())?;
+        let entity = &rest[ampersand + 1..ampersand + len];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()),
+                };
+                code.and_then(char::from_u32).ok_or(invalid)?
+            }
+        };
+        decoded.push(c);
+        rest = &rest[ampersand + len + 1..];
+    }
+    decoded.push_str(rest);
+    return Ok(decoded);
+}
+
+/**
+ * 要素名や属性名に使える文字かどうか (first は名前の先頭の文字かどうか)
+ */
+fn is_name_char(c: char, first: bool) -> bool {
+    return c.is_alphabetic() || c == '_' || c == ':' || (!first && (c.is_numeric() || c == '-' || c == '.'));
+}
+
+/**
+ * XML の要素名や属性名として使える文字列かどうか
+ */
+pub fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    return chars.next().is_some_and(|c| is_name_char(c, true)) && chars.all(|c| is_name_char(c, false));
+}
+
+impl JsonValue {
+    /**
+     * parse_xml と逆の対応で XML に変換した文字列を返す (indent_unit は1段分の字下げ)
+     * 1つのメンバーからなるオブジェクトはそのキーをルートの要素名にし、それ以外は全体を <root> で囲む
+     * 配列のメンバーは同じ名前の要素を繰り返し、配列の中の配列は <item> 要素を並べる (空の配列のメンバーは出力しない)
+     * 数値や真偽値はテキストにし、null は空の要素にする
+     * 要素名や属性名として使えないキーがあればエラーを返す
+     */
+    pub fn to_xml(&self, indent_unit: &str) -> Result<String, XmlNameError> {
+        let mut writer = XmlWriter {
+            indent_unit,
+            newline: "\n",
+            output: "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string(),
+            path: JsonPath::root(),
+        };
+        match self {
+            JsonValue::Object(obj) if obj.len() == 1 && !obj[0].is_array() && is_element_key(obj.get_index(0)) => {
+                let (name, value) = obj.get_index(0).unwrap();
+                writer.path.push(PathSegment::Key(name.clone()));
+                writer.write_element(name, value, 0)?;
+            }
+            _ => writer.write_element(XML_ROOT, self, 0)?,
+        }
+        return Ok(writer.output);
+    }
+}
+
+/**
+ * ルートの要素にできるメンバーのキーかどうか (属性やテキストを表すキーではないこと)
+ */
+fn is_element_key(member: Option<(&String, &JsonValue)>) -> bool {
+    return member.is_some_and(|(key, _)| !key.starts_with(ATTRIBUTE_PREFIX) && key != TEXT_KEY && key != CONTENT_KEY);
+}
+
+struct XmlWriter<'a> {
+    indent_unit: &'a str,
+    newline: &'a str, // 要素の後の改行 (混在する内容の中では空文字列にして1行に書き込む)
+    output: String,
+    path: JsonPath, // 書き込んでいる値の経路 (エラーの報告に使う)
+}
+
+impl XmlWriter<'_> {
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.output.push_str(self.indent_unit);
+        }
+    }
+
+    fn name_error(&self, name: &str) -> XmlNameError {
+        return XmlNameError {
+            name: name.to_string(),
+            path: self.path.clone(),
+        };
+    }
+
+    /**
+     * 値を要素名 name の要素として、深さ depth の字下げで1行以上書き込む
+     */
+    fn write_element(&mut self, name: &str, value: &JsonValue, depth: usize) -> Result<(), XmlNameError> {
+        if !is_xml_name(name) {
+            return Err(self.name_error(name));
+        }
+        self.indent(depth);
+        self.output.push('<');
+        self.output.push_str(name);
+        let (mut text, mut children, mut content) = (None, Vec::new(), None);
+        match value {
+            JsonValue::Null => {}
+            JsonValue::Object(obj) => {
+                for (key, member) in obj {
+                    if let Some(attribute) = key.strip_prefix(ATTRIBUTE_PREFIX) {
+                        if !is_xml_name(attribute) {
+                            self.path.push(PathSegment::Key(key.clone()));
+                            let error = self.name_error(attribute);
+                            self.path.pop();
+                            return Err(error);
+                        }
+                        self.output.push(' ');
+                        self.output.push_str(attribute);
+                        self.output.push_str("=\"");
+                        push_escaped_text(&mut self.output, &scalar_text(member), true);
+                        self.output.push('"');
+                    } else if key == TEXT_KEY {
+                        text = Some(scalar_text(member));
+                    } else if let (CONTENT_KEY, JsonValue::Array(items)) = (key.as_str(), member) {
+                        content = Some(items);
+                    } else {
+                        children.push((key.as_str(), member, Some(key)));
+                    }
+                }
+            }
+            JsonValue::Array(array) => children.extend(array.iter().map(|element| (XML_ITEM, element, None))),
+            _ => text = Some(scalar_text(value)),
+        }
+
+        // テキストと子要素が混在する内容は、空白が意味を持つため字下げや改行を加えずに1行に書き込む
+        if let Some(items) = content {
+            self.output.push('>');
+            if let Some(text) = text {
+                push_escaped_text(&mut self.output, &text, false);
+            }
+            self.path.push(PathSegment::Key(CONTENT_KEY.to_string()));
+            for (index, item) in items.iter().enumerate() {
+                self.path.push(PathSegment::Index(index));
+                self.write_content(item)?;
+                self.path.pop();
+            }
+            self.path.pop();
+            self.write_end_tag(name);
+            return Ok(());
+        }
+
+        if children.is_empty() {
+            match text {
+                None => {
+                    self.output.push_str("/>");
+                    self.output.push_str(self.newline);
+                }
+                Some(text) => {
+                    self.output.push('>');
+                    push_escaped_text(&mut self.output, &text, false);
+                    self.write_end_tag(name);
+                }
+            }
+            return Ok(());
+        }
+        self.output.push('>');
+        self.output.push_str(self.newline);
+        if let Some(text) = text {
+            self.indent(depth + 1);
+            push_escaped_text(&mut self.output, &text, false);
+            self.output.push_str(self.newline);
+        }
+        for (index, (child_name, child, key)) in children.into_iter().enumerate() {
+            self.path.push(match key {
+                Some(key) => PathSegment::Key(key.clone()),
+                None => PathSegment::Index(index),
+            });
+            match child {
+                // 配列のメンバーは、同じ名前の要素を繰り返す
+                JsonValue::Array(elements) if key.is_some() => {
+                    for (index, element) in elements.iter().enumerate() {
+                        self.path.push(PathSegment::Index(index));
+                        self.write_element(child_name, element, depth + 1)?;
+                        self.path.pop();
+                    }
+                }
+                _ => self.write_element(child_name, child, depth + 1)?,
+            }
+            self.path.pop();
+        }
+        self.indent(depth);
+        self.write_end_tag(name);
+        return Ok(());
+    }
+
+    /**
+     * "#content" の配列の要素を、字下げや改行を加えずに書き込む
+     * 1つのメンバーからなるオブジェクトはそのキーを要素名とする子要素に、それ以外の値はテキストにする
+     */
+    fn write_content(&mut self, item: &JsonValue) -> Result<(), XmlNameError> {
+        let JsonValue::Object(obj) = item else {
+            push_escaped_text(&mut self.output, &scalar_text(item), false);
+            return Ok(());
+        };
+        if obj.len() != 1 || !is_element_key(obj.get_index(0)) {
+            push_escaped_text(&mut self.output, &scalar_text(item), false);
+            return Ok(());
+        }
+        let (name, value) = obj.get_index(0).unwrap();
+        let mut inline = XmlWriter {
+            indent_unit: "",
+            newline: "",
+            output: std::mem::take(&mut self.output),
+            path: self.path.clone(),
+        };
+        inline.path.push(PathSegment::Key(name.clone()));
+        let result = inline.write_element(name, value, 0);
+        self.output = inline.output;
+        return result;
+    }
+
+    fn write_end_tag(&mut self, name: &str) {
+        self.output.push_str("</");
+        self.output.push_str(name);
+        self.output.push('>');
+        self.output.push_str(self.newline);
+    }
+}
+
+/**
+ * 属性の値やテキストにする値を文字列に変換する (オブジェクトや配列は JSON のまま埋め込む)
+ */
+fn scalar_text(value: &JsonValue) -> String {
+    return match value {
+        JsonValue::String(str) => str.clone(),
+        JsonValue::Null => String::new(),
+        _ => value.format(0),
+    };
+}
+
+/**
+ * テキストを XML のエスケープを施して書き込む (attribute が true の場合は " もエスケープする)
+ */
+pub(crate) fn push_escaped_text(output: &mut String, text: &str, attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '&' => output.push_str("&amp;"),
+            '"' if attribute => output.push_str("&quot;"),
+            '\r' => output.push_str("&#13;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xml() {
+        let input = r#"<?xml version="1.0"?>
+<!-- comment -->
+<library name="central">
+  <book id="1" lang='en'>Rust &amp; You</book>
+  <book id="2"><title>XML</title><note/></book>
+  <open/>
+  <empty></empty>
+  <code><![CDATA[a < b]]></code>
+  <label>text &#x41;</label>
+</library>
+"#;
+        assert_eq!(
+            parse_xml(input).unwrap().to_string(),
+            r##"{"library":{"@name":"central","book":[{"@id":"1","@lang":"en","#text":"Rust & You"},{"@id":"2","title":"XML","note":null}],"open":null,"empty":null,"code":"a < b","label":"text A"}}"##
+        );
+    }
+
+    #[test]
+    fn test_parse_xml_mixed_content() {
+        let input = "<p class=\"x\">\n  Hello <b>big</b> <i>world</i>!\n  <br/>\n</p>";
+        assert_eq!(
+            parse_xml(input).unwrap().to_string(),
+            r##"{"p":{"@class":"x","#content":["\n  Hello ",{"b":"big"}," ",{"i":"world"},"!\n  ",{"br":null}]}}"##
+        );
+    }
+
+    #[test]
+    fn test_parse_xml_keeps_text_of_leaf() {
+        assert_eq!(parse_xml("<a> x\r\n y </a>").unwrap().to_string(), r#"{"a":" x\n y "}"#);
+    }
+
+    #[test]
+    fn test_parse_xml_invalid() {
+        let kind = |input: &str| parse_xml(input).unwrap_err().kind;
+        assert_eq!(kind(""), XmlErrorKind::ExpectedRoot);
+        assert_eq!(kind("<a>"), XmlErrorKind::UnexpectedEof);
+        assert_eq!(kind("<a></b>"), XmlErrorKind::MismatchedTag);
+        assert_eq!(kind("<a x=1/>"), XmlErrorKind::ExpectedQuote);
+        assert_eq!(kind("<a x='1' x='2'/>"), XmlErrorKind::DuplicateAttribute);
+        assert_eq!(kind("<a>&nbsp;</a>"), XmlErrorKind::InvalidEntity);
+        assert_eq!(kind("<a/><b/>"), XmlErrorKind::TrailingCharacters);
+
+        let error = parse_xml("<a>\n  <b></c>\n</a>").unwrap_err();
+        assert_eq!(error.line_column("<a>\n  <b></c>\n</a>"), (2, 6));
+    }
+
+    #[test]
+    fn test_to_xml() {
+        let value: JsonValue =
+            r##"{"config": {"@version": 2, "#text": "a<b", "server": [{"host": "x"}, {"host": "y"}], "debug": true, "log": null}}"##
+                .parse()
+                .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<config version="2">
+  a&lt;b
+  <server>
+    <host>x</host>
+  </server>
+  <server>
+    <host>y</host>
+  </server>
+  <debug>true</debug>
+  <log/>
+</config>
+"#;
+        assert_eq!(value.to_xml("  ").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_xml_wraps_in_root() {
+        let value: JsonValue = r#"[1, [2, 3]]"#.parse().unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+	<item>1</item>
+	<item>
+		<item>2</item>
+		<item>3</item>
+	</item>
+</root>
+"#;
+        assert_eq!(value.to_xml("\t").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_xml_invalid_name() {
+        let value: JsonValue = r#"{"a": {"b": [{"1st": 0}]}}"#.parse().unwrap();
+        let error = value.to_xml("  ").unwrap_err();
+        assert_eq!(error.name, "1st");
+        assert_eq!(error.path.to_string(), "/a/b/0/1st");
+    }
+
+    #[test]
+    fn test_xml_round_trip() {
+        let input = r#"<a x="1"><b>2</b><b>3</b><c/></a>"#;
+        let value = parse_xml(input).unwrap();
+        assert_eq!(parse_xml(&value.to_xml("  ").unwrap()), Ok(value));
+    }
+
+    #[test]
+    fn test_xml_round_trip_mixed_content() {
+        let input = r#"<doc><p>a <b>b <i>i</i> &amp;</b> c<br/>d</p><list><item>1</item><item>2</item></list></doc>"#;
+        let value = parse_xml(input).unwrap();
+        let xml = value.to_xml("  ").unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<doc>
+  <p>a <b>b <i>i</i> &amp;</b> c<br/>d</p>
+  <list>
+    <item>1</item>
+    <item>2</item>
+  </list>
+</doc>
+"#;
+        assert_eq!(xml, expected);
+        assert_eq!(parse_xml(&xml), Ok(value));
+    }
+}